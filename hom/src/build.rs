@@ -0,0 +1,936 @@
+// build.rs     Model building
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use anyhow::{bail, Context, Result};
+use glam::{Quat, Vec3};
+use homunculus::{
+    import_glb, GltfOptions, Husk, MeshStats, ModelDef, Plane, PlyFormat,
+    Scene, SectionOptions, UpAxis,
+};
+use serde_json::{json, Map, Value};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Length of vertex normal debug lines
+const NORMAL_LENGTH: f32 = 0.1;
+
+/// Output file format
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Binary glTF (`.glb`)
+    #[default]
+    Glb,
+
+    /// Text glTF (`.gltf`), with external buffer (`.bin`)
+    Gltf,
+
+    /// Text glTF (`.gltf`), with embedded buffer
+    GltfEmbedded,
+
+    /// Wavefront OBJ (`.obj`)
+    Obj,
+
+    /// Binary PLY (`.ply`)
+    Ply,
+
+    /// ASCII PLY (`.ply`)
+    PlyAscii,
+}
+
+/// Options for building a model
+#[derive(Clone, Debug, Default)]
+pub struct BuildOptions {
+    /// Uniform scale factor
+    pub scale: Option<f32>,
+
+    /// Recenter with base at origin
+    pub center: bool,
+
+    /// Also write vertex normals as lines
+    pub debug_normals: bool,
+
+    /// Up axis convention
+    pub up: UpAxis,
+
+    /// Levels of detail (ratios of faces to keep)
+    pub lods: Vec<f32>,
+
+    /// Use MSFT_lod extension for levels of detail
+    pub msft_lod: bool,
+
+    /// Ambient occlusion rays per vertex
+    pub ao: Option<usize>,
+
+    /// Copyright message
+    pub copyright: Option<String>,
+
+    /// Quantize vertex attributes
+    pub quantize: bool,
+
+    /// Record build provenance in asset extras
+    pub metadata: bool,
+
+    /// Turntable animation period (seconds)
+    pub turntable: Option<f32>,
+
+    /// Output file format
+    pub format: Format,
+
+    /// Distance for welding vertices
+    pub weld: Option<f32>,
+
+    /// Mirror axis
+    pub mirror: Option<String>,
+
+    /// Levels of subdivision
+    pub subdivide: Option<usize>,
+
+    /// Fix winding order of inside out meshes
+    pub fix_winding: bool,
+
+    /// Wall thickness of hollow shell
+    pub thickness: Option<f32>,
+}
+
+/// Parse an up axis (`y` or `z`)
+pub fn parse_up(value: &str) -> std::result::Result<UpAxis, String> {
+    match value {
+        "y" | "Y" => Ok(UpAxis::Y),
+        "z" | "Z" => Ok(UpAxis::Z),
+        _ => Err(format!("Invalid up axis: {value}")),
+    }
+}
+
+/// Parse an output format (`glb`, `gltf`, `gltf-embedded`, `obj`, `ply` or
+/// `ply-ascii`)
+pub fn parse_format(value: &str) -> std::result::Result<Format, String> {
+    match value {
+        "glb" => Ok(Format::Glb),
+        "gltf" => Ok(Format::Gltf),
+        "gltf-embedded" => Ok(Format::GltfEmbedded),
+        "obj" => Ok(Format::Obj),
+        "ply" => Ok(Format::Ply),
+        "ply-ascii" => Ok(Format::PlyAscii),
+        _ => Err(format!("Invalid format: {value}")),
+    }
+}
+
+/// Parse a vector argument (`x,y,z`)
+pub fn parse_vector(value: &str) -> std::result::Result<Vec3, String> {
+    let vals: Vec<f32> = value
+        .split(',')
+        .map(|v| v.trim().parse::<f32>())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| format!("Invalid vector: {value}"))?;
+    match vals[..] {
+        [x, y, z] if vals.iter().all(|v| v.is_finite()) => {
+            Ok(Vec3::new(x, y, z))
+        }
+        _ => Err(format!("Invalid vector: {value}")),
+    }
+}
+
+/// Parse a field of view argument (degrees)
+pub fn parse_fov(value: &str) -> std::result::Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(fov) if fov > 0.0 && fov < 180.0 => Ok(fov),
+        _ => Err(format!("Invalid field of view: {value}")),
+    }
+}
+
+/// Parse levels of detail (`ratio,ratio,...`)
+pub fn parse_lods(value: &str) -> std::result::Result<Vec<f32>, String> {
+    value
+        .split(',')
+        .map(|v| match v.trim().parse::<f32>() {
+            Ok(ratio) if ratio > 0.0 && ratio < 1.0 => Ok(ratio),
+            _ => Err(format!("Invalid LOD ratio: {v}")),
+        })
+        .collect()
+}
+
+impl Format {
+    /// Check if the format is glTF
+    fn is_gltf(self) -> bool {
+        matches!(self, Format::Glb | Format::Gltf | Format::GltfEmbedded)
+    }
+}
+
+impl BuildOptions {
+    /// Check that options are supported by the output format
+    ///
+    /// Only scale, center and up axis apply to formats other than glTF.
+    fn check_format(&self) -> Result<()> {
+        if self.format.is_gltf() {
+            return Ok(());
+        }
+        let flags = [
+            ("--lods", !self.lods.is_empty()),
+            ("--msft-lod", self.msft_lod),
+            ("--ao", self.ao.is_some()),
+            ("--copyright", self.copyright.is_some()),
+            ("--quantize", self.quantize),
+            ("--metadata", self.metadata),
+            ("--turntable", self.turntable.is_some()),
+        ];
+        match flags.iter().find(|(_, set)| *set) {
+            Some((flag, _)) => bail!("{flag} is only supported for glTF"),
+            None => Ok(()),
+        }
+    }
+
+    /// Make glTF export options for a model
+    fn gltf_options(&self, def: &ModelDef) -> Result<GltfOptions> {
+        let mut options = GltfOptions::default();
+        if let Some(seed) = def.seed() {
+            options.node_extras = Some(json!({ "seed": seed }));
+        }
+        let mut asset = Map::new();
+        if let Some(scale) = self.scale {
+            if !scale.is_finite() || scale <= 0.0 {
+                bail!("Invalid scale: {scale}");
+            }
+            options.scale = Some(scale);
+            asset.insert("scale".into(), json!(scale));
+        }
+        if self.center {
+            options.center = true;
+            asset.insert("center".into(), json!(true));
+        }
+        if self.up == UpAxis::Z {
+            options.up = UpAxis::Z;
+            asset.insert("up".into(), json!("z"));
+        }
+        if !self.lods.is_empty() {
+            options.lods.clone_from(&self.lods);
+            options.msft_lod = self.msft_lod;
+            asset.insert("lods".into(), json!(self.lods));
+        }
+        if let Some(samples) = self.ao {
+            if samples == 0 {
+                bail!("Invalid AO samples: {samples}");
+            }
+            options.ao_samples = Some(samples);
+            asset.insert("ao".into(), json!(samples));
+        }
+        if self.quantize {
+            options.quantize = true;
+            asset.insert("quantize".into(), json!(true));
+        }
+        if let Some(seconds) = self.turntable {
+            if !seconds.is_finite() || seconds <= 0.0 {
+                bail!("Invalid turntable period: {seconds}");
+            }
+            options.turntable = Some(seconds);
+        }
+        options.copyright.clone_from(&self.copyright);
+        if !asset.is_empty() {
+            options.asset_extras = Some(Value::Object(asset));
+        }
+        Ok(options)
+    }
+}
+
+/// Get model name from a file path (the file stem)
+fn model_name(path: &Path) -> Result<String> {
+    let stem = path.file_stem().context("Invalid file name")?;
+    Ok(stem.to_string_lossy().into_owned())
+}
+
+/// Get output path for a model file
+pub fn output_path(path: &Path, ext: &str) -> Result<PathBuf> {
+    let stem = path.file_stem().context("Invalid file name")?;
+    Ok(path.with_file_name(Path::new(stem).with_extension(ext)))
+}
+
+/// Make a scene from a model definition
+///
+/// The `name` is used for the node of a single husk.
+fn def_scene(def: &ModelDef, name: &str) -> Result<Scene> {
+    if def.has_parts() {
+        return Scene::try_from(def).context("Invalid model");
+    }
+    let husk = Husk::try_from(def).context("Invalid model")?;
+    let mut scene = Scene::default();
+    scene.add(name, husk, Vec3::ZERO, Quat::IDENTITY)?;
+    Ok(scene)
+}
+
+/// Make a scene from a model definition, reporting problems
+///
+/// The `name` is used for the scene, and for the node of a single husk.
+fn model_scene(
+    def: &ModelDef,
+    name: &str,
+    options: &GltfOptions,
+) -> Result<(Scene, GltfOptions)> {
+    let scene = def_scene(def, name)?;
+    let report = scene.build_report();
+    if report.degenerate_faces > 0 {
+        let faces = report.degenerate_faces;
+        eprintln!("{name}: {faces} degenerate faces removed");
+    }
+    for part in scene.inside_out() {
+        eprintln!("{part}: mesh is inside out (use --fix-winding)");
+    }
+    let options = GltfOptions {
+        scene_name: Some(name.to_string()),
+        ..options.clone()
+    };
+    Ok((scene, options))
+}
+
+/// Write a model definition as glTF
+fn write_gltf<W: Write>(
+    def: &ModelDef,
+    name: &str,
+    writer: W,
+    options: &GltfOptions,
+) -> Result<()> {
+    let (scene, options) = model_scene(def, name, options)?;
+    scene.write_gltf_with(writer, &options)?;
+    Ok(())
+}
+
+/// Write a model definition in an output format
+///
+/// Returns the path of the model file.
+fn write_model(
+    def: &ModelDef,
+    path: &Path,
+    format: Format,
+    options: &GltfOptions,
+) -> Result<PathBuf> {
+    let name = model_name(path)?;
+    match format {
+        Format::Glb => {
+            let out = output_path(path, "glb")?;
+            write_gltf(def, &name, create(&out)?, options)?;
+            Ok(out)
+        }
+        Format::Gltf => {
+            let out = output_path(path, "gltf")?;
+            let bin = output_path(path, "bin")?;
+            let uri = bin.file_name().context("Invalid file name")?;
+            let (scene, options) = model_scene(def, &name, options)?;
+            scene.write_gltf_json(
+                create(&out)?,
+                &uri.to_string_lossy(),
+                create(&bin)?,
+                &options,
+            )?;
+            Ok(out)
+        }
+        Format::GltfEmbedded => {
+            let out = output_path(path, "gltf")?;
+            let (scene, options) = model_scene(def, &name, options)?;
+            scene.write_gltf_embedded(create(&out)?, &options)?;
+            Ok(out)
+        }
+        Format::Obj => {
+            let out = output_path(path, "obj")?;
+            let (scene, options) = model_scene(def, &name, options)?;
+            scene.write_obj_with(create(&out)?, &options)?;
+            Ok(out)
+        }
+        Format::Ply | Format::PlyAscii => {
+            let out = output_path(path, "ply")?;
+            let (scene, options) = model_scene(def, &name, options)?;
+            let ply = if format == Format::Ply {
+                PlyFormat::Binary
+            } else {
+                PlyFormat::Ascii
+            };
+            scene.write_ply_with(create(&out)?, ply, &options)?;
+            Ok(out)
+        }
+    }
+}
+
+/// Write vertex normals of a model definition as glTF lines
+///
+/// The scale, center and up axis `options` are applied, to match the model.
+fn write_normals_gltf<W: Write>(
+    def: &ModelDef,
+    name: &str,
+    writer: W,
+    options: &GltfOptions,
+) -> Result<()> {
+    let scene = def_scene(def, name)?;
+    scene.write_normals_gltf_with(writer, NORMAL_LENGTH, options)?;
+    Ok(())
+}
+
+/// Create an output file
+fn create(path: &Path) -> Result<File> {
+    File::create(path)
+        .with_context(|| format!("Cannot create {}", path.display()))
+}
+
+/// Read JSON of a glTF model (`.glb` or `.gltf`)
+fn read_gltf_json(path: &Path) -> Result<Value> {
+    let mut buf = Vec::new();
+    File::open(path)
+        .with_context(|| format!("{} not found", path.display()))?
+        .read_to_end(&mut buf)?;
+    if buf.starts_with(b"glTF") && buf.len() >= 20 {
+        let len = u32::from_le_bytes(buf[12..16].try_into()?) as usize;
+        let json = buf.get(20..20 + len).context("Invalid GLB")?;
+        Ok(serde_json::from_slice(json)?)
+    } else {
+        Ok(serde_json::from_slice(&buf)?)
+    }
+}
+
+/// Detect up axis convention of a glTF model
+///
+/// This is recorded in the asset `extras` when building with `--up`.
+pub fn detect_up(path: &Path) -> Result<UpAxis> {
+    let json = read_gltf_json(path)?;
+    match json["asset"]["extras"]["up"].as_str() {
+        Some(up) => parse_up(up).map_err(anyhow::Error::msg),
+        None => Ok(UpAxis::Y),
+    }
+}
+
+/// Add build provenance to glTF asset extras
+///
+/// This records the source file name, `hom` version and random seed.
+fn with_metadata(
+    options: GltfOptions,
+    path: &Path,
+    def: &ModelDef,
+) -> Result<GltfOptions> {
+    let source = path.file_name().context("Invalid file name")?;
+    let mut options = options
+        .with_extra("source", json!(source.to_string_lossy()))
+        .with_extra("hom", json!(env!("CARGO_PKG_VERSION")));
+    if let Some(seed) = def.seed() {
+        options = options.with_extra("seed", json!(seed));
+    }
+    Ok(options)
+}
+
+/// Build homunculus model
+///
+/// Returns the path of the glTF model.
+pub fn build_model(path: &Path, opts: &BuildOptions) -> Result<PathBuf> {
+    let file = File::open(path)
+        .with_context(|| format!("{} not found", path.display()))?;
+    match path.extension() {
+        Some(ext) if ext == "glb" || ext == "gltf" => {
+            eprintln!("{path:?} already glTF model");
+            return Ok(path.to_path_buf());
+        }
+        _ => {}
+    }
+    let mut def = ModelDef::from_reader(file).context("Invalid model")?;
+    if let Some(epsilon) = opts.weld {
+        def.set_weld(epsilon);
+    }
+    if let Some(axis) = &opts.mirror {
+        def.set_mirror(axis);
+    }
+    if let Some(levels) = opts.subdivide {
+        def.set_subdivide(levels);
+    }
+    if opts.fix_winding {
+        def.set_fix_winding(true);
+    }
+    if let Some(thickness) = opts.thickness {
+        def.set_thickness(thickness);
+    }
+    opts.check_format()?;
+    let mut options = opts.gltf_options(&def)?;
+    if opts.metadata {
+        options = with_metadata(options, path, &def)?;
+    }
+    if let Some(seed) = def.seed() {
+        eprintln!("{path:?} seed: {seed}");
+    }
+    let out = write_model(&def, path, opts.format, &options)
+        .with_context(|| format!("Building {}", path.display()))?;
+    if opts.debug_normals {
+        let normals = output_path(path, "normals.glb")?;
+        let name = model_name(path)?;
+        write_normals_gltf(&def, &name, create(&normals)?, &options)
+            .with_context(|| format!("Building {}", path.display()))?;
+    }
+    Ok(out)
+}
+
+/// Write a cross-section of a model as SVG
+///
+/// Returns the path of the SVG file.
+pub fn section_model(
+    path: &Path,
+    plane: Plane,
+    output: Option<PathBuf>,
+    options: &SectionOptions,
+) -> Result<PathBuf> {
+    let file = File::open(path)
+        .with_context(|| format!("{} not found", path.display()))?;
+    let def = ModelDef::from_reader(file).context("Invalid model")?;
+    let husk = Husk::try_from(&def)
+        .with_context(|| format!("Invalid model {}", path.display()))?;
+    let out = match output {
+        Some(out) => out,
+        None => output_path(path, "svg")?,
+    };
+    husk.section_svg_with(plane, create(&out)?, options)
+        .context("Writing SVG")?;
+    Ok(out)
+}
+
+/// Get statistics of a model (`.hom` or `.glb`)
+///
+/// All meshes are merged, with node transforms applied.
+pub fn model_stats(path: &Path) -> Result<MeshStats> {
+    let mut file = File::open(path)
+        .with_context(|| format!("{} not found", path.display()))?;
+    let mut glb = Vec::new();
+    match path.extension() {
+        Some(ext) if ext == "glb" => {
+            file.read_to_end(&mut glb)?;
+        }
+        _ => {
+            let def = ModelDef::from_reader(file).context("Invalid model")?;
+            let name = model_name(path)?;
+            write_gltf(&def, &name, &mut glb, &GltfOptions::default())
+                .with_context(|| format!("Building {}", path.display()))?;
+        }
+    }
+    let model = import_glb(&glb[..])
+        .with_context(|| format!("Invalid model {}", path.display()))?;
+    let mut meshes = model.meshes.into_iter();
+    let mut mesh = meshes.next().context("No meshes")?;
+    for other in meshes {
+        mesh.merge(other)?;
+    }
+    Ok(mesh.stats())
+}
+
+/// Export a glTF binary model (`.glb`) to another format
+///
+/// Returns the path of the exported file.
+pub fn export_model(
+    path: &Path,
+    format: Format,
+    output: Option<PathBuf>,
+) -> Result<PathBuf> {
+    let file = File::open(path)
+        .with_context(|| format!("{} not found", path.display()))?;
+    let model = import_glb(file)
+        .with_context(|| format!("Invalid model {}", path.display()))?;
+    let ext = match format {
+        Format::Obj => "obj",
+        Format::Ply | Format::PlyAscii => "ply",
+        _ => bail!("Export format must be obj, ply or ply-ascii"),
+    };
+    let out = match output {
+        Some(out) => out,
+        None => output_path(path, ext)?,
+    };
+    match format {
+        Format::Ply => model.write_ply(create(&out)?, PlyFormat::Binary)?,
+        Format::PlyAscii => model.write_ply(create(&out)?, PlyFormat::Ascii)?,
+        _ => model.write_obj(create(&out)?)?,
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const VASE: &str = include_str!("../assets/vase.hom");
+
+    /// Build a model and get placed bounds of its first node
+    fn bounds(muon: &str, opts: &BuildOptions) -> Result<(Vec3, Vec3)> {
+        let def: ModelDef = muon.parse()?;
+        let mut glb = Vec::new();
+        write_gltf(&def, "test", &mut glb, &opts.gltf_options(&def)?)?;
+        let len = u32::from_le_bytes(glb[12..16].try_into()?) as usize;
+        let json: Value = serde_json::from_slice(&glb[20..20 + len])?;
+        let vec3 = |v: &Value| {
+            let f = |i: usize| v[i].as_f64().unwrap_or(0.0) as f32;
+            Vec3::new(f(0), f(1), f(2))
+        };
+        let acc = &json["accessors"][1];
+        let translation = vec3(&json["nodes"][0]["translation"]);
+        Ok((
+            vec3(&acc["min"]) + translation,
+            vec3(&acc["max"]) + translation,
+        ))
+    }
+
+    #[test]
+    fn up_axis() -> Result<()> {
+        const TALL: &str = "ring:\n  points: 0.5 * 6\nring:\n  axis: 0 3 0\n";
+        let (min, max) = bounds(TALL, &BuildOptions::default())?;
+        let size = max - min;
+        assert!(size.y > size.x && size.y > size.z);
+        let opts = BuildOptions {
+            up: UpAxis::Z,
+            ..Default::default()
+        };
+        let (zmin, zmax) = bounds(TALL, &opts)?;
+        let zsize = zmax - zmin;
+        assert!(zsize.z > zsize.x && zsize.z > zsize.y);
+        assert!((zsize.z - size.y).abs() < 1e-6);
+        // recorded in extras and detected
+        let def: ModelDef = TALL.parse()?;
+        let path = std::env::temp_dir().join("hom_up_axis.glb");
+        write_gltf(&def, "test", create(&path)?, &opts.gltf_options(&def)?)?;
+        assert_eq!(detect_up(&path)?, UpAxis::Z);
+        write_gltf(&def, "test", create(&path)?, &GltfOptions::default())?;
+        assert_eq!(detect_up(&path)?, UpAxis::Y);
+        std::fs::remove_file(path)?;
+        assert_eq!(parse_up("z"), Ok(UpAxis::Z));
+        assert!(parse_up("x").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn arguments() {
+        assert_eq!(parse_vector("2,3,4"), Ok(Vec3::new(2.0, 3.0, 4.0)));
+        assert_eq!(parse_vector("0, -1.5 ,0"), Ok(Vec3::new(0.0, -1.5, 0.0)));
+        assert!(parse_vector("1,2").is_err());
+        assert!(parse_vector("1,2,3,4").is_err());
+        assert!(parse_vector("1,x,3").is_err());
+        assert!(parse_vector("1,inf,3").is_err());
+        assert!(parse_vector("").is_err());
+        assert_eq!(parse_fov("45"), Ok(45.0));
+        assert!(parse_fov("0").is_err());
+        assert!(parse_fov("180").is_err());
+        assert!(parse_fov("wide").is_err());
+        assert_eq!(parse_lods("0.5,0.25"), Ok(vec![0.5, 0.25]));
+        assert!(parse_lods("0.5,1").is_err());
+        assert!(parse_lods("0").is_err());
+        assert!(parse_lods("").is_err());
+        assert_eq!(parse_format("gltf"), Ok(Format::Gltf));
+        assert_eq!(parse_format("gltf-embedded"), Ok(Format::GltfEmbedded));
+        assert_eq!(parse_format("obj"), Ok(Format::Obj));
+        assert_eq!(parse_format("ply-ascii"), Ok(Format::PlyAscii));
+        assert!(parse_format("stl").is_err());
+    }
+
+    #[test]
+    fn formats() -> Result<()> {
+        let def: ModelDef = VASE.parse()?;
+        let options = GltfOptions::default();
+        let path = std::env::temp_dir().join("hom_formats.hom");
+        let out = write_model(&def, &path, Format::Glb, &options)?;
+        let glb = read_gltf_json(&out)?;
+        std::fs::remove_file(out)?;
+        let out = write_model(&def, &path, Format::Gltf, &options)?;
+        let bin = output_path(&path, "bin")?;
+        let mut gltf = read_gltf_json(&out)?;
+        assert_eq!(gltf["buffers"][0]["uri"], "hom_formats.bin");
+        assert_eq!(
+            gltf["buffers"][0]["byteLength"],
+            std::fs::metadata(&bin)?.len()
+        );
+        gltf["buffers"][0].as_object_mut().unwrap().remove("uri");
+        assert_eq!(gltf, glb);
+        std::fs::remove_file(bin)?;
+        let out = write_model(&def, &path, Format::GltfEmbedded, &options)?;
+        let gltf = read_gltf_json(&out)?;
+        let uri = gltf["buffers"][0]["uri"].as_str().unwrap();
+        assert!(uri.starts_with("data:application/octet-stream;base64,"));
+        assert_eq!(gltf["scenes"][0]["name"], "hom_formats");
+        std::fs::remove_file(out)?;
+        let out = write_model(&def, &path, Format::Obj, &options)?;
+        let obj = std::fs::read_to_string(&out)?;
+        assert!(obj.contains("\no hom_formats\n"));
+        let faces = obj.lines().filter(|l| l.starts_with("f ")).count();
+        let husk = Husk::try_from(&def)?;
+        assert_eq!(faces, husk.into_mesh()?.indices().len() / 3);
+        std::fs::remove_file(out)?;
+        let out = write_model(&def, &path, Format::PlyAscii, &options)?;
+        let ply = std::fs::read_to_string(&out)?;
+        assert!(ply.starts_with("ply\nformat ascii 1.0\n"));
+        std::fs::remove_file(out)?;
+        Ok(())
+    }
+
+    #[test]
+    fn stats() -> Result<()> {
+        let def: ModelDef = VASE.parse()?;
+        let path = std::env::temp_dir().join("hom_stats.hom");
+        std::fs::write(&path, VASE)?;
+        let stats = model_stats(&path)?;
+        let mesh = Husk::try_from(&def)?.into_mesh()?;
+        assert_eq!(stats.faces, mesh.indices().len() / 3);
+        assert_eq!(stats.boundary_edges, mesh.boundary_edges());
+        assert!(stats.surface_area > 0.0);
+        let glb = write_model(&def, &path, Format::Glb, &Default::default())?;
+        assert_eq!(model_stats(&glb)?, stats);
+        std::fs::remove_file(glb)?;
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn export() -> Result<()> {
+        let def: ModelDef = VASE.parse()?;
+        let path = std::env::temp_dir().join("hom_export.hom");
+        let glb = write_model(&def, &path, Format::Glb, &Default::default())?;
+        let out = export_model(&glb, Format::Obj, None)?;
+        assert_eq!(out, output_path(&path, "obj")?);
+        let obj = std::fs::read_to_string(&out)?;
+        let faces = obj.lines().filter(|l| l.starts_with("f ")).count();
+        let husk = Husk::try_from(&def)?;
+        assert_eq!(faces, husk.into_mesh()?.indices().len() / 3);
+        std::fs::remove_file(out)?;
+        let ply = std::env::temp_dir().join("hom_export_binary.ply");
+        let out = export_model(&glb, Format::Ply, Some(ply.clone()))?;
+        assert_eq!(out, ply);
+        let ply = std::fs::read(&out)?;
+        assert!(ply.starts_with(b"ply\nformat binary_little_endian 1.0\n"));
+        std::fs::remove_file(out)?;
+        assert!(export_model(&glb, Format::Gltf, None).is_err());
+        std::fs::remove_file(glb)?;
+        Ok(())
+    }
+
+    #[test]
+    fn paths() -> Result<()> {
+        let path = Path::new("models/creature.hom");
+        assert_eq!(output_path(path, "glb")?, Path::new("models/creature.glb"));
+        assert_eq!(
+            output_path(path, "normals.glb")?,
+            Path::new("models/creature.normals.glb")
+        );
+        assert!(output_path(Path::new(""), "glb").is_err());
+        assert_eq!(model_name(path)?, "creature");
+        Ok(())
+    }
+
+    /// Build a model and get its glTF JSON
+    fn gltf_json(muon: &str, name: &str, opts: &BuildOptions) -> Result<Value> {
+        let def: ModelDef = muon.parse()?;
+        let mut glb = Vec::new();
+        write_gltf(&def, name, &mut glb, &opts.gltf_options(&def)?)?;
+        let len = u32::from_le_bytes(glb[12..16].try_into()?) as usize;
+        Ok(serde_json::from_slice(&glb[20..20 + len])?)
+    }
+
+    #[test]
+    fn names() -> Result<()> {
+        let opts = BuildOptions {
+            copyright: Some("CC0".to_string()),
+            ..Default::default()
+        };
+        let json = gltf_json(VASE, "vase", &opts)?;
+        assert_eq!(json["meshes"][0]["name"], "vase");
+        assert_eq!(json["nodes"][0]["name"], "vase");
+        assert_eq!(json["scenes"][0]["name"], "vase");
+        assert_eq!(json["asset"]["copyright"], "CC0");
+        let wizard = include_str!("../assets/wizard.hom");
+        let json = gltf_json(wizard, "scene", &BuildOptions::default())?;
+        assert_eq!(json["scenes"][0]["name"], "scene");
+        assert_ne!(json["nodes"][0]["name"], "scene");
+        assert!(json["asset"].get("copyright").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn provenance() -> Result<()> {
+        let def: ModelDef = VASE.parse()?;
+        let path = Path::new("models/vase.hom");
+        let options = with_metadata(GltfOptions::default(), path, &def)?;
+        let mut glb = Vec::new();
+        write_gltf(&def, "vase", &mut glb, &options)?;
+        let len = u32::from_le_bytes(glb[12..16].try_into()?) as usize;
+        let json: Value = serde_json::from_slice(&glb[20..20 + len])?;
+        let extras = &json["asset"]["extras"];
+        assert_eq!(extras["source"], "vase.hom");
+        assert_eq!(extras["hom"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(extras.get("seed").is_some(), def.seed().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn metadata() -> Result<()> {
+        let def: ModelDef = VASE.parse()?;
+        let options = BuildOptions::default().gltf_options(&def)?;
+        assert!(options.asset_extras.is_none());
+        assert!(options.scale.is_none());
+        let opts = BuildOptions {
+            scale: Some(0.01),
+            center: true,
+            ..Default::default()
+        };
+        let options = opts.gltf_options(&def)?;
+        assert_eq!(options.scale, Some(0.01));
+        assert!(options.center);
+        let extras = options.asset_extras.unwrap();
+        assert_eq!(extras["scale"], json!(0.01f32));
+        assert_eq!(extras["center"], true);
+        assert!(extras.get("quantize").is_none());
+        let opts = BuildOptions {
+            quantize: true,
+            ..Default::default()
+        };
+        let options = opts.gltf_options(&def)?;
+        assert!(options.quantize);
+        assert_eq!(options.asset_extras.unwrap()["quantize"], true);
+        let opts = BuildOptions {
+            scale: Some(-1.0),
+            ..Default::default()
+        };
+        assert!(opts.gltf_options(&def).is_err());
+        let opts = BuildOptions {
+            turntable: Some(0.0),
+            ..Default::default()
+        };
+        assert!(opts.gltf_options(&def).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn scale_center() -> Result<()> {
+        let (min, max) = bounds(VASE, &BuildOptions::default())?;
+        let opts = BuildOptions {
+            scale: Some(0.01),
+            ..Default::default()
+        };
+        let (smin, smax) = bounds(VASE, &opts)?;
+        assert!(smin.distance(min * 0.01) < 1e-6);
+        assert!(smax.distance(max * 0.01) < 1e-6);
+        let opts = BuildOptions {
+            scale: Some(0.01),
+            center: true,
+            ..Default::default()
+        };
+        let (cmin, cmax) = bounds(VASE, &opts)?;
+        assert!((cmax - cmin).distance(smax - smin) < 1e-6);
+        assert!(cmin.y.abs() < 1e-6);
+        assert!((cmin.x + cmax.x).abs() < 1e-6);
+        assert!((cmin.z + cmax.z).abs() < 1e-6);
+        Ok(())
+    }
+
+    /// Get bounds of vertex positions in an OBJ file
+    fn obj_bounds(path: &Path) -> Result<(Vec3, Vec3)> {
+        let obj = std::fs::read_to_string(path)?;
+        let mut bounds = (Vec3::INFINITY, Vec3::NEG_INFINITY);
+        for v in obj.lines().filter_map(|l| l.strip_prefix("v ")) {
+            let c = v
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<std::result::Result<Vec<f32>, _>>()?;
+            let p = Vec3::new(c[0], c[1], c[2]);
+            bounds = (bounds.0.min(p), bounds.1.max(p));
+        }
+        Ok(bounds)
+    }
+
+    #[test]
+    fn obj_placement() -> Result<()> {
+        let def: ModelDef = VASE.parse()?;
+        let path = std::env::temp_dir().join("hom_obj_placement.hom");
+        let opts = BuildOptions {
+            scale: Some(0.01),
+            center: true,
+            up: UpAxis::Z,
+            format: Format::Obj,
+            ..Default::default()
+        };
+        let (min, max) = bounds(VASE, &opts)?;
+        let out =
+            write_model(&def, &path, Format::Obj, &opts.gltf_options(&def)?)?;
+        let (omin, omax) = obj_bounds(&out)?;
+        std::fs::remove_file(out)?;
+        assert!(omin.distance(min) < 1e-6);
+        assert!(omax.distance(max) < 1e-6);
+        assert!(omin.z.abs() < 1e-6);
+        assert!(opts.check_format().is_ok());
+        let opts = BuildOptions {
+            quantize: true,
+            ..opts
+        };
+        assert!(opts.check_format().is_err());
+        Ok(())
+    }
+
+    /// Get bounds of vertex positions in an ASCII PLY file
+    fn ply_bounds(path: &Path) -> Result<(Vec3, Vec3)> {
+        let ply = std::fs::read_to_string(path)?;
+        let (header, body) =
+            ply.split_once("end_header\n").context("Invalid PLY")?;
+        let count: usize = header
+            .lines()
+            .find_map(|l| l.strip_prefix("element vertex "))
+            .context("Invalid PLY")?
+            .parse()?;
+        let mut bounds = (Vec3::INFINITY, Vec3::NEG_INFINITY);
+        for v in body.lines().take(count) {
+            let c = v
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<std::result::Result<Vec<f32>, _>>()?;
+            let p = Vec3::new(c[0], c[1], c[2]);
+            bounds = (bounds.0.min(p), bounds.1.max(p));
+        }
+        Ok(bounds)
+    }
+
+    #[test]
+    fn ply_placement() -> Result<()> {
+        let def: ModelDef = VASE.parse()?;
+        let path = std::env::temp_dir().join("hom_ply_placement.hom");
+        let opts = BuildOptions {
+            scale: Some(0.01),
+            center: true,
+            up: UpAxis::Z,
+            format: Format::PlyAscii,
+            ..Default::default()
+        };
+        let (min, max) = bounds(VASE, &opts)?;
+        let options = opts.gltf_options(&def)?;
+        let out = write_model(&def, &path, Format::PlyAscii, &options)?;
+        let (pmin, pmax) = ply_bounds(&out)?;
+        std::fs::remove_file(out)?;
+        assert!(pmin.distance(min) < 1e-6);
+        assert!(pmax.distance(max) < 1e-6);
+        assert!(pmin.z.abs() < 1e-6);
+        let opts = BuildOptions {
+            ao: Some(8),
+            ..opts
+        };
+        assert!(opts.check_format().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn normals_placement() -> Result<()> {
+        let def: ModelDef = VASE.parse()?;
+        let opts = BuildOptions {
+            scale: Some(0.01),
+            center: true,
+            up: UpAxis::Z,
+            ..Default::default()
+        };
+        let (min, max) = bounds(VASE, &opts)?;
+        let mut glb = Vec::new();
+        write_normals_gltf(&def, "test", &mut glb, &opts.gltf_options(&def)?)?;
+        let len = u32::from_le_bytes(glb[12..16].try_into()?) as usize;
+        let json: Value = serde_json::from_slice(&glb[20..20 + len])?;
+        let vec3 = |v: &Value| {
+            let f = |i: usize| v[i].as_f64().unwrap_or(0.0) as f32;
+            Vec3::new(f(0), f(1), f(2))
+        };
+        let translation = vec3(&json["nodes"][0]["translation"]);
+        let nmin = vec3(&json["accessors"][0]["min"]) + translation;
+        let nmax = vec3(&json["accessors"][0]["max"]) + translation;
+        // lines extend beyond the model by up to their (scaled) length
+        let length = NORMAL_LENGTH * 0.01 + 1e-6;
+        assert!(nmin.cmple(min).all() && nmin.cmpge(min - length).all());
+        assert!(nmax.cmpge(max).all() && nmax.cmple(max + length).all());
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+// color.rs     Debug colors
+//
+// Copyright (c) 2024  Douglas Lau
+//
+
+/// Parse glTF mesh / primitive indices from an asset label
+///
+/// Labels look like `Mesh2/Primitive1`.
+pub fn primitive_index(label: &str) -> Option<(usize, usize)> {
+    let (mesh, prim) = label.split_once('/')?;
+    let mesh = mesh.strip_prefix("Mesh")?.parse().ok()?;
+    let prim = prim.strip_prefix("Primitive")?.parse().ok()?;
+    Some((mesh, prim))
+}
+
+/// Mix bits of a value (splitmix64 finalizer)
+fn mix(mut v: u64) -> u64 {
+    v = (v ^ (v >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    v = (v ^ (v >> 27)).wrapping_mul(0x94d049bb133111eb);
+    v ^ (v >> 31)
+}
+
+/// Convert HSV color to RGB
+///
+/// Hue is in degrees; saturation and value are in `0.0..=1.0`.
+fn hsv_to_rgb(hue: f32, sat: f32, val: f32) -> [f32; 3] {
+    let c = val * sat;
+    let h = (hue % 360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = val - c;
+    [r + m, g + m, b + m]
+}
+
+/// Get a stable color (RGB) for a glTF primitive
+///
+/// The color is a hash of the mesh and primitive indices, so it does not
+/// change between runs.
+pub fn primitive_color(mesh: usize, prim: usize) -> [f32; 3] {
+    let hash = mix(((mesh as u64) << 32) | prim as u64);
+    let hue = (hash % 360) as f32;
+    hsv_to_rgb(hue, 0.7, 0.9)
+}
+
+/// Pixel size of checkerboard cells
+const CHECKER_CELL: u32 = 16;
+
+/// Make checkerboard image pixels (RGBA)
+///
+/// Returns the image size (width and height) and pixel data.
+pub fn checker_pixels(cells: u32) -> (u32, Vec<u8>) {
+    let size = cells * CHECKER_CELL;
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let light = (x / CHECKER_CELL + y / CHECKER_CELL).is_multiple_of(2);
+            let v = if light { 0xE0 } else { 0x40 };
+            pixels.extend([v, v, v, 0xFF]);
+        }
+    }
+    (size, pixels)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn labels() {
+        assert_eq!(primitive_index("Mesh0/Primitive0"), Some((0, 0)));
+        assert_eq!(primitive_index("Mesh12/Primitive3"), Some((12, 3)));
+        assert_eq!(primitive_index("Mesh0"), None);
+        assert_eq!(primitive_index("Material0/Primitive0"), None);
+        assert_eq!(primitive_index("Mesh0/Primitive"), None);
+    }
+
+    #[test]
+    fn hsv() {
+        assert_eq!(hsv_to_rgb(0.0, 1.0, 1.0), [1.0, 0.0, 0.0]);
+        assert_eq!(hsv_to_rgb(120.0, 1.0, 1.0), [0.0, 1.0, 0.0]);
+        assert_eq!(hsv_to_rgb(240.0, 1.0, 1.0), [0.0, 0.0, 1.0]);
+        assert_eq!(hsv_to_rgb(60.0, 0.0, 0.5), [0.5, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn stable_colors() {
+        assert_eq!(primitive_color(0, 1), primitive_color(0, 1));
+        let colors: Vec<_> = (0..4)
+            .flat_map(|m| (0..4).map(move |p| primitive_color(m, p)))
+            .collect();
+        for (i, c0) in colors.iter().enumerate() {
+            assert!(c0.iter().all(|c| (0.0..=1.0).contains(c)));
+            for c1 in &colors[i + 1..] {
+                assert_ne!(c0, c1);
+            }
+        }
+    }
+
+    #[test]
+    fn checker() {
+        let (size, pixels) = checker_pixels(2);
+        assert_eq!(size, 32);
+        assert_eq!(pixels.len(), 32 * 32 * 4);
+        let px = |x: usize, y: usize| pixels[(y * 32 + x) * 4];
+        assert_eq!(px(0, 0), 0xE0);
+        assert_eq!(px(15, 15), 0xE0);
+        assert_eq!(px(16, 0), 0x40);
+        assert_eq!(px(0, 16), 0x40);
+        assert_eq!(px(31, 31), 0xE0);
+    }
+}
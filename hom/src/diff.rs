@@ -0,0 +1,160 @@
+// diff.rs      Model comparison
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use anyhow::{Context, Result};
+use homunculus::{import_glb, GltfImport, MeshDiff};
+use serde_json::{json, Value};
+use std::fmt;
+use std::fs::File;
+use std::path::Path;
+
+/// Differences between two glTF models
+#[derive(Debug)]
+pub struct ModelDiff {
+    /// Primitive counts (old, new)
+    primitives: (usize, usize),
+
+    /// Material counts (old, new)
+    materials: (usize, usize),
+
+    /// Mesh counts (old, new)
+    meshes: (usize, usize),
+
+    /// Differences of meshes present in both models
+    diffs: Vec<MeshDiff>,
+}
+
+/// Import a GLB file
+fn import(path: &Path) -> Result<GltfImport> {
+    let file = File::open(path)
+        .with_context(|| format!("{} not found", path.display()))?;
+    import_glb(file).with_context(|| format!("Importing {}", path.display()))
+}
+
+/// Compare two GLB files
+pub fn diff_files(old: &Path, new: &Path) -> Result<ModelDiff> {
+    Ok(ModelDiff::new(&import(old)?, &import(new)?))
+}
+
+impl ModelDiff {
+    /// Compare two imported models
+    pub fn new(old: &GltfImport, new: &GltfImport) -> Self {
+        let diffs = old
+            .meshes
+            .iter()
+            .zip(&new.meshes)
+            .map(|(m0, m1)| m0.diff(m1))
+            .collect();
+        ModelDiff {
+            primitives: (old.primitives, new.primitives),
+            materials: (old.materials, new.materials),
+            meshes: (old.meshes.len(), new.meshes.len()),
+            diffs,
+        }
+    }
+
+    /// Check if differences exceed a tolerance
+    pub fn exceeds(&self, tolerance: f32) -> bool {
+        self.primitives.0 != self.primitives.1
+            || self.materials.0 != self.materials.1
+            || self.meshes.0 != self.meshes.1
+            || self.diffs.iter().any(|d| d.exceeds(tolerance))
+    }
+
+    /// Get differences as JSON
+    pub fn to_json(&self) -> Value {
+        let meshes: Vec<_> = self
+            .diffs
+            .iter()
+            .map(|d| {
+                json!({
+                    "vertices": [d.vertices.0, d.vertices.1],
+                    "faces": [d.faces.0, d.faces.1],
+                    "min_delta": d.min_delta.to_array(),
+                    "max_delta": d.max_delta.to_array(),
+                    "max_deviation": d.max_deviation,
+                })
+            })
+            .collect();
+        json!({
+            "primitives": [self.primitives.0, self.primitives.1],
+            "materials": [self.materials.0, self.materials.1],
+            "meshes": [self.meshes.0, self.meshes.1],
+            "mesh_diffs": meshes,
+        })
+    }
+}
+
+impl fmt::Display for ModelDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (p0, p1) = self.primitives;
+        writeln!(f, "primitives: {p0} -> {p1}")?;
+        let (m0, m1) = self.materials;
+        writeln!(f, "materials: {m0} -> {m1}")?;
+        for (i, d) in self.diffs.iter().enumerate() {
+            let (v0, v1) = d.vertices;
+            let (f0, f1) = d.faces;
+            writeln!(f, "mesh {i}: vertices {v0} -> {v1}, faces {f0} -> {f1}")?;
+            writeln!(f, "  bounds min delta: {}", d.min_delta)?;
+            writeln!(f, "  bounds max delta: {}", d.max_delta)?;
+            writeln!(f, "  max vertex deviation: {}", d.max_deviation)?;
+        }
+        let (n0, n1) = self.meshes;
+        for i in n1..n0 {
+            writeln!(f, "mesh {i}: removed")?;
+        }
+        for i in n0..n1 {
+            writeln!(f, "mesh {i}: added")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use homunculus::{GltfOptions, Husk, ModelDef};
+
+    const VASE: &str = include_str!("../assets/vase.hom");
+
+    fn vase(options: &GltfOptions) -> Result<GltfImport> {
+        let def: ModelDef = VASE.parse()?;
+        let mut glb = Vec::new();
+        Husk::try_from(&def)?.write_gltf_with(&mut glb, options)?;
+        Ok(import_glb(&glb[..])?)
+    }
+
+    #[test]
+    fn identical() -> Result<()> {
+        let vase = vase(&GltfOptions::default())?;
+        let diff = ModelDiff::new(&vase, &vase);
+        assert!(!diff.exceeds(0.0));
+        let json = diff.to_json();
+        assert_eq!(json["mesh_diffs"][0]["max_deviation"], 0.0);
+        assert_eq!(json["meshes"], json!([1, 1]));
+        assert!(diff.to_string().contains("max vertex deviation: 0\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn scaled() -> Result<()> {
+        let old = vase(&GltfOptions::default())?;
+        let options = GltfOptions {
+            scale: Some(1.01),
+            ..Default::default()
+        };
+        let new = vase(&options)?;
+        let diff = ModelDiff::new(&old, &new);
+        assert!(diff.exceeds(0.001));
+        assert!(!diff.exceeds(0.1));
+        let dev = diff.to_json()["mesh_diffs"][0]["max_deviation"]
+            .as_f64()
+            .unwrap();
+        assert!(dev > 0.001 && dev < 0.1);
+        let diff = ModelDiff::new(&old, &GltfImport::default());
+        assert!(diff.exceeds(f32::MAX));
+        assert!(diff.to_string().contains("mesh 0: removed"));
+        Ok(())
+    }
+}
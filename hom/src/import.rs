@@ -0,0 +1,375 @@
+// import.rs    Mesh import (slicing into rings)
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use anyhow::{bail, Context, Result};
+use glam::{Vec2, Vec3};
+use homunculus::import_glb;
+use std::f32::consts::PI;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// Triangle soup
+type Triangles = Vec<[Vec3; 3]>;
+
+/// Tolerance for matching slice segment end-points
+const EPSILON: f32 = 1e-5;
+
+/// Horizontal slice of a mesh
+struct Slice {
+    /// Height of slice
+    height: f32,
+
+    /// Center of slice loop (X / Z)
+    center: Vec2,
+
+    /// Distances from center at each spoke angle
+    distances: Vec<f32>,
+}
+
+/// Read triangles from a glTF binary (`.glb`)
+fn read_glb<R: Read>(reader: R) -> Result<Triangles> {
+    let model = import_glb(reader)?;
+    let mut tris = Vec::new();
+    for mesh in &model.meshes {
+        let pos = mesh.positions();
+        for idx in mesh.indices().chunks_exact(3) {
+            let v = |i: usize| pos[usize::from(idx[i].0)];
+            tris.push([v(0), v(1), v(2)]);
+        }
+    }
+    Ok(tris)
+}
+
+/// Read triangles from a Wavefront `.obj`
+///
+/// Only vertex positions and faces are used; polygons are triangulated as
+/// fans.
+fn read_obj<R: BufRead>(reader: R) -> Result<Triangles> {
+    let mut verts = Vec::new();
+    let mut tris = Vec::new();
+    for (n, line) in reader.lines().enumerate() {
+        let line = line?;
+        let mut vals = line.split_whitespace();
+        match vals.next() {
+            Some("v") => {
+                let xyz = vals
+                    .take(3)
+                    .map(|v| v.parse::<f32>())
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .with_context(|| format!("OBJ line {}", n + 1))?;
+                if let [x, y, z] = xyz[..] {
+                    verts.push(Vec3::new(x, y, z));
+                } else {
+                    bail!("OBJ line {}: invalid vertex", n + 1);
+                }
+            }
+            Some("f") => {
+                let mut face = Vec::new();
+                for v in vals {
+                    // vertex index is before first slash
+                    let idx = v.split('/').next().unwrap_or_default();
+                    let idx: isize = idx
+                        .parse()
+                        .with_context(|| format!("OBJ line {}", n + 1))?;
+                    let idx = if idx < 0 {
+                        verts.len() as isize + idx
+                    } else {
+                        idx - 1
+                    };
+                    match usize::try_from(idx).ok().and_then(|i| verts.get(i)) {
+                        Some(pos) => face.push(*pos),
+                        None => bail!("OBJ line {}: invalid index", n + 1),
+                    }
+                }
+                for i in 2..face.len() {
+                    tris.push([face[0], face[i - 1], face[i]]);
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(tris)
+}
+
+/// Read triangles from a mesh file (`.glb` or `.obj`)
+fn read_mesh(path: &Path) -> Result<Triangles> {
+    let file = File::open(path)
+        .with_context(|| format!("{} not found", path.display()))?;
+    match path.extension() {
+        Some(ext) if ext == "glb" => read_glb(file),
+        Some(ext) if ext == "obj" => read_obj(BufReader::new(file)),
+        _ => bail!("Unsupported mesh format: {}", path.display()),
+    }
+}
+
+/// Slice triangles with a horizontal plane into line segments (X / Z)
+fn slice_segments(tris: &Triangles, height: f32) -> Vec<(Vec2, Vec2)> {
+    let mut segments = Vec::new();
+    for tri in tris {
+        let mut pts = Vec::with_capacity(2);
+        for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+            if (a.y < height) != (b.y < height) {
+                let t = (height - a.y) / (b.y - a.y);
+                let p = a.lerp(b, t);
+                pts.push(Vec2::new(p.x, p.z));
+            }
+        }
+        if let [p0, p1] = pts[..] {
+            if p0.distance(p1) > EPSILON {
+                segments.push((p0, p1));
+            }
+        }
+    }
+    segments
+}
+
+/// Chain line segments into closed loops
+fn chain_loops(mut segments: Vec<(Vec2, Vec2)>) -> Vec<Vec<Vec2>> {
+    let mut loops = Vec::new();
+    while let Some((start, mut end)) = segments.pop() {
+        let mut pts = vec![start];
+        while end.distance(start) > EPSILON {
+            let next = segments.iter().position(|(p0, p1)| {
+                p0.distance(end) <= EPSILON || p1.distance(end) <= EPSILON
+            });
+            let Some(i) = next else {
+                break;
+            };
+            let (p0, p1) = segments.swap_remove(i);
+            pts.push(end);
+            end = if p0.distance(end) <= EPSILON { p1 } else { p0 };
+        }
+        if pts.len() > 2 {
+            loops.push(pts);
+        }
+    }
+    loops
+}
+
+/// Calculate signed area of a loop
+fn loop_area(pts: &[Vec2]) -> f32 {
+    let n = pts.len();
+    (0..n)
+        .map(|i| pts[i].perp_dot(pts[(i + 1) % n]))
+        .sum::<f32>()
+        * 0.5
+}
+
+/// Calculate centroid of a loop
+fn loop_centroid(pts: &[Vec2]) -> Vec2 {
+    let area = loop_area(pts);
+    if area.abs() <= EPSILON * EPSILON {
+        return pts.iter().sum::<Vec2>() / pts.len() as f32;
+    }
+    let n = pts.len();
+    let sum: Vec2 = (0..n)
+        .map(|i| {
+            let (p0, p1) = (pts[i], pts[(i + 1) % n]);
+            (p0 + p1) * p0.perp_dot(p1)
+        })
+        .sum();
+    sum / (6.0 * area)
+}
+
+/// Cast a ray from `center` and get the farthest hit distance on a loop
+fn ray_distance(pts: &[Vec2], center: Vec2, dir: Vec2) -> f32 {
+    let n = pts.len();
+    let mut dist = 0.0f32;
+    for i in 0..n {
+        let (p0, p1) = (pts[i], pts[(i + 1) % n]);
+        let edge = p1 - p0;
+        let denom = dir.perp_dot(edge);
+        if denom.abs() <= f32::EPSILON {
+            continue;
+        }
+        let rel = p0 - center;
+        let t = rel.perp_dot(edge) / denom;
+        let s = rel.perp_dot(dir) / denom;
+        if t >= 0.0 && (-EPSILON..=1.0 + EPSILON).contains(&s) {
+            dist = dist.max(t);
+        }
+    }
+    dist
+}
+
+impl Slice {
+    /// Make a slice of triangles at a height
+    fn new(tris: &Triangles, height: f32, spokes: usize) -> Result<Self> {
+        let mut loops = chain_loops(slice_segments(tris, height));
+        if loops.is_empty() {
+            bail!("No mesh at height {height}");
+        }
+        if loops.len() > 1 {
+            eprintln!(
+                "Slice at height {height}: {} loops, using largest",
+                loops.len()
+            );
+            loops.sort_by(|a, b| {
+                loop_area(a).abs().total_cmp(&loop_area(b).abs())
+            });
+        }
+        // unwrap note: loops is not empty
+        let pts = loops.pop().unwrap();
+        let center = loop_centroid(&pts);
+        let distances = (0..spokes)
+            .map(|i| {
+                // same direction as ring spokes (rotation around Y axis)
+                let angle = 2.0 * PI * i as f32 / spokes as f32;
+                let dir = Vec2::new(angle.cos(), -angle.sin());
+                ray_distance(&pts, center, dir)
+            })
+            .collect();
+        Ok(Slice {
+            height,
+            center,
+            distances,
+        })
+    }
+
+    /// Get slice center position
+    fn center(&self) -> Vec3 {
+        Vec3::new(self.center.x, self.height, self.center.y)
+    }
+}
+
+/// Format a number for a `.hom` file
+fn num(val: f32) -> String {
+    let s = format!("{val:.4}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    match s {
+        "-0" | "" => "0".to_string(),
+        _ => s.to_string(),
+    }
+}
+
+/// Slice triangles into a `.hom` model definition
+fn slice_model(
+    tris: &Triangles,
+    rings: usize,
+    spokes: usize,
+    source: &str,
+) -> Result<String> {
+    if rings < 2 || spokes < 3 {
+        bail!("At least 2 rings and 3 spokes required");
+    }
+    let (min, max) = tris
+        .iter()
+        .flatten()
+        .fold((f32::MAX, f32::MIN), |(min, max), p| {
+            (min.min(p.y), max.max(p.y))
+        });
+    if min >= max {
+        bail!("Mesh has no height");
+    }
+    // keep end slices slightly inside the mesh
+    let inset = (max - min) * 1e-4;
+    let mut hom = String::new();
+    writeln!(
+        hom,
+        "# Imported from {source} ({rings} rings, {spokes} spokes)"
+    )?;
+    let mut prev: Option<Vec3> = None;
+    for i in 0..rings {
+        let t = i as f32 / (rings - 1) as f32;
+        let height = (min + (max - min) * t).clamp(min + inset, max - inset);
+        let slice = Slice::new(tris, height, spokes)?;
+        let center = slice.center();
+        writeln!(hom, "ring:")?;
+        if let Some(prev) = prev {
+            let axis = center - prev;
+            let axis = [axis.x, axis.y, axis.z].map(num).join(" ");
+            writeln!(hom, "  axis: {axis}")?;
+        }
+        let points: Vec<_> = slice.distances.into_iter().map(num).collect();
+        writeln!(hom, "  points: {}", points.join(" "))?;
+        prev = Some(center);
+    }
+    Ok(hom)
+}
+
+/// Import a mesh file, slicing it into a `.hom` model definition
+///
+/// Branches are not supported; when a slice has multiple loops, only the
+/// largest is used.
+pub fn import_mesh(path: &Path, rings: usize, spokes: usize) -> Result<String> {
+    let tris = read_mesh(path)?;
+    let source = path.file_name().unwrap_or_default().to_string_lossy();
+    slice_model(&tris, rings, spokes, &source)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use homunculus::{GltfImport, Husk, ModelDef};
+
+    const URN: &str = "ring:\n  points: 1 * 12\n\
+        ring:\n  axis: 0 0.5 0\n  scale: 1.4\n\
+        ring:\n  scale: 1.2\n\
+        ring:\n  scale: 0.6\n\
+        ring:\n  scale: 0.8\n";
+
+    fn glb(muon: &str) -> Result<Vec<u8>> {
+        let def: ModelDef = muon.parse()?;
+        let mut glb = Vec::new();
+        Husk::try_from(&def)?.write_gltf(&mut glb)?;
+        Ok(glb)
+    }
+
+    fn import(glb: &[u8]) -> Result<GltfImport> {
+        Ok(import_glb(glb)?)
+    }
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        let original = glb(URN)?;
+        let tris = read_glb(&original[..])?;
+        let hom = slice_model(&tris, 5, 12, "urn.glb")?;
+        assert!(hom.starts_with("# Imported from urn.glb"));
+        let rebuilt = glb(&hom)?;
+        let old = import(&original)?;
+        let new = import(&rebuilt)?;
+        let diff = old.meshes[0].diff(&new.meshes[0]);
+        assert_eq!(diff.vertices.0, diff.vertices.1);
+        assert_eq!(diff.faces.0, diff.faces.1);
+        // stated tolerance: 0.1% of model height
+        assert!(!diff.exceeds(2.0 * 1e-3), "{diff:?}");
+        Ok(())
+    }
+
+    #[test]
+    fn obj() -> Result<()> {
+        let obj = "# tetrahedron\nv 1 0 0\nv -1 0 1\nv -1 0 -1\nv 0 2 0\n\
+                   f 1 2 3\nf 1/1 2/2 4/4\nf -3 -2 -1\nf 3 1 4\n";
+        let tris = read_obj(obj.as_bytes())?;
+        assert_eq!(tris.len(), 4);
+        assert_eq!(tris[2][2], Vec3::new(0.0, 2.0, 0.0));
+        let hom = slice_model(&tris, 3, 3, "tet.obj")?;
+        let def: ModelDef = hom.parse()?;
+        assert!(Husk::try_from(&def).is_ok());
+        assert!(read_obj("f 1 2 3\n".as_bytes()).is_err());
+        assert!(read_obj("v 1 0\n".as_bytes()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn loops() {
+        let square = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+            .map(|(x, y)| Vec2::new(x, y));
+        let segments =
+            (0..4).map(|i| (square[i], square[(i + 1) % 4])).collect();
+        let loops = chain_loops(segments);
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].len(), 4);
+        assert!((loop_area(&loops[0]).abs() - 1.0).abs() < 1e-6);
+        let center = loop_centroid(&square);
+        assert!(center.distance(Vec2::splat(0.5)) < 1e-6);
+        let d = ray_distance(&square, center, Vec2::X);
+        assert!((d - 0.5).abs() < 1e-6);
+        assert_eq!(num(0.25), "0.25");
+        assert_eq!(num(2.0), "2");
+        assert_eq!(num(-0.00001), "0");
+    }
+}
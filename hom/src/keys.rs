@@ -0,0 +1,338 @@
+// keys.rs      Viewer key bindings
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+
+/// Viewer actions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    ToggleHelp,
+    ToggleWireframe,
+    ToggleStage,
+    PrimitiveColors,
+    UvChecker,
+    LightDirection,
+    NextAnimation,
+    OrbitLeft,
+    OrbitRight,
+    OrbitUp,
+    OrbitDown,
+    ZoomIn,
+    ZoomOut,
+}
+
+/// Supported key names
+const KEY_NAMES: &[&str] = &[
+    "A",
+    "B",
+    "C",
+    "D",
+    "E",
+    "F",
+    "G",
+    "H",
+    "I",
+    "J",
+    "K",
+    "L",
+    "M",
+    "N",
+    "O",
+    "P",
+    "Q",
+    "R",
+    "S",
+    "T",
+    "U",
+    "V",
+    "W",
+    "X",
+    "Y",
+    "Z",
+    "0",
+    "1",
+    "2",
+    "3",
+    "4",
+    "5",
+    "6",
+    "7",
+    "8",
+    "9",
+    "Left",
+    "Right",
+    "Up",
+    "Down",
+    "PageUp",
+    "PageDown",
+    "Home",
+    "End",
+    "Insert",
+    "Delete",
+    "Space",
+    "Tab",
+    "Enter",
+    "Backspace",
+    "Escape",
+];
+
+/// Key bindings for viewer actions
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyBindings {
+    /// Key names, in `Action::ALL` order
+    keys: Vec<&'static str>,
+}
+
+impl Action {
+    /// All actions (help text order)
+    pub const ALL: [Action; 13] = [
+        Action::ToggleHelp,
+        Action::ToggleWireframe,
+        Action::ToggleStage,
+        Action::PrimitiveColors,
+        Action::UvChecker,
+        Action::LightDirection,
+        Action::NextAnimation,
+        Action::OrbitLeft,
+        Action::OrbitRight,
+        Action::OrbitUp,
+        Action::OrbitDown,
+        Action::ZoomIn,
+        Action::ZoomOut,
+    ];
+
+    /// Get action name (in config file)
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::ToggleHelp => "toggle_help",
+            Action::ToggleWireframe => "toggle_wireframe",
+            Action::ToggleStage => "toggle_stage",
+            Action::PrimitiveColors => "primitive_colors",
+            Action::UvChecker => "uv_checker",
+            Action::LightDirection => "light_direction",
+            Action::NextAnimation => "next_animation",
+            Action::OrbitLeft => "orbit_left",
+            Action::OrbitRight => "orbit_right",
+            Action::OrbitUp => "orbit_up",
+            Action::OrbitDown => "orbit_down",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+        }
+    }
+
+    /// Get action description (in help text)
+    fn description(self) -> &'static str {
+        match self {
+            Action::ToggleHelp => "toggle help text",
+            Action::ToggleWireframe => "toggle wireframe",
+            Action::ToggleStage => "toggle stage",
+            Action::PrimitiveColors => "toggle primitive colors",
+            Action::UvChecker => "toggle UV checkerboard",
+            Action::LightDirection => "light direction",
+            Action::NextAnimation => "next animation",
+            Action::OrbitLeft => "orbit left",
+            Action::OrbitRight => "orbit right",
+            Action::OrbitUp => "orbit up",
+            Action::OrbitDown => "orbit down",
+            Action::ZoomIn => "zoom in",
+            Action::ZoomOut => "zoom out",
+        }
+    }
+
+    /// Get default key name
+    fn default_key(self) -> &'static str {
+        match self {
+            Action::ToggleHelp => "Q",
+            Action::ToggleWireframe => "W",
+            Action::ToggleStage => "S",
+            Action::PrimitiveColors => "C",
+            Action::UvChecker => "U",
+            Action::LightDirection => "D",
+            Action::NextAnimation => "Space",
+            Action::OrbitLeft => "Left",
+            Action::OrbitRight => "Right",
+            Action::OrbitUp => "Up",
+            Action::OrbitDown => "Down",
+            Action::ZoomIn => "PageUp",
+            Action::ZoomOut => "PageDown",
+        }
+    }
+
+    /// Lookup an action by name
+    fn from_name(name: &str) -> Option<Self> {
+        Action::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    /// Get index in `ALL`
+    fn index(self) -> usize {
+        // unwrap note: every action is in ALL
+        Action::ALL.iter().position(|a| *a == self).unwrap()
+    }
+}
+
+/// Lookup a supported key name (case-insensitive)
+fn key_name(name: &str) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|k| k.eq_ignore_ascii_case(name))
+        .copied()
+}
+
+/// Parse a quoted TOML string value
+fn parse_quoted(val: &str) -> Option<&str> {
+    let val = val.strip_prefix('"')?;
+    let (val, rest) = val.split_once('"')?;
+    let rest = rest.trim_start();
+    (rest.is_empty() || rest.starts_with('#')).then_some(val)
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let keys = Action::ALL.iter().map(|a| a.default_key()).collect();
+        KeyBindings { keys }
+    }
+}
+
+impl KeyBindings {
+    /// Parse key bindings (TOML)
+    ///
+    /// Each line binds an action to a key, such as `orbit_left = "J"`;
+    /// actions not listed keep their default keys.
+    pub fn parse(toml: &str) -> Result<Self> {
+        let mut bindings = KeyBindings::default();
+        for (n, line) in toml.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line == "[keys]" {
+                continue;
+            }
+            let Some((name, val)) = line.split_once('=') else {
+                bail!("Line {}: expected `action = \"key\"`", n + 1);
+            };
+            let name = name.trim();
+            let Some(action) = Action::from_name(name) else {
+                bail!("Line {}: unknown action: {name}", n + 1);
+            };
+            let Some(key) = parse_quoted(val.trim()) else {
+                bail!("Line {}: expected quoted key name", n + 1);
+            };
+            let Some(key) = key_name(key) else {
+                bail!("Line {}: unknown key: {key}", n + 1);
+            };
+            bindings.keys[action.index()] = key;
+        }
+        Ok(bindings)
+    }
+
+    /// Get config file path (`~/.config/hom/keys.toml`)
+    fn config_path() -> Option<PathBuf> {
+        let dir = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+        Some(dir.join("hom").join("keys.toml"))
+    }
+
+    /// Load key bindings from config file, if it exists
+    pub fn load() -> Result<Self> {
+        match Self::config_path() {
+            Some(path) if path.exists() => {
+                let toml = std::fs::read_to_string(&path)?;
+                Self::parse(&toml)
+                    .with_context(|| format!("Key bindings {}", path.display()))
+            }
+            _ => Ok(KeyBindings::default()),
+        }
+    }
+
+    /// Get key name bound to an action
+    pub fn key(&self, action: Action) -> &'static str {
+        self.keys[action.index()]
+    }
+
+    /// Get conflicting bindings (keys bound to multiple actions)
+    pub fn conflicts(&self) -> Vec<(&'static str, Vec<Action>)> {
+        let mut conflicts: Vec<(&'static str, Vec<Action>)> = Vec::new();
+        for (i, key) in self.keys.iter().enumerate() {
+            let actions: Vec<_> = Action::ALL
+                .into_iter()
+                .filter(|a| self.key(*a) == *key)
+                .collect();
+            if actions.len() > 1 && actions[0] == Action::ALL[i] {
+                conflicts.push((key, actions));
+            }
+        }
+        conflicts
+    }
+
+    /// Get help text for key bindings
+    pub fn help_text(&self) -> String {
+        let mut text = String::from("_____ Keys _____");
+        for action in Action::ALL {
+            let key = self.key(action);
+            text.push('\n');
+            if key.len() == 1 {
+                text.push_str(&format!("'{key}'"));
+            } else {
+                text.push_str(key);
+            }
+            text.push_str(": ");
+            text.push_str(action.description());
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        let keys = KeyBindings::default();
+        assert_eq!(keys.key(Action::ToggleHelp), "Q");
+        assert_eq!(keys.key(Action::ZoomOut), "PageDown");
+        assert!(keys.conflicts().is_empty());
+        let help = keys.help_text();
+        assert!(help.contains("\n'W': toggle wireframe\n"));
+        assert!(help.ends_with("\nPageDown: zoom out"));
+        for key in Action::ALL.map(|a| a.default_key()) {
+            assert_eq!(key_name(key), Some(key));
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let toml = "# laptop keys\n[keys]\n\
+                    orbit_left = \"j\"  # lowercase\n\
+                    orbit_right = \"L\"\n\n\
+                    zoom_in=\"Home\"\n";
+        let keys = KeyBindings::parse(toml).unwrap();
+        assert_eq!(keys.key(Action::OrbitLeft), "J");
+        assert_eq!(keys.key(Action::OrbitRight), "L");
+        assert_eq!(keys.key(Action::ZoomIn), "Home");
+        assert_eq!(keys.key(Action::OrbitUp), "Up");
+        assert!(KeyBindings::parse("orbit = \"J\"").is_err());
+        assert!(KeyBindings::parse("orbit_up = \"F13\"").is_err());
+        assert!(KeyBindings::parse("orbit_up = J").is_err());
+        assert!(KeyBindings::parse("orbit_up \"J\"").is_err());
+        assert!(KeyBindings::parse("orbit_up = \"J\" x").is_err());
+    }
+
+    #[test]
+    fn conflicts() {
+        let toml = "orbit_left = \"W\"\nzoom_in = \"S\"\nzoom_out = \"S\"\n";
+        let keys = KeyBindings::parse(toml).unwrap();
+        assert_eq!(
+            keys.conflicts(),
+            vec![
+                ("W", vec![Action::ToggleWireframe, Action::OrbitLeft]),
+                (
+                    "S",
+                    vec![Action::ToggleStage, Action::ZoomIn, Action::ZoomOut]
+                ),
+            ]
+        );
+    }
+}
@@ -2,17 +2,24 @@
 //
 // Copyright (c) 2022-2023  Douglas Lau
 //
+mod build;
+mod color;
 mod cube;
-mod mesh;
-mod model;
+mod diff;
+mod import;
+mod keys;
 mod view;
 
-use crate::model::ModelDef;
-use anyhow::{Context, Result};
+use crate::build::{
+    parse_format, parse_fov, parse_lods, parse_up, parse_vector, BuildOptions,
+    Format,
+};
+use crate::view::ViewOptions;
+use anyhow::{bail, Result};
 use argh::FromArgs;
-use homunculus::Husk;
+use glam::Vec3;
+use homunculus::{Plane, SectionOptions, UpAxis};
 use std::ffi::OsString;
-use std::fs::File;
 use std::path::{Path, PathBuf};
 
 /// Crate name
@@ -24,17 +31,296 @@ const VERSION: &str = std::env!("CARGO_PKG_VERSION");
 /// Command-line arguments
 #[derive(FromArgs, PartialEq, Debug)]
 struct Args {
-    /// build model only
-    #[argh(switch, short = 'b')]
-    build: bool,
-
     /// show version
     #[argh(switch, short = 'V')]
     version: bool,
 
+    #[argh(subcommand)]
+    cmd: Option<Command>,
+}
+
+/// Sub-commands
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand)]
+enum Command {
+    Build(BuildCmd),
+    View(ViewCmd),
+    Diff(DiffCmd),
+    Import(ImportCmd),
+    Export(ExportCmd),
+    Section(SectionCmd),
+    Stats(StatsCmd),
+}
+
+/// Build a model (.hom to .glb)
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "build")]
+struct BuildCmd {
+    /// uniform scale factor (unit conversion)
+    #[argh(option)]
+    scale: Option<f32>,
+
+    /// recenter with base at origin
+    #[argh(switch)]
+    center: bool,
+
+    /// also write vertex normals as lines (.normals.glb)
+    #[argh(switch)]
+    debug_normals: bool,
+
+    /// up axis: y (default) or z
+    #[argh(option, from_str_fn(parse_up), default = "UpAxis::Y")]
+    up: UpAxis,
+
+    /// levels of detail, as ratios of faces to keep (0.5,0.25)
+    #[argh(option, from_str_fn(parse_lods))]
+    lods: Option<Vec<f32>>,
+
+    /// use MSFT_lod extension for levels of detail
+    #[argh(switch)]
+    msft_lod: bool,
+
+    /// bake ambient occlusion into vertex colors (rays per vertex)
+    #[argh(option)]
+    ao: Option<usize>,
+
+    /// copyright message for the glTF asset
+    #[argh(option)]
+    copyright: Option<String>,
+
+    /// quantize vertex attributes (KHR_mesh_quantization) for smaller files
+    #[argh(switch)]
+    quantize: bool,
+
+    /// record source file, version and seed in the glTF asset extras
+    #[argh(switch)]
+    metadata: bool,
+
+    /// add a turntable animation, spinning once in this many seconds
+    #[argh(option)]
+    turntable: Option<f32>,
+
+    /// weld vertices closer than this distance
+    #[argh(option)]
+    weld: Option<f32>,
+
+    /// mirror the mesh across the plane at 0 on this axis: x, y or z
+    #[argh(option)]
+    mirror: Option<String>,
+
+    /// subdivide the mesh this many times (Loop subdivision)
+    #[argh(option)]
+    subdivide: Option<usize>,
+
+    /// flip winding order of meshes which are inside out
+    #[argh(switch)]
+    fix_winding: bool,
+
+    /// build as a hollow shell with walls of this thickness
+    #[argh(option)]
+    thickness: Option<f32>,
+
+    /// output format: glb (default), gltf, gltf-embedded, obj, ply or
+    /// ply-ascii
+    #[argh(option, from_str_fn(parse_format), default = "Format::Glb")]
+    format: Format,
+
+    /// model file name (.hom)
+    #[argh(positional)]
+    file: OsString,
+}
+
+/// View a model (building it first if needed)
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "view")]
+struct ViewCmd {
+    /// up axis: y or z (default: detect from glTF extras)
+    #[argh(option, from_str_fn(parse_up))]
+    up: Option<UpAxis>,
+
+    /// checkerboard cells for UV preview
+    #[argh(option, default = "8")]
+    checker: u32,
+
+    /// camera position (x,y,z)
+    #[argh(option, from_str_fn(parse_vector))]
+    camera_pos: Option<Vec3>,
+
+    /// camera look target (x,y,z)
+    #[argh(option, from_str_fn(parse_vector))]
+    camera_look: Option<Vec3>,
+
+    /// camera vertical field of view (degrees)
+    #[argh(option, from_str_fn(parse_fov))]
+    fov: Option<f32>,
+
     /// model file name (.hom, .glb, .gltf)
     #[argh(positional)]
-    file: Option<OsString>,
+    file: OsString,
+}
+
+/// Compare two glTF models structurally
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "diff")]
+struct DiffCmd {
+    /// output as JSON
+    #[argh(switch)]
+    json: bool,
+
+    /// maximum allowed difference (exit code 1 if exceeded)
+    #[argh(option, default = "0.0")]
+    tolerance: f32,
+
+    /// old model file name (.glb)
+    #[argh(positional)]
+    old: OsString,
+
+    /// new model file name (.glb)
+    #[argh(positional)]
+    new: OsString,
+}
+
+/// Import a mesh (.glb, .obj), slicing it into rings (.hom)
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "import")]
+struct ImportCmd {
+    /// output file name (default: input with .hom extension)
+    #[argh(option, short = 'o')]
+    output: Option<OsString>,
+
+    /// number of rings (horizontal slices)
+    #[argh(option, default = "40")]
+    rings: usize,
+
+    /// number of spokes per ring
+    #[argh(option, default = "16")]
+    spokes: usize,
+
+    /// mesh file name (.glb, .obj)
+    #[argh(positional)]
+    file: OsString,
+}
+
+/// Export a model to another format (.glb to .obj, .ply)
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "export")]
+struct ExportCmd {
+    /// output format: obj (default), ply or ply-ascii
+    #[argh(option, from_str_fn(parse_format), default = "Format::Obj")]
+    format: Format,
+
+    /// output file name (default: input with format extension)
+    #[argh(option, short = 'o')]
+    output: Option<OsString>,
+
+    /// model file name (.glb)
+    #[argh(positional)]
+    file: OsString,
+}
+
+/// Write a cross-section of a model (.hom to .svg)
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "section")]
+struct SectionCmd {
+    /// section plane at X coordinate
+    #[argh(option)]
+    x: Option<f32>,
+
+    /// section plane at Y coordinate
+    #[argh(option)]
+    y: Option<f32>,
+
+    /// section plane at Z coordinate
+    #[argh(option)]
+    z: Option<f32>,
+
+    /// length of one model unit, in cm (default 1)
+    #[argh(option, default = "1.0")]
+    unit: f32,
+
+    /// output file name (default: input with .svg extension)
+    #[argh(option, short = 'o')]
+    output: Option<OsString>,
+
+    /// model file name (.hom)
+    #[argh(positional)]
+    file: OsString,
+}
+
+/// Print model statistics (size, area, volume, watertightness)
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "stats")]
+struct StatsCmd {
+    /// model file name (.hom, .glb)
+    #[argh(positional)]
+    file: OsString,
+}
+
+impl BuildCmd {
+    /// Get build options
+    fn options(&self) -> BuildOptions {
+        BuildOptions {
+            scale: self.scale,
+            center: self.center,
+            debug_normals: self.debug_normals,
+            up: self.up,
+            lods: self.lods.clone().unwrap_or_default(),
+            msft_lod: self.msft_lod,
+            ao: self.ao,
+            copyright: self.copyright.clone(),
+            quantize: self.quantize,
+            metadata: self.metadata,
+            turntable: self.turntable,
+            format: self.format,
+            weld: self.weld,
+            mirror: self.mirror.clone(),
+            subdivide: self.subdivide,
+            fix_winding: self.fix_winding,
+            thickness: self.thickness,
+        }
+    }
+}
+
+impl ViewCmd {
+    /// Get view options
+    fn options(&self, up: UpAxis) -> Result<ViewOptions> {
+        if self.checker == 0 || self.checker > 256 {
+            bail!("Invalid checker cells: {}", self.checker);
+        }
+        if let (Some(pos), Some(look)) = (self.camera_pos, self.camera_look) {
+            if pos == look {
+                bail!("Camera position and look target must differ");
+            }
+        }
+        Ok(ViewOptions {
+            up,
+            checker: self.checker,
+            // glam versions of bevy and homunculus differ
+            camera_pos: self.camera_pos.map(|v| v.to_array().into()),
+            camera_look: self.camera_look.map(|v| v.to_array().into()),
+            fov: self.fov,
+        })
+    }
+}
+
+impl SectionCmd {
+    /// Get section plane
+    fn plane(&self) -> Result<Plane> {
+        match (self.x, self.y, self.z) {
+            (Some(x), None, None) => Ok(Plane::x(x)),
+            (None, Some(y), None) => Ok(Plane::y(y)),
+            (None, None, Some(z)) => Ok(Plane::z(z)),
+            _ => bail!("Specify exactly one of --x, --y or --z"),
+        }
+    }
+
+    /// Get section options
+    fn options(&self) -> Result<SectionOptions> {
+        if !(self.unit > 0.0 && self.unit.is_finite()) {
+            bail!("Invalid unit: {}", self.unit);
+        }
+        Ok(SectionOptions { unit_cm: self.unit })
+    }
 }
 
 /// Main function
@@ -44,39 +330,82 @@ fn main() -> Result<()> {
         println!("{NAME} {VERSION}");
         return Ok(());
     }
-    if let Some(file) = &args.file {
-        let path = build_homunculus(Path::new(file))?;
-        if !args.build {
-            view(path)?;
+    match args.cmd {
+        Some(Command::Build(cmd)) => {
+            build::build_model(Path::new(&cmd.file), &cmd.options())?;
         }
-    }
-    Ok(())
-}
-
-/// Build homunculus model
-fn build_homunculus(path: &Path) -> Result<PathBuf> {
-    let file = File::open(path)
-        .with_context(|| format!("{} not found", path.display()))?;
-    match path.extension() {
-        Some(ext) if ext == "glb" || ext == "gltf" => {
-            eprintln!("{path:?} already glTF model");
-            return Ok(path.to_path_buf());
+        Some(Command::View(cmd)) => {
+            let opts = BuildOptions::default();
+            let path = build::build_model(Path::new(&cmd.file), &opts)?;
+            let up = match cmd.up {
+                Some(up) => up,
+                None => build::detect_up(&path)?,
+            };
+            view(path, cmd.options(up)?)?;
+        }
+        Some(Command::Diff(cmd)) => {
+            let diff =
+                diff::diff_files(Path::new(&cmd.old), Path::new(&cmd.new))?;
+            if cmd.json {
+                println!("{}", diff.to_json());
+            } else {
+                print!("{diff}");
+            }
+            if diff.exceeds(cmd.tolerance) {
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Import(cmd)) => {
+            let path = Path::new(&cmd.file);
+            let hom = import::import_mesh(path, cmd.rings, cmd.spokes)?;
+            let output = match &cmd.output {
+                Some(output) => PathBuf::from(output),
+                None => build::output_path(path, "hom")?,
+            };
+            std::fs::write(output, hom)?;
         }
-        _ => {}
+        Some(Command::Export(cmd)) => {
+            build::export_model(
+                Path::new(&cmd.file),
+                cmd.format,
+                cmd.output.as_ref().map(PathBuf::from),
+            )?;
+        }
+        Some(Command::Section(cmd)) => {
+            build::section_model(
+                Path::new(&cmd.file),
+                cmd.plane()?,
+                cmd.output.as_ref().map(PathBuf::from),
+                &cmd.options()?,
+            )?;
+        }
+        Some(Command::Stats(cmd)) => {
+            let stats = build::model_stats(Path::new(&cmd.file))?;
+            println!("vertices: {}", stats.vertices);
+            println!("faces: {}", stats.faces);
+            println!("bounds: {} to {}", stats.min, stats.max);
+            println!("surface area: {}", stats.surface_area);
+            if stats.is_watertight() {
+                println!("volume: {}", stats.volume);
+                println!("watertight: yes");
+            } else {
+                let edges = stats.boundary_edges;
+                println!("watertight: no ({edges} boundary edges)");
+            }
+        }
+        None => eprintln!("No command specified; try `{NAME} --help`"),
     }
-    let stem = path.file_stem().context("Invalid file name")?;
-    let def: ModelDef = muon_rs::from_reader(file).context("Invalid model")?;
-    let husk = Husk::try_from(&def).context("Invalid model")?;
-    let out = path.with_file_name(Path::new(stem).with_extension("glb"));
-    let writer = File::create(&out)
-        .with_context(|| format!("Cannot create {}", out.display()))?;
-    husk.write_gltf(&writer).context("Writing glTF")?;
-    Ok(out)
+    Ok(())
 }
 
 /// View glTF model
-fn view(path: PathBuf) -> Result<()> {
+fn view(path: PathBuf, options: ViewOptions) -> Result<()> {
+    let keys = keys::KeyBindings::load()?;
+    for (key, actions) in keys.conflicts() {
+        let names: Vec<_> = actions.iter().map(|a| a.name()).collect();
+        eprintln!("Key conflict: {key} bound to {}", names.join(", "));
+    }
     let folder = std::env::current_dir()?.display().to_string();
-    view::view_gltf(folder, path);
+    view::view_gltf(folder, path, options, keys);
     Ok(())
 }
@@ -2,24 +2,52 @@
 //
 // Copyright (c) 2022-2024  Douglas Lau
 //
+use crate::color::{checker_pixels, primitive_color, primitive_index};
 use crate::cube::build_cube;
+use crate::keys::{Action, KeyBindings};
 use bevy::{
     asset::LoadState,
     gltf::Gltf,
     input::mouse::{MouseMotion, MouseWheel},
     pbr::wireframe::{WireframeConfig, WireframePlugin},
     prelude::*,
-    render::primitives::Aabb,
+    render::{
+        primitives::Aabb,
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
     scene::InstanceId,
     window::{PrimaryWindow, Window},
 };
+use homunculus::UpAxis;
 use std::f32::consts::PI;
 use std::path::PathBuf;
 
+/// Viewer options
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ViewOptions {
+    /// Up axis convention
+    pub up: UpAxis,
+
+    /// Checkerboard cells for UV preview
+    pub checker: u32,
+
+    /// Camera position (default: framed from bounding box)
+    pub camera_pos: Option<Vec3>,
+
+    /// Camera look target (default: bounding box center)
+    pub camera_look: Option<Vec3>,
+
+    /// Camera vertical field of view (degrees)
+    pub fov: Option<f32>,
+}
+
 /// Path configuration resource for glTF
 #[derive(Resource)]
 struct PathConfig {
     path: PathBuf,
+    options: ViewOptions,
 }
 
 /// Scene state
@@ -46,6 +74,7 @@ struct SceneRes {
 struct CameraController {
     focus: Vec3,
     distance: f32,
+    up: Vec3,
 }
 
 /// Cursor for camera
@@ -56,12 +85,122 @@ struct Cursor;
 #[derive(Component)]
 struct Stage;
 
+/// Key bindings resource
+#[derive(Resource)]
+struct Keys(KeyBindings);
+
+impl Keys {
+    /// Get key code bound to an action
+    fn code(&self, action: Action) -> KeyCode {
+        key_code(self.0.key(action))
+    }
+
+    /// Check if the key for an action is pressed
+    fn pressed(&self, keyboard: &ButtonInput<KeyCode>, action: Action) -> bool {
+        keyboard.pressed(self.code(action))
+    }
+
+    /// Check if the key for an action was just pressed
+    fn just_pressed(
+        &self,
+        keyboard: &ButtonInput<KeyCode>,
+        action: Action,
+    ) -> bool {
+        keyboard.just_pressed(self.code(action))
+    }
+}
+
+/// Get key code from a key name
+fn key_code(name: &str) -> KeyCode {
+    match name {
+        "A" => KeyCode::KeyA,
+        "B" => KeyCode::KeyB,
+        "C" => KeyCode::KeyC,
+        "D" => KeyCode::KeyD,
+        "E" => KeyCode::KeyE,
+        "F" => KeyCode::KeyF,
+        "G" => KeyCode::KeyG,
+        "H" => KeyCode::KeyH,
+        "I" => KeyCode::KeyI,
+        "J" => KeyCode::KeyJ,
+        "K" => KeyCode::KeyK,
+        "L" => KeyCode::KeyL,
+        "M" => KeyCode::KeyM,
+        "N" => KeyCode::KeyN,
+        "O" => KeyCode::KeyO,
+        "P" => KeyCode::KeyP,
+        "Q" => KeyCode::KeyQ,
+        "R" => KeyCode::KeyR,
+        "S" => KeyCode::KeyS,
+        "T" => KeyCode::KeyT,
+        "U" => KeyCode::KeyU,
+        "V" => KeyCode::KeyV,
+        "W" => KeyCode::KeyW,
+        "X" => KeyCode::KeyX,
+        "Y" => KeyCode::KeyY,
+        "Z" => KeyCode::KeyZ,
+        "0" => KeyCode::Digit0,
+        "1" => KeyCode::Digit1,
+        "2" => KeyCode::Digit2,
+        "3" => KeyCode::Digit3,
+        "4" => KeyCode::Digit4,
+        "5" => KeyCode::Digit5,
+        "6" => KeyCode::Digit6,
+        "7" => KeyCode::Digit7,
+        "8" => KeyCode::Digit8,
+        "9" => KeyCode::Digit9,
+        "Left" => KeyCode::ArrowLeft,
+        "Right" => KeyCode::ArrowRight,
+        "Up" => KeyCode::ArrowUp,
+        "Down" => KeyCode::ArrowDown,
+        "PageUp" => KeyCode::PageUp,
+        "PageDown" => KeyCode::PageDown,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "Insert" => KeyCode::Insert,
+        "Delete" => KeyCode::Delete,
+        "Tab" => KeyCode::Tab,
+        "Enter" => KeyCode::Enter,
+        "Backspace" => KeyCode::Backspace,
+        "Escape" => KeyCode::Escape,
+        _ => KeyCode::Space,
+    }
+}
+
+/// "No UVs" notice
+#[derive(Component)]
+struct UvNotice;
+
+/// Material override mode
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Override {
+    /// Stable random color per primitive
+    PrimitiveColors,
+
+    /// Checkerboard base color texture
+    Checker,
+}
+
+/// Material override resource
+#[derive(Resource, Default)]
+struct MaterialOverride {
+    /// Active override mode
+    mode: Option<Override>,
+
+    /// Original primitive materials
+    originals: Vec<(Entity, Handle<StandardMaterial>)>,
+
+    /// Checkerboard texture
+    checker: Handle<Image>,
+}
+
 impl CameraController {
     /// Create a new camera controller
-    fn new(pos: Vec3, focus: Vec3) -> Self {
+    fn new(pos: Vec3, focus: Vec3, up: Vec3) -> Self {
         CameraController {
             focus,
             distance: pos.distance(focus),
+            up,
         }
     }
 
@@ -88,7 +227,7 @@ impl CameraController {
     fn rotate(&mut self, xform: &mut Transform, motion: Vec2, win_sz: Vec2) {
         let delta_x = motion.x / win_sz.x * PI;
         let delta_y = motion.y / win_sz.y * PI;
-        xform.rotation = Quat::from_rotation_y(-delta_x * 2.0)
+        xform.rotation = Quat::from_axis_angle(self.up, -delta_x * 2.0)
             * xform.rotation
             * Quat::from_rotation_x(-delta_y);
         self.update_transform(xform);
@@ -115,9 +254,16 @@ impl CameraController {
 }
 
 /// View glTF in an app window
-pub fn view_gltf(folder: String, path: PathBuf) {
+pub fn view_gltf(
+    folder: String,
+    path: PathBuf,
+    options: ViewOptions,
+    keys: KeyBindings,
+) {
     let mut app = App::new();
-    app.insert_resource(PathConfig { path })
+    app.insert_resource(PathConfig { path, options })
+        .insert_resource(Keys(keys))
+        .init_resource::<MaterialOverride>()
         .insert_resource(AmbientLight {
             color: Color::WHITE,
             brightness: 500.0,
@@ -139,7 +285,13 @@ pub fn view_gltf(folder: String, path: PathBuf) {
         .add_plugins(WireframePlugin)
         .add_systems(
             Startup,
-            (init_wireframe, init_gizmo, spawn_light, start_loading),
+            (
+                init_wireframe,
+                init_gizmo,
+                init_checker,
+                spawn_light,
+                start_loading,
+            ),
         )
         .add_systems(
             Update,
@@ -152,9 +304,11 @@ pub fn view_gltf(folder: String, path: PathBuf) {
                 draw_cursor,
                 pan_rotate_camera,
                 zoom_camera,
+                keyboard_camera,
                 update_light_direction,
                 toggle_stage,
                 toggle_wireframe,
+                toggle_material_override,
                 toggle_help,
             ),
         )
@@ -175,6 +329,28 @@ fn init_gizmo(mut config_store: ResMut<GizmoConfigStore>) {
     }
 }
 
+/// System to initialize checkerboard texture
+fn init_checker(
+    config: Res<PathConfig>,
+    mut images: ResMut<Assets<Image>>,
+    mut over: ResMut<MaterialOverride>,
+) {
+    let (size, pixels) = checker_pixels(config.options.checker);
+    let mut image = Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    over.checker = images.add(image);
+}
+
 /// System to spawn light
 fn spawn_light(mut commands: Commands) {
     commands.spawn(DirectionalLightBundle {
@@ -187,22 +363,21 @@ fn spawn_light(mut commands: Commands) {
 }
 
 /// System to spawn help text
-fn spawn_help(commands: &mut Commands, camera_id: Entity) {
+fn spawn_help(commands: &mut Commands, camera_id: Entity, keys: &KeyBindings) {
+    let text = format!(
+        "_____ Mouse _____\n\
+         left: pan camera\n\
+         right: rotate camera\n\
+         wheel: zoom camera\n\
+         /pressed: forward/back\n\
+         \n\
+         {}",
+        keys.help_text()
+    );
     commands.spawn((
         TargetCamera(camera_id),
         TextBundle::from_section(
-            "_____ Mouse _____\n\
-             left: pan camera\n\
-             right: rotate camera\n\
-             wheel: zoom camera\n\
-             /pressed: forward/back\n\
-             \n\
-             _____ Keys _____\n\
-             'Q': toggle help text\n\
-             'W': toggle wireframe\n\
-             'S': toggle stage\n\
-             'D': light direction\n\
-             Space: next animation",
+            text,
             TextStyle {
                 font_size: 18.0,
                 ..default()
@@ -215,6 +390,22 @@ fn spawn_help(commands: &mut Commands, camera_id: Entity) {
             ..default()
         }),
     ));
+    let mut notice = TextBundle::from_section(
+        "no UVs",
+        TextStyle {
+            font_size: 24.0,
+            color: Color::ORANGE_RED,
+            ..default()
+        },
+    )
+    .with_style(Style {
+        position_type: PositionType::Absolute,
+        top: Val::Px(12.0),
+        left: Val::Px(12.0),
+        ..default()
+    });
+    notice.visibility = Visibility::Hidden;
+    commands.spawn((UvNotice, TargetCamera(camera_id), notice));
 }
 
 /// System to start loading scene
@@ -266,6 +457,8 @@ fn check_ready(mut scene_res: ResMut<SceneRes>, spawner: Res<SceneSpawner>) {
 /// System to spawn camera
 fn spawn_camera(
     mut scene_res: ResMut<SceneRes>,
+    config: Res<PathConfig>,
+    keys: Res<Keys>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
@@ -276,11 +469,11 @@ fn spawn_camera(
     }
     scene_res.state = SceneState::StartAnimation;
     let aabb = bounding_box_meshes(query);
-    let (bundle, cam) = camera_bundle(aabb);
+    let (bundle, cam) = camera_bundle(aabb, &config.options);
     let mut xform = Transform::from_translation(aabb.center.into());
     xform.scale = Vec3::splat(cam.distance * 0.02);
     let id = commands.spawn((bundle, cam)).id();
-    spawn_help(&mut commands, id);
+    spawn_help(&mut commands, id, &keys.0);
     commands.spawn((
         Cursor,
         MaterialMeshBundle {
@@ -300,8 +493,11 @@ fn spawn_camera(
     commands.spawn((
         Stage,
         MaterialMeshBundle {
-            mesh: meshes
-                .add(Mesh::from(Plane3d::default().mesh().size(size, size))),
+            mesh: meshes.add(Mesh::from(
+                Plane3d::new(up_vector(config.options.up))
+                    .mesh()
+                    .size(size, size),
+            )),
             material: materials.add(StandardMaterial {
                 base_color: Color::DARK_GREEN,
                 ..default()
@@ -325,18 +521,44 @@ fn bounding_box_meshes(
     Aabb::from_min_max(min, max)
 }
 
+/// Get up vector for an axis convention
+fn up_vector(up: UpAxis) -> Vec3 {
+    match up {
+        UpAxis::Y => Vec3::Y,
+        UpAxis::Z => Vec3::Z,
+    }
+}
+
 /// Build camera bundle with controller
-fn camera_bundle(aabb: Aabb) -> (Camera3dBundle, CameraController) {
-    let look = Vec3::from(aabb.center);
-    let pos = look
-        + Vec3::new(0.0, 2.0 * aabb.half_extents.y, 4.0 * aabb.half_extents.z);
+///
+/// Camera position and look target from options take precedence over
+/// framing the bounding box.
+fn camera_bundle(
+    aabb: Aabb,
+    options: &ViewOptions,
+) -> (Camera3dBundle, CameraController) {
+    let look = options.camera_look.unwrap_or(Vec3::from(aabb.center));
+    let half = Vec3::from(aabb.half_extents);
+    let offset = match options.up {
+        UpAxis::Y => Vec3::new(0.0, 2.0 * half.y, 4.0 * half.z),
+        UpAxis::Z => Vec3::new(0.0, -4.0 * half.y, 2.0 * half.z),
+    };
+    let pos = match options.camera_pos {
+        Some(pos) if pos.distance(look) > f32::EPSILON => pos,
+        _ => look + offset,
+    };
+    let up = up_vector(options.up);
+    let mut projection = PerspectiveProjection::default();
+    if let Some(fov) = options.fov {
+        projection.fov = fov.to_radians();
+    }
     (
         Camera3dBundle {
-            transform: Transform::from_translation(pos)
-                .looking_at(look, Vec3::Y),
+            transform: Transform::from_translation(pos).looking_at(look, up),
+            projection: Projection::Perspective(projection),
             ..Default::default()
         },
-        CameraController::new(pos, look),
+        CameraController::new(pos, look, up),
     )
 }
 
@@ -360,6 +582,7 @@ fn start_animation(
 fn control_animation(
     scene_res: Res<SceneRes>,
     keyboard: Res<ButtonInput<KeyCode>>,
+    keys: Res<Keys>,
     mut players: Query<&mut AnimationPlayer>,
     mut animation_idx: Local<usize>,
     mut is_changing: Local<bool>,
@@ -368,7 +591,7 @@ fn control_animation(
         return;
     }
     let mut player = players.get_single_mut().unwrap();
-    if keyboard.pressed(KeyCode::Space) {
+    if keys.pressed(&keyboard, Action::NextAnimation) {
         player.pause();
         *is_changing = true;
     } else if *is_changing {
@@ -458,16 +681,69 @@ fn zoom_camera(
     }
 }
 
+/// System to orbit / zoom the camera with keys
+#[allow(clippy::type_complexity)]
+fn keyboard_camera(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keys: Res<Keys>,
+    time: Res<Time>,
+    mut queries: ParamSet<(
+        Query<(&mut CameraController, &mut Transform)>,
+        Query<&mut Transform, With<Cursor>>,
+    )>,
+) {
+    let axis = |neg, pos| {
+        let neg = if keys.pressed(&keyboard, neg) {
+            1.0
+        } else {
+            0.0
+        };
+        let pos = if keys.pressed(&keyboard, pos) {
+            1.0
+        } else {
+            0.0
+        };
+        pos - neg
+    };
+    let orbit = Vec2::new(
+        axis(Action::OrbitLeft, Action::OrbitRight),
+        axis(Action::OrbitUp, Action::OrbitDown),
+    );
+    let zoom = axis(Action::ZoomOut, Action::ZoomIn);
+    if orbit == Vec2::ZERO && zoom == 0.0 {
+        return;
+    }
+    let secs = time.delta_seconds();
+    let mut scale = None;
+    if let Ok((mut cam, mut xform)) = queries.p0().get_single_mut() {
+        if orbit != Vec2::ZERO {
+            // half turn per second, as if dragging the mouse
+            let win_sz = primary_window_size(windows);
+            cam.rotate(&mut xform, orbit * win_sz * secs * 0.5, win_sz);
+        }
+        if zoom != 0.0 {
+            cam.zoom(&mut xform, zoom * secs * 10.0);
+            scale = Some(cam.distance);
+        }
+    }
+    if let (Some(scale), Ok(mut xform)) = (scale, queries.p1().get_single_mut())
+    {
+        xform.scale = Vec3::splat(scale * 0.02);
+    }
+}
+
 /// System to update the directional light
 #[allow(clippy::type_complexity)]
 fn update_light_direction(
     keyboard: Res<ButtonInput<KeyCode>>,
+    keys: Res<Keys>,
     mut queries: ParamSet<(
         Query<&Transform, With<CameraController>>,
         Query<&mut Transform, With<DirectionalLight>>,
     )>,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyD) {
+    if keys.just_pressed(&keyboard, Action::LightDirection) {
         let cam_rot = queries.p0().get_single().unwrap().rotation;
         for mut xform in &mut queries.p1() {
             xform.rotation = cam_rot;
@@ -478,9 +754,10 @@ fn update_light_direction(
 /// System to toggle stage
 fn toggle_stage(
     keyboard: Res<ButtonInput<KeyCode>>,
+    keys: Res<Keys>,
     mut query: Query<&mut Visibility, With<Stage>>,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyS) {
+    if keys.just_pressed(&keyboard, Action::ToggleStage) {
         let mut vis = query.single_mut();
         *vis = if *vis == Visibility::Hidden {
             Visibility::Visible
@@ -493,19 +770,96 @@ fn toggle_stage(
 /// System to toggle wireframe
 fn toggle_wireframe(
     keyboard: Res<ButtonInput<KeyCode>>,
+    keys: Res<Keys>,
     mut wireframe_config: ResMut<WireframeConfig>,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyW) {
+    if keys.just_pressed(&keyboard, Action::ToggleWireframe) {
         wireframe_config.global = !wireframe_config.global;
     }
 }
 
+/// System to toggle material overrides
+#[allow(clippy::type_complexity)]
+fn toggle_material_override(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keys: Res<Keys>,
+    mut over: ResMut<MaterialOverride>,
+    meshes: Res<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut query: Query<
+        (Entity, &Handle<Mesh>, &mut Handle<StandardMaterial>),
+        (Without<Cursor>, Without<Stage>),
+    >,
+    mut notice: Query<&mut Visibility, With<UvNotice>>,
+) {
+    let mode = if keys.just_pressed(&keyboard, Action::PrimitiveColors) {
+        Override::PrimitiveColors
+    } else if keys.just_pressed(&keyboard, Action::UvChecker) {
+        Override::Checker
+    } else {
+        return;
+    };
+    // restore original materials first
+    for (entity, original) in over.originals.drain(..).collect::<Vec<_>>() {
+        if let Ok((_, _, mut material)) = query.get_mut(entity) {
+            *material = original;
+        }
+    }
+    let mut no_uvs = false;
+    if over.mode == Some(mode) {
+        over.mode = None;
+    } else {
+        over.mode = Some(mode);
+        for (entity, mesh, mut material) in &mut query {
+            let mut mat =
+                materials.get(&*material).cloned().unwrap_or_default();
+            match mode {
+                Override::PrimitiveColors => {
+                    let Some((m, p)) = mesh
+                        .path()
+                        .and_then(|path| path.label())
+                        .and_then(primitive_index)
+                    else {
+                        continue;
+                    };
+                    let [r, g, b] = primitive_color(m, p);
+                    mat.base_color = Color::rgb(r, g, b);
+                    mat.base_color_texture = None;
+                }
+                Override::Checker => {
+                    let has_uvs = meshes
+                        .get(mesh)
+                        .and_then(|m| m.attribute(Mesh::ATTRIBUTE_UV_0))
+                        .is_some();
+                    if !has_uvs {
+                        no_uvs = true;
+                        continue;
+                    }
+                    mat.base_color = Color::WHITE;
+                    mat.base_color_texture = Some(over.checker.clone());
+                }
+            }
+            let original =
+                std::mem::replace(&mut *material, materials.add(mat));
+            over.originals.push((entity, original));
+        }
+    }
+    if let Ok(mut vis) = notice.get_single_mut() {
+        *vis = if no_uvs {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
 /// System to toggle help text
 fn toggle_help(
     keyboard: Res<ButtonInput<KeyCode>>,
-    mut query: Query<&mut Visibility, With<Text>>,
+    keys: Res<Keys>,
+    mut query: Query<&mut Visibility, (With<Text>, Without<UvNotice>)>,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyQ) {
+    if keys.just_pressed(&keyboard, Action::ToggleHelp) {
         for mut vis in &mut query {
             *vis = if *vis == Visibility::Hidden {
                 Visibility::Visible
@@ -515,3 +869,35 @@ fn toggle_help(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn camera_pose() {
+        let aabb = Aabb::from_min_max(Vec3::ZERO, Vec3::splat(2.0));
+        let (bundle, cam) = camera_bundle(aabb, &ViewOptions::default());
+        assert_eq!(cam.focus, Vec3::ONE);
+        assert_eq!(bundle.transform.translation, Vec3::new(1.0, 3.0, 5.0));
+        let options = ViewOptions {
+            camera_pos: Some(Vec3::new(2.0, 3.0, 4.0)),
+            camera_look: Some(Vec3::new(0.0, 1.0, 0.0)),
+            fov: Some(45.0),
+            ..Default::default()
+        };
+        let (bundle, cam) = camera_bundle(aabb, &options);
+        assert_eq!(bundle.transform.translation, Vec3::new(2.0, 3.0, 4.0));
+        assert_eq!(cam.focus, Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(cam.distance, Vec3::new(2.0, 2.0, 4.0).length());
+        match bundle.projection {
+            Projection::Perspective(proj) => {
+                assert_eq!(proj.fov, 45f32.to_radians())
+            }
+            _ => panic!("not perspective"),
+        }
+        let mut xform = bundle.transform;
+        cam.update_transform(&mut xform);
+        assert!(xform.translation.distance(Vec3::new(2.0, 3.0, 4.0)) < 1e-5);
+    }
+}
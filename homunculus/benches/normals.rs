@@ -0,0 +1,34 @@
+// normals benchmark
+//
+// Compare with and without the `rayon` feature:
+//
+//     cargo bench -p homunculus --bench normals
+//     cargo bench -p homunculus --bench normals --features rayon
+use criterion::{criterion_group, criterion_main, Criterion};
+use glam::Vec3;
+use homunculus::{Husk, Mesh, Ring};
+
+/// Make a wavy tube near the vertex limit (64k vertices, 128k faces)
+fn tube() -> Husk {
+    let mut husk = Husk::new();
+    let ring = Ring::default().axis(Vec3::new(0.0, 0.05, 0.0));
+    husk.ring((0..128).fold(ring, |r, i| r.spoke(1.0 + 0.1 * (i % 3) as f32)))
+        .unwrap();
+    for i in 0..500 {
+        let scale = 1.0 + 0.2 * (i as f32 * 0.1).sin();
+        husk.ring(Ring::default().scale(scale)).unwrap();
+    }
+    husk
+}
+
+fn normals(c: &mut Criterion) {
+    let husk = tube();
+    c.bench_function("build", |b| b.iter(|| husk.to_mesh().unwrap()));
+    let mut mesh: Mesh = husk.into_mesh().unwrap();
+    c.bench_function("recompute_normals", |b| {
+        b.iter(|| mesh.recompute_normals())
+    });
+}
+
+criterion_group!(benches, normals);
+criterion_main!(benches);
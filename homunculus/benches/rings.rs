@@ -0,0 +1,54 @@
+// rings benchmark
+//
+//     cargo bench -p homunculus --bench rings
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use glam::Vec3;
+use homunculus::{Husk, Ring};
+
+/// Make a ring with `n` spokes, with a branch label on every 4th spoke
+fn branching(n: usize) -> Ring {
+    let ring = Ring::default().axis(Vec3::new(0.0, 0.5, 0.0));
+    (0..n).fold(ring, |r, i| match i % 4 {
+        0 => r.spoke((1.0, format!("B{}", i / 4).as_str())),
+        _ => r.spoke(1.0),
+    })
+}
+
+/// Make one band between rings with different spoke counts (`make_band`)
+fn make_band(c: &mut Criterion) {
+    let mut base = Husk::new();
+    base.ring(branching(32)).unwrap();
+    let next = (0..24).fold(Ring::default(), |r, _| r.spoke(0.8));
+    c.bench_function("make_band", |b| {
+        b.iter_batched(
+            || (base.clone(), next.clone()),
+            |(mut husk, ring)| husk.ring(ring).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+/// Build 10k rings with 16 spokes each (split into husks of 2500 rings, to
+/// stay below the vertex limit)
+fn rings_10k(c: &mut Criterion) {
+    let spokes = (0..16).fold(Ring::default(), |r, _| r.spoke(1.0));
+    let mut group = c.benchmark_group("rings");
+    group.sample_size(10);
+    group.bench_function("10k x 16 spokes", |b| {
+        b.iter(|| {
+            for _ in 0..4 {
+                let mut husk = Husk::new();
+                husk.ring(spokes.clone()).unwrap();
+                for i in 1..2500 {
+                    let scale = 1.0 + 0.1 * (i as f32 * 0.1).sin();
+                    let axis = Vec3::new(0.0, 0.1, 0.0);
+                    husk.ring(Ring::default().axis(axis).scale(scale)).unwrap();
+                }
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, make_band, rings_10k);
+criterion_main!(benches);
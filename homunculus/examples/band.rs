@@ -0,0 +1,55 @@
+// band example (micro-benchmark of ring bands)
+use anyhow::Result;
+use argh::FromArgs;
+use glam::Vec3;
+use homunculus::{Husk, Ring};
+use std::time::Instant;
+
+/// Command-line arguments
+#[derive(FromArgs, PartialEq, Debug)]
+struct Args {
+    /// spokes per ring
+    #[argh(option, default = "64")]
+    spokes: usize,
+
+    /// number of rings
+    #[argh(option, default = "200")]
+    rings: usize,
+
+    /// number of iterations
+    #[argh(option, default = "20")]
+    iterations: usize,
+}
+
+/// Make a ring with some branch points
+fn make_ring(spokes: usize, i: usize) -> Ring {
+    let mut ring = Ring::default().axis(Vec3::new(0.0, 0.1, 0.0));
+    for s in 0..spokes {
+        ring = if s % 16 == 8 && i % 4 == 2 {
+            ring.spoke(format!("branch {i} {s}").as_str())
+        } else {
+            ring.spoke(1.0)
+        };
+    }
+    ring
+}
+
+/// Build a husk (bands are made as rings are added)
+fn build(args: &Args) -> Result<Husk> {
+    let mut husk = Husk::new();
+    for i in 0..args.rings {
+        husk.ring(make_ring(args.spokes, i))?;
+    }
+    Ok(husk)
+}
+
+fn main() -> Result<()> {
+    let args: Args = argh::from_env();
+    let start = Instant::now();
+    for _ in 0..args.iterations {
+        build(&args)?;
+    }
+    let per = start.elapsed() / args.iterations as u32;
+    println!("{} x {} rings: {per:?}", args.spokes, args.rings);
+    Ok(())
+}
@@ -0,0 +1,13 @@
+// cube example (mesh built without a husk)
+use anyhow::Result;
+use glam::{Quat, Vec3};
+use homunculus::{build_cube, Scene};
+use std::fs::File;
+
+fn main() -> Result<()> {
+    let mut scene = Scene::default();
+    scene.add_mesh("cube", build_cube()?, Vec3::ZERO, Quat::IDENTITY);
+    let file = File::create("cube.glb")?;
+    scene.write_gltf(file)?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+// normals example (micro-benchmark of building vertex normals)
+//
+// Run with `--features parallel` to compare.
+use anyhow::Result;
+use argh::FromArgs;
+use glam::Vec3;
+use homunculus::{Husk, Mesh, Ring};
+use std::time::Instant;
+
+/// Command-line arguments
+#[derive(FromArgs, PartialEq, Debug)]
+struct Args {
+    /// spokes per ring
+    #[argh(option, default = "128")]
+    spokes: usize,
+
+    /// number of rings (limited by maximum vertices)
+    #[argh(option, default = "500")]
+    rings: usize,
+
+    /// number of iterations
+    #[argh(option, default = "20")]
+    iterations: usize,
+}
+
+/// Build a wavy tube mesh
+fn build(args: &Args) -> Result<Mesh> {
+    let mut husk = Husk::new();
+    let mut ring = Ring::default().axis(Vec3::new(0.0, 0.1, 0.0));
+    for s in 0..args.spokes {
+        ring = ring.spoke(1.0 + 0.1 * (s % 3) as f32);
+    }
+    husk.ring(ring)?;
+    for i in 1..args.rings {
+        let scale = 1.0 + 0.2 * (i as f32 * 0.1).sin();
+        husk.ring(Ring::default().scale(scale))?;
+    }
+    Ok(husk.into_mesh()?)
+}
+
+fn main() -> Result<()> {
+    let args: Args = argh::from_env();
+    let mut mesh = build(&args)?;
+    let faces = mesh.indices().len() / 3;
+    let start = Instant::now();
+    for _ in 0..args.iterations {
+        mesh.recompute_normals();
+    }
+    let per = start.elapsed() / args.iterations as u32;
+    println!("{faces} faces: {per:?}");
+    Ok(())
+}
@@ -1,6 +1,6 @@
 // pyramid example
 use anyhow::Result;
-use homunculus::{Husk, Ring, Shading};
+use homunculus::prelude::*;
 use std::fs::File;
 
 fn main() -> Result<()> {
@@ -0,0 +1,125 @@
+// ao.rs        Ambient occlusion module
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::mesh::Mesh;
+use crate::raycast::{Bvh, Ray};
+use fastrand::Rng;
+use glam::Vec3;
+use std::f32::consts::TAU;
+
+/// Get a cosine-weighted random direction in the hemisphere of a normal
+fn cosine_sample(rng: &mut Rng, norm: Vec3) -> Vec3 {
+    let (u, v) = norm.any_orthonormal_pair();
+    let angle = rng.f32() * TAU;
+    let r2 = rng.f32();
+    let r = r2.sqrt();
+    let (sin, cos) = angle.sin_cos();
+    u * (r * cos) + v * (r * sin) + norm * (1.0 - r2).sqrt()
+}
+
+impl Mesh {
+    /// Bake ambient occlusion into vertex colors
+    ///
+    /// For each vertex, `samples` rays are cast over the hemisphere around
+    /// its normal, with a cosine-weighted distribution.  The ratio of rays
+    /// which miss the mesh is stored, and exported as `COLOR_0`.
+    pub fn bake_ao(&mut self, samples: usize) {
+        self.bake_ao_seeded(samples, 0);
+    }
+
+    /// Bake ambient occlusion, with a random number seed
+    ///
+    /// The result is deterministic for a given sample count and seed.
+    pub fn bake_ao_seeded(&mut self, samples: usize, seed: u64) {
+        let bvh = Bvh::new(self);
+        // offset ray origins to avoid hitting their own faces
+        let offset = if self.positions().is_empty() {
+            0.0
+        } else {
+            (self.pos_max() - self.pos_min()).length() * 1e-4
+        };
+        let ao = self
+            .positions()
+            .iter()
+            .zip(self.normals())
+            .enumerate()
+            .map(|(i, (pos, norm))| {
+                if samples == 0 {
+                    return 1.0;
+                }
+                let mut rng = Rng::with_seed(seed.wrapping_add(i as u64));
+                let origin = *pos + *norm * offset;
+                let hits = (0..samples)
+                    .filter(|_| {
+                        let dir = cosine_sample(&mut rng, *norm);
+                        bvh.hits(&Ray::new(origin, dir))
+                    })
+                    .count();
+                1.0 - hits as f32 / samples as f32
+            })
+            .collect();
+        self.ao = ao;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Husk, Ring};
+
+    /// Make a star-shaped column, with deep crevices between points
+    fn star() -> Mesh {
+        let mut husk = Husk::new();
+        let mut ring = Ring::default().axis(Vec3::new(0.0, 1.0, 0.0));
+        for i in 0..12 {
+            ring = ring.spoke(if i % 2 == 0 { 1.0 } else { 0.25 });
+        }
+        husk.ring(ring).unwrap();
+        for _ in 0..6 {
+            husk.ring(Ring::default()).unwrap();
+        }
+        husk.into_mesh().unwrap()
+    }
+
+    /// Get mean AO of vertices near the middle, by distance from axis
+    fn mean_ao(mesh: &Mesh, dist: f32) -> f32 {
+        let ao: Vec<f32> = mesh
+            .positions()
+            .iter()
+            .zip(mesh.ambient_occlusion())
+            .filter(|(p, _)| (p.y - 3.0).abs() < 0.1)
+            .filter(|(p, _)| {
+                (Vec3::new(p.x, 0.0, p.z).length() - dist).abs() < 0.01
+            })
+            .map(|(_, ao)| *ao)
+            .collect();
+        assert!(!ao.is_empty());
+        ao.iter().sum::<f32>() / ao.len() as f32
+    }
+
+    #[test]
+    fn crevice_tip() {
+        let mut mesh = star();
+        assert!(mesh.ambient_occlusion().is_empty());
+        mesh.bake_ao(64);
+        let ao = mesh.ambient_occlusion();
+        assert_eq!(ao.len(), mesh.positions().len());
+        assert!(ao.iter().all(|a| (0.0..=1.0).contains(a)));
+        let crevice = mean_ao(&mesh, 0.25);
+        let tip = mean_ao(&mesh, 1.0);
+        assert!(tip > 0.9, "{tip}");
+        assert!(crevice < tip - 0.3, "{crevice} {tip}");
+    }
+
+    #[test]
+    fn deterministic() {
+        let mut a = star();
+        let mut b = star();
+        a.bake_ao_seeded(16, 5);
+        b.bake_ao_seeded(16, 5);
+        assert_eq!(a.ambient_occlusion(), b.ambient_occlusion());
+        b.bake_ao_seeded(16, 6);
+        assert_ne!(a.ambient_occlusion(), b.ambient_occlusion());
+    }
+}
@@ -0,0 +1,74 @@
+// bevy_mesh.rs Bevy mesh conversion
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::mesh::Mesh;
+use bevy::render::mesh::{Indices, Mesh as BevyMesh};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::PrimitiveTopology;
+
+impl From<&Mesh> for BevyMesh {
+    /// Convert to a Bevy triangle list mesh
+    ///
+    /// Texture coordinates and vertex colors are included when present.
+    fn from(mesh: &Mesh) -> Self {
+        let mut bevy = BevyMesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        let pos: Vec<[f32; 3]> =
+            mesh.positions().iter().map(|p| p.to_array()).collect();
+        bevy.insert_attribute(BevyMesh::ATTRIBUTE_POSITION, pos);
+        let norm: Vec<[f32; 3]> =
+            mesh.normals().iter().map(|n| n.to_array()).collect();
+        bevy.insert_attribute(BevyMesh::ATTRIBUTE_NORMAL, norm);
+        if !mesh.uvs().is_empty() {
+            let uv: Vec<[f32; 2]> =
+                mesh.uvs().iter().map(|uv| uv.to_array()).collect();
+            bevy.insert_attribute(BevyMesh::ATTRIBUTE_UV_0, uv);
+        }
+        if !mesh.colors().is_empty() {
+            let color: Vec<[f32; 4]> =
+                mesh.colors().iter().map(|c| c.to_array()).collect();
+            bevy.insert_attribute(BevyMesh::ATTRIBUTE_COLOR, color);
+        }
+        let indices = mesh.indices().iter().map(|v| v.0).collect();
+        bevy.insert_indices(Indices::U16(indices));
+        bevy
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build_cube;
+    use glam::{Vec2, Vec3};
+
+    #[test]
+    fn cube() {
+        let cube = build_cube().unwrap();
+        let bevy = BevyMesh::from(&cube);
+        assert_eq!(bevy.count_vertices(), cube.positions().len());
+        assert_eq!(bevy.indices().unwrap().len(), cube.indices().len());
+        assert!(bevy.attribute(BevyMesh::ATTRIBUTE_NORMAL).is_some());
+        assert!(bevy.attribute(BevyMesh::ATTRIBUTE_UV_0).is_none());
+        assert!(bevy.attribute(BevyMesh::ATTRIBUTE_COLOR).is_none());
+    }
+
+    #[test]
+    fn uv_color() {
+        let mut builder = Mesh::builder();
+        for (x, y) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)] {
+            let v = builder.push_vtx(Vec3::new(x, y, 0.0)).unwrap();
+            builder.set_uv(v, Vec2::new(x, y));
+            builder.set_color(v, Vec3::new(x, y, 1.0).extend(1.0));
+        }
+        builder.push_face(crate::Face::new([0, 1, 2], 0));
+        let mesh = builder.build().unwrap();
+        let bevy = BevyMesh::from(&mesh);
+        let uv = bevy.attribute(BevyMesh::ATTRIBUTE_UV_0).unwrap();
+        assert_eq!(uv.len(), mesh.positions().len());
+        let color = bevy.attribute(BevyMesh::ATTRIBUTE_COLOR).unwrap();
+        assert_eq!(color.len(), mesh.positions().len());
+    }
+}
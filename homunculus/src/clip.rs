@@ -0,0 +1,545 @@
+// clip.rs  Clipping meshes by a plane
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::error::Result;
+use crate::mesh::{Mesh, Vertex};
+use crate::plane::Plane;
+use glam::{Vec2, Vec3, Vec4};
+use std::collections::HashMap;
+
+/// Distance for snapping vertices onto the clipping plane
+const EPSILON: f32 = 1e-5;
+
+/// Mesh clipper
+struct Clipper<'a> {
+    /// Source mesh
+    mesh: &'a Mesh,
+
+    /// Clipping plane
+    plane: Plane,
+
+    /// Signed distances of source vertices (snapped to 0 near the plane)
+    dist: Vec<f32>,
+
+    /// Clipped vertices of source vertices
+    remap: Vec<Option<usize>>,
+
+    /// Clipped vertices on source edges
+    edges: HashMap<(usize, usize), usize>,
+
+    /// Vertex positions
+    pos: Vec<Vec3>,
+
+    /// Vertex normals
+    norm: Vec<Vec3>,
+
+    /// Vertex texture coordinates
+    uv: Vec<Vec2>,
+
+    /// Vertex colors
+    color: Vec<Vec4>,
+
+    /// Vertex skeleton joints
+    joints: Vec<u16>,
+
+    /// Vertices on the plane
+    on_plane: Vec<bool>,
+}
+
+impl<'a> Clipper<'a> {
+    /// Create a new mesh clipper
+    fn new(mesh: &'a Mesh, plane: Plane) -> Self {
+        let dist = mesh
+            .positions()
+            .iter()
+            .map(|p| plane.distance(*p))
+            .map(|d| if d.abs() <= EPSILON { 0.0 } else { d })
+            .collect();
+        Clipper {
+            mesh,
+            plane,
+            dist,
+            remap: vec![None; mesh.positions().len()],
+            edges: HashMap::new(),
+            pos: Vec::new(),
+            norm: Vec::new(),
+            uv: Vec::new(),
+            color: Vec::new(),
+            joints: Vec::new(),
+            on_plane: Vec::new(),
+        }
+    }
+
+    /// Get clipped vertex of a source vertex
+    fn vertex(&mut self, v: usize) -> usize {
+        if let Some(cv) = self.remap[v] {
+            return cv;
+        }
+        let mesh = self.mesh;
+        let on_plane = self.dist[v] == 0.0;
+        let pos = if on_plane {
+            self.plane.project(mesh.positions()[v])
+        } else {
+            mesh.positions()[v]
+        };
+        self.pos.push(pos);
+        self.norm.push(mesh.normals()[v]);
+        if let Some(uv) = mesh.uvs().get(v) {
+            self.uv.push(*uv);
+        }
+        if let Some(color) = mesh.colors().get(v) {
+            self.color.push(*color);
+        }
+        if let Some(joint) = mesh.joints().get(v) {
+            self.joints.push(*joint);
+        }
+        self.on_plane.push(on_plane);
+        let cv = self.pos.len() - 1;
+        self.remap[v] = Some(cv);
+        cv
+    }
+
+    /// Get clipped vertex where a source edge crosses the plane
+    fn intersect(&mut self, a: usize, b: usize) -> usize {
+        let key = (a.min(b), a.max(b));
+        if let Some(cv) = self.edges.get(&key) {
+            return *cv;
+        }
+        let mesh = self.mesh;
+        let (a, b) = key;
+        let t = self.dist[a] / (self.dist[a] - self.dist[b]);
+        let pos = mesh.positions()[a].lerp(mesh.positions()[b], t);
+        self.pos.push(self.plane.project(pos));
+        let (na, nb) = (mesh.normals()[a], mesh.normals()[b]);
+        self.norm.push(na.lerp(nb, t).try_normalize().unwrap_or(na));
+        if !mesh.uvs().is_empty() {
+            self.uv.push(mesh.uvs()[a].lerp(mesh.uvs()[b], t));
+        }
+        if !mesh.colors().is_empty() {
+            self.color.push(mesh.colors()[a].lerp(mesh.colors()[b], t));
+        }
+        if !mesh.joints().is_empty() {
+            let v = if t < 0.5 { a } else { b };
+            self.joints.push(mesh.joints()[v]);
+        }
+        self.on_plane.push(true);
+        let cv = self.pos.len() - 1;
+        self.edges.insert(key, cv);
+        cv
+    }
+
+    /// Clip one triangle, returning a polygon of clipped vertices
+    fn clip_triangle(&mut self, tri: [usize; 3]) -> Vec<usize> {
+        if tri.iter().all(|v| self.dist[*v] <= 0.0) {
+            return Vec::new();
+        }
+        let mut poly = Vec::with_capacity(4);
+        for i in 0..3 {
+            let (a, b) = (tri[i], tri[(i + 1) % 3]);
+            let (da, db) = (self.dist[a], self.dist[b]);
+            if da >= 0.0 {
+                poly.push(self.vertex(a));
+            }
+            if (da > 0.0 && db < 0.0) || (da < 0.0 && db > 0.0) {
+                poly.push(self.intersect(a, b));
+            }
+        }
+        poly
+    }
+}
+
+/// Get position key of a point
+fn pos_key(pt: Vec3) -> [u32; 3] {
+    pt.to_array().map(f32::to_bits)
+}
+
+/// Chain cut edges into closed loops for a cap
+///
+/// Cap edges run opposite to the cut edges, and edges cut in both directions
+/// cancel out.
+fn cap_loops(pos: &[Vec3], cuts: &[(usize, usize, usize)]) -> Vec<Vec<usize>> {
+    let mut edges = HashMap::<([u32; 3], [u32; 3]), Vec<(usize, usize)>>::new();
+    for (a, b, _) in cuts {
+        let (ka, kb) = (pos_key(pos[*a]), pos_key(pos[*b]));
+        if let Some(rev) = edges.get_mut(&(kb, ka)) {
+            if rev.pop().is_some() {
+                continue;
+            }
+        }
+        edges.entry((ka, kb)).or_default().push((*b, *a));
+    }
+    let mut cap_edges: Vec<(usize, usize)> =
+        edges.into_values().flatten().collect();
+    cap_edges.sort_unstable();
+    // cap edges by starting position
+    let mut starts = HashMap::<[u32; 3], Vec<usize>>::new();
+    for (i, (from, _)) in cap_edges.iter().enumerate() {
+        starts.entry(pos_key(pos[*from])).or_default().push(i);
+    }
+    let mut used = vec![false; cap_edges.len()];
+    let mut loops = Vec::new();
+    for i in 0..cap_edges.len() {
+        if used[i] {
+            continue;
+        }
+        used[i] = true;
+        let (from, mut to) = cap_edges[i];
+        let start = pos_key(pos[from]);
+        let mut lp = vec![from];
+        let closed = loop {
+            let key = pos_key(pos[to]);
+            if key == start {
+                break true;
+            }
+            lp.push(to);
+            let next = starts
+                .get(&key)
+                .and_then(|es| es.iter().find(|e| !used[**e]).copied());
+            match next {
+                Some(e) => {
+                    used[e] = true;
+                    to = cap_edges[e].1;
+                }
+                None => break false,
+            }
+        };
+        if closed && lp.len() >= 3 {
+            loops.push(lp);
+        }
+    }
+    loops
+}
+
+/// Calculate signed area of a polygon (positive if counter-clockwise)
+fn signed_area(pts: &[Vec2], poly: &[usize]) -> f32 {
+    let n = poly.len();
+    (0..n)
+        .map(|i| pts[poly[i]].perp_dot(pts[poly[(i + 1) % n]]))
+        .sum::<f32>()
+        * 0.5
+}
+
+/// Check if a point is inside a polygon
+fn contains(pts: &[Vec2], poly: &[usize], pt: Vec2) -> bool {
+    let n = poly.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (pts[poly[i]], pts[poly[(i + 1) % n]]);
+        if (a.y > pt.y) != (b.y > pt.y)
+            && pt.x < a.x + (pt.y - a.y) * (b.x - a.x) / (b.y - a.y)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Check if two segments cross, excluding end-points
+fn crosses(a0: Vec2, a1: Vec2, b0: Vec2, b1: Vec2) -> bool {
+    let da = a1 - a0;
+    let db = b1 - b0;
+    let s0 = da.perp_dot(b0 - a0);
+    let s1 = da.perp_dot(b1 - a0);
+    let t0 = db.perp_dot(a0 - b0);
+    let t1 = db.perp_dot(a1 - b0);
+    s0 * s1 < 0.0 && t0 * t1 < 0.0
+}
+
+/// Check if a bridge segment crosses any polygon edges
+fn bridge_crosses(
+    pts: &[Vec2],
+    polys: &[&[usize]],
+    p0: Vec2,
+    p1: Vec2,
+) -> bool {
+    polys.iter().any(|poly| {
+        let n = poly.len();
+        (0..n).any(|i| crosses(p0, p1, pts[poly[i]], pts[poly[(i + 1) % n]]))
+    })
+}
+
+/// Merge a hole into an outer polygon, with a bridge between them
+fn bridge_hole(
+    pts: &[Vec2],
+    outer: &[usize],
+    hole: &[usize],
+    others: &[Vec<usize>],
+) -> Vec<usize> {
+    let mut polys: Vec<&[usize]> = vec![outer, hole];
+    polys.extend(others.iter().map(|p| &p[..]));
+    let mut best = None;
+    for (j, h) in hole.iter().enumerate() {
+        for (i, o) in outer.iter().enumerate() {
+            let d = pts[*h].distance_squared(pts[*o]);
+            if best.is_none_or(|(_, _, bd)| d < bd)
+                && !bridge_crosses(pts, &polys, pts[*h], pts[*o])
+            {
+                best = Some((i, j, d));
+            }
+        }
+    }
+    let (i, j, _) = best.unwrap_or((0, 0, 0.0));
+    let mut merged = Vec::with_capacity(outer.len() + hole.len() + 2);
+    merged.extend_from_slice(&outer[..=i]);
+    merged.extend_from_slice(&hole[j..]);
+    merged.extend_from_slice(&hole[..=j]);
+    merged.extend_from_slice(&outer[i..]);
+    merged
+}
+
+/// Check if a point is inside (or on the edge of) a triangle
+fn in_triangle(pt: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    (b - a).perp_dot(pt - a) >= 0.0
+        && (c - b).perp_dot(pt - b) >= 0.0
+        && (a - c).perp_dot(pt - c) >= 0.0
+}
+
+/// Triangulate a counter-clockwise polygon by ear clipping
+fn triangulate(pts: &[Vec2], mut poly: Vec<usize>) -> Vec<[usize; 3]> {
+    let mut tris = Vec::with_capacity(poly.len().saturating_sub(2));
+    while poly.len() > 3 {
+        let n = poly.len();
+        let ear = (0..n)
+            .find(|i| {
+                let [a, b, c] = [i + n - 1, *i, i + 1].map(|j| poly[j % n]);
+                let [pa, pb, pc] = [a, b, c].map(|v| pts[v]);
+                (pb - pa).perp_dot(pc - pb) > 0.0
+                    && poly.iter().all(|v| {
+                        let p = pts[*v];
+                        p == pa
+                            || p == pb
+                            || p == pc
+                            || !in_triangle(p, pa, pb, pc)
+                    })
+            })
+            .unwrap_or(0);
+        let [a, b, c] = [ear + n - 1, ear, ear + 1].map(|j| poly[j % n]);
+        tris.push([a, b, c]);
+        poly.remove(ear);
+    }
+    if let [a, b, c] = poly[..] {
+        tris.push([a, b, c]);
+    }
+    tris
+}
+
+/// Triangulate loops of a planar cap, with holes
+fn triangulate_loops(pts: &[Vec2], loops: Vec<Vec<usize>>) -> Vec<[usize; 3]> {
+    let (mut outers, mut holes): (Vec<_>, Vec<_>) =
+        loops.into_iter().partition(|l| signed_area(pts, l) > 0.0);
+    // merge holes from right to left, into the smallest containing loop
+    let right =
+        |l: &Vec<usize>| l.iter().map(|v| pts[*v].x).fold(f32::MIN, f32::max);
+    holes.sort_by(|a, b| right(b).total_cmp(&right(a)));
+    while !holes.is_empty() {
+        let hole = holes.remove(0);
+        let pt = pts[hole[0]];
+        let outer = outers
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| contains(pts, o, pt))
+            .min_by(|(_, a), (_, b)| {
+                signed_area(pts, a).total_cmp(&signed_area(pts, b))
+            })
+            .map(|(i, _)| i);
+        if let Some(i) = outer {
+            outers[i] = bridge_hole(pts, &outers[i], &hole, &holes);
+        }
+    }
+    outers
+        .into_iter()
+        .flat_map(|o| triangulate(pts, o))
+        .collect()
+}
+
+impl Mesh {
+    /// Make a copy clipped by a plane
+    ///
+    /// Faces on the negative side of the plane (opposite its normal) are
+    /// removed, and faces crossing it are split.  New vertices are
+    /// interpolated from the ends of the split edges.  Faces lying in the
+    /// plane are removed.
+    ///
+    /// If `cap` is `true`, the cut is closed with flat faces on a new
+    /// surface, so a watertight mesh stays watertight.  This is useful for
+    /// cutaway views, or flattening the bottom of a model for printing.
+    /// Ambient occlusion is not kept.
+    ///
+    /// ```rust
+    /// # use homunculus::{build_cube, Error, Plane};
+    /// # use glam::Vec3;
+    /// # fn main() -> Result<(), Error> {
+    /// let cube = build_cube()?;
+    /// let plane = Plane::new(Vec3::NEG_Y, Vec3::new(0.0, 0.25, 0.0));
+    /// let slab = cube.clipped(plane, true)?;
+    /// assert!(slab.is_watertight());
+    /// assert!((slab.volume() - 0.25).abs() < 1e-5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [Error::TooManyVertices] if the result has more than 65536 vertices
+    ///
+    /// [Error::TooManyVertices]: enum.Error.html#variant.TooManyVertices
+    pub fn clipped(&self, plane: Plane, cap: bool) -> Result<Mesh> {
+        let n_faces = self.indices().len() / 3;
+        let has_materials = self.face_materials.len() == n_faces;
+        let has_surfaces = self.surfaces.len() == n_faces;
+        let mut clip = Clipper::new(self, plane);
+        let mut indices = Vec::with_capacity(self.indices().len());
+        let mut face_materials = Vec::new();
+        let mut surfaces = Vec::new();
+        // edges on the plane, with source face
+        let mut cuts = Vec::new();
+        for (f, tri) in self.indices().chunks_exact(3).enumerate() {
+            let tri = [0, 1, 2].map(|i| usize::from(tri[i].0));
+            let poly = clip.clip_triangle(tri);
+            let n = poly.len();
+            for i in 0..n {
+                let (a, b) = (poly[i], poly[(i + 1) % n]);
+                if clip.on_plane[a] && clip.on_plane[b] {
+                    cuts.push((a, b, f));
+                }
+            }
+            for i in 1..n.saturating_sub(1) {
+                let vtx = [poly[0], poly[i], poly[i + 1]];
+                if vtx[0] == vtx[1] || vtx[1] == vtx[2] || vtx[0] == vtx[2] {
+                    continue;
+                }
+                for v in vtx {
+                    indices.push(Vertex::try_from(v)?);
+                }
+                if has_materials {
+                    face_materials.push(self.face_materials[f]);
+                }
+                if has_surfaces {
+                    surfaces.push(self.surfaces[f]);
+                }
+            }
+        }
+        let Clipper {
+            mut pos,
+            mut norm,
+            mut uv,
+            mut color,
+            mut joints,
+            ..
+        } = clip;
+        if cap {
+            let surface = self.surfaces.iter().max().map_or(0, |s| s + 1);
+            let material = cuts.first().and_then(|(_, _, f)| {
+                self.face_materials.get(*f).copied().flatten()
+            });
+            let (u, v) = plane.basis();
+            let mut pts = Vec::new();
+            let mut loops = Vec::new();
+            let base = pos.len();
+            for lp in cap_loops(&pos, &cuts) {
+                let mut ids = Vec::with_capacity(lp.len());
+                for cv in lp {
+                    let p = pos[cv];
+                    let pt = Vec2::new(p.dot(u), p.dot(v));
+                    ids.push(pts.len());
+                    pts.push(pt);
+                    pos.push(p);
+                    norm.push(-plane.normal());
+                    if !uv.is_empty() {
+                        uv.push(pt);
+                    }
+                    if !color.is_empty() {
+                        color.push(color[cv]);
+                    }
+                    if !joints.is_empty() {
+                        joints.push(joints[cv]);
+                    }
+                }
+                loops.push(ids);
+            }
+            for tri in triangulate_loops(&pts, loops) {
+                for i in tri {
+                    indices.push(Vertex::try_from(base + i)?);
+                }
+                if has_materials {
+                    face_materials.push(material);
+                }
+                if has_surfaces {
+                    surfaces.push(surface);
+                }
+            }
+        }
+        let mut mesh = Mesh::from_parts(pos, norm, indices);
+        mesh.uv = uv;
+        mesh.color = color;
+        mesh.joints = joints;
+        mesh.skeleton.clone_from(&self.skeleton);
+        mesh.materials = self.materials.clone();
+        mesh.face_materials = face_materials;
+        mesh.surfaces = surfaces;
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{build_cube, Husk, Mesh, Plane, Ring};
+    use glam::Vec3;
+
+    /// Make a cup, with walls of a given thickness
+    fn cup(thickness: f32) -> Mesh {
+        let mut husk = Husk::new();
+        husk.set_auto_cap(false);
+        let ring = (0..16).fold(Ring::default(), |r, _| r.spoke(1.0));
+        husk.ring_capped(ring).unwrap();
+        husk.ring(Ring::default().axis(Vec3::new(0.0, 2.0, 0.0)))
+            .unwrap();
+        husk.shell(thickness);
+        husk.into_mesh().unwrap()
+    }
+
+    #[test]
+    fn cube() {
+        let cube = build_cube().unwrap();
+        let plane = Plane::new(Vec3::new(1.0, 1.0, 0.0), Vec3::splat(0.5));
+        let half = cube.clipped(plane, true).unwrap();
+        assert!(half.is_watertight());
+        assert!(!half.is_inside_out());
+        assert!((half.volume() - cube.volume() * 0.5).abs() < 1e-5);
+        assert!(half.positions().iter().all(|p| plane.distance(*p) >= 0.0));
+        // cap is a separate surface
+        let cap = cube.surfaces().iter().max().unwrap() + 1;
+        assert!(half.surfaces().contains(&cap));
+        // without a cap, the cut is left open
+        let open = cube.clipped(plane, false).unwrap();
+        assert!(!open.is_watertight());
+        assert!(!open.surfaces().contains(&cap));
+    }
+
+    #[test]
+    fn unchanged() {
+        let cube = build_cube().unwrap();
+        let clipped = cube.clipped(Plane::y(-1.0), true).unwrap();
+        assert_eq!(clipped.indices().len(), cube.indices().len());
+        let clipped = cube.clipped(Plane::y(2.0), true).unwrap();
+        assert!(clipped.indices().is_empty());
+    }
+
+    #[test]
+    fn cap_with_hole() {
+        let cup = cup(0.1);
+        // keep the bottom half, cutting through the walls
+        let plane = Plane::new(Vec3::NEG_Y, Vec3::new(0.0, 1.0, 0.0));
+        let bottom = cup.clipped(plane, true).unwrap();
+        assert!(bottom.is_watertight());
+        assert!(!bottom.is_inside_out());
+        // top half is a tube, capped with an annulus at the cut
+        let plane = Plane::new(Vec3::Y, Vec3::new(0.0, 1.0, 0.0));
+        let top = cup.clipped(plane, true).unwrap();
+        assert!(top.is_watertight());
+        let volume = bottom.volume() + top.volume();
+        assert!((volume - cup.volume()).abs() < 1e-4);
+        assert!(top.volume() > 0.0 && top.volume() < bottom.volume());
+    }
+}
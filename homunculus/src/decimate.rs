@@ -0,0 +1,446 @@
+// decimate.rs  Mesh decimation (edge collapse)
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::mesh::{Mesh, Vertex};
+use crate::skeleton::Skeleton;
+use glam::{Vec2, Vec3, Vec4};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Edge collapse candidate
+#[derive(Clone, Copy, Debug)]
+struct Candidate {
+    /// Collapse cost (squared edge length)
+    cost: f32,
+
+    /// Edge vertices
+    vtx: (usize, usize),
+
+    /// Vertex versions when candidate was made
+    versions: (u32, u32),
+}
+
+/// Mesh decimator
+struct Decimator {
+    /// Vertex positions
+    pos: Vec<Vec3>,
+
+    /// Vertex normals
+    norm: Vec<Vec3>,
+
+    /// Vertex texture coordinates (may be empty)
+    uv: Vec<Vec2>,
+
+    /// Vertex colors (may be empty)
+    color: Vec<Vec4>,
+
+    /// Vertex ambient occlusion (may be empty)
+    ao: Vec<f32>,
+
+    /// Vertex skeleton joints (may be empty)
+    joints: Vec<u16>,
+
+    /// Skeleton for skinning
+    skeleton: Option<Skeleton>,
+
+    /// Vertex versions (incremented when moved)
+    version: Vec<u32>,
+
+    /// Vertex removed by collapse
+    removed: Vec<bool>,
+
+    /// Vertex on a boundary (or seam) edge
+    boundary: Vec<bool>,
+
+    /// Faces of each vertex (some may be dead)
+    vfaces: Vec<Vec<usize>>,
+
+    /// Triangle faces
+    faces: Vec<[usize; 3]>,
+
+    /// Material names
+    materials: Vec<String>,
+
+    /// Material of each face (may be empty)
+    face_materials: Vec<Option<u16>>,
+
+    /// Surface of each face (may be empty)
+    surfaces: Vec<u16>,
+
+    /// Face still alive
+    alive: Vec<bool>,
+
+    /// Count of alive faces
+    n_faces: usize,
+
+    /// Collapse candidates (cheapest first)
+    heap: BinaryHeap<Candidate>,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost.total_cmp(&other.cost) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed, for a min-heap
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// Get normal of a triangle (not normalized)
+fn tri_normal(p: [Vec3; 3]) -> Vec3 {
+    (p[1] - p[0]).cross(p[2] - p[0])
+}
+
+impl Decimator {
+    /// Create a decimator for a mesh
+    fn new(mesh: &Mesh) -> Self {
+        let pos = mesh.positions().to_vec();
+        let norm = mesh.normals().to_vec();
+        let n_vtx = pos.len();
+        let faces: Vec<[usize; 3]> = mesh
+            .indices()
+            .chunks_exact(3)
+            .map(|t| [0, 1, 2].map(|i| usize::from(t[i].0)))
+            .collect();
+        let mut vfaces = vec![Vec::new(); n_vtx];
+        let mut edges = HashMap::new();
+        for (f, face) in faces.iter().enumerate() {
+            for i in 0..3 {
+                vfaces[face[i]].push(f);
+                let (a, b) = (face[i], face[(i + 1) % 3]);
+                *edges.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+            }
+        }
+        let mut boundary = vec![false; n_vtx];
+        for ((a, b), count) in edges {
+            if count != 2 {
+                boundary[a] = true;
+                boundary[b] = true;
+            }
+        }
+        let n_faces = faces.len();
+        let mut dec = Decimator {
+            pos,
+            norm,
+            uv: mesh.uvs().to_vec(),
+            color: mesh.colors().to_vec(),
+            ao: mesh.ambient_occlusion().to_vec(),
+            joints: mesh.joints().to_vec(),
+            skeleton: mesh.skeleton().cloned(),
+            version: vec![0; n_vtx],
+            removed: vec![false; n_vtx],
+            boundary,
+            vfaces,
+            materials: mesh.materials.clone(),
+            face_materials: mesh.face_materials.clone(),
+            surfaces: mesh.surfaces.clone(),
+            alive: vec![true; n_faces],
+            faces,
+            n_faces,
+            heap: BinaryHeap::new(),
+        };
+        for f in 0..n_faces {
+            let face = dec.faces[f];
+            for i in 0..3 {
+                let (a, b) = (face[i], face[(i + 1) % 3]);
+                if a < b {
+                    dec.push_candidate(a, b);
+                }
+            }
+        }
+        dec
+    }
+
+    /// Push an edge collapse candidate
+    fn push_candidate(&mut self, a: usize, b: usize) {
+        if self.boundary[a] && self.boundary[b] {
+            return;
+        }
+        self.heap.push(Candidate {
+            cost: self.pos[a].distance_squared(self.pos[b]),
+            vtx: (a, b),
+            versions: (self.version[a], self.version[b]),
+        });
+    }
+
+    /// Get alive faces of a vertex
+    fn faces_of(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        self.vfaces[v].iter().copied().filter(|f| self.alive[*f])
+    }
+
+    /// Get neighbor vertices of a vertex
+    fn neighbors(&self, v: usize) -> Vec<usize> {
+        let mut nbrs: Vec<usize> = self
+            .faces_of(v)
+            .flat_map(|f| self.faces[f])
+            .filter(|n| *n != v)
+            .collect();
+        nbrs.sort_unstable();
+        nbrs.dedup();
+        nbrs
+    }
+
+    /// Check if a collapse candidate is still valid
+    fn is_current(&self, cand: &Candidate) -> bool {
+        let (a, b) = cand.vtx;
+        !self.removed[a]
+            && !self.removed[b]
+            && cand.versions == (self.version[a], self.version[b])
+    }
+
+    /// Try to collapse an edge
+    fn collapse(&mut self, a: usize, b: usize) -> bool {
+        // keep boundary vertices in place
+        let (keep, drop) = if self.boundary[b] { (b, a) } else { (a, b) };
+        let shared: Vec<usize> = self
+            .faces_of(drop)
+            .filter(|f| self.faces[*f].contains(&keep))
+            .collect();
+        if shared.is_empty() {
+            return false;
+        }
+        // link condition: only opposite vertices of shared faces in common
+        let nk = self.neighbors(keep);
+        let common = self
+            .neighbors(drop)
+            .iter()
+            .filter(|n| nk.contains(n))
+            .count();
+        if common != shared.len() {
+            return false;
+        }
+        let pos = if self.boundary[keep] {
+            self.pos[keep]
+        } else {
+            (self.pos[keep] + self.pos[drop]) * 0.5
+        };
+        // reject collapses which would flip faces
+        for v in [keep, drop] {
+            for f in self.faces_of(v) {
+                if shared.contains(&f) {
+                    continue;
+                }
+                let before = self.faces[f].map(|i| self.pos[i]);
+                let after = self.faces[f].map(|i| {
+                    if i == keep || i == drop {
+                        pos
+                    } else {
+                        self.pos[i]
+                    }
+                });
+                if tri_normal(before).dot(tri_normal(after)) <= 0.0 {
+                    return false;
+                }
+            }
+        }
+        for f in shared {
+            self.alive[f] = false;
+            self.n_faces -= 1;
+        }
+        let moved: Vec<usize> = self.faces_of(drop).collect();
+        for f in moved {
+            for i in self.faces[f].iter_mut() {
+                if *i == drop {
+                    *i = keep;
+                }
+            }
+            self.vfaces[keep].push(f);
+        }
+        self.pos[keep] = pos;
+        let norm = self.norm[keep] + self.norm[drop];
+        self.norm[keep] = norm.try_normalize().unwrap_or(self.norm[keep]);
+        if !self.uv.is_empty() && !self.boundary[keep] {
+            self.uv[keep] = (self.uv[keep] + self.uv[drop]) * 0.5;
+        }
+        if !self.color.is_empty() {
+            self.color[keep] = (self.color[keep] + self.color[drop]) * 0.5;
+        }
+        if !self.ao.is_empty() {
+            self.ao[keep] = (self.ao[keep] + self.ao[drop]) * 0.5;
+        }
+        self.removed[drop] = true;
+        self.version[keep] += 1;
+        for n in self.neighbors(keep) {
+            self.push_candidate(keep, n);
+        }
+        true
+    }
+
+    /// Collapse edges until face count is reached
+    fn run(&mut self, faces: usize) {
+        while self.n_faces > faces {
+            let Some(cand) = self.heap.pop() else {
+                break;
+            };
+            if self.is_current(&cand) {
+                self.collapse(cand.vtx.0, cand.vtx.1);
+            }
+        }
+    }
+
+    /// Build the decimated mesh
+    fn build(self) -> Mesh {
+        let mut vids = vec![None; self.pos.len()];
+        let mut pos = Vec::new();
+        let mut norm = Vec::new();
+        let mut uv = Vec::new();
+        let mut color = Vec::new();
+        let mut ao = Vec::new();
+        let mut joints = Vec::new();
+        let mut indices = Vec::with_capacity(self.n_faces * 3);
+        let mut face_materials = Vec::new();
+        let mut surfaces = Vec::new();
+        for (f, (face, alive)) in self.faces.iter().zip(&self.alive).enumerate()
+        {
+            if !alive {
+                continue;
+            }
+            if let Some(m) = self.face_materials.get(f) {
+                face_materials.push(*m);
+            }
+            if let Some(s) = self.surfaces.get(f) {
+                surfaces.push(*s);
+            }
+            for v in face {
+                let vid = *vids[*v].get_or_insert_with(|| {
+                    pos.push(self.pos[*v]);
+                    norm.push(self.norm[*v]);
+                    if let Some(u) = self.uv.get(*v) {
+                        uv.push(*u);
+                    }
+                    if let Some(c) = self.color.get(*v) {
+                        color.push(*c);
+                    }
+                    if let Some(a) = self.ao.get(*v) {
+                        ao.push(*a);
+                    }
+                    if let Some(j) = self.joints.get(*v) {
+                        joints.push(*j);
+                    }
+                    pos.len() - 1
+                });
+                // unwrap note: fewer vertices than the original mesh
+                indices.push(Vertex::try_from(vid).unwrap());
+            }
+        }
+        let mut mesh = Mesh::from_parts(pos, norm, indices);
+        mesh.uv = uv;
+        mesh.color = color;
+        mesh.materials = self.materials;
+        mesh.face_materials = face_materials;
+        mesh.surfaces = surfaces;
+        mesh.ao = ao;
+        mesh.joints = joints;
+        mesh.skeleton = self.skeleton;
+        mesh
+    }
+}
+
+impl Mesh {
+    /// Decimate the mesh, keeping a ratio of its faces
+    ///
+    /// Shortest edges are collapsed first.  Vertices on boundary edges
+    /// (including seams of split vertices) are kept in place, so meshes
+    /// with flat shading are not reduced much.
+    ///
+    /// # Panics
+    ///
+    /// - If `ratio` is not between 0 and 1
+    pub fn decimate(&self, ratio: f32) -> Mesh {
+        assert!((0.0..=1.0).contains(&ratio));
+        let faces = self.indices().len() / 3;
+        self.decimate_to((faces as f32 * ratio).round() as usize)
+    }
+
+    /// Decimate the mesh to a number of faces
+    fn decimate_to(&self, faces: usize) -> Mesh {
+        let mut dec = Decimator::new(self);
+        dec.run(faces);
+        dec.build()
+    }
+
+    /// Make a chain of levels of detail
+    ///
+    /// Each ratio is the faces to keep, relative to this mesh.  Each level
+    /// is decimated from the previous one.
+    ///
+    /// # Panics
+    ///
+    /// - If any ratio is not between 0 and 1
+    pub fn lod_chain(&self, ratios: &[f32]) -> Vec<Mesh> {
+        let faces = self.indices().len() / 3;
+        let mut lods: Vec<Mesh> = Vec::with_capacity(ratios.len());
+        for ratio in ratios {
+            assert!((0.0..=1.0).contains(ratio));
+            let target = (faces as f32 * ratio).round() as usize;
+            let lod = lods.last().unwrap_or(self).decimate_to(target);
+            lods.push(lod);
+        }
+        lods
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Husk, Ring};
+
+    /// Make a smooth tapered tube mesh
+    fn tube() -> Mesh {
+        let mut husk = Husk::new();
+        let mut ring = Ring::default().axis(Vec3::new(0.0, 0.3, 0.0));
+        for _ in 0..16 {
+            ring = ring.spoke(1.0);
+        }
+        husk.ring(ring).unwrap();
+        for i in 1..20 {
+            let scale = 1.0 - 0.02 * i as f32;
+            husk.ring(Ring::default().scale(scale)).unwrap();
+        }
+        husk.into_mesh().unwrap()
+    }
+
+    fn face_count(mesh: &Mesh) -> usize {
+        mesh.indices().len() / 3
+    }
+
+    #[test]
+    fn lod_ratios() {
+        let mesh = tube();
+        let faces = face_count(&mesh);
+        let lods = mesh.lod_chain(&[0.5, 0.25]);
+        assert_eq!(lods.len(), 2);
+        for (lod, ratio) in lods.iter().zip([0.5, 0.25]) {
+            let target = faces as f32 * ratio;
+            let count = face_count(lod) as f32;
+            assert!((count - target).abs() <= target * 0.1, "{count}");
+            assert!(lod.positions().len() < mesh.positions().len());
+            assert_eq!(lod.positions().len(), lod.normals().len());
+        }
+        // bounds are roughly preserved
+        let lod = &lods[1];
+        assert!(lod.pos_min().distance(mesh.pos_min()) < 0.1);
+        assert!(lod.pos_max().distance(mesh.pos_max()) < 0.1);
+    }
+
+    #[test]
+    fn decimate_none() {
+        let mesh = tube();
+        let same = mesh.decimate(1.0);
+        assert_eq!(face_count(&same), face_count(&mesh));
+        assert_eq!(same.positions().len(), mesh.positions().len());
+    }
+}
@@ -14,9 +14,147 @@ pub enum Error {
     #[error("Invalid branches: {0}")]
     InvalidBranches(String),
 
+    /// Invalid ring axis
+    #[error("Invalid axis: {0}")]
+    InvalidAxis(glam::Vec3),
+
+    /// Invalid ring offset
+    #[error("Invalid offset: {0}")]
+    InvalidOffset(glam::Vec2),
+
+    /// Invalid ring scale
+    #[error("Invalid scale: {0}")]
+    InvalidScale(f32),
+
+    /// Invalid ring jitter
+    #[error("Invalid jitter: {0}")]
+    InvalidJitter(f32),
+
+    /// Invalid spoke distance
+    #[error("Invalid spoke: {0}")]
+    InvalidSpoke(f32),
+
+    /// Invalid spoke angle
+    #[error("Invalid spoke angle: {0}")]
+    InvalidAngle(f32),
+
+    /// Invalid ring color
+    #[error("Invalid color: {0}")]
+    InvalidColor(glam::Vec4),
+
+    /// Invalid ring twist
+    #[error("Invalid twist: {0}")]
+    InvalidTwist(f32),
+
+    /// Invalid ring seam angle
+    #[error("Invalid seam: {0}")]
+    InvalidSeam(f32),
+
+    /// Invalid superellipse exponent
+    #[error("Invalid exponent: {0}")]
+    InvalidExponent(f32),
+
+    /// Invalid count (of spokes or rings)
+    #[error("Invalid count: {0}")]
+    InvalidCount(usize),
+
+    /// Invalid weld distance
+    #[error("Invalid weld: {0}")]
+    InvalidWeld(f32),
+
+    /// Invalid shell thickness
+    #[error("Invalid thickness: {0}")]
+    InvalidThickness(f32),
+
+    /// Branch ring cannot be changed
+    #[error("Branch ring: {0}")]
+    BranchRing(&'static str),
+
+    /// Invalid glTF
+    #[error("Invalid glTF: {0}")]
+    InvalidGltf(String),
+
+    /// Invalid GLB header
+    #[error("Invalid GLB header: {0}")]
+    InvalidGlbHeader(&'static str),
+
+    /// Unsupported glTF accessor component type
+    #[error("Unsupported component type: {0}")]
+    UnsupportedComponentType(u64),
+
+    /// Mesh with no vertices, which cannot be exported
+    #[error("Empty mesh")]
+    EmptyMesh,
+
+    /// Too many vertices for 16-bit indices
+    #[error("Too many vertices: {count} (max {max})")]
+    TooManyVertices { count: usize, max: usize },
+
+    /// Face vertex index out of range
+    #[error("Invalid vertex index: {index} (len {len})")]
+    InvalidVertexIndex { index: usize, len: usize },
+
+    /// Face with a repeated vertex index
+    #[error("Repeated vertex index: {0}")]
+    RepeatedVertexIndex(usize),
+
     /// Unknown Branch Label
     #[error("Unknown branch label: {0}")]
     UnknownBranchLabel(String),
+
+    /// Branch labels never used by [Husk::branch]
+    ///
+    /// [Husk::branch]: struct.Husk.html#method.branch
+    #[error("Unresolved branches: {}", .0.join(", "))]
+    UnresolvedBranches(Vec<String>),
+
+    /// Part which cannot be attached by [Husk::attach]
+    ///
+    /// [Husk::attach]: struct.Husk.html#method.attach
+    #[error("Invalid part: {0}")]
+    InvalidPart(String),
+
+    /// Model definition parse error
+    #[error("Invalid model: {0}")]
+    ParseModel(String),
+
+    /// Invalid model definition
+    #[error("{0}")]
+    InvalidModel(&'static str),
+
+    /// Invalid field of a model definition
+    #[error("Invalid {field}: {value}")]
+    InvalidField { field: &'static str, value: String },
+
+    /// Invalid expression in a model definition
+    #[error("{0}")]
+    InvalidExpression(String),
+
+    /// Duplicate name of a model part
+    #[error("Duplicate model name: {0}")]
+    DuplicateName(String),
+
+    /// Error in a ring definition
+    #[error("Ring {ring}: {source}")]
+    RingDef { ring: usize, source: Box<Error> },
+
+    /// Error in a lathe definition
+    #[error("Lathe: {0}")]
+    LatheDef(Box<Error>),
+
+    /// Error in a named part definition
+    #[error("Model {name}: {source}")]
+    PartDef { name: String, source: Box<Error> },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// Handle an invalid setting
+///
+/// Panics in debug builds, otherwise logs a warning.
+pub(crate) fn ignore_invalid(err: Error) {
+    if cfg!(debug_assertions) {
+        panic!("{err}");
+    }
+    log::warn!("{err} (ignored)");
+}
@@ -0,0 +1,242 @@
+// expr.rs      Arithmetic expressions
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Variable values
+pub type Vars = HashMap<String, f32>;
+
+/// Recursive-descent expression parser
+///
+/// ```text
+/// expr   := term (('+' | '-') term)*
+/// term   := factor (('*' | '/') factor)*
+/// factor := '-' factor | '(' expr ')' | number | '$' name
+/// ```
+struct Parser<'a> {
+    /// Remaining characters
+    chars: Peekable<Chars<'a>>,
+
+    /// Variable values
+    vars: &'a Vars,
+}
+
+/// Make an invalid expression error
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::InvalidExpression(msg.into())
+}
+
+/// Evaluate an arithmetic expression
+///
+/// Supports `+ - * /`, parentheses, numbers and `$name` variables.
+pub fn eval(code: &str, vars: &Vars) -> Result<f32> {
+    let mut parser = Parser {
+        chars: code.chars().peekable(),
+        vars,
+    };
+    let val = parser.expr()?;
+    parser.skip_space();
+    match parser.chars.next() {
+        Some(c) => {
+            Err(invalid(format!("Unexpected '{c}' in expression: {code}")))
+        }
+        None => Ok(val),
+    }
+}
+
+/// Evaluate an angle expression (degrees)
+///
+/// An optional `deg` unit suffix is allowed, as in `15deg`.
+pub fn eval_degrees(code: &str, vars: &Vars) -> Result<f32> {
+    let code = code.trim();
+    eval(code.strip_suffix("deg").unwrap_or(code), vars)
+}
+
+/// Check if a character is valid in a variable name
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Parse variable definitions (`name=expr`)
+///
+/// Each expression can refer to previously defined variables.
+pub fn parse_vars(defs: &[String]) -> Result<Vars> {
+    let mut vars = Vars::new();
+    for def in defs {
+        match def.split_once('=') {
+            Some((name, code))
+                if !name.is_empty() && name.chars().all(is_name_char) =>
+            {
+                let val = eval(code, &vars)?;
+                vars.insert(name.to_string(), val);
+            }
+            _ => {
+                return Err(Error::InvalidField {
+                    field: "variable definition",
+                    value: def.to_string(),
+                })
+            }
+        }
+    }
+    Ok(vars)
+}
+
+impl<'a> Parser<'a> {
+    /// Skip whitespace
+    fn skip_space(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    /// Get next non-whitespace character, if it matches
+    fn next_if_eq(&mut self, ch: char) -> bool {
+        self.skip_space();
+        self.chars.next_if_eq(&ch).is_some()
+    }
+
+    /// Parse an expression
+    fn expr(&mut self) -> Result<f32> {
+        let mut val = self.term()?;
+        loop {
+            if self.next_if_eq('+') {
+                val += self.term()?;
+            } else if self.next_if_eq('-') {
+                val -= self.term()?;
+            } else {
+                return Ok(val);
+            }
+        }
+    }
+
+    /// Parse a term
+    fn term(&mut self) -> Result<f32> {
+        let mut val = self.factor()?;
+        loop {
+            if self.next_if_eq('*') {
+                val *= self.factor()?;
+            } else if self.next_if_eq('/') {
+                let div = self.factor()?;
+                if div == 0.0 {
+                    return Err(invalid("Division by zero"));
+                }
+                val /= div;
+            } else {
+                return Ok(val);
+            }
+        }
+    }
+
+    /// Parse a factor
+    fn factor(&mut self) -> Result<f32> {
+        if self.next_if_eq('-') {
+            return Ok(-self.factor()?);
+        }
+        if self.next_if_eq('(') {
+            let val = self.expr()?;
+            if !self.next_if_eq(')') {
+                return Err(invalid("Missing ')' in expression"));
+            }
+            return Ok(val);
+        }
+        if self.next_if_eq('$') {
+            let mut name = String::new();
+            while let Some(c) = self.chars.next_if(|c| is_name_char(*c)) {
+                name.push(c);
+            }
+            return match self.vars.get(&name) {
+                Some(val) => Ok(*val),
+                None => Err(invalid(format!("Unknown variable: ${name}"))),
+            };
+        }
+        self.number()
+    }
+
+    /// Parse a number
+    fn number(&mut self) -> Result<f32> {
+        let mut num = String::new();
+        while let Some(c) =
+            self.chars.next_if(|c| c.is_ascii_digit() || *c == '.')
+        {
+            num.push(c);
+        }
+        match num.parse() {
+            Ok(val) => Ok(val),
+            Err(_) => match self.chars.peek() {
+                Some(c) => {
+                    Err(invalid(format!("Unexpected '{c}' in expression")))
+                }
+                None => Err(invalid("Unexpected end of expression")),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn vars() -> Vars {
+        let mut vars = Vars::new();
+        vars.insert("girth".to_string(), 2.5);
+        vars.insert("h_2".to_string(), 4.0);
+        vars
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(eval("1", &vars()).unwrap(), 1.0);
+        assert_eq!(eval(" 0.25 ", &vars()).unwrap(), 0.25);
+        assert_eq!(eval("-3", &vars()).unwrap(), -3.0);
+        assert_eq!(eval("--3", &vars()).unwrap(), 3.0);
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(eval("1 + 2 * 3", &vars()).unwrap(), 7.0);
+        assert_eq!(eval("(1 + 2) * 3", &vars()).unwrap(), 9.0);
+        assert_eq!(eval("8 / 2 / 2", &vars()).unwrap(), 2.0);
+        assert_eq!(eval("8 - 2 - 2", &vars()).unwrap(), 4.0);
+        assert_eq!(eval("2*-(1+1)", &vars()).unwrap(), -4.0);
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(eval("$girth * 0.8", &vars()).unwrap(), 2.0);
+        assert_eq!(eval("$h_2/$girth", &vars()).unwrap(), 1.6);
+    }
+
+    #[test]
+    fn degrees() {
+        assert_eq!(eval_degrees("15deg", &vars()).unwrap(), 15.0);
+        assert_eq!(eval_degrees(" 15 deg ", &vars()).unwrap(), 15.0);
+        assert_eq!(eval_degrees("$girth * 2", &vars()).unwrap(), 5.0);
+        assert!(eval_degrees("deg", &vars()).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        let err = eval("$height", &vars()).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown variable: $height");
+        let err = eval("1 / (2 - 2)", &vars()).unwrap_err();
+        assert_eq!(err.to_string(), "Division by zero");
+        assert!(eval("(1 + 2", &vars()).is_err());
+        assert!(eval("1 2", &vars()).is_err());
+        assert!(eval("1 +", &vars()).is_err());
+        assert!(eval("", &vars()).is_err());
+        assert!(eval("abc", &vars()).is_err());
+    }
+
+    #[test]
+    fn definitions() {
+        let defs = ["w=2".to_string(), "h=$w*3+1".to_string()];
+        let vars = parse_vars(&defs).unwrap();
+        assert_eq!(vars["w"], 2.0);
+        assert_eq!(vars["h"], 7.0);
+        assert!(parse_vars(&["h=$w".to_string()]).is_err());
+        assert!(parse_vars(&["1.5".to_string()]).is_err());
+        assert!(parse_vars(&["a b=1".to_string()]).is_err());
+    }
+}
@@ -2,14 +2,16 @@
 //
 // Copyright (c) 2022-2024  Douglas Lau
 //
+use crate::error::{Error, Result};
 use crate::mesh::Mesh;
+use crate::skeleton::Skeleton;
+use glam::{Affine3A, Mat4, Quat, Vec3, Vec4};
 use serde_json::{json, Value};
-use serde_repr::Serialize_repr;
-use std::io::{Result, Write};
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::io::{self, Write};
 use std::mem::size_of;
 
 /// Component types for glTF accessor
-#[derive(Serialize_repr)]
 #[repr(u32)]
 #[allow(unused)]
 enum ComponentType {
@@ -21,14 +23,194 @@ enum ComponentType {
     F32 = 5126,
 }
 
+/// Primitive topology mode
+#[repr(u32)]
+enum Mode {
+    Lines = 1,
+}
+
 /// Target for glTF buffer view
-#[derive(Serialize_repr)]
 #[repr(u32)]
 enum Target {
     ArrayBuffer = 34962,
     ElementArrayBuffer = 34963,
 }
 
+/// Up axis convention
+///
+/// Models are built with Y up; other conventions are converted at export.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UpAxis {
+    /// Y axis up (glTF convention)
+    #[default]
+    Y,
+
+    /// Z axis up
+    Z,
+}
+
+/// glTF export options
+///
+/// Options can be set as fields, or chained from the defaults:
+///
+/// ```rust
+/// # use homunculus::{GltfOptions, UpAxis};
+/// let options = GltfOptions::default()
+///     .with_scale(0.01)
+///     .with_up(UpAxis::Z)
+///     .with_turntable(6.0);
+/// assert_eq!(options.up, UpAxis::Z);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GltfOptions {
+    /// Extras for the mesh
+    ///
+    /// Keys of an object value are merged into the mesh `extras`.
+    pub mesh_extras: Option<Value>,
+
+    /// Extras for the node
+    ///
+    /// Keys of an object value are merged into the node `extras`.
+    pub node_extras: Option<Value>,
+
+    /// Quantize vertex attributes ([KHR_mesh_quantization])
+    ///
+    /// Positions are stored as normalized `u16` within the bounding box,
+    /// with a node translation and uniform scale to restore them.  Normals
+    /// are stored as normalized `i8`, unless `i16_normals` is set.
+    ///
+    /// [KHR_mesh_quantization]: https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Khronos/KHR_mesh_quantization
+    pub quantize: bool,
+
+    /// Store quantized normals as normalized `i16`
+    ///
+    /// This is more precise than `i8`, at twice the size.  It is only used
+    /// with `quantize`.
+    pub i16_normals: bool,
+
+    /// Extras for the asset
+    ///
+    /// Keys of an object value are merged into the asset `extras`.
+    pub asset_extras: Option<Value>,
+
+    /// Uniform scale factor, for unit conversion
+    ///
+    /// Vertex positions and node translations are scaled at export time.
+    pub scale: Option<f32>,
+
+    /// Recenter so that the base of the bounding box is at the origin
+    ///
+    /// The bounding box is centered on the X and Z axes, with its minimum Y
+    /// at zero.  This is applied after `scale`.
+    pub center: bool,
+
+    /// Up axis convention
+    ///
+    /// Positions and normals are rotated at export time.  With `Z` up,
+    /// `center` puts the minimum Z at zero instead.
+    pub up: UpAxis,
+
+    /// Levels of detail, as ratios of faces to keep
+    ///
+    /// Each mesh is exported with additional decimated meshes, and nodes
+    /// are named `model_LOD0..N` (or with the mesh name instead of
+    /// `model`).
+    pub lods: Vec<f32>,
+
+    /// Use the [MSFT_lod] extension for levels of detail
+    ///
+    /// Only the `LOD0` nodes are put in the scene, with the others
+    /// referenced by the extension.
+    ///
+    /// [MSFT_lod]: https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Vendor/MSFT_lod
+    pub msft_lod: bool,
+
+    /// Bake ambient occlusion, with a number of rays per vertex
+    ///
+    /// Occlusion is exported as vertex colors (`COLOR_0`).  Meshes which
+    /// are already baked are not changed.
+    pub ao_samples: Option<usize>,
+
+    /// Scene name
+    ///
+    /// When not set, a scene with only one node uses its name.
+    pub scene_name: Option<String>,
+
+    /// Copyright message for the asset
+    pub copyright: Option<String>,
+
+    /// Omit vertex normals (`NORMAL`)
+    ///
+    /// This makes a smaller file.  Viewers must then calculate flat normals,
+    /// so smooth shading is lost.
+    pub omit_normals: bool,
+
+    /// Turntable animation period, in seconds
+    ///
+    /// A root node is added, with an animation spinning it one full turn
+    /// around the up axis.
+    pub turntable: Option<f32>,
+}
+
+/// Material for glTF export
+///
+/// Materials use the metallic-roughness model.  They are matched to rings by
+/// [Ring::material] name, and a material which does not match any ring is
+/// used for the remaining faces.
+///
+/// [Ring::material]: struct.Ring.html#method.material
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Material {
+    /// Material name
+    pub name: Option<String>,
+
+    /// Base color (RGBA), from 0 to 1
+    pub base_color: [f32; 4],
+
+    /// Metalness, from 0 to 1
+    pub metallic: f32,
+
+    /// Roughness, from 0 to 1
+    pub roughness: f32,
+
+    /// Render back faces
+    ///
+    /// This is useful for open husks, whose insides would otherwise be
+    /// invisible.
+    pub double_sided: bool,
+}
+
+/// Mesh with node name and placement
+pub(crate) struct MeshNode<'a> {
+    /// Node / mesh name
+    pub name: Option<&'a str>,
+
+    /// Mesh to export
+    pub mesh: &'a Mesh,
+
+    /// Node translation
+    pub translation: Vec3,
+
+    /// Node rotation
+    pub rotation: Quat,
+
+    /// Material definitions
+    pub materials: &'a [Material],
+
+    /// Named anchor transforms, exported as child nodes
+    pub anchors: &'a [(String, Affine3A)],
+}
+
+/// Mesh quantization extension name
+const QUANTIZATION: &str = "KHR_mesh_quantization";
+
+/// Level of detail extension name
+const MSFT_LOD: &str = "MSFT_lod";
+
+/// Generator name for the asset
+const GENERATOR: &str = concat!("homunculus ", env!("CARGO_PKG_VERSION"));
+
 /// Builder for glTF
 #[derive(Default)]
 struct Builder {
@@ -36,6 +218,16 @@ struct Builder {
     views: Vec<Value>,
     accessors: Vec<Value>,
     meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    skins: Vec<Value>,
+    animations: Vec<Value>,
+    extensions: Vec<&'static str>,
+    extensions_optional: Vec<&'static str>,
+    lod_nodes: Vec<usize>,
+    child_nodes: Vec<usize>,
+    skeletons: Vec<(Skeleton, Vec<usize>)>,
+    materials: Vec<Material>,
+    scene_name: Option<String>,
 }
 
 /// GLB writer
@@ -49,25 +241,517 @@ fn as_u8_slice<T: Sized>(p: &[T]) -> &[u8] {
     body
 }
 
+/// Merge user extras into a JSON object
+///
+/// User values replace any generated values with the same key.
+fn merge_extras(obj: &mut Value, extras: &Value) {
+    let Some(obj) = obj.as_object_mut() else {
+        return;
+    };
+    match (obj.get_mut("extras"), extras) {
+        (Some(Value::Object(gen)), Value::Object(user)) => {
+            for (key, val) in user {
+                if gen.contains_key(key) {
+                    log::warn!("glTF extras key \"{key}\" replaced");
+                }
+                gen.insert(key.clone(), val.clone());
+            }
+        }
+        (Some(gen), _) => {
+            log::warn!("glTF extras replaced");
+            *gen = extras.clone();
+        }
+        (None, _) => {
+            obj.insert("extras".to_string(), extras.clone());
+        }
+    }
+}
+
+/// Quantize positions to normalized `u16`
+///
+/// Returns quantized positions, translation and uniform scale.
+fn quantize_positions(mesh: &Mesh) -> (Vec<[u16; 4]>, Vec3, f32) {
+    let min = mesh.pos_min();
+    let scale = (mesh.pos_max() - min).max_element();
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    let pos = mesh
+        .positions()
+        .iter()
+        .map(|p| {
+            let q = ((*p - min) / scale * f32::from(u16::MAX)).round();
+            [q.x as u16, q.y as u16, q.z as u16, 0]
+        })
+        .collect();
+    (pos, min, scale)
+}
+
+/// Quantize normals to normalized `i8`
+fn quantize_normals(mesh: &Mesh) -> Vec<[i8; 4]> {
+    mesh.normals()
+        .iter()
+        .map(|n| {
+            let q = (*n * f32::from(i8::MAX)).round();
+            [q.x as i8, q.y as i8, q.z as i8, 0]
+        })
+        .collect()
+}
+
+/// Quantize normals to normalized `i16`
+fn quantize_normals_i16(mesh: &Mesh) -> Vec<[i16; 4]> {
+    mesh.normals()
+        .iter()
+        .map(|n| {
+            let q = (*n * f32::from(i16::MAX)).round();
+            [q.x as i16, q.y as i16, q.z as i16, 0]
+        })
+        .collect()
+}
+
+/// Get minimum / maximum components of quantized positions
+fn quantized_bounds(pos: &[[u16; 4]]) -> ([u16; 3], [u16; 3]) {
+    let mut min = [u16::MAX; 3];
+    let mut max = [0; 3];
+    for p in pos {
+        for i in 0..3 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    }
+    (min, max)
+}
+
+impl GltfOptions {
+    /// Set extras for the mesh
+    pub fn with_mesh_extras(mut self, extras: Value) -> Self {
+        self.mesh_extras = Some(extras);
+        self
+    }
+
+    /// Set extras for the node
+    pub fn with_node_extras(mut self, extras: Value) -> Self {
+        self.node_extras = Some(extras);
+        self
+    }
+
+    /// Set extras for the asset
+    pub fn with_asset_extras(mut self, extras: Value) -> Self {
+        self.asset_extras = Some(extras);
+        self
+    }
+
+    /// Add one key to the asset extras
+    ///
+    /// This is useful for recording provenance, such as the source file.
+    ///
+    /// ```rust
+    /// # use homunculus::GltfOptions;
+    /// # use serde_json::json;
+    /// let options = GltfOptions::default()
+    ///     .with_extra("source", json!("tree.hom"))
+    ///     .with_extra("seed", json!(42));
+    /// assert_eq!(options.asset_extras.unwrap()["seed"], 42);
+    /// ```
+    pub fn with_extra(mut self, key: &str, value: Value) -> Self {
+        let extras = self.asset_extras.get_or_insert_with(|| json!({}));
+        if !extras.is_object() {
+            log::warn!("glTF asset extras replaced");
+            *extras = json!({});
+        }
+        extras[key] = value;
+        self
+    }
+
+    /// Quantize vertex attributes
+    pub fn with_quantize(mut self) -> Self {
+        self.quantize = true;
+        self
+    }
+
+    /// Store quantized normals as `i16`
+    pub fn with_i16_normals(mut self) -> Self {
+        self.i16_normals = true;
+        self
+    }
+
+    /// Set uniform scale factor
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    /// Recenter with base of bounding box at the origin
+    pub fn with_center(mut self) -> Self {
+        self.center = true;
+        self
+    }
+
+    /// Set up axis convention
+    pub fn with_up(mut self, up: UpAxis) -> Self {
+        self.up = up;
+        self
+    }
+
+    /// Set levels of detail, as ratios of faces to keep
+    pub fn with_lods(mut self, lods: &[f32]) -> Self {
+        self.lods = lods.to_vec();
+        self
+    }
+
+    /// Use the `MSFT_lod` extension for levels of detail
+    pub fn with_msft_lod(mut self) -> Self {
+        self.msft_lod = true;
+        self
+    }
+
+    /// Bake ambient occlusion, with a number of rays per vertex
+    pub fn with_ao_samples(mut self, samples: usize) -> Self {
+        self.ao_samples = Some(samples);
+        self
+    }
+
+    /// Set scene name
+    pub fn with_scene_name(mut self, name: &str) -> Self {
+        self.scene_name = Some(name.to_string());
+        self
+    }
+
+    /// Set copyright message
+    pub fn with_copyright(mut self, copyright: &str) -> Self {
+        self.copyright = Some(copyright.to_string());
+        self
+    }
+
+    /// Omit vertex normals
+    pub fn with_omit_normals(mut self) -> Self {
+        self.omit_normals = true;
+        self
+    }
+
+    /// Add a turntable animation, with a period in seconds
+    pub fn with_turntable(mut self, seconds: f32) -> Self {
+        self.turntable = Some(seconds);
+        self
+    }
+}
+
+impl UpAxis {
+    /// Get rotation from Y up to this convention
+    fn rotation(self) -> Quat {
+        match self {
+            UpAxis::Y => Quat::IDENTITY,
+            UpAxis::Z => Quat::from_rotation_x(FRAC_PI_2),
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            name: None,
+            base_color: [1.0; 4],
+            metallic: 0.0,
+            roughness: 1.0,
+            double_sided: false,
+        }
+    }
+}
+
+impl Material {
+    /// Create a default material with a name
+    pub fn named(name: &str) -> Self {
+        Material {
+            name: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Check if all factors are within 0 to 1
+    pub(crate) fn is_valid(&self) -> bool {
+        self.base_color
+            .iter()
+            .chain([&self.metallic, &self.roughness])
+            .all(|v| (0.0..=1.0).contains(v))
+    }
+
+    /// Get material JSON
+    fn json(&self) -> Value {
+        let mut mat = json!({
+            "pbrMetallicRoughness": {
+                "baseColorFactor": self.base_color,
+                "metallicFactor": self.metallic,
+                "roughnessFactor": self.roughness,
+            },
+        });
+        if let Some(name) = &self.name {
+            mat["name"] = json!(name);
+        }
+        if self.double_sided {
+            mat["doubleSided"] = json!(true);
+        }
+        mat
+    }
+}
+
+impl<'a> MeshNode<'a> {
+    /// Create an unnamed mesh node at the origin
+    pub fn new(mesh: &'a Mesh) -> Self {
+        MeshNode {
+            name: None,
+            mesh,
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            materials: &[],
+            anchors: &[],
+        }
+    }
+
+    /// Get material for faces with a ring material name
+    fn material(&self, name: &str) -> Material {
+        self.materials
+            .iter()
+            .find(|m| m.name.as_deref() == Some(name))
+            .cloned()
+            .unwrap_or_else(|| Material::named(name))
+    }
+
+    /// Get material for faces without a ring material
+    fn default_material(&self) -> Option<Material> {
+        let names = self.mesh.materials();
+        self.materials
+            .iter()
+            .find(|m| m.name.as_ref().is_none_or(|n| !names.contains(n)))
+            .cloned()
+    }
+
+    /// Get minimum and maximum placed positions
+    fn bounds(&self) -> (Vec3, Vec3) {
+        self.mesh
+            .positions()
+            .iter()
+            .map(|p| self.rotation * *p + self.translation)
+            .fold((Vec3::MAX, Vec3::MIN), |(min, max), p| {
+                (min.min(p), max.max(p))
+            })
+    }
+
+    /// Make node JSON for a mesh index
+    ///
+    /// The dequantization `offset` and `scale` are applied before the node
+    /// placement.
+    fn json(&self, mesh_idx: usize, offset: Vec3, scale: Option<f32>) -> Value {
+        let mut node = json!({
+            "mesh": mesh_idx,
+        });
+        if let Some(name) = self.name {
+            node["name"] = json!(name);
+        }
+        let translation = self.translation + self.rotation * offset;
+        if translation != Vec3::ZERO || scale.is_some() {
+            node["translation"] = json!(translation);
+        }
+        if self.rotation != Quat::IDENTITY {
+            node["rotation"] = json!(self.rotation);
+        }
+        if let Some(scale) = scale {
+            node["scale"] = json!([scale, scale, scale]);
+        }
+        node
+    }
+}
+
 impl Builder {
-    /// Add a mesh
-    fn add_mesh(&mut self, mesh: &Mesh) {
-        let count = mesh.positions().len();
-        // indices
-        let idx_view = self.views.len();
+    /// Add a mesh node
+    fn add_mesh(&mut self, node: &MeshNode, options: &GltfOptions) {
+        let mesh_idx = self.meshes.len();
+        let normals = !options.omit_normals;
+        if options.quantize {
+            self.add_mesh_quantized(node, normals, options.i16_normals);
+        } else {
+            self.add_mesh_f32(node, normals);
+        }
+        if let Some(name) = node.name {
+            self.meshes[mesh_idx]["name"] = json!(name);
+        }
+        let node_idx = self.nodes.len();
+        let (json, matrix) = if options.quantize {
+            let (_pos, min, scale) = quantize_positions(node.mesh);
+            if !self.extensions.contains(&QUANTIZATION) {
+                self.extensions.push(QUANTIZATION);
+            }
+            let matrix = Mat4::from_scale_rotation_translation(
+                Vec3::splat(scale),
+                node.rotation,
+                node.translation + node.rotation * min,
+            );
+            (node.json(mesh_idx, min, Some(scale)), matrix)
+        } else {
+            let matrix = Mat4::from_rotation_translation(
+                node.rotation,
+                node.translation,
+            );
+            (node.json(mesh_idx, Vec3::ZERO, None), matrix)
+        };
+        self.nodes.push(json);
+        if let Some(skin) = self.add_skin(node, matrix) {
+            self.nodes[node_idx]["skin"] = json!(skin);
+        }
+        self.add_anchors(node_idx, node, matrix);
+    }
+
+    /// Add anchor nodes, as children of a mesh node
+    ///
+    /// Anchors are placed relative to the node transform `matrix`, which
+    /// may include dequantization.
+    fn add_anchors(&mut self, node_idx: usize, node: &MeshNode, matrix: Mat4) {
+        let placement =
+            Mat4::from_rotation_translation(node.rotation, node.translation);
+        let inverse = matrix.inverse() * placement;
+        let mut children = Vec::with_capacity(node.anchors.len());
+        for (name, xform) in node.anchors {
+            let local = inverse * Mat4::from(*xform);
+            let (scale, rotation, translation) =
+                local.to_scale_rotation_translation();
+            let mut anchor = json!({ "name": name });
+            if translation != Vec3::ZERO {
+                anchor["translation"] = json!(translation);
+            }
+            if !rotation.abs_diff_eq(Quat::IDENTITY, 1e-6) {
+                anchor["rotation"] = json!(rotation);
+            }
+            if !scale.abs_diff_eq(Vec3::ONE, 1e-6) {
+                anchor["scale"] = json!(scale);
+            }
+            children.push(self.nodes.len());
+            self.child_nodes.push(self.nodes.len());
+            self.nodes.push(anchor);
+        }
+        if !children.is_empty() {
+            self.nodes[node_idx]["children"] = json!(children);
+        }
+    }
+
+    /// Add a skin for a mesh node, returning its index
+    ///
+    /// The node transform is ignored for skinned meshes, so `matrix` is
+    /// included in the inverse bind matrices.  Joint nodes are shared by
+    /// nodes with the same placed skeleton (such as levels of detail).
+    fn add_skin(&mut self, node: &MeshNode, matrix: Mat4) -> Option<usize> {
+        let skeleton = node.mesh.skeleton()?;
+        if node.mesh.joints().is_empty() {
+            return None;
+        }
+        let mut placed = skeleton.clone();
+        placed.transform(Affine3A::from_rotation_translation(
+            node.rotation,
+            node.translation,
+        ));
+        let joints = match self.skeletons.iter().find(|(s, _)| *s == placed) {
+            Some((_, joints)) => joints.clone(),
+            None => {
+                let joints = self.add_joint_nodes(&placed);
+                self.skeletons.push((placed.clone(), joints.clone()));
+                joints
+            }
+        };
+        let ibm: Vec<Mat4> = placed
+            .joints()
+            .iter()
+            .map(|joint| Mat4::from_translation(-joint.pos) * matrix)
+            .collect();
+        let ibm_view = self.views.len();
         self.accessors.push(json!({
-            "bufferView": idx_view,
-            "componentType": ComponentType::U16,
-            "type": "SCALAR",
-            "count": mesh.indices().len(),
+            "bufferView": ibm_view,
+            "componentType": ComponentType::F32 as u32,
+            "type": "MAT4",
+            "count": ibm.len(),
         }));
-        let v = self.push_index_view(mesh.indices());
+        let v = self.push_data_view(&ibm);
         self.views.push(v);
+        self.skins.push(json!({
+            "inverseBindMatrices": ibm_view,
+            "joints": joints,
+            "skeleton": joints[0],
+        }));
+        Some(self.skins.len() - 1)
+    }
+
+    /// Add a node for each joint of a placed skeleton
+    ///
+    /// Returns the node indices of all joints.
+    fn add_joint_nodes(&mut self, skeleton: &Skeleton) -> Vec<usize> {
+        let base = self.nodes.len();
+        let joints = skeleton.joints();
+        for (i, joint) in joints.iter().enumerate() {
+            let parent = joint.parent.map_or(Vec3::ZERO, |p| joints[p].pos);
+            let mut node = json!({
+                "name": joint.name,
+                "translation": joint.pos - parent,
+            });
+            let children: Vec<_> =
+                skeleton.children(i).map(|c| base + c).collect();
+            if !children.is_empty() {
+                node["children"] = json!(children);
+            }
+            if joint.parent.is_some() {
+                self.child_nodes.push(base + i);
+            }
+            self.nodes.push(node);
+        }
+        (base..self.nodes.len()).collect()
+    }
+
+    /// Add a mesh node with levels of detail
+    fn add_lods(
+        &mut self,
+        node: &MeshNode,
+        lods: &[Mesh],
+        options: &GltfOptions,
+    ) {
+        let base = node.name.unwrap_or("model");
+        let name = format!("{base}_LOD0");
+        let lod0 = self.nodes.len();
+        self.add_mesh(
+            &MeshNode {
+                name: Some(&name),
+                ..*node
+            },
+            options,
+        );
+        let mut ids = Vec::with_capacity(lods.len());
+        for (i, mesh) in lods.iter().enumerate() {
+            let name = format!("{base}_LOD{}", i + 1);
+            ids.push(self.nodes.len());
+            self.add_mesh(
+                &MeshNode {
+                    name: Some(&name),
+                    mesh,
+                    anchors: &[],
+                    ..*node
+                },
+                options,
+            );
+        }
+        if options.msft_lod {
+            self.nodes[lod0]["extensions"] = json!({
+                MSFT_LOD: { "ids": ids },
+            });
+            self.lod_nodes.extend(ids);
+            if !self.extensions_optional.contains(&MSFT_LOD) {
+                self.extensions_optional.push(MSFT_LOD);
+            }
+        }
+    }
+
+    /// Add a mesh with `f32` attributes
+    fn add_mesh_f32(&mut self, node: &MeshNode, normals: bool) {
+        let mesh = node.mesh;
+        let count = mesh.positions().len();
+        let idx_views = self.push_index_views(node);
         // positions
         let pos_view = self.views.len();
         self.accessors.push(json!({
             "bufferView": pos_view,
-            "componentType": ComponentType::F32,
+            "componentType": ComponentType::F32 as u32,
             "type": "VEC3",
             "count": count,
             "min": mesh.pos_min(),
@@ -76,30 +760,295 @@ impl Builder {
         let v = self.push_array_view(mesh.positions());
         self.views.push(v);
         // normals
-        let norm_view = self.views.len();
+        let norm_view = normals.then(|| {
+            let norm_view = self.views.len();
+            self.accessors.push(json!({
+                "bufferView": norm_view,
+                "componentType": ComponentType::F32 as u32,
+                "type": "VEC3",
+                "count": count,
+            }));
+            let v = self.push_array_view(mesh.normals());
+            self.views.push(v);
+            norm_view
+        });
+        let uv_view = self.push_uv_view(mesh);
+        let color_view = self.push_color_view(mesh);
+        let skin_views = self.push_skin_views(mesh);
+        self.push_primitives(
+            &idx_views, pos_view, norm_view, uv_view, color_view, skin_views,
+        );
+    }
+
+    /// Add a mesh with quantized attributes
+    fn add_mesh_quantized(
+        &mut self,
+        node: &MeshNode,
+        normals: bool,
+        i16_normals: bool,
+    ) {
+        let mesh = node.mesh;
+        let count = mesh.positions().len();
+        let idx_views = self.push_index_views(node);
+        // positions
+        let (pos, _min, _scale) = quantize_positions(mesh);
+        let (pos_min, pos_max) = quantized_bounds(&pos);
+        let pos_view = self.views.len();
         self.accessors.push(json!({
-            "bufferView": norm_view,
-            "componentType": ComponentType::F32,
+            "bufferView": pos_view,
+            "componentType": ComponentType::U16 as u32,
+            "normalized": true,
             "type": "VEC3",
             "count": count,
+            "min": pos_min,
+            "max": pos_max,
+        }));
+        let v = self.push_array_view(&pos);
+        self.views.push(v);
+        // normals
+        let norm_view = normals.then(|| {
+            let norm_view = self.views.len();
+            let (ctype, v) = if i16_normals {
+                let norm = quantize_normals_i16(mesh);
+                (ComponentType::I16, self.push_array_view(&norm))
+            } else {
+                let norm = quantize_normals(mesh);
+                (ComponentType::I8, self.push_array_view(&norm))
+            };
+            self.accessors.push(json!({
+                "bufferView": norm_view,
+                "componentType": ctype as u32,
+                "normalized": true,
+                "type": "VEC3",
+                "count": count,
+            }));
+            self.views.push(v);
+            norm_view
+        });
+        let uv_view = self.push_uv_view(mesh);
+        let color_view = self.push_color_view(mesh);
+        let skin_views = self.push_skin_views(mesh);
+        self.push_primitives(
+            &idx_views, pos_view, norm_view, uv_view, color_view, skin_views,
+        );
+    }
+
+    /// Push index views, one for each material group
+    ///
+    /// Returns global material index and view of each group.
+    fn push_index_views(
+        &mut self,
+        node: &MeshNode,
+    ) -> Vec<(Option<usize>, usize)> {
+        let mesh = node.mesh;
+        let mut idx_views = Vec::new();
+        for (material, indices) in mesh.material_groups() {
+            let material = match material {
+                Some(m) => Some(node.material(&mesh.materials()[m])),
+                None => node.default_material(),
+            };
+            let material = material.map(|m| self.material(m));
+            let idx_view = self.views.len();
+            self.accessors.push(json!({
+                "bufferView": idx_view,
+                "componentType": ComponentType::U16 as u32,
+                "type": "SCALAR",
+                "count": indices.len(),
+            }));
+            let v = self.push_index_view(&indices);
+            self.views.push(v);
+            idx_views.push((material, idx_view));
+        }
+        idx_views
+    }
+
+    /// Get global index of a material
+    fn material(&mut self, material: Material) -> usize {
+        match self.materials.iter().position(|m| *m == material) {
+            Some(idx) => idx,
+            None => {
+                self.materials.push(material);
+                self.materials.len() - 1
+            }
+        }
+    }
+
+    /// Push texture coordinates view
+    fn push_uv_view(&mut self, mesh: &Mesh) -> Option<usize> {
+        let uv = mesh.uvs();
+        if uv.is_empty() {
+            return None;
+        }
+        let uv_view = self.views.len();
+        self.accessors.push(json!({
+            "bufferView": uv_view,
+            "componentType": ComponentType::F32 as u32,
+            "type": "VEC2",
+            "count": uv.len(),
+        }));
+        let v = self.push_array_view(uv);
+        self.views.push(v);
+        Some(uv_view)
+    }
+
+    /// Push vertex colors as a normalized `u8` color view
+    ///
+    /// Colors are multiplied by ambient occlusion, when present.
+    fn push_color_view(&mut self, mesh: &Mesh) -> Option<usize> {
+        let colors = mesh.colors();
+        let ao = mesh.ambient_occlusion();
+        if colors.is_empty() && ao.is_empty() {
+            return None;
+        }
+        let color: Vec<[u8; 4]> = (0..mesh.positions().len())
+            .map(|i| {
+                let mut c = colors.get(i).copied().unwrap_or(Vec4::ONE);
+                if let Some(a) = ao.get(i) {
+                    c = (c.truncate() * *a).extend(c.w);
+                }
+                (c * f32::from(u8::MAX)).round().to_array().map(|c| c as u8)
+            })
+            .collect();
+        let color_view = self.views.len();
+        self.accessors.push(json!({
+            "bufferView": color_view,
+            "componentType": ComponentType::U8 as u32,
+            "normalized": true,
+            "type": "VEC4",
+            "count": color.len(),
+        }));
+        let v = self.push_array_view(&color);
+        self.views.push(v);
+        Some(color_view)
+    }
+
+    /// Push joint and weight views, for a mesh with a skeleton
+    ///
+    /// Each vertex has one joint, with a weight of 1.
+    fn push_skin_views(&mut self, mesh: &Mesh) -> Option<(usize, usize)> {
+        mesh.skeleton()?;
+        let joints = mesh.joints();
+        if joints.is_empty() {
+            return None;
+        }
+        let joint: Vec<[u16; 4]> =
+            joints.iter().map(|j| [*j, 0, 0, 0]).collect();
+        let joint_view = self.views.len();
+        self.accessors.push(json!({
+            "bufferView": joint_view,
+            "componentType": ComponentType::U16 as u32,
+            "type": "VEC4",
+            "count": joint.len(),
+        }));
+        let v = self.push_array_view(&joint);
+        self.views.push(v);
+        let weight = vec![[1.0f32, 0.0, 0.0, 0.0]; joints.len()];
+        let weight_view = self.views.len();
+        self.accessors.push(json!({
+            "bufferView": weight_view,
+            "componentType": ComponentType::F32 as u32,
+            "type": "VEC4",
+            "count": weight.len(),
         }));
-        let v = self.push_array_view(mesh.normals());
+        let v = self.push_array_view(&weight);
+        self.views.push(v);
+        Some((joint_view, weight_view))
+    }
+
+    /// Push a mesh with one primitive for each index view
+    ///
+    /// All primitives share the same vertex attributes.
+    fn push_primitives(
+        &mut self,
+        idx_views: &[(Option<usize>, usize)],
+        pos_view: usize,
+        norm_view: Option<usize>,
+        uv_view: Option<usize>,
+        color_view: Option<usize>,
+        skin_views: Option<(usize, usize)>,
+    ) {
+        let mut primitives = Vec::with_capacity(idx_views.len());
+        for (material, idx_view) in idx_views {
+            let mut prim = json!({
+                "attributes": {
+                    "POSITION": pos_view,
+                },
+                "indices": idx_view,
+            });
+            if let Some(norm_view) = norm_view {
+                prim["attributes"]["NORMAL"] = json!(norm_view);
+            }
+            if let Some(uv_view) = uv_view {
+                prim["attributes"]["TEXCOORD_0"] = json!(uv_view);
+            }
+            if let Some(color_view) = color_view {
+                prim["attributes"]["COLOR_0"] = json!(color_view);
+            }
+            if let Some((joint_view, weight_view)) = skin_views {
+                prim["attributes"]["JOINTS_0"] = json!(joint_view);
+                prim["attributes"]["WEIGHTS_0"] = json!(weight_view);
+            }
+            if let Some(material) = material {
+                prim["material"] = json!(material);
+            }
+            primitives.push(prim);
+        }
+        self.meshes.push(json!({ "primitives": primitives }));
+    }
+
+    /// Add a line mesh with one segment per vertex normal
+    fn add_normal_lines(&mut self, node: &MeshNode, length: f32) {
+        let mesh = node.mesh;
+        let mut pos = Vec::with_capacity(mesh.positions().len() * 2);
+        let mut color = Vec::with_capacity(pos.capacity());
+        for (p, n) in mesh.positions().iter().zip(mesh.normals()) {
+            pos.push(*p);
+            pos.push(*p + *n * length);
+            let c = *n * 0.5 + Vec3::splat(0.5);
+            color.push(c);
+            color.push(c);
+        }
+        let min = pos.iter().copied().reduce(|min, v| v.min(min));
+        let max = pos.iter().copied().reduce(|max, v| v.max(max));
+        // positions
+        let pos_view = self.views.len();
+        self.accessors.push(json!({
+            "bufferView": pos_view,
+            "componentType": ComponentType::F32 as u32,
+            "type": "VEC3",
+            "count": pos.len(),
+            "min": min.unwrap_or_default(),
+            "max": max.unwrap_or_default(),
+        }));
+        let v = self.push_array_view(&pos);
+        self.views.push(v);
+        // colors
+        let color_view = self.views.len();
+        self.accessors.push(json!({
+            "bufferView": color_view,
+            "componentType": ComponentType::F32 as u32,
+            "type": "VEC3",
+            "count": color.len(),
+        }));
+        let v = self.push_array_view(&color);
         self.views.push(v);
         // mesh
+        self.nodes
+            .push(node.json(self.meshes.len(), Vec3::ZERO, None));
         self.meshes.push(json!({
             "primitives": [{
                 "attributes": {
                     "POSITION": pos_view,
-                    "NORMAL": norm_view,
+                    "COLOR_0": color_view,
                 },
-                "indices": idx_view,
+                "mode": Mode::Lines as u32,
             }],
         }));
     }
 
     /// Push an index view
     fn push_index_view<V>(&mut self, buf: &[V]) -> Value {
-        while self.bin.len() % 4 != 0 {
+        while !self.bin.len().is_multiple_of(4) {
             self.bin.push(0);
         }
         let byte_offset = self.bin.len();
@@ -110,13 +1059,13 @@ impl Builder {
             "buffer": 0,
             "byteLength": bytes.len(),
             "byteOffset": byte_offset,
-            "target": Target::ElementArrayBuffer,
+            "target": Target::ElementArrayBuffer as u32,
         })
     }
 
     /// Push an array view
     fn push_array_view<V>(&mut self, buf: &[V]) -> Value {
-        while self.bin.len() % 4 != 0 {
+        while !self.bin.len().is_multiple_of(4) {
             self.bin.push(0);
         }
         let byte_offset = self.bin.len();
@@ -127,29 +1076,150 @@ impl Builder {
             "byteLength": bytes.len(),
             "byteOffset": byte_offset,
             "byteStride": size_of::<V>(),
-            "target": Target::ArrayBuffer,
+            "target": Target::ArrayBuffer as u32,
         })
     }
 
-    /// Get root JSON of glTF
-    fn json(&self) -> Value {
+    /// Push a data view (not for vertex attributes)
+    fn push_data_view<V>(&mut self, buf: &[V]) -> Value {
+        while !self.bin.len().is_multiple_of(4) {
+            self.bin.push(0);
+        }
+        let byte_offset = self.bin.len();
+        let bytes = as_u8_slice(buf);
+        self.bin.extend_from_slice(bytes);
         json!({
-            "asset": {
-                "version": "2.0"
-            },
+            "buffer": 0,
+            "byteLength": bytes.len(),
+            "byteOffset": byte_offset,
+        })
+    }
+
+    /// Add a turntable root node, with an animation spinning it
+    ///
+    /// All scene nodes become children of the turntable node.
+    fn add_turntable(&mut self, seconds: f32, up: UpAxis) {
+        if !seconds.is_finite() || seconds <= 0.0 {
+            log::warn!("Invalid turntable period: {seconds}");
+            return;
+        }
+        let roots = self.scene_nodes();
+        let node_idx = self.nodes.len();
+        self.child_nodes.extend(&roots);
+        self.nodes.push(json!({
+            "name": "turntable",
+            "children": roots,
+        }));
+        // quarter turns, so that each key is interpolated the short way
+        let axis = up.rotation() * Vec3::Y;
+        let times: Vec<f32> =
+            (0..=4).map(|i| seconds * i as f32 / 4.0).collect();
+        let rotations: Vec<Quat> = (0..=4)
+            .map(|i| Quat::from_axis_angle(axis, TAU * i as f32 / 4.0))
+            .collect();
+        let time_view = self.views.len();
+        self.accessors.push(json!({
+            "bufferView": time_view,
+            "componentType": ComponentType::F32 as u32,
+            "type": "SCALAR",
+            "count": times.len(),
+            "min": [0.0],
+            "max": [seconds],
+        }));
+        let v = self.push_data_view(&times);
+        self.views.push(v);
+        let rot_view = self.views.len();
+        self.accessors.push(json!({
+            "bufferView": rot_view,
+            "componentType": ComponentType::F32 as u32,
+            "type": "VEC4",
+            "count": rotations.len(),
+        }));
+        let v = self.push_data_view(&rotations);
+        self.views.push(v);
+        self.animations.push(json!({
+            "name": "turntable",
+            "channels": [{
+                "sampler": 0,
+                "target": { "node": node_idx, "path": "rotation" },
+            }],
+            "samplers": [{
+                "input": time_view,
+                "interpolation": "LINEAR",
+                "output": rot_view,
+            }],
+        }));
+    }
+
+    /// Get indices of scene (root) nodes
+    fn scene_nodes(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|n| {
+                !self.lod_nodes.contains(n) && !self.child_nodes.contains(n)
+            })
+            .collect()
+    }
+
+    /// Get root JSON of glTF
+    fn json(&self, options: &GltfOptions) -> Value {
+        let mut meshes = self.meshes.clone();
+        let mut nodes = self.nodes.clone();
+        if let Some(extras) = &options.mesh_extras {
+            meshes.iter_mut().for_each(|m| merge_extras(m, extras));
+        }
+        if let Some(extras) = &options.node_extras {
+            nodes.iter_mut().for_each(|n| merge_extras(n, extras));
+        }
+        let mut asset = json!({
+            "generator": GENERATOR,
+            "version": "2.0",
+        });
+        if let Some(copyright) = &options.copyright {
+            asset["copyright"] = json!(copyright);
+        }
+        if let Some(extras) = &options.asset_extras {
+            merge_extras(&mut asset, extras);
+        }
+        let mut root = json!({
+            "asset": asset,
             "buffers": [{
                 "byteLength": self.bin.len(),
             }],
             "bufferViews": self.views,
             "accessors": self.accessors,
-            "meshes": self.meshes,
-            "nodes": [{
-                "mesh": 0
-            }],
+            "meshes": meshes,
+            "nodes": nodes,
             "scenes": [{
-                "nodes": [0]
+                "nodes": self.scene_nodes(),
             }],
-        })
+        });
+        if let Some(name) =
+            options.scene_name.as_ref().or(self.scene_name.as_ref())
+        {
+            root["scenes"][0]["name"] = json!(name);
+        }
+        if !self.skins.is_empty() {
+            root["skins"] = json!(self.skins);
+        }
+        if !self.animations.is_empty() {
+            root["animations"] = json!(self.animations);
+        }
+        if !self.materials.is_empty() {
+            root["materials"] =
+                self.materials.iter().map(Material::json).collect();
+        }
+        if !self.extensions.is_empty() {
+            root["extensionsRequired"] = json!(self.extensions);
+        }
+        let used: Vec<_> = self
+            .extensions
+            .iter()
+            .chain(&self.extensions_optional)
+            .collect();
+        if !used.is_empty() {
+            root["extensionsUsed"] = json!(used);
+        }
+        root
     }
 
     /// Get binary buffer
@@ -158,22 +1228,237 @@ impl Builder {
     }
 }
 
-/// Export a mesh to a writer as a GLB
-pub fn export<W: Write>(writer: W, mesh: &Mesh) -> Result<()> {
+/// Export mesh nodes to a writer as a GLB
+pub(crate) fn export<W: Write>(
+    writer: W,
+    nodes: &[MeshNode],
+    options: &GltfOptions,
+) -> Result<()> {
+    write_glb(writer, &build(nodes, options)?, options)
+}
+
+/// Export mesh nodes as `.gltf` JSON, with an external binary buffer
+///
+/// The buffer is written to `bin_writer`, and referenced by `bin_uri`.
+pub(crate) fn export_json<J: Write, B: Write>(
+    writer: J,
+    bin_uri: &str,
+    mut bin_writer: B,
+    nodes: &[MeshNode],
+    options: &GltfOptions,
+) -> Result<()> {
+    let builder = build(nodes, options)?;
+    bin_writer.write_all(builder.bin())?;
+    write_json(writer, &builder, options, bin_uri)
+}
+
+/// Export mesh nodes as `.gltf` JSON, with an embedded binary buffer
+///
+/// The buffer is embedded as a base64 data URI.
+pub(crate) fn export_embedded<W: Write>(
+    writer: W,
+    nodes: &[MeshNode],
+    options: &GltfOptions,
+) -> Result<()> {
+    let builder = build(nodes, options)?;
+    let uri = format!(
+        "data:application/octet-stream;base64,{}",
+        base64(builder.bin())
+    );
+    write_json(writer, &builder, options, &uri)
+}
+
+/// Build glTF for mesh nodes
+///
+/// Returns [Error::EmptyMesh] if any mesh has no vertices, since glTF
+/// accessors cannot be empty.
+fn build(nodes: &[MeshNode], options: &GltfOptions) -> Result<Builder> {
+    if nodes.iter().any(|n| n.mesh.positions().is_empty()) {
+        return Err(Error::EmptyMesh);
+    }
+    let mut placed = Placed::new(nodes, options);
+    if let Some(samples) = options.ao_samples {
+        for mesh in placed.meshes.iter_mut() {
+            if mesh.ambient_occlusion().is_empty() {
+                mesh.bake_ao(samples);
+            }
+        }
+    }
+    let nodes = placed.nodes(nodes, options);
     let mut builder = Builder::default();
-    builder.add_mesh(mesh);
-    let bin = builder.bin();
-    let mut root_json = builder.json().to_string();
-    while root_json.len() % 4 != 0 {
+    for node in &nodes {
+        if options.lods.is_empty() {
+            builder.add_mesh(node, options);
+        } else {
+            let lods = node.mesh.lod_chain(&options.lods);
+            builder.add_lods(node, &lods, options);
+        }
+    }
+    if let Some(seconds) = options.turntable {
+        builder.add_turntable(seconds, options.up);
+    }
+    if let [node] = &nodes[..] {
+        builder.scene_name = node.name.map(str::to_string);
+    }
+    Ok(builder)
+}
+
+/// Meshes and anchors of nodes, with export scale and up axis applied
+pub(crate) struct Placed {
+    /// Transformed meshes
+    meshes: Vec<Mesh>,
+
+    /// Transformed anchors
+    anchors: Vec<Vec<(String, Affine3A)>>,
+}
+
+impl Placed {
+    /// Place mesh nodes with scale and up axis of export options
+    pub(crate) fn new(nodes: &[MeshNode], options: &GltfOptions) -> Self {
+        let scale = options.scale.unwrap_or(1.0);
+        let up = options.up.rotation();
+        let meshes = nodes
+            .iter()
+            .map(|n| n.mesh.transformed(scale, up))
+            .collect();
+        let anchors = nodes
+            .iter()
+            .map(|n| {
+                n.anchors
+                    .iter()
+                    .map(|(name, xform)| {
+                        (name.clone(), up_xform(*xform, scale, up))
+                    })
+                    .collect()
+            })
+            .collect();
+        Placed { meshes, anchors }
+    }
+
+    /// Get placed mesh nodes, recentered if enabled in export options
+    pub(crate) fn nodes<'a>(
+        &'a self,
+        nodes: &[MeshNode<'a>],
+        options: &GltfOptions,
+    ) -> Vec<MeshNode<'a>> {
+        let scale = options.scale.unwrap_or(1.0);
+        let up = options.up.rotation();
+        let mut nodes: Vec<_> = nodes
+            .iter()
+            .zip(&self.meshes)
+            .zip(&self.anchors)
+            .map(|((node, mesh), anchors)| MeshNode {
+                name: node.name,
+                materials: node.materials,
+                mesh,
+                translation: up * node.translation * scale,
+                rotation: up * node.rotation * up.inverse(),
+                anchors,
+            })
+            .collect();
+        if options.center {
+            let offset = base_position(&nodes, options.up);
+            nodes.iter_mut().for_each(|n| n.translation -= offset);
+        }
+        nodes
+    }
+}
+
+/// Apply export scale and up axis rotation to a transform
+fn up_xform(xform: Affine3A, scale: f32, up: Quat) -> Affine3A {
+    let up = Affine3A::from_quat(up);
+    let scale = Affine3A::from_scale(Vec3::splat(scale));
+    up * scale * xform * scale.inverse() * up.inverse()
+}
+
+/// Get base position of mesh nodes (bottom center of bounding box)
+fn base_position(nodes: &[MeshNode], up: UpAxis) -> Vec3 {
+    let (min, max) = nodes
+        .iter()
+        .map(|n| n.bounds())
+        .reduce(|(min0, max0), (min1, max1)| (min0.min(min1), max0.max(max1)))
+        .unwrap_or_default();
+    let center = (min + max) * 0.5;
+    match up {
+        UpAxis::Y => Vec3::new(center.x, min.y, center.z),
+        UpAxis::Z => Vec3::new(center.x, center.y, min.z),
+    }
+}
+
+/// Export vertex normals of mesh nodes as line segments to a GLB
+///
+/// Each segment starts at a vertex position and extends `length` along its
+/// normal.  Segments are colored by normal direction.  Nodes are placed
+/// with the scale, center and up axis `options`, which also scale `length`.
+pub(crate) fn export_normals_debug<W: Write>(
+    writer: W,
+    nodes: &[MeshNode],
+    length: f32,
+    options: &GltfOptions,
+) -> Result<()> {
+    let placed = Placed::new(nodes, options);
+    let length = length * options.scale.unwrap_or(1.0);
+    let mut builder = Builder::default();
+    for node in placed.nodes(nodes, options) {
+        builder.add_normal_lines(&node, length);
+    }
+    write_glb(writer, &builder, &GltfOptions::default())
+}
+
+/// Write a builder to a GLB
+fn write_glb<W: Write>(
+    writer: W,
+    builder: &Builder,
+    options: &GltfOptions,
+) -> Result<()> {
+    let mut bin = builder.bin().to_vec();
+    while !bin.len().is_multiple_of(4) {
+        bin.push(0);
+    }
+    let mut root_json = builder.json(options).to_string();
+    while !root_json.len().is_multiple_of(4) {
         root_json.push(' ');
     }
     let mut glb = Glb::new(writer);
     glb.write_header(2, (root_json.len() + bin.len()).try_into().unwrap())?;
     glb.write_json(&root_json)?;
-    glb.write_bin(bin)?;
+    glb.write_bin(&bin)?;
     Ok(())
 }
 
+/// Write a builder as `.gltf` JSON, with a buffer URI
+fn write_json<W: Write>(
+    writer: W,
+    builder: &Builder,
+    options: &GltfOptions,
+    uri: &str,
+) -> Result<()> {
+    let mut root = builder.json(options);
+    root["buffers"][0]["uri"] = json!(uri);
+    serde_json::to_writer_pretty(writer, &root).map_err(io::Error::from)?;
+    Ok(())
+}
+
+/// Encode bytes as base64 (with padding)
+fn base64(bytes: &[u8]) -> String {
+    const CHARS: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [0, 1, 2].map(|i| chunk.get(i).copied().unwrap_or(0));
+        let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let c = (n >> (18 - i * 6)) & 0x3F;
+                out.push(char::from(CHARS[c as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
 impl<W: Write> Glb<W> {
     /// Create new GLB writer
     fn new(writer: W) -> Self {
@@ -208,3 +1493,767 @@ impl<W: Write> Glb<W> {
         self.write_chunk(b"BIN\0", bin)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mesh::{Face, Vertex};
+    use crate::primitives::pyramid;
+    use crate::{Husk, Ring};
+
+    /// Parse the JSON chunk of a GLB
+    fn glb_json(glb: &[u8]) -> Value {
+        let len = u32::from_le_bytes(glb[12..16].try_into().unwrap());
+        assert_eq!(&glb[16..20], b"JSON");
+        serde_json::from_slice(&glb[20..20 + len as usize]).unwrap()
+    }
+
+    fn pyramid_glb(options: &GltfOptions) -> Vec<u8> {
+        let husk = pyramid(Ring::default());
+        let mut buf = Vec::new();
+        husk.write_gltf_with(&mut buf, options).unwrap();
+        buf
+    }
+
+    /// Get the BIN chunk of a GLB
+    fn glb_bin(glb: &[u8]) -> &[u8] {
+        let len = u32::from_le_bytes(glb[12..16].try_into().unwrap());
+        let start = 20 + len as usize;
+        assert_eq!(&glb[start + 4..start + 8], b"BIN\0");
+        &glb[start + 8..]
+    }
+
+    /// Read position of a vertex
+    fn read_position(glb: &[u8], vtx: usize) -> Vec3 {
+        let json = glb_json(glb);
+        let bin = glb_bin(glb);
+        let acc = &json["accessors"][1];
+        let view =
+            &json["bufferViews"][acc["bufferView"].as_u64().unwrap() as usize];
+        let offset = view["byteOffset"].as_u64().unwrap() as usize;
+        let stride = view["byteStride"].as_u64().unwrap() as usize;
+        let start = offset + vtx * stride;
+        let comp = |i: usize| match acc["componentType"].as_u64() {
+            Some(5126) => {
+                let b = &bin[start + i * 4..start + i * 4 + 4];
+                f32::from_le_bytes(b.try_into().unwrap())
+            }
+            Some(5123) => {
+                let b = &bin[start + i * 2..start + i * 2 + 2];
+                let q = u16::from_le_bytes(b.try_into().unwrap());
+                f32::from(q) / f32::from(u16::MAX)
+            }
+            _ => unreachable!(),
+        };
+        let pos = Vec3::new(comp(0), comp(1), comp(2));
+        let node = &json["nodes"][0];
+        let scale = node["scale"][0].as_f64().unwrap_or(1.0) as f32;
+        let translation = match node["translation"].as_array() {
+            Some(t) => Vec3::new(
+                t[0].as_f64().unwrap() as f32,
+                t[1].as_f64().unwrap() as f32,
+                t[2].as_f64().unwrap() as f32,
+            ),
+            None => Vec3::ZERO,
+        };
+        pos * scale + translation
+    }
+
+    #[test]
+    fn quantized() {
+        let plain = pyramid_glb(&GltfOptions::default());
+        let options = GltfOptions {
+            quantize: true,
+            ..Default::default()
+        };
+        let quant = pyramid_glb(&options);
+        let json = glb_json(&quant);
+        assert_eq!(json["extensionsRequired"][0], QUANTIZATION);
+        assert_eq!(json["accessors"][1]["componentType"], 5123);
+        assert_eq!(json["accessors"][1]["normalized"], true);
+        assert_eq!(json["accessors"][2]["componentType"], 5120);
+        // vertex attributes shrink from 24 to 12 bytes
+        let count = json["accessors"][1]["count"].as_u64().unwrap() as usize;
+        assert_eq!(glb_bin(&plain).len() - glb_bin(&quant).len(), count * 12);
+        for vtx in 0..count {
+            let p0 = read_position(&plain, vtx);
+            let p1 = read_position(&quant, vtx);
+            assert!(p0.distance(p1) < 1e-4);
+        }
+        // i16 normals are 4 bytes larger per vertex than i8
+        let options = options.with_i16_normals();
+        let quant16 = pyramid_glb(&options);
+        let json = glb_json(&quant16);
+        assert_eq!(json["accessors"][2]["componentType"], 5122);
+        assert_eq!(json["accessors"][2]["normalized"], true);
+        assert_eq!(json["bufferViews"][2]["byteStride"], 8);
+        assert_eq!(glb_bin(&quant16).len() - glb_bin(&quant).len(), count * 4);
+        assert!(glb_bin(&quant16).len() < glb_bin(&plain).len());
+        let mesh = crate::Mesh::from_glb(&quant16[..]).unwrap();
+        let plain = crate::Mesh::from_glb(&plain[..]).unwrap();
+        for (n0, n1) in mesh.normals().iter().zip(plain.normals()) {
+            assert!(n0.distance(*n1) < 1e-4, "{n0} {n1}");
+        }
+    }
+
+    #[test]
+    fn omit_normals() {
+        let plain = pyramid_glb(&GltfOptions::default());
+        let options = GltfOptions {
+            omit_normals: true,
+            ..Default::default()
+        };
+        let glb = pyramid_glb(&options);
+        let json = glb_json(&glb);
+        let attrs = &json["meshes"][0]["primitives"][0]["attributes"];
+        assert!(attrs.get("NORMAL").is_none());
+        assert_eq!(attrs["POSITION"], 1);
+        // all accessors and views are still used
+        let count = json["accessors"][1]["count"].as_u64().unwrap() as usize;
+        assert_eq!(glb_bin(&plain).len() - glb_bin(&glb).len(), count * 12);
+        let views = json["bufferViews"].as_array().unwrap().len();
+        assert_eq!(json["accessors"].as_array().unwrap().len(), views);
+        let mesh = crate::Mesh::from_glb(&glb[..]).unwrap();
+        assert_eq!(mesh.positions().len(), count);
+        let options = GltfOptions {
+            quantize: true,
+            ..options
+        };
+        let json = glb_json(&pyramid_glb(&options));
+        let attrs = &json["meshes"][0]["primitives"][0]["attributes"];
+        assert!(attrs.get("NORMAL").is_none());
+    }
+
+    #[test]
+    fn turntable() {
+        let options = GltfOptions {
+            turntable: Some(6.0),
+            ..Default::default()
+        };
+        let glb = pyramid_glb(&options);
+        let json = glb_json(&glb);
+        assert_eq!(json["scenes"][0]["nodes"], json!([1]));
+        assert_eq!(json["nodes"][1]["name"], "turntable");
+        assert_eq!(json["nodes"][1]["children"], json!([0]));
+        let anim = &json["animations"][0];
+        assert_eq!(anim["channels"][0]["target"]["node"], 1);
+        assert_eq!(anim["channels"][0]["target"]["path"], "rotation");
+        let sampler = &anim["samplers"][0];
+        let input = sampler["input"].as_u64().unwrap() as usize;
+        assert_eq!(json["accessors"][input]["max"], json!([6.0]));
+        // last key is a full turn
+        let output = sampler["output"].as_u64().unwrap() as usize;
+        let acc = &json["accessors"][output];
+        let view =
+            &json["bufferViews"][acc["bufferView"].as_u64().unwrap() as usize];
+        let offset = view["byteOffset"].as_u64().unwrap() as usize;
+        let len = view["byteLength"].as_u64().unwrap() as usize;
+        let key = &glb_bin(&glb)[offset + len - 16..offset + len];
+        let w = f32::from_le_bytes(key[12..16].try_into().unwrap());
+        assert!((w.abs() - 1.0).abs() < 1e-6);
+        // invalid period is ignored
+        let options = GltfOptions {
+            turntable: Some(0.0),
+            ..Default::default()
+        };
+        let json = glb_json(&pyramid_glb(&options));
+        assert!(json.get("animations").is_none());
+    }
+
+    #[test]
+    fn builder() {
+        let options = GltfOptions::default()
+            .with_quantize()
+            .with_scale(2.0)
+            .with_lods(&[0.5])
+            .with_copyright("CC0");
+        let expected = GltfOptions {
+            quantize: true,
+            scale: Some(2.0),
+            lods: vec![0.5],
+            copyright: Some("CC0".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(options, expected);
+    }
+
+    #[test]
+    fn extra() {
+        let options = GltfOptions::default()
+            .with_asset_extras(json!({ "scale": 2.0 }))
+            .with_extra("source", json!("pyramid.hom"))
+            .with_extra("seed", json!(7));
+        let json = glb_json(&pyramid_glb(&options));
+        let extras = &json["asset"]["extras"];
+        assert_eq!(extras["scale"], 2.0);
+        assert_eq!(extras["source"], "pyramid.hom");
+        assert_eq!(extras["seed"], 7);
+        assert_eq!(json["asset"]["generator"], GENERATOR);
+    }
+
+    #[test]
+    fn options_change_output() {
+        let plain = glb_json(&pyramid_glb(&GltfOptions::default()));
+        let changed = |options: GltfOptions| {
+            let json = glb_json(&pyramid_glb(&options));
+            assert_ne!(json, plain, "{options:?}");
+            json
+        };
+        let json = changed(GltfOptions::default().with_mesh_extras(json!(1)));
+        assert_eq!(json["meshes"][0]["extras"], 1);
+        let json = changed(GltfOptions::default().with_node_extras(json!(2)));
+        assert_eq!(json["nodes"][0]["extras"], 2);
+        let json = changed(GltfOptions::default().with_asset_extras(json!(3)));
+        assert_eq!(json["asset"]["extras"], 3);
+        let json = changed(GltfOptions::default().with_quantize());
+        assert_eq!(json["accessors"][1]["componentType"], 5123);
+        let json =
+            changed(GltfOptions::default().with_quantize().with_i16_normals());
+        assert_eq!(json["accessors"][2]["componentType"], 5122);
+        let max = |json: &Value, i: usize| {
+            json["accessors"][1]["max"][i].as_f64().unwrap()
+        };
+        let json = changed(GltfOptions::default().with_scale(2.0));
+        assert!((max(&json, 1) - max(&plain, 1) * 2.0).abs() < 1e-5);
+        let json = changed(GltfOptions::default().with_center());
+        assert!(json["nodes"][0].get("translation").is_some());
+        let json = changed(GltfOptions::default().with_up(UpAxis::Z));
+        assert!((max(&json, 2) - max(&plain, 1)).abs() < 1e-5);
+        let json = changed(GltfOptions::default().with_lods(&[0.5]));
+        assert_eq!(json["nodes"][0]["name"], "model_LOD0");
+        let json =
+            changed(GltfOptions::default().with_lods(&[0.5]).with_msft_lod());
+        assert_eq!(json["extensionsUsed"][0], MSFT_LOD);
+        let json = changed(GltfOptions::default().with_ao_samples(4));
+        assert!(json["meshes"][0]["primitives"][0]["attributes"]
+            .get("COLOR_0")
+            .is_some());
+        let json = changed(GltfOptions::default().with_scene_name("pyramid"));
+        assert_eq!(json["scenes"][0]["name"], "pyramid");
+        let json = changed(GltfOptions::default().with_copyright("CC0"));
+        assert_eq!(json["asset"]["copyright"], "CC0");
+        let json = changed(GltfOptions::default().with_omit_normals());
+        assert!(json["meshes"][0]["primitives"][0]["attributes"]
+            .get("NORMAL")
+            .is_none());
+        let json = changed(GltfOptions::default().with_turntable(2.0));
+        assert_eq!(json["animations"][0]["name"], "turntable");
+    }
+
+    #[test]
+    fn normals_debug() {
+        let mut builder = Mesh::builder();
+        builder.push_vtx(Vec3::new(0.0, 0.0, 0.0)).unwrap();
+        builder.push_vtx(Vec3::new(1.0, 0.0, 0.0)).unwrap();
+        builder.push_vtx(Vec3::new(0.0, 0.0, 1.0)).unwrap();
+        builder.push_face(Face::new([0, 2, 1], 0));
+        let mesh = builder.build().unwrap();
+        let mut glb = Vec::new();
+        let nodes = [MeshNode::new(&mesh)];
+        let options = GltfOptions::default();
+        export_normals_debug(&mut glb, &nodes, 0.5, &options).unwrap();
+        let json = glb_json(&glb);
+        let prim = &json["meshes"][0]["primitives"][0];
+        assert_eq!(prim["mode"], 1);
+        assert_eq!(json["accessors"][0]["count"], 6);
+        let bin = glb_bin(&glb);
+        let vec3 = |i: usize| {
+            let f = |j: usize| {
+                let b = &bin[i * 12 + j * 4..i * 12 + j * 4 + 4];
+                f32::from_le_bytes(b.try_into().unwrap())
+            };
+            Vec3::new(f(0), f(1), f(2))
+        };
+        for vtx in 0..3 {
+            let pos = mesh.positions()[vtx];
+            assert_eq!(vec3(vtx * 2), pos);
+            assert_eq!(vec3(vtx * 2 + 1), pos + Vec3::new(0.0, 0.5, 0.0));
+        }
+    }
+
+    /// Get placed bounds of the first node (min, max)
+    fn node_bounds(json: &Value) -> (Vec3, Vec3) {
+        let vec3 = |v: &Value| {
+            let f = |i: usize| v[i].as_f64().unwrap_or(0.0) as f32;
+            Vec3::new(f(0), f(1), f(2))
+        };
+        let acc = &json["accessors"][1];
+        let translation = vec3(&json["nodes"][0]["translation"]);
+        (
+            vec3(&acc["min"]) + translation,
+            vec3(&acc["max"]) + translation,
+        )
+    }
+
+    #[test]
+    fn scale_center() {
+        let plain = glb_json(&pyramid_glb(&GltfOptions::default()));
+        let (min, max) = node_bounds(&plain);
+        let options = GltfOptions {
+            scale: Some(0.01),
+            ..Default::default()
+        };
+        let scaled = glb_json(&pyramid_glb(&options));
+        let (smin, smax) = node_bounds(&scaled);
+        assert!(smin.distance(min * 0.01) < 1e-6);
+        assert!(smax.distance(max * 0.01) < 1e-6);
+        let options = GltfOptions {
+            scale: Some(2.0),
+            center: true,
+            asset_extras: Some(json!({ "scale": 2.0, "center": true })),
+            ..Default::default()
+        };
+        let centered = glb_json(&pyramid_glb(&options));
+        let (cmin, cmax) = node_bounds(&centered);
+        assert!((cmax - cmin).distance((max - min) * 2.0) < 1e-5);
+        assert!(cmin.y.abs() < 1e-6);
+        assert!((cmin.x + cmax.x).abs() < 1e-5);
+        assert!((cmin.z + cmax.z).abs() < 1e-5);
+        assert_eq!(centered["asset"]["extras"]["scale"], 2.0);
+        assert_eq!(centered["asset"]["version"], "2.0");
+    }
+
+    #[test]
+    fn up_axis() {
+        let mut husk = Husk::new();
+        let base = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        husk.ring(base.axis(Vec3::new(0.0, 3.0, 0.0))).unwrap();
+        husk.ring(Ring::default().spoke(0.0)).unwrap();
+        let options = GltfOptions {
+            up: UpAxis::Z,
+            center: true,
+            ..Default::default()
+        };
+        let mut glb = Vec::new();
+        husk.write_gltf_with(&mut glb, &options).unwrap();
+        let json = glb_json(&glb);
+        let (min, max) = node_bounds(&json);
+        let size = max - min;
+        assert!(size.z > size.x && size.z > size.y);
+        assert!((size.z - 3.0).abs() < 1e-5);
+        assert!(min.z.abs() < 1e-6);
+        // apex normal points up Z
+        let bin = glb_bin(&glb);
+        let acc = &json["accessors"][2];
+        let view =
+            &json["bufferViews"][acc["bufferView"].as_u64().unwrap() as usize];
+        let offset = view["byteOffset"].as_u64().unwrap() as usize;
+        let count = acc["count"].as_u64().unwrap() as usize;
+        let nz = (0..count).map(|v| {
+            let i = offset + v * 12 + 8;
+            f32::from_le_bytes(bin[i..i + 4].try_into().unwrap())
+        });
+        assert!(nz.fold(f32::MIN, f32::max) > 0.9);
+    }
+
+    #[test]
+    fn no_extras() {
+        let json = glb_json(&pyramid_glb(&GltfOptions::default()));
+        assert!(!json.to_string().contains("extras"));
+    }
+
+    #[test]
+    fn chunk_padding() {
+        let mut builder = Builder::default();
+        let v = builder.push_index_view(&[Vertex(0), Vertex(1), Vertex(2)]);
+        builder.views.push(v);
+        let mut glb = Vec::new();
+        write_glb(&mut glb, &builder, &GltfOptions::default()).unwrap();
+        let u32_at = |i: usize| {
+            u32::from_le_bytes(glb[i..i + 4].try_into().unwrap()) as usize
+        };
+        assert_eq!(u32_at(8), glb.len());
+        let mut start = 12;
+        let mut chunks = Vec::new();
+        while start < glb.len() {
+            let len = u32_at(start);
+            assert!(len.is_multiple_of(4));
+            chunks.push((&glb[start + 4..start + 8], len));
+            start += 8 + len;
+        }
+        assert_eq!(start, glb.len());
+        assert_eq!(chunks[0].0, b"JSON");
+        assert_eq!(chunks[1], (&b"BIN\0"[..], 8));
+        assert_eq!(&glb[glb.len() - 2..], [0, 0]);
+        assert_eq!(glb_json(&glb)["buffers"][0]["byteLength"], 6);
+    }
+
+    #[test]
+    fn text_gltf() {
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))
+            .unwrap();
+        husk.ring(Ring::default().spoke(0.0)).unwrap();
+        let options = GltfOptions::default();
+        let mut glb = Vec::new();
+        husk.write_gltf_with(&mut glb, &options).unwrap();
+        let (mut text, mut bin) = (Vec::new(), Vec::new());
+        husk.write_gltf_json(&mut text, "pyramid.bin", &mut bin, &options)
+            .unwrap();
+        assert_eq!(bin, glb_bin(&glb));
+        let mut json: Value = serde_json::from_slice(&text).unwrap();
+        assert_eq!(json["buffers"][0]["uri"], "pyramid.bin");
+        assert_eq!(json["buffers"][0]["byteLength"], bin.len());
+        json["buffers"][0].as_object_mut().unwrap().remove("uri");
+        assert_eq!(json, glb_json(&glb));
+        let mut text = Vec::new();
+        husk.write_gltf_embedded(&mut text, &options).unwrap();
+        let json: Value = serde_json::from_slice(&text).unwrap();
+        let uri = json["buffers"][0]["uri"].as_str().unwrap();
+        let data = uri
+            .strip_prefix("data:application/octet-stream;base64,")
+            .unwrap();
+        assert_eq!(data, base64(&bin));
+    }
+
+    #[test]
+    fn base64_encode() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"M"), "TQ==");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(&[0xFF, 0xEF, 0x00, 0x3E]), "/+8APg==");
+    }
+
+    #[test]
+    fn names() {
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))
+            .unwrap();
+        husk.ring(Ring::default().spoke(0.0)).unwrap();
+        husk.name("creature");
+        let mut glb = Vec::new();
+        husk.write_gltf(&mut glb).unwrap();
+        let json = glb_json(&glb);
+        assert_eq!(json["meshes"][0]["name"], "creature");
+        assert_eq!(json["nodes"][0]["name"], "creature");
+        assert_eq!(json["scenes"][0]["name"], "creature");
+        let options = GltfOptions {
+            scene_name: Some("world".to_string()),
+            ..Default::default()
+        };
+        let mut glb = Vec::new();
+        husk.write_gltf_with(&mut glb, &options).unwrap();
+        let json = glb_json(&glb);
+        assert_eq!(json["nodes"][0]["name"], "creature");
+        assert_eq!(json["scenes"][0]["name"], "world");
+    }
+
+    #[test]
+    fn asset() {
+        let json = glb_json(&pyramid_glb(&GltfOptions::default()));
+        let generator = json["asset"]["generator"].as_str().unwrap();
+        assert_eq!(
+            generator,
+            concat!("homunculus ", env!("CARGO_PKG_VERSION"))
+        );
+        assert!(json["asset"].get("copyright").is_none());
+        assert!(json["scenes"][0].get("name").is_none());
+        let options = GltfOptions {
+            copyright: Some("2024 Someone".to_string()),
+            ..Default::default()
+        };
+        let json = glb_json(&pyramid_glb(&options));
+        assert_eq!(json["asset"]["copyright"], "2024 Someone");
+    }
+
+    #[test]
+    fn mesh_node_extras() {
+        let options = GltfOptions {
+            mesh_extras: Some(json!({ "collision": "convex" })),
+            node_extras: Some(json!({ "socket": "hand_r" })),
+            ..Default::default()
+        };
+        let json = glb_json(&pyramid_glb(&options));
+        assert_eq!(
+            json["meshes"][0]["extras"],
+            json!({ "collision": "convex" })
+        );
+        assert_eq!(json["nodes"][0]["extras"], json!({ "socket": "hand_r" }));
+        assert_eq!(json.to_string().matches("extras").count(), 2);
+    }
+
+    #[test]
+    fn lods() {
+        let options = GltfOptions {
+            lods: vec![0.5, 0.25],
+            ..Default::default()
+        };
+        let json = glb_json(&pyramid_glb(&options));
+        let names: Vec<_> = json["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["model_LOD0", "model_LOD1", "model_LOD2"]);
+        assert_eq!(json["meshes"].as_array().unwrap().len(), 3);
+        assert_eq!(json["scenes"][0]["nodes"], json!([0, 1, 2]));
+        assert!(json.get("extensionsUsed").is_none());
+        let options = GltfOptions {
+            lods: vec![0.5, 0.25],
+            msft_lod: true,
+            quantize: true,
+            ..Default::default()
+        };
+        let json = glb_json(&pyramid_glb(&options));
+        assert_eq!(
+            json["nodes"][0]["extensions"],
+            json!({ "MSFT_lod": { "ids": [1, 2] } })
+        );
+        assert_eq!(json["scenes"][0]["nodes"], json!([0]));
+        assert_eq!(json["extensionsUsed"], json!([QUANTIZATION, MSFT_LOD]));
+        assert_eq!(json["extensionsRequired"], json!([QUANTIZATION]));
+    }
+
+    #[test]
+    fn skin() {
+        let mut husk = Husk::new();
+        husk.skeleton(1);
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))
+            .unwrap();
+        husk.ring(Ring::default().axis(Vec3::Y)).unwrap();
+        husk.ring(Ring::default().axis(Vec3::Y)).unwrap();
+        let options = GltfOptions {
+            quantize: true,
+            ..Default::default()
+        };
+        let mut glb = Vec::new();
+        husk.write_gltf_with(&mut glb, &options).unwrap();
+        let json = glb_json(&glb);
+        assert_eq!(json["nodes"][0]["skin"], json!(0));
+        assert_eq!(json["nodes"][1]["name"], json!("root"));
+        assert_eq!(json["nodes"][1]["children"], json!([2]));
+        assert_eq!(json["nodes"][3]["name"], json!("root.2"));
+        assert_eq!(json["nodes"][3]["translation"], json!([0.0, 1.0, 0.0]));
+        assert_eq!(json["scenes"][0]["nodes"], json!([0, 1]));
+        assert_eq!(json["skins"][0]["joints"], json!([1, 2, 3]));
+        assert_eq!(json["skins"][0]["skeleton"], json!(1));
+        let attrs = &json["meshes"][0]["primitives"][0]["attributes"];
+        assert!(attrs.get("JOINTS_0").is_some());
+        assert!(attrs.get("WEIGHTS_0").is_some());
+        // joint transform times inverse bind matrix is the node transform
+        let node = &json["nodes"][0];
+        let vec3 = |v: &Value| {
+            let v: Vec<f32> = serde_json::from_value(v.clone()).unwrap();
+            Vec3::from_slice(&v)
+        };
+        let scale = vec3(&node["scale"]);
+        let matrix = Mat4::from_scale_rotation_translation(
+            scale,
+            Quat::IDENTITY,
+            vec3(&node["translation"]),
+        );
+        let acc = &json["accessors"][json["skins"][0]["inverseBindMatrices"]
+            .as_u64()
+            .unwrap() as usize];
+        assert_eq!(acc["count"], json!(3));
+        let view =
+            &json["bufferViews"][acc["bufferView"].as_u64().unwrap() as usize];
+        let offset = view["byteOffset"].as_u64().unwrap() as usize;
+        let bin = glb_bin(&glb);
+        let ibm: Vec<f32> = bin[offset + 64..offset + 128]
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        let joint = Mat4::from_translation(Vec3::Y);
+        let diff = joint * Mat4::from_cols_slice(&ibm) - matrix;
+        assert!(diff.to_cols_array().iter().all(|d| d.abs() < 1e-5));
+    }
+
+    #[test]
+    fn anchors() {
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))
+            .unwrap();
+        husk.ring(Ring::default().axis(Vec3::new(0.0, 2.0, 0.0)))
+            .unwrap();
+        husk.anchor("eye");
+        let mut glb = Vec::new();
+        husk.write_gltf(&mut glb).unwrap();
+        let json = glb_json(&glb);
+        assert_eq!(json["nodes"][0]["children"], json!([1]));
+        assert_eq!(json["nodes"][1]["name"], json!("eye"));
+        assert_eq!(json["nodes"][1]["translation"], json!([0.0, 2.0, 0.0]));
+        assert!(json["nodes"][1].get("mesh").is_none());
+        assert_eq!(json["scenes"][0]["nodes"], json!([0]));
+        let options = GltfOptions {
+            quantize: true,
+            scale: Some(0.5),
+            ..Default::default()
+        };
+        glb.clear();
+        husk.write_gltf_with(&mut glb, &options).unwrap();
+        let json = glb_json(&glb);
+        let vec3 = |v: &Value| {
+            let v: Vec<f32> = serde_json::from_value(v.clone()).unwrap();
+            Vec3::from_slice(&v)
+        };
+        let node = &json["nodes"][0];
+        let eye = vec3(&node["translation"])
+            + vec3(&node["scale"]) * vec3(&json["nodes"][1]["translation"]);
+        assert!(eye.distance(Vec3::new(0.0, 1.0, 0.0)) < 1e-5);
+    }
+
+    #[test]
+    fn texcoords() {
+        let glb = pyramid_glb(&GltfOptions::default());
+        let json = glb_json(&glb);
+        let attrs = &json["meshes"][0]["primitives"][0]["attributes"];
+        let acc =
+            &json["accessors"][attrs["TEXCOORD_0"].as_u64().unwrap() as usize];
+        assert_eq!(acc["componentType"], 5126);
+        assert_eq!(acc["type"], "VEC2");
+        assert_eq!(acc["count"], json["accessors"][1]["count"]);
+    }
+
+    #[test]
+    fn vertex_colors() {
+        let mut husk = Husk::new();
+        let base = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        husk.ring(base.color([1.0, 0.5, 0.0, 1.0])).unwrap();
+        husk.ring(Ring::default().spoke(0.0)).unwrap();
+        let mut glb = Vec::new();
+        husk.write_gltf(&mut glb).unwrap();
+        let json = glb_json(&glb);
+        let attrs = &json["meshes"][0]["primitives"][0]["attributes"];
+        let acc =
+            &json["accessors"][attrs["COLOR_0"].as_u64().unwrap() as usize];
+        assert_eq!(acc["componentType"], 5121);
+        assert_eq!(acc["normalized"], true);
+        assert_eq!(acc["type"], "VEC4");
+        let view =
+            &json["bufferViews"][acc["bufferView"].as_u64().unwrap() as usize];
+        let offset = view["byteOffset"].as_u64().unwrap() as usize;
+        let len = view["byteLength"].as_u64().unwrap() as usize;
+        for c in glb_bin(&glb)[offset..offset + len].chunks_exact(4) {
+            assert_eq!(c, [255, 128, 0, 255]);
+        }
+    }
+
+    #[test]
+    fn materials() {
+        let mut husk = Husk::new();
+        let ring = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        husk.ring(ring.material("side")).unwrap();
+        husk.ring(Ring::default().material("cap")).unwrap();
+        let mut glb = Vec::new();
+        husk.write_gltf(&mut glb).unwrap();
+        let json = glb_json(&glb);
+        let names: Vec<_> = json["materials"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["side", "cap"]);
+        let prims = json["meshes"][0]["primitives"].as_array().unwrap();
+        assert_eq!(prims.len(), 2);
+        let mut counts = Vec::new();
+        for (i, prim) in prims.iter().enumerate() {
+            assert_eq!(prim["material"], i);
+            assert_eq!(prim["attributes"], prims[0]["attributes"]);
+            let acc =
+                &json["accessors"][prim["indices"].as_u64().unwrap() as usize];
+            counts.push(acc["count"].as_u64().unwrap());
+        }
+        // band is "side", top cap is "cap"
+        assert_eq!(counts, [3 * 6, 3 * 3]);
+    }
+
+    #[test]
+    fn pbr_material() {
+        let mut husk = Husk::new();
+        let ring = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        husk.ring(ring.material("bark")).unwrap();
+        husk.ring(Ring::default()).unwrap();
+        husk.ring(Ring::default().material("cut")).unwrap();
+        husk.material(Material {
+            base_color: [0.5, 0.25, 0.0, 1.0],
+            metallic: 0.5,
+            double_sided: true,
+            ..Material::named("bark")
+        });
+        let mut glb = Vec::new();
+        husk.write_gltf(&mut glb).unwrap();
+        let json = glb_json(&glb);
+        assert_eq!(
+            json["materials"],
+            json!([{
+                "name": "bark",
+                "doubleSided": true,
+                "pbrMetallicRoughness": {
+                    "baseColorFactor": [0.5, 0.25, 0.0, 1.0],
+                    "metallicFactor": 0.5,
+                    "roughnessFactor": 1.0,
+                },
+            }, {
+                "name": "cut",
+                "pbrMetallicRoughness": {
+                    "baseColorFactor": [1.0, 1.0, 1.0, 1.0],
+                    "metallicFactor": 0.0,
+                    "roughnessFactor": 1.0,
+                },
+            }])
+        );
+    }
+
+    #[test]
+    fn default_material() {
+        let mut husk = Husk::new();
+        let ring = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        husk.ring(ring).unwrap();
+        husk.ring(Ring::default().material("cap")).unwrap();
+        husk.material(Material::named("skin"));
+        let mut glb = Vec::new();
+        husk.write_gltf(&mut glb).unwrap();
+        let json = glb_json(&glb);
+        assert_eq!(json["materials"][0]["name"], "skin");
+        assert_eq!(json["materials"][1]["name"], "cap");
+        let prims = &json["meshes"][0]["primitives"];
+        assert_eq!(prims[0]["material"], 0);
+        assert_eq!(prims[1]["material"], 1);
+    }
+
+    #[test]
+    fn no_materials() {
+        let json = glb_json(&pyramid_glb(&GltfOptions::default()));
+        assert!(json.get("materials").is_none());
+        let prims = json["meshes"][0]["primitives"].as_array().unwrap();
+        assert_eq!(prims.len(), 1);
+        assert!(prims[0].get("material").is_none());
+    }
+
+    #[test]
+    fn ao_colors() {
+        let options = GltfOptions {
+            ao_samples: Some(8),
+            ..Default::default()
+        };
+        let glb = pyramid_glb(&options);
+        let json = glb_json(&glb);
+        let attrs = &json["meshes"][0]["primitives"][0]["attributes"];
+        let acc =
+            &json["accessors"][attrs["COLOR_0"].as_u64().unwrap() as usize];
+        assert_eq!(acc["componentType"], 5121);
+        assert_eq!(acc["normalized"], true);
+        assert_eq!(acc["type"], "VEC4");
+        assert_eq!(acc["count"], json["accessors"][1]["count"]);
+        // a convex pyramid is unoccluded
+        let view =
+            &json["bufferViews"][acc["bufferView"].as_u64().unwrap() as usize];
+        let offset = view["byteOffset"].as_u64().unwrap() as usize;
+        let len = view["byteLength"].as_u64().unwrap() as usize;
+        assert!(glb_bin(&glb)[offset..offset + len]
+            .iter()
+            .all(|c| *c == u8::MAX));
+    }
+
+    #[test]
+    fn extras_user_wins() {
+        let mut obj = json!({ "mesh": 0, "extras": { "a": 1, "b": 2 } });
+        merge_extras(&mut obj, &json!({ "b": 3, "c": 4 }));
+        assert_eq!(obj["extras"], json!({ "a": 1, "b": 3, "c": 4 }));
+        assert_eq!(obj["mesh"], 0);
+    }
+}
@@ -0,0 +1,319 @@
+// hull.rs      Convex hull module
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::mesh::{Face, Mesh};
+use glam::Vec3;
+
+/// Hull face (triangle)
+struct HullFace {
+    /// Point indices (counter-clockwise from outside)
+    vtx: [usize; 3],
+
+    /// Outward unit normal
+    norm: Vec3,
+
+    /// Plane offset along normal
+    offset: f32,
+
+    /// Points outside of face
+    outside: Vec<usize>,
+
+    /// Face is still part of hull
+    alive: bool,
+}
+
+impl HullFace {
+    /// Create a new hull face
+    fn new(pts: &[Vec3], vtx: [usize; 3]) -> Self {
+        let [p0, p1, p2] = vtx.map(|v| pts[v]);
+        let norm = (p1 - p0).cross(p2 - p0).normalize_or_zero();
+        HullFace {
+            vtx,
+            norm,
+            offset: norm.dot(p0),
+            outside: Vec::new(),
+            alive: true,
+        }
+    }
+
+    /// Get signed distance of a point above face plane
+    fn distance(&self, pt: Vec3) -> f32 {
+        self.norm.dot(pt) - self.offset
+    }
+
+    /// Get directed edges
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [v0, v1, v2] = self.vtx;
+        [(v0, v1), (v1, v2), (v2, v0)]
+    }
+}
+
+/// Find initial tetrahedron for hull
+fn initial_simplex(pts: &[Vec3], eps: f32) -> Option<[usize; 4]> {
+    if pts.len() < 4 {
+        return None;
+    }
+    // extreme points along X axis
+    let (mut i0, mut i1) = (0, 0);
+    for (i, p) in pts.iter().enumerate() {
+        if p.x < pts[i0].x {
+            i0 = i;
+        }
+        if p.x > pts[i1].x {
+            i1 = i;
+        }
+    }
+    if pts[i0].distance(pts[i1]) <= eps {
+        // try farthest point from first instead
+        i1 = farthest(pts, |p| p.distance(pts[i0]))?;
+        if pts[i0].distance(pts[i1]) <= eps {
+            return None;
+        }
+    }
+    let dir = (pts[i1] - pts[i0]).normalize();
+    let i2 =
+        farthest(pts, |p| (p - pts[i0]).reject_from_normalized(dir).length())?;
+    let norm = (pts[i1] - pts[i0])
+        .cross(pts[i2] - pts[i0])
+        .normalize_or_zero();
+    if norm == Vec3::ZERO {
+        return None;
+    }
+    let i3 = farthest(pts, |p| norm.dot(p - pts[i0]).abs())?;
+    if norm.dot(pts[i3] - pts[i0]).abs() <= eps {
+        return None;
+    }
+    Some([i0, i1, i2, i3])
+}
+
+/// Find index of point farthest by a distance function
+fn farthest(pts: &[Vec3], dist: impl Fn(Vec3) -> f32) -> Option<usize> {
+    pts.iter()
+        .enumerate()
+        .map(|(i, p)| (i, dist(*p)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+/// Assign points to the first face they are outside of
+fn assign_outside(
+    pts: &[Vec3],
+    faces: &mut [HullFace],
+    candidates: impl IntoIterator<Item = usize>,
+    eps: f32,
+) {
+    for i in candidates {
+        if let Some(face) = faces
+            .iter_mut()
+            .filter(|f| f.alive)
+            .find(|f| f.distance(pts[i]) > eps)
+        {
+            face.outside.push(i);
+        }
+    }
+}
+
+/// Build convex hull faces (quickhull)
+fn hull_faces(pts: &[Vec3], eps: f32) -> Vec<HullFace> {
+    let Some([i0, i1, i2, i3]) = initial_simplex(pts, eps) else {
+        return Vec::new();
+    };
+    let mut faces = Vec::with_capacity(pts.len() * 2);
+    let center = (pts[i0] + pts[i1] + pts[i2] + pts[i3]) / 4.0;
+    for vtx in [[i0, i1, i2], [i0, i3, i1], [i1, i3, i2], [i2, i3, i0]] {
+        let mut face = HullFace::new(pts, vtx);
+        if face.distance(center) > 0.0 {
+            face = HullFace::new(pts, [vtx[0], vtx[2], vtx[1]]);
+        }
+        faces.push(face);
+    }
+    let simplex = [i0, i1, i2, i3];
+    assign_outside(
+        pts,
+        &mut faces,
+        (0..pts.len()).filter(|i| !simplex.contains(i)),
+        eps,
+    );
+    while let Some(fi) =
+        faces.iter().position(|f| f.alive && !f.outside.is_empty())
+    {
+        let face = &faces[fi];
+        // unwrap note: outside is not empty
+        let eye = *face
+            .outside
+            .iter()
+            .max_by(|a, b| {
+                face.distance(pts[**a]).total_cmp(&face.distance(pts[**b]))
+            })
+            .unwrap();
+        let visible: Vec<usize> = (0..faces.len())
+            .filter(|i| faces[*i].alive && faces[*i].distance(pts[eye]) > eps)
+            .collect();
+        // horizon edges are not shared by two visible faces
+        let mut horizon = Vec::new();
+        for vi in &visible {
+            for (a, b) in faces[*vi].edges() {
+                let shared = visible
+                    .iter()
+                    .any(|vj| faces[*vj].edges().contains(&(b, a)));
+                if !shared {
+                    horizon.push((a, b));
+                }
+            }
+        }
+        let mut orphans = Vec::new();
+        for vi in &visible {
+            faces[*vi].alive = false;
+            orphans.append(&mut faces[*vi].outside);
+        }
+        let first = faces.len();
+        for (a, b) in horizon {
+            faces.push(HullFace::new(pts, [a, b, eye]));
+        }
+        assign_outside(
+            pts,
+            &mut faces[first..],
+            orphans.into_iter().filter(|i| *i != eye),
+            eps,
+        );
+    }
+    faces.retain(|f| f.alive);
+    faces
+}
+
+/// Make a convex hull mesh from points
+///
+/// Returns an empty mesh if the points are all coplanar.
+pub(crate) fn convex_hull(pts: &[Vec3]) -> Mesh {
+    let (min, max) = pts.iter().fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), p| (min.min(*p), max.max(*p)),
+    );
+    let eps = (max - min).max_element().max(1.0) * 1e-5;
+    let faces = hull_faces(pts, eps);
+    let mut builder = Mesh::builder();
+    let mut vids = vec![None; pts.len()];
+    for face in &faces {
+        let mut vtx = face.vtx;
+        for v in vtx.iter_mut() {
+            *v = match vids[*v] {
+                Some(vid) => vid,
+                None => {
+                    // unwrap note: hull points are from a mesh
+                    let vid = builder.push_vtx(pts[*v]).unwrap();
+                    vids[*v] = Some(vid);
+                    vid
+                }
+            };
+        }
+        builder.push_face(Face::new(vtx, 0));
+    }
+    // unwrap note: hull faces are all on one surface (no splitting)
+    builder.build().unwrap()
+}
+
+impl Mesh {
+    /// Make a convex hull of the mesh
+    ///
+    /// The hull is closed, with outward facing triangles and normals.
+    pub fn convex_hull(&self) -> Mesh {
+        convex_hull(self.positions())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::Result;
+    use crate::{Husk, Ring};
+    use std::collections::HashMap;
+
+    /// Check that a mesh is closed and convex (at every edge)
+    fn assert_convex(mesh: &Mesh) {
+        let pos = mesh.positions();
+        let tris: Vec<[usize; 3]> = mesh
+            .indices()
+            .chunks_exact(3)
+            .map(|t| [0, 1, 2].map(|i| usize::from(t[i].0)))
+            .collect();
+        let mut edges = HashMap::new();
+        for (t, [v0, v1, v2]) in tris.iter().enumerate() {
+            for edge in [(*v0, *v1), (*v1, *v2), (*v2, *v0)] {
+                assert!(edges.insert(edge, t).is_none(), "duplicate edge");
+            }
+        }
+        let center = pos.iter().sum::<Vec3>() / pos.len() as f32;
+        for (&(a, b), &t0) in &edges {
+            // every edge must have a matching reversed edge
+            let t1 = edges[&(b, a)];
+            let [p0, p1, p2] = tris[t0].map(|v| pos[v]);
+            let norm = (p1 - p0).cross(p2 - p0).normalize();
+            assert!(norm.dot(p0 - center) > 0.0, "inward face");
+            let opposite =
+                tris[t1].into_iter().find(|v| *v != a && *v != b).unwrap();
+            assert!(norm.dot(pos[opposite] - p0) <= 1e-4, "concave edge");
+        }
+        for (p, n) in pos.iter().zip(mesh.normals()) {
+            assert!(n.dot(*p - center) > 0.0, "inward normal");
+        }
+    }
+
+    fn cube_points() -> Vec<Vec3> {
+        let mut pts: Vec<_> = (0..8)
+            .map(|i| {
+                let c = |bit| if i & bit != 0 { 1.0 } else { -1.0 };
+                Vec3::new(c(1), c(2), c(4))
+            })
+            .collect();
+        // interior and face points are not on the hull
+        pts.push(Vec3::ZERO);
+        pts.push(Vec3::new(0.5, 0.25, -0.5));
+        pts.push(Vec3::new(1.0, 0.5, 0.0));
+        pts
+    }
+
+    #[test]
+    fn cube() {
+        let hull = convex_hull(&cube_points());
+        assert_eq!(hull.positions().len(), 8);
+        assert_eq!(hull.indices().len(), 12 * 3);
+        assert_convex(&hull);
+        let again = hull.convex_hull();
+        assert_eq!(again.positions().len(), 8);
+        assert_eq!(again.indices().len(), 12 * 3);
+    }
+
+    #[test]
+    fn degenerate() {
+        let flat = [Vec3::ZERO, Vec3::X, Vec3::Z, Vec3::ONE - Vec3::Y];
+        assert!(convex_hull(&flat).positions().is_empty());
+        assert!(convex_hull(&[Vec3::ONE; 5]).positions().is_empty());
+    }
+
+    #[test]
+    fn star_segments() -> Result<()> {
+        let mut husk = Husk::new();
+        let mut star = Ring::default();
+        for i in 0..10 {
+            star = star.spoke(if i % 2 == 0 { 1.0 } else { 0.4 });
+        }
+        husk.ring(star)?;
+        for i in 1..7 {
+            let scale = 1.0 + 0.2 * (i % 3) as f32;
+            husk.ring(
+                Ring::default().axis(Vec3::new(0.1, 1.0, 0.0)).scale(scale),
+            )?;
+        }
+        let hulls = husk.hull_per_ring_segment(2);
+        assert_eq!(hulls.len(), 3);
+        for hull in &hulls {
+            assert_convex(hull);
+        }
+        // inner star points are never on hull
+        assert!(hulls[0].positions().len() <= 15);
+        assert_eq!(husk.hull_per_ring_segment(6).len(), 1);
+        assert_eq!(husk.hull_per_ring_segment(4).len(), 2);
+        Ok(())
+    }
+}
@@ -2,13 +2,97 @@
 //
 // Copyright (c) 2022-2023  Douglas Lau
 //
-use crate::error::{Error, Result};
-use crate::gltf;
+use crate::error::{ignore_invalid, Error, Result};
+use crate::gltf::{self, GltfOptions, Material, MeshNode};
+use crate::hull;
 use crate::mesh::{Face, Mesh, MeshBuilder};
-use crate::ring::{Branch, Degrees, Point, Pt, Ring, Shading};
-use glam::Vec3;
-use std::collections::HashMap;
+use crate::obj;
+use crate::plane::Plane;
+use crate::ply::{self, PlyFormat};
+use crate::ring::{
+    Branch, Degrees, LabelId, Labels, Point, Pt, Ring, RingView, Shading,
+};
+use crate::skeleton::Skeleton;
+use fastrand::Rng;
+use glam::{Affine3A, Vec2, Vec3};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::f32::consts::{FRAC_PI_2, PI};
 use std::io::Write;
+use std::ops::Range;
+
+/// Radius of cap discs in texture coordinates
+const CAP_UV_RADIUS: f32 = 0.1;
+
+/// Taper profile
+///
+/// Shape of the scale ramp applied by [Husk::taper].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Taper {
+    /// Constant rate of change
+    Linear,
+
+    /// Slow start, fast finish
+    EaseIn,
+
+    /// Fast start, slow finish
+    EaseOut,
+
+    /// Slow start and finish
+    Cosine,
+}
+
+/// Ring frame mode
+///
+/// Determines how the spokes of each ring are oriented around its axis.
+/// Set with [Husk::set_frame_mode].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FrameMode {
+    /// Rotation-minimizing frame
+    ///
+    /// Each ring's frame is the previous frame, rotated by the shortest arc
+    /// between their axes.  Spokes follow a curving spine without twisting.
+    #[default]
+    ParallelTransport,
+
+    /// Frame fixed to world X axis
+    ///
+    /// Spoke 0 of each ring points toward world +X (projected onto the ring
+    /// plane), or +Z when the axis is parallel to X.  Spokes may twist
+    /// around a curving spine.
+    Fixed,
+}
+
+/// Random jitter settings
+///
+/// Used by [Husk::jitter] to perturb rings for organic variation.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Jitter {
+    /// Maximum relative change of spoke distances
+    pub distance: f32,
+
+    /// Maximum deviation of ring axis (radians)
+    pub axis: f32,
+
+    /// Random number seed
+    pub seed: u64,
+}
+
+/// Position of a ring along the spine
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct SpinePos {
+    /// Center of ring
+    center: Vec3,
+
+    /// Arc length from branch base
+    arclength: f32,
+
+    /// Arc length of branch base on parent branch
+    base_offset: f32,
+}
 
 /// Outer shell of a 3D model
 ///
@@ -26,6 +110,8 @@ use std::io::Write;
 /// ```
 ///
 /// [ring]: struct.Ring.html
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Husk {
     /// Mesh builder
     builder: MeshBuilder,
@@ -36,8 +122,103 @@ pub struct Husk {
     /// Current ring
     ring: Option<Ring>,
 
+    /// First ring, if left open (for attaching to another husk)
+    first: Option<Ring>,
+
+    /// Interned branch labels
+    labels: Labels,
+
     /// Mapping of labels to branches
-    branches: HashMap<String, Branch>,
+    branches: HashMap<LabelId, Branch>,
+
+    /// Pending taper scales for upcoming rings
+    taper: VecDeque<f32>,
+
+    /// Random jitter settings
+    jitter: Jitter,
+
+    /// Ring frame mode
+    frame_mode: FrameMode,
+
+    /// Cap branches when ended implicitly
+    auto_cap: bool,
+
+    /// Distance for welding vertices when building
+    weld: Option<f32>,
+
+    /// Mirror plane when building
+    mirror: Option<Plane>,
+
+    /// Levels of subdivision when building
+    subdivide: usize,
+
+    /// Flip winding when building, if inside out
+    fix_winding: bool,
+
+    /// Wall thickness of hollow shell when building
+    thickness: Option<f32>,
+
+    /// Rings between skeleton joints, if building a skeleton
+    joint_spacing: Option<usize>,
+
+    /// Rings marked as skeleton joints
+    joint_rings: HashSet<usize>,
+
+    /// Spine positions of all rings added
+    spine: Vec<SpinePos>,
+
+    /// Arc length offset of current branch base
+    base_offset: f32,
+
+    /// Mapping of crease vertices to original vertices
+    creases: HashMap<usize, usize>,
+
+    /// Face ranges of caps, with hub vertices
+    caps: Vec<(Range<usize>, usize)>,
+
+    /// Point positions of all rings added
+    ring_points: Vec<RingPoints>,
+
+    /// Branch base rings, with label and parent ring
+    bases: HashMap<usize, (String, usize)>,
+
+    /// Label and parent ring of most recent branch
+    base: Option<(String, usize)>,
+
+    /// Ring of each vertex, for skinning
+    vertex_rings: Vec<usize>,
+
+    /// Material definitions for export
+    materials: Vec<Material>,
+
+    /// Named anchor transforms for export
+    anchors: Vec<(String, Affine3A)>,
+
+    /// Name for export
+    name: Option<String>,
+}
+
+/// Serializable snapshot of a [Husk] under construction
+///
+/// Made by [Husk::to_snapshot], and restored by [Husk::from_snapshot].  It
+/// includes everything needed to continue adding rings, such as open
+/// branches and the current ring.
+#[cfg(feature = "serde")]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct HuskSnapshot {
+    /// Snapshot husk
+    husk: Husk,
+}
+
+/// Point positions of a ring
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct RingPoints {
+    /// Connected to previous ring
+    connected: bool,
+
+    /// Point positions
+    pos: Vec<Vec3>,
 }
 
 impl Default for Husk {
@@ -46,6 +227,48 @@ impl Default for Husk {
     }
 }
 
+impl Taper {
+    /// Evaluate the profile at `t` (from 0 to 1)
+    fn eval(self, t: f32) -> f32 {
+        match self {
+            Taper::Linear => t,
+            Taper::EaseIn => t * t,
+            Taper::EaseOut => t * (2.0 - t),
+            Taper::Cosine => (1.0 - (t * PI).cos()) * 0.5,
+        }
+    }
+}
+
+impl Jitter {
+    /// Check if jitter has any effect
+    fn is_active(&self) -> bool {
+        self.distance != 0.0 || self.axis != 0.0
+    }
+
+    /// Get random number generator for a ring
+    fn rng(&self, ring: usize) -> Rng {
+        Rng::with_seed(self.seed.wrapping_add(ring as u64))
+    }
+
+    /// Tilt ring axis randomly
+    fn tilt(&self, rng: &mut Rng, ring: &mut Ring) {
+        let azimuth = rng.f32() * 2.0 * PI;
+        let angle = rng.f32() * self.axis;
+        ring.tilt(azimuth, angle);
+    }
+
+    /// Make random spoke distance factors
+    fn distance_factors(
+        rng: &mut Rng,
+        count: usize,
+        distance: f32,
+    ) -> Vec<f32> {
+        (0..count)
+            .map(|_| 1.0 + (rng.f32() * 2.0 - 1.0) * distance)
+            .collect()
+    }
+}
+
 impl Husk {
     /// Create a new husk
     pub fn new() -> Self {
@@ -53,56 +276,240 @@ impl Husk {
             builder: Mesh::builder(),
             surface: 0,
             ring: None,
+            first: None,
+            labels: Labels::default(),
             branches: HashMap::new(),
+            taper: VecDeque::new(),
+            jitter: Jitter::default(),
+            frame_mode: FrameMode::default(),
+            auto_cap: true,
+            weld: None,
+            mirror: None,
+            subdivide: 0,
+            fix_winding: false,
+            thickness: None,
+            joint_spacing: None,
+            joint_rings: HashSet::new(),
+            spine: Vec::new(),
+            base_offset: 0.0,
+            creases: HashMap::new(),
+            caps: Vec::new(),
+            ring_points: Vec::new(),
+            bases: HashMap::new(),
+            base: None,
+            vertex_rings: Vec::new(),
+            materials: Vec::new(),
+            anchors: Vec::new(),
+            name: None,
         }
     }
 
-    /// Push internal branch point
-    fn push_branch_internal(&mut self, label: &str, pos: Vec3) {
-        if !self.branches.contains_key(label) {
-            self.branches.insert(label.to_string(), Branch::default());
-        }
-        if let Some(branch) = self.branches.get_mut(label) {
-            branch.push_internal(pos);
+    /// Make a husk by revolving a profile around the Y axis
+    ///
+    /// Each profile point is (radius, height), making a ring of `segments`
+    /// equal spokes.  The first ring is at the origin, so heights are
+    /// relative to the first point.  A point with zero radius closes the
+    /// husk to a point.  The start is capped, and the end is capped when
+    /// building, unless disabled with [Husk::set_auto_cap].
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk};
+    /// # use glam::Vec2;
+    /// # fn main() -> Result<(), Error> {
+    /// let profile = [
+    ///     Vec2::new(0.5, 0.0),
+    ///     Vec2::new(0.8, 0.5),
+    ///     Vec2::new(0.4, 1.5),
+    ///     Vec2::new(0.6, 2.0),
+    /// ];
+    /// let vase = Husk::lathe(&profile, 16)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [Error::InvalidSpoke] if a radius is negative or not finite
+    /// - [Error::InvalidAxis] if a height is not finite, or is less than the
+    ///   previous height
+    ///
+    /// # Panics
+    ///
+    /// - If `segments` is less than 3
+    pub fn lathe(profile: &[Vec2], segments: usize) -> Result<Self> {
+        assert!(segments >= 3);
+        let mut husk = Husk::new();
+        for (i, pt) in profile.iter().enumerate() {
+            let mut ring = if pt.x == 0.0 {
+                Ring::default().spoke(0.0)
+            } else {
+                (0..segments)
+                    .try_fold(Ring::default(), |r, _| r.try_spoke(pt.x))?
+            };
+            if i == 0 {
+                husk.ring_capped(ring)?;
+            } else {
+                let axis = Vec3::new(0.0, pt.y - profile[i - 1].y, 0.0);
+                if axis.y < 0.0 {
+                    return Err(Error::InvalidAxis(axis));
+                }
+                ring = ring.try_axis(axis)?;
+                husk.ring(ring)?;
+            }
         }
+        Ok(husk)
+    }
+
+    /// Push internal branch point
+    fn push_branch_internal(&mut self, label: LabelId, pos: Vec3) {
+        self.branches.entry(label).or_default().push_internal(pos);
     }
 
     /// Push branch edge
-    fn push_branch_edge(&mut self, label: &str, v0: usize, v1: usize) {
-        if !self.branches.contains_key(label) {
-            self.branches.insert(label.to_string(), Branch::default());
-        }
-        if let Some(branch) = self.branches.get_mut(label) {
-            branch.push_edge(v0, v1);
-        }
+    fn push_branch_edge(&mut self, label: LabelId, v0: usize, v1: usize) {
+        let v0 = self.creases.get(&v0).copied().unwrap_or(v0);
+        let v1 = self.creases.get(&v1).copied().unwrap_or(v1);
+        self.branches.entry(label).or_default().push_edge(v0, v1);
     }
 
     /// Add branch points for a ring
-    fn add_branch_points(&mut self, ring: &Ring) {
+    fn add_branch_points(&mut self, ring: &Ring, arclength: f32) {
+        let index = self.ring_points.len();
+        for point in ring.points() {
+            match (point.pt, point.crease) {
+                (Pt::Branch(label, pos), _) => {
+                    self.push_branch_internal(label, pos);
+                    if let Some(branch) = self.branches.get_mut(&label) {
+                        branch.set_offset(self.base_offset + arclength);
+                        branch.set_ring(index);
+                        branch.set_color(ring.vertex_color());
+                        branch.set_material(ring.material_name());
+                    }
+                }
+                (Pt::Vertex(vid), Some(crease)) => {
+                    self.creases.insert(crease, vid);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Push spine position of a ring
+    fn push_spine(&mut self, ring: &Ring, connected: bool) -> f32 {
+        let center = ring.center();
+        let pos = match (self.spine.last(), connected) {
+            (Some(prev), true) => SpinePos {
+                center,
+                arclength: prev.arclength + prev.center.distance(center),
+                base_offset: prev.base_offset,
+            },
+            _ => SpinePos {
+                center,
+                arclength: 0.0,
+                base_offset: self.base_offset,
+            },
+        };
+        self.spine.push(pos);
+        pos.arclength
+    }
+
+    /// Set texture coordinates and colors of ring vertices
+    ///
+    /// U is from the angle around the ring, and V is the arc length (which
+    /// is normalized when building).
+    fn set_ring_attributes(&mut self, ring: &Ring) {
+        let v = self
+            .spine
+            .last()
+            .map_or(0.0, |pos| pos.base_offset + pos.arclength);
         for point in ring.points() {
-            if let Pt::Branch(label, pos) = &point.pt {
-                self.push_branch_internal(label, *pos);
+            let uv = Vec2::new(point.order.0 / 360.0, v);
+            let vids = match point.pt {
+                Pt::Vertex(vid) => [Some(vid), point.crease],
+                Pt::Branch(..) => [None, point.crease],
+            };
+            for vid in vids.into_iter().flatten() {
+                self.builder.set_uv(vid, uv);
+                if let Some(color) = ring.vertex_color() {
+                    self.builder.set_color(vid, color);
+                }
             }
         }
     }
 
+    /// Normalize V texture coordinates and map cap discs
+    fn finish_uvs(&mut self) -> Result<()> {
+        self.builder.normalize_uv_height();
+        for (faces, hub) in std::mem::take(&mut self.caps) {
+            self.builder.map_uv_disc(faces, hub, CAP_UV_RADIUS)?;
+        }
+        self.builder.split_uv_seams()
+    }
+
+    /// Push point positions of a ring
+    fn push_ring_points(&mut self, ring: &Ring, connected: bool) {
+        let pos = ring
+            .points()
+            .map(|point| match point.pt {
+                Pt::Vertex(vid) => self.builder.vertex(vid),
+                Pt::Branch(_label, pos) => pos,
+            })
+            .collect();
+        self.ring_points.push(RingPoints { connected, pos });
+    }
+
     /// Add a ring to the current branch
     ///
     /// All unset properties are copied from the previous ring:
     /// - spacing
     /// - scale
     /// - shading
+    /// - twist
+    /// - seam
     /// - spokes
-    pub fn ring(&mut self, ring: Ring) -> Result<()> {
+    pub fn ring(&mut self, mut ring: Ring) -> Result<()> {
+        if let Some(scale) = self.taper.pop_front() {
+            ring = ring.scale_or(scale);
+        }
         let pring = self.ring.take();
         let mut ring = match &pring {
             Some(pr) => pr.with_ring(&ring),
             None => ring,
         };
+        if self.frame_mode == FrameMode::Fixed && !ring.is_branch_base() {
+            ring.fix_frame();
+        }
+        let mut rng = self.jitter.rng(self.spine.len());
+        if self.jitter.is_active() {
+            self.jitter.tilt(&mut rng, &mut ring);
+        }
+        let arclength = self.push_spine(&ring, pring.is_some());
+        let id = self.spine.len() - 1;
+        if pring.is_none() && ring.is_branch_base() {
+            if let Some(base) = self.base.take() {
+                self.bases.insert(id, base);
+            }
+        }
         if ring.points().len() == 0 {
-            ring.make_points(&mut self.builder);
-            self.add_branch_points(&ring);
+            let distance = ring.jitter_or(self.jitter.distance);
+            let factors = if distance != 0.0 {
+                Jitter::distance_factors(
+                    &mut rng,
+                    ring.spokes().count(),
+                    distance,
+                )
+            } else {
+                Vec::new()
+            };
+            ring.make_points(&mut self.builder, &mut self.labels, &factors)?;
+            self.vertex_rings.resize(self.builder.len(), id);
+            self.add_branch_points(&ring, arclength);
+            self.set_ring_attributes(&ring);
         }
+        if self.ring_points.is_empty() {
+            self.first = Some(ring.clone());
+        }
+        self.push_ring_points(&ring, pring.is_some());
         if let Some(pring) = &pring {
             self.make_band(pring, &ring)?;
         }
@@ -110,164 +517,2920 @@ impl Husk {
         Ok(())
     }
 
-    /// Add a cap face on the current branch
-    fn cap(&mut self) -> Result<()> {
-        match self.ring.take() {
-            Some(ring) => self.cap_ring(ring),
-            None => Ok(()),
+    /// Add a ring, with interpolated rings before it
+    ///
+    /// Instead of a single band from the current ring, `steps` intermediate
+    /// rings are added.  Their spoke distances, scale and spacing are
+    /// interpolated linearly from the current ring to `ring`, and its axis
+    /// rotation is divided evenly among them.  Branch labels are only on
+    /// the final ring.  If the spoke counts differ, the current ring's
+    /// spokes are resampled, as with [Ring::resample].
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+    /// husk.ring_lerp(Ring::default().spoke(3.0).spoke(3.0).spoke(3.0), 4)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - Any error from [Husk::ring]
+    pub fn ring_lerp(&mut self, ring: Ring, steps: usize) -> Result<()> {
+        let rings = match &self.ring {
+            Some(pring) => pring.lerp_rings(&ring, steps, &self.builder),
+            None => vec![ring],
+        };
+        for ring in rings {
+            self.ring(ring)?;
         }
+        Ok(())
     }
 
-    /// Add a cap face on the given ring
-    fn cap_ring(&mut self, ring: Ring) -> Result<()> {
-        let mut pts = ring.points_offset(Degrees(0));
-        // unwrap note: ring will always have at least one point
-        let last = pts.pop().unwrap();
-        if pts.len() < 2 {
+    /// Add rings along a path
+    ///
+    /// A copy of `ring` is added at each point of the path, which is
+    /// relative to its first point.  When there is a current ring, it is at
+    /// the first point; otherwise, a ring is added there (at the origin).
+    /// The axis of each ring follows the path segment leading to it, with
+    /// frames rotated by the shortest arc, so spokes do not spin around the
+    /// path.  Repeated points are skipped, and `ring` should not have an
+    /// axis set.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # use glam::Vec3;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.ring_capped(Ring::default().spoke(0.2).spoke(0.2).spoke(0.2))?;
+    /// let path = [Vec3::ZERO, Vec3::new(0.0, 1.0, 0.0), Vec3::ONE];
+    /// husk.sweep(&path, Ring::default())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [Error::InvalidAxis] if any point is infinite or NaN
+    /// - Any error from [Husk::ring]
+    pub fn sweep(&mut self, path: &[Vec3], ring: Ring) -> Result<()> {
+        let Some(mut prev) = path.first().copied() else {
             return Ok(());
+        };
+        if self.ring.is_none() {
+            // orient the first ring toward the first segment
+            let axis = path
+                .iter()
+                .map(|pt| *pt - prev)
+                .find(|seg| seg.try_normalize().is_some())
+                .unwrap_or(Vec3::Y);
+            self.ring(ring.clone().try_axis(axis)?)?;
         }
-        // add hub point
-        let (order, pos) = ring.make_hub();
-        let vid = self.builder.push_vtx(pos);
-        let hub = Point::new(Pt::Vertex(vid), order);
-        let mut prev = last.clone();
-        for pt in pts.drain(..) {
-            self.add_face([&pt, &prev, &hub])?;
-            prev = pt;
-            if ring.shading_or_default() == Shading::Flat {
-                self.surface += 1;
+        for pt in &path[1..] {
+            let seg = *pt - prev;
+            if !seg.is_finite() {
+                return Err(Error::InvalidAxis(seg));
             }
-        }
-        self.add_face([&last, &prev, &hub])?;
-        if ring.shading_or_default() == Shading::Flat {
-            self.surface += 1;
+            if seg.try_normalize().is_none() {
+                continue;
+            }
+            // unwrap note: a ring was added above
+            let axis = self.ring.as_ref().unwrap().local_dir(seg);
+            self.ring(ring.clone().try_axis(axis)?)?;
+            prev = *pt;
         }
         Ok(())
     }
 
-    /// End the current branch and get the `label` branch
+    /// Add rings along a helix
     ///
-    /// The `label` must match one or more [Spoke]s from earlier rings.
+    /// The helix is right-handed, around an axis parallel to Y, at `radius`
+    /// along -X from the current ring (or the origin).  It rises by `pitch`
+    /// for each turn, with `rings_per_turn` copies of `profile` (as with
+    /// [Husk::sweep]).
     ///
-    /// [spoke]: struct.Spoke.html
-    pub fn branch(&mut self, label: impl AsRef<str>) -> Result<Ring> {
-        self.cap()?;
-        let branch = self.take_branch(label.as_ref())?;
-        Ok(Ring::with_branch(branch, &self.builder))
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// let profile = (0..8).fold(Ring::default(), |r, _| r.spoke(0.1));
+    /// husk.helix(3.0, 1.0, 0.5, 24, profile)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [Error::InvalidAxis] if any parameter is infinite or NaN
+    /// - Any error from [Husk::ring]
+    ///
+    /// # Panics
+    ///
+    /// - If `rings_per_turn` is less than 3
+    pub fn helix(
+        &mut self,
+        turns: f32,
+        radius: f32,
+        pitch: f32,
+        rings_per_turn: usize,
+        profile: Ring,
+    ) -> Result<()> {
+        assert!(rings_per_turn >= 3);
+        let rings = (turns.max(0.0) * rings_per_turn as f32).ceil() as usize;
+        let path: Vec<_> = (0..=rings)
+            .map(|i| {
+                let turn = turns * i as f32 / rings.max(1) as f32;
+                let angle = 2.0 * PI * turn;
+                Vec3::new(
+                    radius * (angle.cos() - 1.0),
+                    pitch * turn,
+                    -radius * angle.sin(),
+                )
+            })
+            .collect();
+        self.sweep(&path, profile)
     }
 
-    /// Take a branch by label
-    fn take_branch(&mut self, label: &str) -> Result<Branch> {
-        self.branches
-            .remove(label)
-            .ok_or_else(|| Error::UnknownBranchLabel(label.to_string()))
+    /// Get a view of the current ring
+    ///
+    /// This is the most recently added ring, which the next ring will
+    /// extend.  Returns `None` before the first ring, or after the branch
+    /// is capped.
+    pub fn current_ring(&self) -> Option<RingView<'_>> {
+        self.ring.as_ref().map(RingView::new)
     }
 
-    /// Make a band of faces between two rings
-    fn make_band(&mut self, ring0: &Ring, ring1: &Ring) -> Result<()> {
-        if ring0.shading_or_default() != Shading::Smooth {
-            self.surface += 1;
-        }
-        // get points for each ring
-        let mut pts0 = ring0.points_offset(ring1.half_step());
-        let mut pts1 = ring1.points_offset(ring0.half_step());
-        // unwrap note: ring will always have at least one point
-        let first0 = pts0.pop().unwrap();
-        let first1 = pts1.pop().unwrap();
-        let (mut pt0, mut pt1) = (first0.clone(), first1.clone());
-        let mut band = Vec::with_capacity(pts0.len() + pts1.len());
-        band.extend_from_slice(&pts0[..]);
-        band.append(&mut pts1);
-        band.sort_by(|a, b| b.order.partial_cmp(&a.order).unwrap());
-        // create faces of band as a triangle strip
-        while let Some(pt) = band.pop() {
-            self.add_face([&pt1, &pt0, &pt])?;
-            if pts0.contains(&pt) {
-                pt0 = pt;
-            } else {
-                pt1 = pt;
-            }
-            if ring0.shading_or_default() == Shading::Flat {
-                self.surface += 1;
-            }
-        }
-        // connect with first vertices on band
-        if pt1 != first1 {
-            self.add_face([&pt1, &pt0, &first1])?;
-            if ring0.shading_or_default() == Shading::Flat {
-                self.surface += 1;
-            }
-        }
-        if pt0 != first0 {
-            self.add_face([&first0, &first1, &pt0])?;
-            if ring0.shading_or_default() == Shading::Flat {
-                self.surface += 1;
-            }
+    /// Taper scale across the next rings
+    ///
+    /// The scale of each of the next `rings` added is ramped from the scale
+    /// of the current ring to `target`, following a [Taper] profile.  A scale
+    /// set explicitly on any of those rings takes precedence.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring, Taper};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+    /// husk.taper(0.4, 10, Taper::Cosine);
+    /// for _ in 0..10 {
+    ///     husk.ring(Ring::default())?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, on any error from [Husk::try_taper].  Otherwise, an
+    /// invalid target is ignored with a warning.
+    pub fn taper(&mut self, target: f32, rings: usize, profile: Taper) {
+        if let Err(e) = self.try_taper(target, rings, profile) {
+            ignore_invalid(e);
         }
-        Ok(())
     }
 
-    /// Add a triangle face
-    fn add_face(&mut self, pts: [&Point; 3]) -> Result<()> {
-        match (&pts[0].pt, &pts[1].pt, &pts[2].pt) {
-            (Pt::Vertex(v0), Pt::Vertex(v1), Pt::Vertex(v2)) => {
-                let face = Face::new([*v0, *v1, *v2], self.surface);
-                self.builder.push_face(face);
-            }
-            (Pt::Branch(lbl, _), Pt::Vertex(v0), Pt::Vertex(v1))
-            | (Pt::Vertex(v1), Pt::Branch(lbl, _), Pt::Vertex(v0))
-            | (Pt::Vertex(v0), Pt::Vertex(v1), Pt::Branch(lbl, _)) => {
-                self.push_branch_edge(lbl, *v0, *v1);
-            }
-            (Pt::Vertex(_v), Pt::Branch(b0, _), Pt::Branch(b1, _))
-            | (Pt::Branch(b0, _), Pt::Vertex(_v), Pt::Branch(b1, _))
-            | (Pt::Branch(b0, _), Pt::Branch(b1, _), Pt::Vertex(_v)) => {
-                // A single vertex and two branch points:
-                // - both points must be for the same branch
-                // - no edges need to be added
-                if b0 != b1 {
-                    return Err(Error::InvalidBranches(format!(
-                        "{b0} != {b1}"
-                    )));
-                }
-            }
-            (Pt::Branch(b0, _), Pt::Branch(b1, _), Pt::Branch(b2, _)) => {
-                // Three adjacent branch points:
-                // - all points must be for the same branch
-                if b0 != b1 {
-                    return Err(Error::InvalidBranches(format!(
-                        "{b0} != {b1}"
-                    )));
-                }
-                if b0 != b2 {
-                    return Err(Error::InvalidBranches(format!(
-                        "{b0} != {b2}"
-                    )));
-                }
-            }
+    /// Taper scale across the next rings, checking for errors
+    ///
+    /// Returns [Error::InvalidScale] if the target scale is negative,
+    /// infinite, or NaN.
+    pub fn try_taper(
+        &mut self,
+        target: f32,
+        rings: usize,
+        profile: Taper,
+    ) -> Result<()> {
+        if !target.is_finite() || !target.is_sign_positive() {
+            return Err(Error::InvalidScale(target));
         }
+        let start = self.ring.as_ref().map_or(1.0, |r| r.scale_or_default());
+        self.taper = (1..=rings)
+            .map(|i| {
+                let t = profile.eval(i as f32 / rings as f32);
+                start + (target - start) * t
+            })
+            .collect();
         Ok(())
     }
 
-    /// Write husk as [glTF] `.glb`
+    /// Get the length of the spine
     ///
-    /// ```rust,no_run
-    /// # use homunculus::{Error, Husk};
-    /// # use std::fs::File;
-    /// # fn main() -> Result<(), Error> {
-    /// let mut husk = Husk::new();
-    /// // add rings …
-    /// let file = File::create("husk.glb")?;
-    /// husk.write_gltf(file)?;
-    /// # Ok(())
-    /// # }
-    /// ```
+    /// This is the sum of distances between ring centers on the branch of
+    /// the most recently added ring, starting from the branch base.
+    pub fn spine_length(&self) -> f32 {
+        self.spine.last().map_or(0.0, |pos| pos.arclength)
+    }
+
+    /// Get the arc length of a ring along its branch
     ///
-    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
-    pub fn write_gltf<W: Write>(mut self, writer: W) -> Result<()> {
-        self.cap()?;
-        let mesh = self.builder.build();
-        gltf::export(writer, &mesh)?;
+    /// Rings are identified by the order they were added, starting at 0.
+    /// Arc length restarts at 0 for each branch base ring.
+    ///
+    /// # Panics
+    ///
+    /// - If `id` is not a valid ring
+    pub fn ring_arclength(&self, id: usize) -> f32 {
+        self.spine[id].arclength
+    }
+
+    /// Get the arc length of a ring's branch base along its parent branch
+    ///
+    /// Rings on the main (trunk) branch have an offset of 0.
+    ///
+    /// # Panics
+    ///
+    /// - If `id` is not a valid ring
+    pub fn ring_base_offset(&self, id: usize) -> f32 {
+        self.spine[id].base_offset
+    }
+
+    /// Jitter subsequently added rings
+    ///
+    /// Spoke distances are multiplied by a random factor within
+    /// `1 ± distance`, and the ring axis is tilted randomly within a cone of
+    /// `axis` radians around its nominal direction.  Tilts do not accumulate
+    /// from ring to ring, so the spine is not changed.  Random values are
+    /// derived from `seed` and the ring index, so results are reproducible.
+    /// Branch rings and caps are not affected.
+    ///
+    /// # Panics
+    ///
+    /// - If `distance` or `axis` is negative, infinite, or NaN
+    pub fn jitter(&mut self, settings: Jitter) {
+        assert!(settings.distance.is_finite());
+        assert!(settings.distance.is_sign_positive());
+        assert!(settings.axis.is_finite());
+        assert!(settings.axis.is_sign_positive());
+        self.jitter = settings;
+    }
+
+    /// Set random number seed for jitter
+    ///
+    /// This is the same seed as in [Husk::jitter], used for
+    /// [Ring::jitter] as well.
+    pub fn set_rng_seed(&mut self, seed: u64) {
+        self.jitter.seed = seed;
+    }
+
+    /// Set frame mode for subsequently added rings
+    ///
+    /// The default is [FrameMode::ParallelTransport].
+    pub fn set_frame_mode(&mut self, mode: FrameMode) {
+        self.frame_mode = mode;
+    }
+
+    /// Set crease angle (radians)
+    ///
+    /// When building the mesh, vertices are split wherever the angle
+    /// between adjacent faces exceeds this.  This augments creases from
+    /// [Shading] and sharp spokes.
+    ///
+    /// # Panics
+    ///
+    /// - If the angle is negative, infinite, or NaN
+    pub fn crease_angle(&mut self, angle: f32) {
+        assert!(angle.is_finite());
+        assert!(angle.is_sign_positive());
+        self.builder.set_crease_angle(angle);
+    }
+
+    /// Weld vertices closer than `epsilon` when building the mesh
+    ///
+    /// This removes coincident vertices, such as those of rings with the
+    /// same position.  Vertices are only welded if their texture coordinates,
+    /// colors and skeleton joints match, and vertices split for flat shading or sharp
+    /// creases are kept apart.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, on any error from [Husk::try_weld].  Otherwise, an
+    /// invalid `epsilon` is ignored with a warning.
+    pub fn weld(&mut self, epsilon: f32) {
+        if let Err(e) = self.try_weld(epsilon) {
+            ignore_invalid(e);
+        }
+    }
+
+    /// Weld vertices when building the mesh, checking for errors
+    ///
+    /// Returns [Error::InvalidWeld] if `epsilon` is not positive and finite.
+    pub fn try_weld(&mut self, epsilon: f32) -> Result<()> {
+        if !(epsilon > 0.0 && epsilon.is_finite()) {
+            return Err(Error::InvalidWeld(epsilon));
+        }
+        self.weld = Some(epsilon);
+        Ok(())
+    }
+
+    /// Mirror the mesh across a plane when building
+    ///
+    /// The mesh is merged with its reflection, for symmetric models where
+    /// only one half is defined.  See [Mesh::mirrored].
+    ///
+    /// [Mesh::mirrored]: struct.Mesh.html#method.mirrored
+    pub fn mirror(&mut self, plane: Plane) {
+        self.mirror = Some(plane);
+    }
+
+    /// Subdivide the mesh when building
+    ///
+    /// Each level splits every face into four, smoothing the surface.  See
+    /// [Mesh::subdivided].
+    ///
+    /// [Mesh::subdivided]: struct.Mesh.html#method.subdivided
+    pub fn subdivide(&mut self, levels: usize) {
+        self.subdivide = levels;
+    }
+
+    /// Fix winding order when building, if the mesh is inside out
+    ///
+    /// Rings with spokes in clockwise order make a mesh which is inside
+    /// out.  See [Mesh::is_inside_out].
+    ///
+    /// [Mesh::is_inside_out]: struct.Mesh.html#method.is_inside_out
+    pub fn fix_winding(&mut self) {
+        self.fix_winding = true;
+    }
+
+    /// Build as a hollow shell, with walls of a given thickness
+    ///
+    /// Open ends are joined to the inner surface by rim faces.  See
+    /// [Mesh::shelled].
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, on any error from [Husk::try_shell].  Otherwise, an
+    /// invalid `thickness` is ignored with a warning.
+    ///
+    /// [Mesh::shelled]: struct.Mesh.html#method.shelled
+    pub fn shell(&mut self, thickness: f32) {
+        if let Err(e) = self.try_shell(thickness) {
+            ignore_invalid(e);
+        }
+    }
+
+    /// Build as a hollow shell, checking for errors
+    ///
+    /// Returns [Error::InvalidThickness] if `thickness` is not positive and
+    /// finite.
+    pub fn try_shell(&mut self, thickness: f32) -> Result<()> {
+        if !(thickness > 0.0 && thickness.is_finite()) {
+            return Err(Error::InvalidThickness(thickness));
+        }
+        self.thickness = Some(thickness);
+        Ok(())
+    }
+
+    /// Build a skeleton for skinning, with a joint every `rings` rings
+    ///
+    /// Each branch has a chain of joints along its rings, starting at its
+    /// base ring, whose joint is a child of the joint of the parent ring.
+    /// The first ring has the root joint.  Joints are named for branch
+    /// labels, such as `arm`, `arm.1`, `arm.2`.  Each vertex is bound to the
+    /// joint of the ring which added it: the nearest joint at or before that
+    /// ring on its branch.  See [Mesh::skeleton].
+    ///
+    /// Mirrored vertices (see [Husk::mirror]) are bound to the same joints
+    /// as the originals.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, on any error from [Husk::try_skeleton].  Otherwise,
+    /// it is ignored with a warning.
+    ///
+    /// [Mesh::skeleton]: struct.Mesh.html#method.skeleton
+    pub fn skeleton(&mut self, rings: usize) {
+        if let Err(e) = self.try_skeleton(rings) {
+            ignore_invalid(e);
+        }
+    }
+
+    /// Build a skeleton for skinning, checking for errors
+    ///
+    /// Returns [Error::InvalidCount] if `rings` is zero.
+    pub fn try_skeleton(&mut self, rings: usize) -> Result<()> {
+        if rings == 0 {
+            return Err(Error::InvalidCount(rings));
+        }
+        self.joint_spacing = Some(rings);
+        Ok(())
+    }
+
+    /// Mark the most recently added ring as a skeleton joint
+    ///
+    /// This adds to joints from [Husk::skeleton], and also enables building
+    /// a skeleton without them.
+    pub fn joint(&mut self) {
+        if let Some(id) = self.spine.len().checked_sub(1) {
+            self.joint_rings.insert(id);
+        }
+    }
+
+    /// Set the name for export
+    ///
+    /// The glTF mesh, node and scene are given this name.
+    pub fn name(&mut self, name: impl Into<String>) {
+        self.name = Some(name.into());
+    }
+
+    /// Add a named anchor at the current ring
+    ///
+    /// The anchor records the ring center and orientation, with the Y axis
+    /// along the ring axis.  Without a current ring, it is at the origin.
+    /// Anchors are exported to glTF as nodes without meshes, which are
+    /// children of the mesh node.  They can be used for placing eyes or
+    /// props.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # use glam::Vec3;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+    /// husk.ring(Ring::default().axis(Vec3::new(0.0, 2.0, 0.0)))?;
+    /// husk.anchor("hat");
+    /// let (name, xform) = &husk.anchors()[0];
+    /// assert_eq!(name, "hat");
+    /// assert_eq!(xform.translation, Vec3::new(0.0, 2.0, 0.0).into());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn anchor(&mut self, name: impl Into<String>) {
+        let xform = match &self.ring {
+            Some(ring) => Affine3A {
+                matrix3: ring.xform().matrix3,
+                translation: ring.center().into(),
+            },
+            None => Affine3A::IDENTITY,
+        };
+        self.anchors.push((name.into(), xform));
+    }
+
+    /// Get named anchor transforms
+    ///
+    /// See [Husk::anchor].
+    pub fn anchors(&self) -> &[(String, Affine3A)] {
+        &self.anchors
+    }
+
+    /// Define a material for export
+    ///
+    /// A material with the same name as a ring material (see
+    /// [Ring::material]) is used for faces of those rings.  The first other
+    /// material is used for remaining faces.  A material defined again with
+    /// the same name replaces the previous definition.
+    ///
+    /// # Panics
+    ///
+    /// - If any color or factor is not within 0 to 1
+    ///
+    /// [Ring::material]: struct.Ring.html#method.material
+    pub fn material(&mut self, material: Material) {
+        assert!(material.is_valid());
+        match self
+            .materials
+            .iter_mut()
+            .find(|m| material.name.is_some() && m.name == material.name)
+        {
+            Some(m) => *m = material,
+            None => self.materials.push(material),
+        }
+    }
+
+    /// Get material definitions
+    pub fn materials(&self) -> &[Material] {
+        &self.materials[..]
+    }
+
+    /// Add a ring, with a cap facing backward
+    ///
+    /// This closes the start of a branch, such as the bottom of a goblet.
+    /// Normally, the first ring of a husk is left open.
+    pub fn ring_capped(&mut self, ring: Ring) -> Result<()> {
+        self.ring(ring)?;
+        // unwrap note: ring was just added
+        let ring = self.ring.clone().unwrap();
+        if self.ring_points.len() == 1 {
+            self.first = None;
+        }
+        self.cap_ring(&ring, true, 0.0)
+    }
+
+    /// Cap the current branch, facing forward
+    ///
+    /// The branch is ended, so the next ring starts a new piece.
+    pub fn cap_current(&mut self) -> Result<()> {
+        match self.ring.take() {
+            Some(ring) => self.cap_ring(&ring, false, 0.0),
+            None => Ok(()),
+        }
+    }
+
+    /// Set whether branches are capped when ended implicitly
+    ///
+    /// When `true` (the default), the current branch is capped by
+    /// [Husk::branch] and when exporting.  Otherwise, those branches are
+    /// left open, unless [Husk::cap_current] is called.
+    pub fn set_auto_cap(&mut self, auto_cap: bool) {
+        self.auto_cap = auto_cap;
+    }
+
+    /// End the current branch, with a cap if enabled
+    fn end_branch(&mut self) -> Result<()> {
+        if self.auto_cap {
+            self.cap_current()
+        } else {
+            self.ring = None;
+            Ok(())
+        }
+    }
+
+    /// Cap the current branch with a rounded dome
+    ///
+    /// The dome adds `rings` rings, shrinking along a quarter-circle
+    /// profile, before a final hub point.  Its height is the mean radius
+    /// of the current ring.  Dome rings inherit shading from the current
+    /// ring.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+    /// husk.ring(Ring::default())?;
+    /// husk.cap_dome(4)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cap_dome(&mut self, rings: usize) -> Result<()> {
+        let Some(ring) = self.ring.clone() else {
+            return Ok(());
+        };
+        let radius = ring.mean_radius(&self.builder);
+        let mut height = 0.0;
+        for i in 1..=rings {
+            let angle = FRAC_PI_2 * i as f32 / (rings + 1) as f32;
+            let (sin, cos) = angle.sin_cos();
+            let step = radius * sin - height;
+            self.ring(ring.dome_ring(radius, step, cos))?;
+            height = radius * sin;
+        }
+        match self.ring.take() {
+            Some(ring) => self.cap_ring(&ring, false, radius - height),
+            None => Ok(()),
+        }
+    }
+
+    /// Add a cap face on the given ring
+    ///
+    /// When `reverse` is set, the cap faces backward.  The hub is placed at
+    /// `height` along the ring axis.
+    fn cap_ring(
+        &mut self,
+        ring: &Ring,
+        reverse: bool,
+        height: f32,
+    ) -> Result<()> {
+        let mut pts = ring.points_offset(Degrees(0.0), 0);
+        // unwrap note: ring will always have at least one point
+        let last = pts.pop().unwrap();
+        if pts.len() < 2 {
+            return Ok(());
+        }
+        self.builder.set_material(ring.material_name());
+        let shading = ring.cap_shading_or_default();
+        if shading != Shading::Smooth {
+            // separate from surface of last band
+            self.surface += 1;
+        }
+        // add hub point
+        let (order, pos) = ring.make_hub(height);
+        let vid = self.builder.push_vtx(pos)?;
+        let id = self.spine.len().saturating_sub(1);
+        self.vertex_rings.resize(self.builder.len(), id);
+        let ring_v = pts.iter().find_map(|pt| match pt.pt {
+            Pt::Vertex(v) => self.builder.uv(v).map(|uv| uv.y),
+            Pt::Branch(..) => None,
+        });
+        if let Some(v) = ring_v {
+            self.builder.set_uv(vid, Vec2::new(0.5, v + height));
+        }
+        if let Some(color) = ring.vertex_color() {
+            self.builder.set_color(vid, color);
+        }
+        let hub = Point::new(Pt::Vertex(vid), order);
+        let start = self.builder.face_count();
+        let mut prev = last;
+        for pt in pts.drain(..) {
+            self.add_cap_face(&pt, &prev.trailing(), &hub, reverse)?;
+            prev = pt;
+            if shading == Shading::Flat {
+                self.surface += 1;
+            }
+        }
+        self.add_cap_face(&last, &prev.trailing(), &hub, reverse)?;
+        if shading == Shading::Flat {
+            self.surface += 1;
+        }
+        self.caps.push((start..self.builder.face_count(), vid));
+        Ok(())
+    }
+
+    /// Add a cap face, optionally reversed
+    fn add_cap_face(
+        &mut self,
+        pt0: &Point,
+        pt1: &Point,
+        hub: &Point,
+        reverse: bool,
+    ) -> Result<()> {
+        if reverse {
+            self.add_face([pt1, pt0, hub])
+        } else {
+            self.add_face([pt0, pt1, hub])
+        }
+    }
+
+    /// End the current branch and get the `label` branch
+    ///
+    /// The `label` must match one or more [Spoke]s from earlier rings.
+    /// Those spokes must be adjacent, or an [Error::InvalidBranches] is
+    /// returned.
+    ///
+    /// [spoke]: struct.Spoke.html
+    pub fn branch(&mut self, label: impl AsRef<str>) -> Result<Ring> {
+        self.end_branch()?;
+        let branch = self.take_branch(label.as_ref())?;
+        self.base = Some((label.as_ref().to_string(), branch.ring()));
+        self.base_offset = branch.offset();
+        Ok(Ring::with_branch(branch, &self.builder))
+    }
+
+    /// End the current branch and attach a part at the `label` branch
+    ///
+    /// The part is placed as if its rings were added after the branch base
+    /// ring, and its first ring is joined to the base with a band.  Its
+    /// current branch is ended, with a cap if enabled.  The part's rings
+    /// are treated as branching from the base, for arc lengths and texture
+    /// coordinates.  Anchors of the part are added, as are its material
+    /// definitions (unless already defined).  Build options of the part (such as
+    /// [Husk::subdivide]) are ignored.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # use glam::Vec3;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut hand = Husk::new();
+    /// hand.ring(Ring::default().spoke(0.3).spoke(0.3).spoke(0.3))?;
+    /// hand.ring(Ring::default().spoke(0.5).axis(Vec3::new(0.0, 0.5, 0.0)))?;
+    /// let mut husk = Husk::new();
+    /// let ring = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+    /// husk.ring(ring.clone().axis(Vec3::Y))?;
+    /// husk.ring(Ring::default().spoke(1.0).spoke("arm").spoke(1.0))?;
+    /// husk.ring(ring)?;
+    /// husk.attach("arm", hand)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [Error::InvalidPart] if the part's first ring is capped or has
+    ///   branch labels
+    /// - [Error::UnresolvedBranches] if the part has unused branch labels
+    /// - Any error from [Husk::branch]
+    pub fn attach(
+        &mut self,
+        label: impl AsRef<str>,
+        mut part: Husk,
+    ) -> Result<()> {
+        part.end_branch()?;
+        let Some(first) = part.first.take() else {
+            return Err(Error::InvalidPart("first ring is capped".into()));
+        };
+        if first.points().any(|pt| matches!(pt.pt, Pt::Branch(..))) {
+            return Err(Error::InvalidPart(
+                "first ring has branch labels".into(),
+            ));
+        }
+        let open = part.open_branches();
+        if !open.is_empty() {
+            let open = open.into_iter().map(String::from).collect();
+            return Err(Error::UnresolvedBranches(open));
+        }
+        let ring = self.branch(label)?;
+        self.ring(ring)?;
+        let rings = self.spine.len();
+        // unwrap note: ring was just added
+        let base = self.ring.take().unwrap();
+        let mut ring = base.with_ring(&first);
+        if self.frame_mode == FrameMode::Fixed {
+            ring.fix_frame();
+        }
+        let xform = ring.xform() * first.xform().inverse();
+        let offset = self.builder.len();
+        ring.copy_points(&first, offset);
+        let faces = self.builder.face_count();
+        let surface = self.surface;
+        self.builder.append(part.builder, xform, surface)?;
+        let shift = self.base_offset + base.center().distance(ring.center());
+        for vid in offset..self.builder.len() {
+            if let Some(uv) = self.builder.uv(vid) {
+                self.builder.set_uv(vid, uv + Vec2::new(0.0, shift));
+            }
+        }
+        self.spine
+            .extend(part.spine.into_iter().map(|pos| SpinePos {
+                center: xform.transform_point3(pos.center),
+                arclength: pos.arclength,
+                base_offset: pos.base_offset + shift,
+            }));
+        for (i, rp) in part.ring_points.into_iter().enumerate() {
+            let pos = rp.pos.iter().map(|p| xform.transform_point3(*p));
+            self.ring_points.push(RingPoints {
+                connected: rp.connected || i == 0,
+                pos: pos.collect(),
+            });
+        }
+        self.vertex_rings.resize(offset, rings - 1);
+        self.vertex_rings
+            .extend(part.vertex_rings.iter().map(|id| id + rings));
+        self.bases.extend(
+            part.bases
+                .into_iter()
+                .map(|(id, (label, pid))| (id + rings, (label, pid + rings))),
+        );
+        self.joint_rings
+            .extend(part.joint_rings.iter().map(|id| id + rings));
+        self.creases.extend(
+            part.creases
+                .into_iter()
+                .map(|(c, v)| (c + offset, v + offset)),
+        );
+        self.caps.extend(part.caps.into_iter().map(|(range, hub)| {
+            (range.start + faces..range.end + faces, hub + offset)
+        }));
+        self.anchors.extend(
+            part.anchors
+                .into_iter()
+                .map(|(name, anchor)| (name, xform * anchor)),
+        );
+        for material in part.materials {
+            if material.name.is_none()
+                || self.materials.iter().all(|m| m.name != material.name)
+            {
+                self.materials.push(material);
+            }
+        }
+        // share surface with part when smooth
+        let last = surface + part.surface;
+        if base.shading_or_default() != Shading::Smooth {
+            self.surface = last;
+        }
+        self.make_band(&base, &ring)?;
+        self.surface = self.surface.max(last);
+        Ok(())
+    }
+
+    /// Get labels of branches which have not been used
+    ///
+    /// These are labels of [Spoke]s from earlier rings, which have not been
+    /// passed to [Husk::branch].  They are listed in the order declared.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # use glam::Vec3;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// let ring = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+    /// husk.ring(ring.clone().axis(Vec3::Y))?;
+    /// husk.ring(Ring::default().spoke(1.0).spoke("arm").spoke(1.0))?;
+    /// husk.ring(ring)?;
+    /// assert_eq!(husk.open_branches(), ["arm"]);
+    /// husk.branch("arm")?;
+    /// assert!(husk.open_branches().is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [spoke]: struct.Spoke.html
+    pub fn open_branches(&self) -> Vec<&str> {
+        let mut ids: Vec<LabelId> = self.branches.keys().copied().collect();
+        ids.sort();
+        ids.into_iter().map(|id| self.labels.name(id)).collect()
+    }
+
+    /// Take a branch by label
+    fn take_branch(&mut self, label: &str) -> Result<Branch> {
+        let branch = self
+            .labels
+            .get(label)
+            .and_then(|id| self.branches.remove(&id))
+            .ok_or_else(|| Error::UnknownBranchLabel(label.to_string()))?;
+        let ring = branch.ring();
+        if branch.edges().count() < 3 {
+            return Err(Error::InvalidBranches(format!(
+                "label '{label}' has fewer than 3 base edges on ring {ring}"
+            )));
+        }
+        if !branch.is_connected() {
+            return Err(Error::InvalidBranches(format!(
+                "label '{label}' spans disconnected spokes on ring {ring}"
+            )));
+        }
+        if branch.axis(&self.builder).is_none() {
+            return Err(Error::InvalidBranches(format!(
+                "label '{label}' has a degenerate base on ring {ring}"
+            )));
+        }
+        Ok(branch)
+    }
+
+    /// Make a band of faces between two rings
+    fn make_band(&mut self, ring0: &Ring, ring1: &Ring) -> Result<()> {
+        self.builder.set_material(ring0.material_name());
+        let flat = ring0.shading_or_default() == Shading::Flat;
+        if ring0.shading_or_default() != Shading::Smooth {
+            self.surface += 1;
+        }
+        let start = self.surface;
+        // get points for each ring
+        let mut pts0 = ring0.points_offset(ring1.half_step(), 0);
+        let mut pts1 = ring1.points_offset(ring0.half_step(), 1);
+        // start with adjacent points (first1 just before first0), since
+        // orders wrap around at 360 degrees
+        // unwrap note: ring will always have at least one point
+        let order1 = pts1.last().unwrap().order;
+        let i0 = (0..pts0.len())
+            .min_by_key(|i| pts0[*i].order.behind(order1))
+            .unwrap();
+        let first0 = pts0.remove(i0);
+        let i1 = (0..pts1.len())
+            .min_by_key(|i| first0.order.behind(pts1[*i].order))
+            .unwrap();
+        let first1 = pts1.remove(i1);
+        let start1 = -first0.order.behind(first1.order).0;
+        let (mut pt0, mut pt1) = (first0, first1);
+        // unwrap orders, relative to first points
+        let mut band = Vec::with_capacity(pts0.len() + pts1.len());
+        for mut pt in pts0 {
+            pt.order = pt.order.behind(first0.order);
+            band.push(pt);
+        }
+        for mut pt in pts1 {
+            pt.order = Degrees(start1 + pt.order.behind(first1.order).0);
+            band.push(pt);
+        }
+        band.sort_by_key(|p| Reverse(p.order));
+        // create faces of band as a triangle strip
+        while let Some(pt) = band.pop() {
+            self.add_face([&pt1.trailing(), &pt0.trailing(), &pt])?;
+            if pt.ring == 0 {
+                pt0 = pt;
+            } else {
+                pt1 = pt;
+            }
+            if flat && !pt0.is_smooth() {
+                self.surface += 1;
+            }
+        }
+        // connect with first vertices on band
+        if pt1 != first1 {
+            self.add_face([&pt1.trailing(), &pt0.trailing(), &first1])?;
+            if flat && !pt0.is_smooth() {
+                self.surface += 1;
+            }
+        }
+        if pt0 != first0 {
+            let surface = self.surface;
+            if flat && first0.is_smooth() {
+                // share surface with first faces
+                self.surface = start;
+            }
+            self.add_face([&first0, &first1, &pt0.trailing()])?;
+            self.surface = surface;
+            if flat {
+                self.surface += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a triangle face
+    fn add_face(&mut self, pts: [&Point; 3]) -> Result<()> {
+        match (&pts[0].pt, &pts[1].pt, &pts[2].pt) {
+            (Pt::Vertex(v0), Pt::Vertex(v1), Pt::Vertex(v2)) => {
+                let face = Face::new([*v0, *v1, *v2], self.surface);
+                self.builder.push_face(face);
+            }
+            (Pt::Branch(lbl, _), Pt::Vertex(v0), Pt::Vertex(v1))
+            | (Pt::Vertex(v1), Pt::Branch(lbl, _), Pt::Vertex(v0))
+            | (Pt::Vertex(v0), Pt::Vertex(v1), Pt::Branch(lbl, _)) => {
+                self.push_branch_edge(*lbl, *v0, *v1);
+            }
+            (Pt::Vertex(_v), Pt::Branch(b0, _), Pt::Branch(b1, _))
+            | (Pt::Branch(b0, _), Pt::Vertex(_v), Pt::Branch(b1, _))
+            | (Pt::Branch(b0, _), Pt::Branch(b1, _), Pt::Vertex(_v)) => {
+                // A single vertex and two branch points:
+                // - both points must be for the same branch
+                // - no edges need to be added
+                if b0 != b1 {
+                    return Err(self.invalid_branches(*b0, *b1));
+                }
+            }
+            (Pt::Branch(b0, _), Pt::Branch(b1, _), Pt::Branch(b2, _)) => {
+                // Three adjacent branch points:
+                // - all points must be for the same branch
+                if b0 != b1 {
+                    return Err(self.invalid_branches(*b0, *b1));
+                }
+                if b0 != b2 {
+                    return Err(self.invalid_branches(*b0, *b2));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Make an invalid branches error
+    fn invalid_branches(&self, b0: LabelId, b1: LabelId) -> Error {
+        let (b0, b1) = (self.labels.name(b0), self.labels.name(b1));
+        Error::InvalidBranches(format!("{b0} != {b1}"))
+    }
+
+    /// Make a serializable snapshot of the husk
+    ///
+    /// This can be used to checkpoint construction, or send it elsewhere.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke("arm"))?;
+    /// let json = serde_json::to_string(&husk.to_snapshot()).unwrap();
+    /// let snapshot = serde_json::from_str(&json).unwrap();
+    /// let mut husk = Husk::from_snapshot(snapshot);
+    /// husk.ring(Ring::default().spoke(0.0))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn to_snapshot(&self) -> HuskSnapshot {
+        HuskSnapshot { husk: self.clone() }
+    }
+
+    /// Restore a husk from a snapshot
+    ///
+    /// See [Husk::to_snapshot].
+    #[cfg(feature = "serde")]
+    pub fn from_snapshot(snapshot: HuskSnapshot) -> Self {
+        snapshot.husk
+    }
+
+    /// Write husk as [glTF] `.glb`
+    ///
+    /// The husk is not changed, so it can be written again after adding
+    /// more rings.
+    ///
+    /// ```rust,no_run
+    /// # use homunculus::{Error, Husk};
+    /// # use std::fs::File;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// // add rings …
+    /// let file = File::create("husk.glb")?;
+    /// husk.write_gltf(file)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
+    pub fn write_gltf<W: Write>(&self, writer: W) -> Result<()> {
+        self.write_gltf_with(writer, &GltfOptions::default())
+    }
+
+    /// Get husk as [glTF] `.glb` bytes
+    ///
+    /// This is useful without a file system, such as in a web browser.  A
+    /// husk with no rings returns [Error::EmptyMesh].
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+    /// husk.ring(Ring::default().spoke(0.0))?;
+    /// let glb = husk.to_glb_bytes()?;
+    /// assert!(glb.starts_with(b"glTF"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
+    pub fn to_glb_bytes(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.write_gltf(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Write husk as [glTF] `.glb`, with export options
+    ///
+    /// ```rust,no_run
+    /// # use homunculus::{Error, GltfOptions, Husk};
+    /// # use serde_json::json;
+    /// # use std::fs::File;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// // add rings …
+    /// let options = GltfOptions {
+    ///     node_extras: Some(json!({ "collision": "convex" })),
+    ///     ..Default::default()
+    /// };
+    /// let file = File::create("husk.glb")?;
+    /// husk.write_gltf_with(file, &options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
+    pub fn write_gltf_with<W: Write>(
+        &self,
+        writer: W,
+        options: &GltfOptions,
+    ) -> Result<()> {
+        let mesh = self.to_mesh()?;
+        gltf::export(writer, &[self.mesh_node(&mesh)], options)?;
+        Ok(())
+    }
+
+    /// Write husk as [glTF] `.gltf` JSON, with an external `.bin` buffer
+    ///
+    /// The buffer is written to `bin_writer`, and `bin_uri` is its file
+    /// name, relative to the `.gltf` file.
+    ///
+    /// ```rust,no_run
+    /// # use homunculus::{Error, GltfOptions, Husk};
+    /// # use std::fs::File;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// // add rings …
+    /// let file = File::create("husk.gltf")?;
+    /// let bin = File::create("husk.bin")?;
+    /// husk.write_gltf_json(file, "husk.bin", bin, &GltfOptions::default())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
+    pub fn write_gltf_json<J: Write, B: Write>(
+        &self,
+        writer: J,
+        bin_uri: &str,
+        bin_writer: B,
+        options: &GltfOptions,
+    ) -> Result<()> {
+        let mesh = self.to_mesh()?;
+        let nodes = [self.mesh_node(&mesh)];
+        gltf::export_json(writer, bin_uri, bin_writer, &nodes, options)?;
+        Ok(())
+    }
+
+    /// Write husk as [glTF] `.gltf` JSON, with an embedded buffer
+    ///
+    /// The buffer is embedded as a base64 data URI, so the file is larger
+    /// than a `.glb`, but self-contained.
+    ///
+    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
+    pub fn write_gltf_embedded<W: Write>(
+        &self,
+        writer: W,
+        options: &GltfOptions,
+    ) -> Result<()> {
+        let mesh = self.to_mesh()?;
+        gltf::export_embedded(writer, &[self.mesh_node(&mesh)], options)?;
+        Ok(())
+    }
+
+    /// Write husk as [Wavefront OBJ] `.obj`
+    ///
+    /// Vertex positions, texture coordinates and normals are written, with
+    /// a smoothing group for each surface.  When the husk has a name, it is
+    /// used for the object and group.
+    ///
+    /// [Wavefront OBJ]: https://en.wikipedia.org/wiki/Wavefront_.obj_file
+    pub fn write_obj<W: Write>(&self, writer: W) -> Result<()> {
+        let mesh = self.to_mesh()?;
+        obj::export(writer, &[self.mesh_node(&mesh)])?;
+        Ok(())
+    }
+
+    /// Write husk as [PLY] `.ply`
+    ///
+    /// Vertex positions and normals are written, with colors if any rings
+    /// have them.
+    ///
+    /// [PLY]: https://en.wikipedia.org/wiki/PLY_(file_format)
+    pub fn write_ply<W: Write>(
+        &self,
+        writer: W,
+        format: PlyFormat,
+    ) -> Result<()> {
+        let mesh = self.to_mesh()?;
+        ply::export(writer, &[self.mesh_node(&mesh)], format)?;
+        Ok(())
+    }
+
+    /// Make a mesh node for export
+    fn mesh_node<'a>(&'a self, mesh: &'a Mesh) -> MeshNode<'a> {
+        MeshNode {
+            name: self.name.as_deref(),
+            materials: &self.materials,
+            anchors: &self.anchors,
+            ..MeshNode::new(mesh)
+        }
+    }
+
+    /// Write vertex normals of husk as [glTF] `.glb` line segments
+    ///
+    /// This is useful for checking shading in any glTF viewer.  Each segment
+    /// starts at a vertex and extends `length` along its normal.
+    ///
+    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
+    pub fn write_normals_gltf<W: Write>(
+        &self,
+        writer: W,
+        length: f32,
+    ) -> Result<()> {
+        let mesh = self.to_mesh()?;
+        let nodes = [MeshNode::new(&mesh)];
+        let options = GltfOptions::default();
+        gltf::export_normals_debug(writer, &nodes, length, &options)?;
+        Ok(())
+    }
+
+    /// Make convex hulls, each covering a span of rings
+    ///
+    /// Each hull covers `n` bands between rings of one branch (`n + 1`
+    /// rings), with adjacent hulls sharing a ring.  Spans which are flat are
+    /// skipped.  The result roughly follows limbs, for physics proxies.
+    ///
+    /// # Panics
+    ///
+    /// - If `n` is zero
+    pub fn hull_per_ring_segment(&self, n: usize) -> Vec<Mesh> {
+        assert!(n > 0);
+        let mut hulls = Vec::new();
+        // split rings into branches
+        let mut branches: Vec<Vec<&RingPoints>> = Vec::new();
+        for rp in &self.ring_points {
+            match branches.last_mut() {
+                Some(branch) if rp.connected => branch.push(rp),
+                _ => branches.push(vec![rp]),
+            }
+        }
+        for branch in branches {
+            let mut start = 0;
+            while start + 1 < branch.len() {
+                let end = (start + n).min(branch.len() - 1);
+                let pts: Vec<Vec3> = branch[start..=end]
+                    .iter()
+                    .flat_map(|rp| rp.pos.iter().copied())
+                    .collect();
+                let hull = hull::convex_hull(&pts);
+                if !hull.positions().is_empty() {
+                    hulls.push(hull);
+                }
+                start = end;
+            }
+        }
+        hulls
+    }
+
+    /// Make skeleton, with the joint of each ring
+    fn make_skeleton(&self) -> Option<(Skeleton, Vec<usize>)> {
+        if self.joint_spacing.is_none() && self.joint_rings.is_empty() {
+            return None;
+        }
+        let mut skeleton = Skeleton::default();
+        let mut ring_joints: Vec<usize> = Vec::with_capacity(self.spine.len());
+        let (mut start, mut count, mut pieces) = (0, 0, 0);
+        let mut name = String::new();
+        let rings = self.ring_points.iter().zip(&self.spine).enumerate();
+        for (id, (rp, pos)) in rings {
+            let joint = if id == 0 || !rp.connected {
+                (start, count) = (id, 0);
+                let parent = match self.bases.get(&id) {
+                    Some((label, pid)) => {
+                        name.clone_from(label);
+                        Some(ring_joints[*pid])
+                    }
+                    None if id == 0 => {
+                        name = "root".to_string();
+                        None
+                    }
+                    None => {
+                        pieces += 1;
+                        name = format!("piece{pieces}");
+                        Some(0)
+                    }
+                };
+                skeleton.push(name.clone(), parent, pos.center)
+            } else if self.joint_spacing.is_some_and(|n| (id - start) % n == 0)
+                || self.joint_rings.contains(&id)
+            {
+                count += 1;
+                let parent = Some(ring_joints[id - 1]);
+                skeleton.push(format!("{name}.{count}"), parent, pos.center)
+            } else {
+                ring_joints[id - 1]
+            };
+            ring_joints.push(joint);
+        }
+        Some((skeleton, ring_joints))
+    }
+
+    /// Build the mesh, without changing the husk
+    ///
+    /// A copy of the current branch is capped, so more rings can still be
+    /// added afterwards.
+    pub fn to_mesh(&self) -> Result<Mesh> {
+        self.clone().into_mesh()
+    }
+
+    /// End the current branch and build the mesh
+    ///
+    /// The branch is capped, unless disabled with [Husk::set_auto_cap].
+    /// All branch labels must have been used, or an
+    /// [Error::UnresolvedBranches] is returned (see [Husk::open_branches]).
+    ///
+    /// The mesh is identical to what [Husk::write_gltf] exports.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+    /// husk.ring(Ring::default().spoke(0.0))?;
+    /// let mesh = husk.into_mesh()?;
+    /// for (pos, norm) in mesh.positions().iter().zip(mesh.normals()) {
+    ///     println!("{pos} {norm}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_mesh(mut self) -> Result<Mesh> {
+        self.end_branch()?;
+        let open = self.open_branches();
+        if !open.is_empty() {
+            let open = open.into_iter().map(str::to_string).collect();
+            return Err(Error::UnresolvedBranches(open));
+        }
+        let skeleton = self.make_skeleton();
+        if let Some((_, ring_joints)) = &skeleton {
+            for vid in 0..self.builder.len() {
+                let id = self.vertex_rings.get(vid).copied().unwrap_or(0);
+                let joint = ring_joints.get(id).copied().unwrap_or(0);
+                // unwrap note: more joints than that is unreasonable
+                self.builder.set_joint(vid, u16::try_from(joint).unwrap());
+            }
+        }
+        self.finish_uvs()?;
+        if let Some(epsilon) = self.weld {
+            self.builder.weld(epsilon);
+        }
+        let mut mesh = self.builder.build()?;
+        mesh.skeleton = skeleton.map(|(skeleton, _)| skeleton);
+        if let Some(plane) = self.mirror {
+            mesh = mesh.mirrored(plane)?;
+        }
+        if self.subdivide > 0 {
+            mesh = mesh.subdivided(self.subdivide)?;
+        }
+        if self.fix_winding && mesh.is_inside_out() {
+            mesh.flip_winding();
+        }
+        if let Some(thickness) = self.thickness {
+            mesh = mesh.shelled(thickness)?;
+        }
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BuildReport, Spoke};
+    use glam::Vec4;
+    use std::collections::HashSet;
+
+    fn ring_scale(husk: &Husk) -> f32 {
+        husk.ring.as_ref().unwrap().scale_or_default()
+    }
+
+    #[test]
+    fn taper_linear() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        husk.taper(0.4, 4, Taper::Linear);
+        for scale in [0.85, 0.7, 0.55, 0.4, 0.4, 0.4] {
+            husk.ring(Ring::default())?;
+            assert!((ring_scale(&husk) - scale).abs() < 1e-6);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn taper_profiles() -> Result<()> {
+        for profile in [Taper::EaseIn, Taper::EaseOut, Taper::Cosine] {
+            let mut husk = Husk::new();
+            husk.ring(Ring::default().scale(2.0).spoke(1.0).spoke(1.0))?;
+            husk.taper(1.0, 10, profile);
+            for i in 1..=10 {
+                husk.ring(Ring::default())?;
+                let scale = 2.0 - profile.eval(i as f32 / 10.0);
+                assert!((ring_scale(&husk) - scale).abs() < 1e-6);
+            }
+        }
+        Ok(())
+    }
+
+    fn jitter_glb(jitter: Jitter) -> Result<Vec<u8>> {
+        let mut husk = Husk::new();
+        husk.jitter(jitter);
+        husk.ring(Ring::default().spoke(1.0).spoke("b").spoke(1.0))?;
+        for _ in 0..4 {
+            husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        }
+        let ring = husk.branch("b")?;
+        husk.ring(ring.axis(Vec3::new(0.0, 0.0, 1.0)))?;
+        let mut buf = Vec::new();
+        husk.write_gltf(&mut buf)?;
+        Ok(buf)
+    }
+
+    #[test]
+    fn unresolved_branches() -> Result<()> {
+        let mut husk = Husk::new();
+        let ring = Ring::default().spoke(1.0).spoke("armL").spoke(1.0);
+        husk.ring(ring.spoke("armR"))?;
+        husk.ring(Ring::default().axis(Vec3::Y))?;
+        let arm = husk.branch("armR")?;
+        husk.ring(arm.axis(Vec3::X))?;
+        assert_eq!(husk.open_branches(), ["armL"]);
+        let err = husk.to_mesh().err().unwrap();
+        assert!(matches!(&err, Error::UnresolvedBranches(l) if l == &["armL"]));
+        assert_eq!(err.to_string(), "Unresolved branches: armL");
+        let arm = husk.branch("armL")?;
+        husk.ring(arm.axis(Vec3::NEG_X))?;
+        assert!(husk.open_branches().is_empty());
+        husk.into_mesh()?;
+        Ok(())
+    }
+
+    #[test]
+    fn attach() -> Result<()> {
+        let ring = (0..8).fold(Ring::default(), |r, _| r.spoke(1.0));
+        let arm = (0..8).fold(Ring::default(), |r, i| match i {
+            0 | 1 => r.spoke("arm"),
+            _ => r.spoke(1.0),
+        });
+        let mut husk = Husk::new();
+        husk.ring_capped(ring.clone())?;
+        husk.ring(arm.clone().axis(Vec3::Y))?;
+        husk.ring(ring.clone())?;
+        let mut part = Husk::new();
+        let cylinder = (0..6).fold(Ring::default(), |r, _| r.spoke(0.3));
+        part.ring(cylinder)?;
+        part.ring(Ring::default())?;
+        part.ring(Ring::default())?;
+        part.anchor("hand");
+        husk.attach("arm", part)?;
+        assert!(husk.open_branches().is_empty());
+        let (name, hand) = &husk.anchors()[0];
+        assert_eq!(name, "hand");
+        let tip = husk.ring_points.last().unwrap().pos.iter().sum::<Vec3>();
+        assert!(Vec3::from(hand.translation).distance(tip / 6.0) < 1e-5);
+        assert_eq!(husk.ring_points.len(), 7);
+        let mesh = husk.into_mesh()?;
+        assert!(mesh.is_watertight());
+        let reach = mesh
+            .positions()
+            .iter()
+            .map(|p| Vec2::new(p.x, p.z).length())
+            .fold(0.0, f32::max);
+        assert!(reach > 2.5, "{reach}");
+        // capped first ring
+        let mut husk = Husk::new();
+        husk.ring(ring.clone())?;
+        husk.ring(arm.axis(Vec3::Y))?;
+        husk.ring(ring.clone())?;
+        let mut part = Husk::new();
+        part.ring_capped(ring)?;
+        part.ring(Ring::default())?;
+        let err = husk.attach("arm", part).err().unwrap();
+        assert!(matches!(err, Error::InvalidPart(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn skeleton() -> Result<()> {
+        let ring = (0..8).fold(Ring::default(), |r, _| r.spoke(1.0));
+        let arm = (0..8).fold(Ring::default(), |r, i| match i {
+            0 | 1 => r.spoke("arm"),
+            _ => r.spoke(1.0),
+        });
+        let mut husk = Husk::new();
+        husk.skeleton(2);
+        husk.ring(ring.clone())?;
+        husk.ring(arm.axis(Vec3::Y))?;
+        for _ in 0..3 {
+            husk.ring(ring.clone())?;
+        }
+        let base = husk.branch("arm")?;
+        husk.ring(base.axis(Vec3::X))?;
+        husk.ring(Ring::default())?;
+        husk.joint();
+        husk.ring(Ring::default())?;
+        let tip = husk.ring_points.last().unwrap().pos.clone();
+        let mesh = husk.into_mesh()?;
+        let skeleton = mesh.skeleton().unwrap();
+        let joints: Vec<_> = skeleton
+            .joints()
+            .iter()
+            .map(|j| (j.name.as_str(), j.parent))
+            .collect();
+        assert_eq!(
+            joints,
+            [
+                ("root", None),
+                ("root.1", Some(0)),
+                ("root.2", Some(1)),
+                ("arm", Some(0)),
+                ("arm.1", Some(3)),
+                ("arm.2", Some(4)),
+            ]
+        );
+        assert_eq!(skeleton.joints()[1].pos, Vec3::new(0.0, 2.0, 0.0));
+        assert_eq!(mesh.joints().len(), mesh.positions().len());
+        // vertices of last arm ring are bound to last joint
+        let mut count = 0;
+        for (pos, joint) in mesh.positions().iter().zip(mesh.joints()) {
+            if tip.iter().any(|p| p.distance(*pos) < 1e-5) {
+                assert_eq!(*joint, 5);
+                count += 1;
+            }
+        }
+        assert!(count >= tip.len());
+        Ok(())
+    }
+
+    #[test]
+    fn skeleton_weld() -> Result<()> {
+        let ring = (0..4).fold(Ring::default(), |r, _| r.spoke(1.0));
+        let mut husk = Husk::new();
+        husk.skeleton(2);
+        husk.weld(1e-4);
+        husk.ring(ring)?;
+        husk.ring(Ring::default().axis(Vec3::ZERO))?;
+        for _ in 0..3 {
+            husk.ring(Ring::default().axis(Vec3::Y))?;
+        }
+        let mut unwelded = husk.clone();
+        unwelded.weld = None;
+        let unwelded = unwelded.into_mesh()?;
+        let mesh = husk.into_mesh()?;
+        assert!(mesh.positions().len() < unwelded.positions().len());
+        assert_eq!(mesh.joints().len(), mesh.positions().len());
+        // each vertex is bound to the joint of its own ring
+        for (pos, joint) in mesh.positions().iter().zip(mesh.joints()) {
+            let expected = match pos.y {
+                y if y < 1.0 => 0,
+                y if y < 3.0 => 1,
+                _ => 2,
+            };
+            assert_eq!(*joint, expected, "{pos}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn no_skeleton() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default().axis(Vec3::Y))?;
+        let mesh = husk.into_mesh()?;
+        assert!(mesh.skeleton().is_none());
+        assert!(mesh.joints().is_empty());
+        Ok(())
+    }
+
+    /// Build a husk with "arm" labels on two spokes, returning the branch
+    fn two_arms(first: usize, second: usize) -> Result<Ring> {
+        let mut husk = Husk::new();
+        let ring = (0..8).fold(Ring::default(), |r, _| r.spoke(1.0));
+        husk.ring(ring.clone().axis(Vec3::Y))?;
+        let arms = (0..8).fold(Ring::default(), |r, i| {
+            if i == first || i == second {
+                r.spoke("arm")
+            } else {
+                r.spoke(1.0)
+            }
+        });
+        husk.ring(arms)?;
+        husk.ring(ring)?;
+        husk.branch("arm")
+    }
+
+    #[test]
+    fn disconnected_branch() -> Result<()> {
+        two_arms(1, 2)?;
+        two_arms(7, 0)?;
+        match two_arms(1, 6) {
+            Err(Error::InvalidBranches(msg)) => assert_eq!(
+                msg,
+                "label 'arm' spans disconnected spokes on ring 1"
+            ),
+            _ => panic!("split label not detected"),
+        }
+        Ok(())
+    }
+
+    /// Check that a branch is invalid, and the mesh has no NaN positions
+    fn check_invalid_branch(mut husk: Husk, label: &str) -> Result<()> {
+        assert!(matches!(husk.branch(label), Err(Error::InvalidBranches(_))));
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default().axis(Vec3::Y))?;
+        let mesh = husk.into_mesh()?;
+        assert!(mesh.positions().iter().all(|p| p.is_finite()));
+        Ok(())
+    }
+
+    /// Make a husk with a branch containing the given edges
+    fn edge_branch(pos: &[Vec3], edges: &[(usize, usize)]) -> Result<Husk> {
+        let mut husk = Husk::new();
+        let id = husk.labels.intern("x");
+        let mut branch = Branch::default();
+        for p in pos {
+            husk.builder.push_vtx(*p)?;
+            branch.push_internal(*p);
+        }
+        for (v0, v1) in edges {
+            branch.push_edge(*v0, *v1);
+        }
+        husk.branches.insert(id, branch);
+        Ok(husk)
+    }
+
+    #[test]
+    fn degenerate_branches() -> Result<()> {
+        // label on a single ring has no edges
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke("x").spoke(1.0))?;
+        check_invalid_branch(husk, "x")?;
+        let pos = [Vec3::ZERO, Vec3::X, Vec3::X * 2.0, Vec3::Z];
+        check_invalid_branch(edge_branch(&pos, &[(0, 1)])?, "x")?;
+        // collinear edges
+        let edges = [(0, 1), (1, 2), (2, 0)];
+        check_invalid_branch(edge_branch(&pos[..3], &edges)?, "x")?;
+        // valid triangle base
+        let edges = [(0, 1), (1, 3), (3, 0)];
+        let mut husk = edge_branch(&pos, &edges)?;
+        let ring = husk.branch("x")?;
+        husk.ring(ring.axis(Vec3::Y))?;
+        Ok(())
+    }
+
+    /// Build a husk with an "arm" branch, returning the base ring radius
+    fn branch_scaled(scale: impl Fn(Ring) -> Ring) -> Result<(Husk, f32)> {
+        let mut husk = Husk::new();
+        let ring = (0..8).fold(Ring::default(), |r, _| r.spoke(1.0));
+        husk.ring(ring.clone().axis(Vec3::Y))?;
+        let arm = (0..8).fold(Ring::default(), |r, i| match i {
+            0 | 1 => r.spoke("arm"),
+            _ => r.spoke(1.0),
+        });
+        husk.ring(arm)?;
+        husk.ring(ring)?;
+        let base = scale(husk.branch("arm")?.axis(Vec3::X * 0.5));
+        let center = base.center();
+        let radius = base
+            .base_points()
+            .iter()
+            .map(|p| p.distance(center))
+            .sum::<f32>()
+            / base.base_point_count() as f32;
+        husk.ring(base)?;
+        husk.ring(Ring::default())?;
+        Ok((husk, radius))
+    }
+
+    /// Get distances of last ring points from their center
+    fn last_ring_radii(husk: &Husk) -> Vec<f32> {
+        let pos = &husk.ring_points.last().unwrap().pos;
+        let center = pos.iter().sum::<Vec3>() / pos.len() as f32;
+        pos.iter().map(|p| p.distance(center)).collect()
+    }
+
+    #[test]
+    fn branch_scale() -> Result<()> {
+        let (husk, radius) = branch_scaled(|base| base.scale(2.0))?;
+        // first band doubles in radius
+        for r in last_ring_radii(&husk) {
+            assert!((r - radius * 2.0).abs() < 1e-4, "{r} != {}", radius * 2.0);
+        }
+        assert!(husk.to_mesh()?.positions().iter().all(|p| p.is_finite()));
+        // scaling X and Z is also deferred to the first band
+        let (husk, radius) = branch_scaled(|base| base.scale_xz(2.0, 0.5))?;
+        let radii = last_ring_radii(&husk);
+        let max = radii.iter().copied().fold(0.0, f32::max);
+        let min = radii.iter().copied().fold(f32::MAX, f32::min);
+        assert!((max - radius * 2.0).abs() < 1e-4, "{max}");
+        assert!((min - radius * 0.5).abs() < 1e-4, "{min}");
+        assert!(husk.to_mesh()?.positions().iter().all(|p| p.is_finite()));
+        Ok(())
+    }
+
+    #[test]
+    fn jitter_seeded() -> Result<()> {
+        let jitter = Jitter {
+            distance: 0.1,
+            axis: 0.2,
+            seed: 1234,
+        };
+        assert_eq!(jitter_glb(jitter)?, jitter_glb(jitter)?);
+        let other = Jitter {
+            seed: 4321,
+            ..jitter
+        };
+        assert_ne!(jitter_glb(jitter)?, jitter_glb(other)?);
+        Ok(())
+    }
+
+    #[test]
+    fn jitter_tilt_cone() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.jitter(Jitter {
+            distance: 0.0,
+            axis: 0.2,
+            seed: 99,
+        });
+        husk.ring((0..8).fold(Ring::default(), |r, _| r.spoke(1.0)))?;
+        for i in 1..=40 {
+            husk.ring(Ring::default().axis(Vec3::Y))?;
+            let xform = husk.ring.as_ref().unwrap().xform();
+            let axis = Vec3::from(xform.matrix3.y_axis);
+            let angle = axis.angle_between(Vec3::Y);
+            assert!(angle <= 0.2 + 1e-5, "{angle}");
+            let center = Vec3::from(xform.translation);
+            assert!(center.distance(Vec3::Y * i as f32) < 1e-4, "{center}");
+        }
+        Ok(())
+    }
+
+    /// Build a husk with ring jitter, returning GLB
+    fn ring_jitter_glb(seed: u64) -> Result<Vec<u8>> {
+        let mut husk = Husk::new();
+        husk.set_rng_seed(seed);
+        let ring = Ring::default().jitter(0.2);
+        husk.ring((0..8).fold(ring, |r, _| r.spoke(1.0)))?;
+        for _ in 0..8 {
+            husk.ring(Ring::default().axis(Vec3::Y))?;
+        }
+        let mut buf = Vec::new();
+        husk.write_gltf(&mut buf)?;
+        Ok(buf)
+    }
+
+    #[test]
+    fn ring_jitter() -> Result<()> {
+        assert_eq!(ring_jitter_glb(1234)?, ring_jitter_glb(1234)?);
+        assert_ne!(ring_jitter_glb(1234)?, ring_jitter_glb(4321)?);
+        // jitter is inherited, within range
+        let mut husk = Husk::new();
+        let ring = Ring::default().jitter(0.1);
+        husk.ring((0..8).fold(ring, |r, _| r.spoke(1.0)))?;
+        husk.ring(Ring::default().axis(Vec3::Y))?;
+        let pos = &husk.ring_points[1].pos;
+        assert!(pos.iter().any(|p| (p.x.hypot(p.z) - 1.0).abs() > 1e-3));
+        for p in pos {
+            let r = p.x.hypot(p.z);
+            assert!((0.9..=1.1).contains(&r), "{r}");
+        }
+        // ring jitter overrides husk jitter
+        husk.jitter(Jitter {
+            distance: 0.5,
+            ..Default::default()
+        });
+        husk.ring(Ring::default().jitter(0.0))?;
+        for p in &husk.ring_points[2].pos {
+            assert!((p.x.hypot(p.z) - 1.0).abs() < 1e-6);
+        }
+        assert!(matches!(
+            Ring::default().try_jitter(-0.1),
+            Err(Error::InvalidJitter(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn jitter_zero() -> Result<()> {
+        let zero = Jitter {
+            seed: 99,
+            ..Default::default()
+        };
+        assert_eq!(jitter_glb(zero)?, jitter_glb(Jitter::default())?);
+        Ok(())
+    }
+
+    #[test]
+    fn spine_straight() -> Result<()> {
+        let mut husk = Husk::new();
+        let ring = Ring::default().axis(Vec3::new(0.0, 0.5, 0.0));
+        husk.ring(ring.spoke(1.0).spoke(1.0).spoke(1.0))?;
+        for _ in 1..10 {
+            husk.ring(Ring::default())?;
+        }
+        assert!((husk.spine_length() - 4.5).abs() < 1e-6);
+        assert_eq!(husk.ring_arclength(0), 0.0);
+        assert!((husk.ring_arclength(4) - 2.0).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn spine_branch() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default().spoke(1.0).spoke("a").spoke(1.0))?;
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default())?;
+        assert!((husk.spine_length() - 3.0).abs() < 1e-6);
+        let ring = husk.branch("a")?;
+        husk.ring(ring)?;
+        husk.ring(Ring::default().spoke(0.5))?;
+        assert_eq!(husk.ring_arclength(4), 0.0);
+        assert_eq!(husk.ring_base_offset(4), 1.0);
+        assert_eq!(husk.ring_base_offset(5), 1.0);
+        assert_eq!(husk.ring_base_offset(0), 0.0);
+        assert!(husk.spine_length() > 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn spine_branch_on_branch() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default().spoke(1.0).spoke("a").spoke(1.0))?;
+        husk.ring(Ring::default())?;
+        let ring = husk.branch("a")?;
+        husk.ring(ring)?;
+        husk.ring(Ring::default().spoke(0.5).spoke("b").spoke(0.5))?;
+        husk.ring(Ring::default())?;
+        assert_eq!(husk.ring_base_offset(4), 1.0);
+        let offset = 1.0 + husk.ring_arclength(4);
+        let ring = husk.branch("b")?;
+        husk.ring(ring)?;
+        husk.ring(Ring::default().spoke(0.25))?;
+        assert!((husk.ring_base_offset(6) - offset).abs() < 1e-6);
+        assert!((husk.ring_base_offset(7) - offset).abs() < 1e-6);
+        Ok(())
+    }
+
+    /// Make a mesh from a stack of rings along an axis
+    fn axis_mesh(axis: Vec3) -> Result<Mesh> {
+        let mut husk = Husk::new();
+        let ring = Ring::default().axis(axis);
+        husk.ring(ring.spoke(1.0).spoke(1.0).spoke(1.0).spoke(1.0))?;
+        for _ in 0..3 {
+            husk.ring(Ring::default())?;
+        }
+        husk.into_mesh()
+    }
+
+    #[test]
+    fn axis_down() -> Result<()> {
+        let mut husk = Husk::new();
+        let ring = Ring::default().axis(Vec3::new(0.0, -1.0, 0.0));
+        husk.ring(ring.spoke(1.0).spoke(1.0).spoke(1.0))?;
+        let first = husk.current_ring().unwrap().center();
+        for _ in 0..3 {
+            husk.ring(Ring::default())?;
+        }
+        let last = husk.current_ring().unwrap().center();
+        assert!(last.distance(first + Vec3::new(0.0, -3.0, 0.0)) < 1e-5);
+        let mesh = husk.into_mesh()?;
+        assert!(mesh.pos_max().y <= first.y + 1e-5);
+        Ok(())
+    }
+
+    /// Get spoke 0 direction of the current ring
+    fn spoke0_dir(husk: &Husk) -> Vec3 {
+        let ring = husk.ring.as_ref().unwrap();
+        let Pt::Vertex(vid) = ring.points().next().unwrap().pt else {
+            panic!("not a vertex");
+        };
+        (husk.builder.vertex(vid) - ring.center()).normalize()
+    }
+
+    /// Make a helix of rings, collecting spoke 0 directions
+    fn helix(mode: FrameMode) -> Result<Vec<Vec3>> {
+        let mut husk = Husk::new();
+        husk.set_frame_mode(mode);
+        let mut dirs = Vec::new();
+        let mut ring = Ring::default();
+        for _ in 0..8 {
+            ring = ring.spoke(1.0);
+        }
+        husk.ring(ring)?;
+        dirs.push(spoke0_dir(&husk));
+        for i in 0..80 {
+            let a = i as f32 * 0.4;
+            let axis = Vec3::new(a.cos() * 0.3, 1.0, a.sin() * 0.3);
+            husk.ring(Ring::default().axis(axis))?;
+            dirs.push(spoke0_dir(&husk));
+        }
+        Ok(dirs)
+    }
+
+    #[test]
+    fn frame_parallel_transport() -> Result<()> {
+        let dirs = helix(FrameMode::ParallelTransport)?;
+        for pair in dirs.windows(2) {
+            let angle = pair[0].angle_between(pair[1]);
+            assert!(angle < 0.35, "{angle}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn frame_fixed() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.set_frame_mode(FrameMode::Fixed);
+        let ring = Ring::default().axis(Vec3::new(0.0, 1.0, 1.0));
+        husk.ring(ring.spoke(1.0).spoke(1.0).spoke(1.0))?;
+        assert!(spoke0_dir(&husk).distance(Vec3::X) < 1e-5);
+        husk.ring(Ring::default().axis(Vec3::new(1.0, 0.0, 1.0)))?;
+        let up = husk.spine[1].center - husk.spine[0].center;
+        let dir = spoke0_dir(&husk);
+        assert!(dir.dot(up).abs() < 1e-5);
+        assert!(dir.dot(Vec3::X.cross(up)).abs() < 1e-5);
+        assert!(dir.x > 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn axis_mirror() -> Result<()> {
+        let right = axis_mesh(Vec3::X)?;
+        let left = axis_mesh(Vec3::NEG_X)?;
+        assert!(right.pos_min().x > -1e-5);
+        assert!(left.pos_max().x < 1e-5);
+        for pos in right.positions() {
+            let mirror = Vec3::new(-pos.x, pos.y, pos.z);
+            assert!(
+                left.positions().iter().any(|p| p.distance(mirror) < 1e-5),
+                "{pos}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn branch_base() -> Result<()> {
+        let ring = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        assert!(!ring.is_branch_base());
+        assert_eq!(ring.base_point_count(), 0);
+        let mut husk = Husk::new();
+        husk.ring(ring)?;
+        husk.ring(Ring::default().spoke(1.0).spoke("a").spoke(1.0))?;
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        let ring = husk.branch("a")?;
+        assert!(ring.is_branch_base());
+        assert_eq!(ring.base_point_count(), 6);
+        for y in [0.0, 1.0, 2.0] {
+            let level = ring
+                .base_points()
+                .iter()
+                .filter(|p| (p.y - y).abs() < 1e-6)
+                .inspect(|p| assert!((p.x.hypot(p.z) - 1.0).abs() < 1e-6))
+                .count();
+            assert_eq!(level, 2);
+        }
+        // base ring can still be added
+        husk.ring(ring)?;
+        Ok(())
+    }
+
+    #[test]
+    fn current_ring() -> Result<()> {
+        let mut husk = Husk::new();
+        assert!(husk.current_ring().is_none());
+        let ring = Ring::default()
+            .axis(Vec3::new(0.0, 2.0, 0.0))
+            .scale(0.5)
+            .shading(Shading::Flat)
+            .spoke(1.0)
+            .spoke(1.0)
+            .spoke("a")
+            .spoke(1.0);
+        husk.ring(ring)?;
+        husk.ring(Ring::default())?;
+        let view = husk.current_ring().unwrap();
+        assert_eq!(view.scale(), 0.5);
+        assert_eq!(view.spacing(), 2.0);
+        assert_eq!(view.shading(), Shading::Flat);
+        assert_eq!(view.spoke_count(), 4);
+        assert!(view.center().distance(Vec3::new(0.0, 2.0, 0.0)) < 1e-6);
+        husk.ring(Ring::default().scale(0.25).spoke(1.0).spoke(1.0))?;
+        let view = husk.current_ring().unwrap();
+        assert_eq!(view.scale(), 0.25);
+        assert_eq!(view.spoke_count(), 2);
+        assert!(view.center().distance(Vec3::new(0.0, 4.0, 0.0)) < 1e-6);
+        // branching caps the current ring
+        let ring = husk.branch("a")?;
+        assert!(husk.current_ring().is_none());
+        let count = ring.base_point_count();
+        husk.ring(ring)?;
+        let view = husk.current_ring().unwrap();
+        assert_eq!(view.spoke_count(), count);
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn snapshot_round_trip() -> Result<()> {
+        let mut husk = Husk::new();
+        let ring = Ring::default()
+            .shading(Shading::Flat)
+            .spoke(1.0)
+            .spoke(1.0)
+            .spoke("arm")
+            .spoke(1.0);
+        husk.ring(ring)?;
+        husk.ring(Ring::default().axis(Vec3::new(0.0, 2.0, 0.0)))?;
+        husk.anchor("top");
+        let json = serde_json::to_string(&husk.to_snapshot()).unwrap();
+        let snapshot = serde_json::from_str(&json).unwrap();
+        let mut restored = Husk::from_snapshot(snapshot);
+        for husk in [&mut husk, &mut restored] {
+            husk.ring(Ring::default().spoke(0.0))?;
+            let arm = husk.branch("arm")?;
+            husk.ring(arm)?;
+            husk.ring(Ring::default().scale(0.5))?;
+        }
+        let (mut glb0, mut glb1) = (Vec::new(), Vec::new());
+        husk.write_gltf(&mut glb0)?;
+        restored.write_gltf(&mut glb1)?;
+        assert_eq!(glb0, glb1);
+        Ok(())
+    }
+
+    #[test]
+    fn export_continue() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default())?;
+        let (mut glb0, mut glb1) = (Vec::new(), Vec::new());
+        husk.write_gltf(&mut glb0)?;
+        husk.write_gltf(&mut glb1)?;
+        assert_eq!(glb0, glb1);
+        let faces = husk.to_mesh()?.indices().len();
+        husk.ring(Ring::default())?;
+        let mut glb2 = Vec::new();
+        husk.write_gltf(&mut glb2)?;
+        assert!(glb2.len() > glb0.len());
+        assert_eq!(husk.to_mesh()?.indices().len(), faces + 6 * 3);
+        Ok(())
+    }
+
+    #[test]
+    fn mesh_matches_gltf() -> Result<()> {
+        let mut husk = Husk::new();
+        let base = Ring::default()
+            .shading(Shading::Flat)
+            .spoke(1.0)
+            .spoke(1.0)
+            .spoke(1.0)
+            .spoke(1.0);
+        husk.ring(base)?;
+        husk.ring(Ring::default().spoke(0.0))?;
+        let mut glb = Vec::new();
+        husk.write_gltf(&mut glb)?;
+        let import = crate::import_glb(&glb[..])?;
+        let mesh = husk.into_mesh()?;
+        let gltf = &import.meshes[0];
+        assert_eq!(mesh.positions().len(), gltf.positions().len());
+        assert_eq!(mesh.indices(), gltf.indices());
+        assert_eq!(mesh.positions(), gltf.positions());
+        // importer renormalizes normals
+        for (n0, n1) in mesh.normals().iter().zip(gltf.normals()) {
+            assert!(n0.distance(*n1) < 1e-6);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn explicit_angles() -> Result<()> {
+        use crate::Spoke;
+        let mut husk = Husk::new();
+        let ring = Ring::default()
+            .spoke(Spoke::from(2.0).with_angle(0.0))
+            .spoke(Spoke::from(1.0).with_angle(PI * 0.5))
+            .spoke(Spoke::from(1.0).with_angle(PI));
+        husk.ring(ring)?;
+        let v0 = husk.builder.vertex(0);
+        let v1 = husk.builder.vertex(1);
+        let v2 = husk.builder.vertex(2);
+        assert!(v0.distance(Vec3::new(2.0, 0.0, 0.0)) < 1e-6);
+        assert!(v1.distance(Vec3::new(0.0, 0.0, -1.0)) < 1e-6);
+        assert!(v2.distance(Vec3::new(-1.0, 0.0, 0.0)) < 1e-6);
+        Ok(())
+    }
+
+    fn sharp_mesh(shading: Shading, sharp: Option<bool>) -> Result<Mesh> {
+        use crate::Spoke;
+        let mut spoke = Spoke::from(1.0);
+        spoke.sharp = sharp;
+        let mut husk = Husk::new();
+        let ring = Ring::default().shading(shading).spoke(1.0).spoke(spoke);
+        husk.ring(ring.spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default())?;
+        husk.ring(Ring::default())?;
+        husk.builder.build()
+    }
+
+    #[test]
+    fn sharp_crease() -> Result<()> {
+        let mesh = sharp_mesh(Shading::Smooth, None)?;
+        assert_eq!(mesh.positions().len(), 12);
+        let mesh = sharp_mesh(Shading::Smooth, Some(true))?;
+        assert_eq!(mesh.positions().len(), 15);
+        for vid in 0..15 {
+            assert!(mesh.indices().iter().any(|v| v.0 as usize == vid));
+        }
+        // crease vertex on middle ring
+        assert_eq!(mesh.positions()[6], mesh.positions()[7]);
+        assert!(mesh.normals()[6].dot(mesh.normals()[7]) < 0.9);
+        Ok(())
+    }
+
+    /// Make a mesh with two coincident rings
+    fn coincident_mesh(weld: Option<f32>, sharp: Option<bool>) -> Result<Mesh> {
+        use crate::Spoke;
+        let mut spoke = Spoke::from(1.0);
+        spoke.sharp = sharp;
+        let mut husk = Husk::new();
+        if let Some(epsilon) = weld {
+            husk.weld(epsilon);
+        }
+        let ring = Ring::default().spoke(1.0).spoke(spoke);
+        husk.ring(ring.spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default().axis(Vec3::ZERO))?;
+        husk.ring(Ring::default().axis(Vec3::Y))?;
+        husk.into_mesh()
+    }
+
+    #[test]
+    fn weld_coincident() -> Result<()> {
+        let mesh = coincident_mesh(None, None)?;
+        let welded = coincident_mesh(Some(1e-4), None)?;
+        // one ring of 4 vertices removed, with its UV seam duplicate
+        assert_eq!(welded.positions().len() + 5, mesh.positions().len());
+        // band between coincident rings is degenerate either way
+        assert_eq!(welded.build_report().degenerate_faces, 8);
+        assert_eq!(welded.indices().len(), mesh.indices().len());
+        for n in welded.normals() {
+            assert!((n.length() - 1.0).abs() < 1e-6, "{n}");
+        }
+        // crease vertices are kept apart
+        let sharp = coincident_mesh(None, Some(true))?;
+        let welded = coincident_mesh(Some(1e-4), Some(true))?;
+        assert_eq!(welded.positions().len() + 6, sharp.positions().len());
+        let crease: Vec<_> = (0..welded.positions().len())
+            .filter(|v| welded.uvs()[*v] == Vec2::new(0.25, 0.0))
+            .map(|v| welded.normals()[v])
+            .collect();
+        assert_eq!(crease.len(), 2);
+        assert!(crease[0].dot(crease[1]) < 0.9);
+        Ok(())
+    }
+
+    #[test]
+    fn subdivide() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.ring_capped(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default().spoke(0.0))?;
+        let mesh = husk.to_mesh()?;
+        husk.subdivide(1);
+        let smooth = husk.into_mesh()?;
+        assert_eq!(smooth.indices().len(), mesh.indices().len() * 4);
+        assert!(smooth.positions().len() > mesh.positions().len());
+        Ok(())
+    }
+
+    #[test]
+    fn fix_winding() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.ring_capped(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default().spoke(0.0))?;
+        let mesh = husk.to_mesh()?;
+        assert!(!mesh.is_inside_out());
+        // meshes which are right side out are unchanged
+        husk.fix_winding();
+        let fixed = husk.into_mesh()?;
+        assert_eq!(fixed.indices(), mesh.indices());
+        assert_eq!(fixed.normals(), mesh.normals());
+        Ok(())
+    }
+
+    #[test]
+    fn mirror() -> Result<()> {
+        // half cylinder, with a flat side on the Z plane
+        let mut husk = Husk::new();
+        let mut ring = Ring::default().axis(Vec3::Y);
+        for i in 0..=6 {
+            let angle = PI * i as f32 / 6.0;
+            ring = ring.spoke(Spoke::from(1.0).with_angle(angle));
+        }
+        husk.ring_capped(ring)?;
+        husk.ring(Ring::default())?;
+        let half = husk.to_mesh()?;
+        husk.mirror(Plane::z(0.0));
+        let mesh = husk.into_mesh()?;
+        assert!(mesh.is_watertight());
+        assert!(!mesh.is_inside_out());
+        // flat side and cap faces on the plane are removed
+        let faces = half.indices().len() / 3;
+        assert_eq!(mesh.indices().len() / 3, (faces - 4) * 2);
+        assert!((mesh.volume() - half.volume() * 2.0).abs() < 1e-4);
+        Ok(())
+    }
+
+    #[test]
+    fn shell() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.set_auto_cap(false);
+        husk.ring_capped(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default().axis(Vec3::new(0.0, 2.0, 0.0)))?;
+        let mesh = husk.to_mesh()?;
+        assert!(!mesh.is_watertight());
+        husk.shell(0.1);
+        let shell = husk.into_mesh()?;
+        assert!(shell.is_watertight());
+        assert!(shell.volume() < mesh.volume());
+        Ok(())
+    }
+
+    #[test]
+    fn empty_glb() {
+        let husk = Husk::new();
+        assert!(matches!(husk.to_glb_bytes(), Err(Error::EmptyMesh)));
+        let options = GltfOptions::default().with_quantize().with_center();
+        let err = husk.write_gltf_with(Vec::new(), &options).err();
+        assert!(matches!(err, Some(Error::EmptyMesh)));
+        let mut mesh = husk.into_mesh().unwrap();
+        mesh.bake_ao_seeded(4, 0);
+        assert!(mesh.ambient_occlusion().is_empty());
+    }
+
+    #[test]
+    fn try_settings() {
+        let mut husk = Husk::new();
+        assert!(matches!(husk.try_weld(0.0), Err(Error::InvalidWeld(_))));
+        assert!(matches!(
+            husk.try_shell(f32::INFINITY),
+            Err(Error::InvalidThickness(_))
+        ));
+        assert!(matches!(husk.try_skeleton(0), Err(Error::InvalidCount(0))));
+        assert!(matches!(
+            husk.try_taper(-1.0, 4, Taper::Linear),
+            Err(Error::InvalidScale(_))
+        ));
+        assert!(husk.weld.is_none());
+        assert!(husk.thickness.is_none());
+        assert!(husk.joint_spacing.is_none());
+        assert!(husk.taper.is_empty());
+        assert!(husk.try_weld(1e-4).is_ok());
+        assert!(husk.try_shell(0.1).is_ok());
+        assert!(husk.try_skeleton(2).is_ok());
+        assert!(husk.try_taper(0.5, 4, Taper::Linear).is_ok());
+        assert_eq!(husk.taper.len(), 4);
+    }
+
+    #[test]
+    fn sweep() -> Result<()> {
+        // quarter circle elbow, from +Y to +X around (1, 0, 0)
+        let center = Vec3::X;
+        let mut path: Vec<_> = (0..=16)
+            .map(|i| {
+                let theta = FRAC_PI_2 * i as f32 / 16.0;
+                Vec3::new(1.0 - theta.cos(), theta.sin(), 0.0)
+            })
+            .collect();
+        path.insert(8, path[8]);
+        let mut husk = Husk::new();
+        let ring = (0..12).fold(Ring::default(), |r, _| r.spoke(0.25));
+        husk.ring_capped(ring)?;
+        husk.sweep(&path, Ring::default())?;
+        let end = husk.current_ring().unwrap().center();
+        assert!(end.distance(Vec3::new(1.0, 1.0, 0.0)) < 1e-5, "{end}");
+        let mesh = husk.into_mesh()?;
+        assert_eq!(mesh.build_report().degenerate_faces, 0);
+        assert!(mesh.is_watertight());
+        // all points except cap hubs are on the elbow surface
+        let hubs = [Vec3::ZERO, end];
+        for p in mesh.positions() {
+            if hubs.iter().any(|h| h.distance(*p) < 1e-5) {
+                continue;
+            }
+            let xy = Vec3::new(p.x, p.y, 0.0) - center;
+            let d = Vec2::new(xy.length() - 1.0, p.z).length();
+            assert!((d - 0.25).abs() < 0.01, "{p} {d}");
+        }
+        // spokes do not spin around the path (2 per ring, plus caps)
+        let side = mesh.positions().iter().filter(|p| p.z.abs() > 0.2499);
+        assert_eq!(side.count(), 2 * (17 + 2));
+        let invalid = [Vec3::ZERO, Vec3::new(0.0, f32::NAN, 0.0)];
+        assert!(matches!(
+            Husk::new().sweep(&invalid, Ring::default().spoke(1.0)),
+            Err(Error::InvalidAxis(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn helix_path() -> Result<()> {
+        let mut husk = Husk::new();
+        let profile = (0..8).fold(Ring::default(), |r, _| r.spoke(0.1));
+        husk.helix(2.5, 1.0, 0.5, 16, profile)?;
+        assert_eq!(husk.ring_points.len(), 41);
+        for (i, ring) in husk.ring_points.iter().enumerate() {
+            let centroid = ring.pos.iter().sum::<Vec3>() / 8.0;
+            let angle = 2.0 * PI * i as f32 / 16.0;
+            let pos = Vec3::new(
+                angle.cos() - 1.0,
+                0.5 * i as f32 / 16.0,
+                -angle.sin(),
+            );
+            assert!(centroid.distance(pos) < 1e-4, "{i}: {centroid} {pos}");
+        }
+        let mesh = husk.into_mesh()?;
+        assert_eq!(mesh.build_report().degenerate_faces, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn ring_lerp() -> Result<()> {
+        let mut husk = Husk::new();
+        let ring = Ring::default().axis(Vec3::new(0.0, 0.5, 0.0));
+        husk.ring((0..4).fold(ring, |r, _| r.spoke(2.0)))?;
+        let ring = Ring::default().axis(Vec3::new(0.0, 2.0, 0.0));
+        husk.ring_lerp((0..4).fold(ring, |r, _| r.spoke(10.0)), 5)?;
+        assert_eq!(husk.ring_points.len(), 7);
+        let mut height = 0.0;
+        for (i, ring) in husk.ring_points.iter().enumerate() {
+            let center = ring.pos.iter().sum::<Vec3>() / 4.0;
+            let radius = ring.pos[0].distance(center);
+            let expected = 2.0 + 8.0 * i as f32 / 6.0;
+            assert!((radius - expected).abs() < 1e-5, "{i}: {radius}");
+            assert!((center.y - height).abs() < 1e-5, "{i}: {center}");
+            height += 0.5 + 1.5 * (i + 1) as f32 / 6.0;
+        }
+        // spokes of the current ring are resampled
+        let ring = (0..8).fold(Ring::default(), |r, _| r.spoke(10.0));
+        husk.ring_lerp(ring, 2)?;
+        assert_eq!(husk.ring_points.len(), 10);
+        assert!(husk.ring_points[7..].iter().all(|r| r.pos.len() == 8));
+        Ok(())
+    }
+
+    #[test]
+    fn scale_xz() -> Result<()> {
+        let mut husk = Husk::new();
+        let ring = Ring::default().scale(2.0).scale_xz(1.5, 0.5);
+        husk.ring((0..8).fold(ring, |r, _| r.spoke(1.0)))?;
+        husk.ring(Ring::default())?;
+        assert_eq!(
+            husk.current_ring().unwrap().scale_xz(),
+            Vec2::new(1.5, 0.5)
+        );
+        let mesh = husk.to_mesh()?;
+        assert!(mesh.pos_min().abs_diff_eq(Vec3::new(-3.0, 0.0, -1.0), 1e-5));
+        assert!(mesh.pos_max().abs_diff_eq(Vec3::new(3.0, 1.0, 1.0), 1e-5));
+        let mut husk = Husk::new();
+        husk.ring(Ring::ellipse(16, 1.2, 0.4))?;
+        let mesh = husk.to_mesh()?;
+        assert!(mesh.pos_min().abs_diff_eq(Vec3::new(-1.2, 0.0, -0.4), 1e-5));
+        assert!(mesh.pos_max().abs_diff_eq(Vec3::new(1.2, 0.0, 0.4), 1e-5));
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke("a").spoke(1.0))?;
+        husk.ring(Ring::default())?;
+        let branch = husk.branch("a")?;
+        assert!(branch.try_scale_xz(1.0, 2.0).is_ok());
+        assert!(matches!(
+            Ring::default().try_scale_xz(1.0, -2.0),
+            Err(Error::InvalidScale(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn offset() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.ring_capped((0..8).fold(Ring::default(), |r, _| r.spoke(1.0)))?;
+        let offset = Vec2::new(0.5, -0.25);
+        husk.ring(Ring::default().axis(Vec3::Y).offset(offset))?;
+        let hub = Vec3::new(0.5, 1.0, -0.25);
+        let view = husk.current_ring().unwrap();
+        assert_eq!(view.offset(), offset);
+        assert!(view.center().distance(hub) < 1e-6);
+        let mesh = husk.to_mesh()?;
+        assert!(mesh.positions().iter().any(|p| p.distance(hub) < 1e-6));
+        assert!(mesh.pos_max().abs_diff_eq(Vec3::new(1.5, 1.0, 1.0), 1e-6));
+        assert!(mesh.is_watertight());
+        assert_eq!(mesh.build_report().degenerate_faces, 0);
+        // offset is not inherited
+        husk.ring(Ring::default())?;
+        let view = husk.current_ring().unwrap();
+        assert!(view.center().distance(Vec3::new(0.0, 2.0, 0.0)) < 1e-6);
+        assert!(matches!(
+            Ring::default().try_offset(Vec2::new(f32::NAN, 0.0)),
+            Err(Error::InvalidOffset(_))
+        ));
+        Ok(())
+    }
+
+    /// Get minimum angle of all triangles in a mesh
+    fn min_angle(mesh: &Mesh) -> f32 {
+        let pos = mesh.positions();
+        mesh.indices()
+            .chunks_exact(3)
+            .flat_map(|tri| {
+                let [a, b, c] = [0, 1, 2].map(|i| pos[usize::from(tri[i].0)]);
+                [(a, b, c), (b, c, a), (c, a, b)]
+            })
+            .map(|(a, b, c)| (b - a).angle_between(c - a))
+            .fold(PI, f32::min)
+    }
+
+    #[test]
+    fn resample_band() -> Result<()> {
+        let band = |first: Ring| -> Result<Mesh> {
+            let mut husk = Husk::new();
+            husk.set_auto_cap(false);
+            husk.ring(first)?;
+            let ring = Ring::default().axis(Vec3::new(0.0, 0.25, 0.0));
+            husk.ring((0..24).fold(ring, |r, _| r.spoke(1.0)))?;
+            husk.into_mesh()
+        };
+        let triangle = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        let skewed = band(triangle.clone())?;
+        assert_eq!(skewed.indices().len() / 3, 27);
+        let mesh = band(triangle.resample(24))?;
+        assert_eq!(mesh.indices().len() / 3, 48);
+        assert!(min_angle(&skewed) < 10.0_f32.to_radians());
+        assert!(min_angle(&mesh) > 40.0_f32.to_radians());
+        Ok(())
+    }
+
+    #[test]
+    fn lathe() -> Result<()> {
+        // semicircle profile approximates a unit sphere
+        let profile: Vec<_> = (0..=16)
+            .map(|i| {
+                let theta = PI * i as f32 / 16.0;
+                let r = if i == 0 || i == 16 { 0.0 } else { theta.sin() };
+                Vec2::new(r, 1.0 - theta.cos())
+            })
+            .collect();
+        let mesh = Husk::lathe(&profile, 24)?.into_mesh()?;
+        assert!(mesh.is_watertight());
+        for p in mesh.positions() {
+            assert!((p.distance(Vec3::Y) - 1.0).abs() < 1e-5, "{p}");
+        }
+        let volume = 4.0 / 3.0 * PI;
+        assert!(mesh.volume() < volume && mesh.volume() > volume * 0.95);
+        // heights must not decrease
+        let profile = [Vec2::new(1.0, 1.0), Vec2::new(1.0, 0.0)];
+        assert!(matches!(
+            Husk::lathe(&profile, 8),
+            Err(Error::InvalidAxis(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn smooth_spoke() -> Result<()> {
+        let flat = sharp_mesh(Shading::Flat, None)?;
+        let smooth = sharp_mesh(Shading::Flat, Some(false))?;
+        assert!(smooth.positions().len() < flat.positions().len());
+        let sharp = sharp_mesh(Shading::Flat, Some(true))?;
+        assert_eq!(sharp.positions().len(), flat.positions().len());
+        Ok(())
+    }
+
+    #[test]
+    fn twist_seam() -> Result<()> {
+        use std::f32::consts::FRAC_1_SQRT_2;
+        let mut husk = Husk::new();
+        let ring = Ring::default().seam(PI * 0.5).twist(PI * 0.25);
+        husk.ring(ring.spoke(1.0).spoke(1.0).spoke(1.0).spoke(1.0))?;
+        assert!(husk.builder.vertex(0).distance(Vec3::NEG_Z) < 1e-6);
+        husk.ring(Ring::default())?;
+        husk.ring(Ring::default())?;
+        let v = Vec3::new(-FRAC_1_SQRT_2, 1.0, -FRAC_1_SQRT_2);
+        assert!(husk.builder.vertex(4).distance(v) < 1e-6);
+        let v = Vec3::new(-1.0, 2.0, 0.0);
+        assert!(husk.builder.vertex(8).distance(v) < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn twist_over_half_step() -> Result<()> {
+        let mut husk = Husk::new();
+        // half step is 45 degrees with 4 spokes
+        let ring = Ring::default().twist(PI * 0.4);
+        husk.ring(ring.spoke(1.0).spoke(1.0).spoke(1.0).spoke(1.0))?;
+        for _ in 0..6 {
+            husk.ring(Ring::default())?;
+        }
+        let mesh = husk.into_mesh()?;
+        assert_eq!(mesh.build_report(), BuildReport::default());
+        // each band has two faces per spoke, plus the cap
+        assert_eq!(mesh.indices().len() / 3, 6 * 8 + 4);
+        let mut faces = HashSet::new();
+        for tri in mesh.indices().chunks_exact(3) {
+            let mut tri = [tri[0].0, tri[1].0, tri[2].0];
+            tri.sort_unstable();
+            assert!(faces.insert(tri), "duplicate face {tri:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn many_spokes_winding() -> Result<()> {
+        let mut husk = Husk::new();
+        let mut ring = Ring::default().axis(Vec3::new(0.0, 0.1, 0.0));
+        for _ in 0..450 {
+            ring = ring.spoke(1.0);
+        }
+        husk.ring(ring)?;
+        husk.ring(Ring::default().twist(0.001))?;
+        husk.ring(Ring::default())?;
+        let mesh = husk.into_mesh()?;
+        assert_eq!(mesh.build_report(), BuildReport::default());
+        let pos = mesh.positions();
+        let mut faces = HashSet::new();
+        for tri in mesh.indices().chunks_exact(3) {
+            let vtx = [tri[0].0, tri[1].0, tri[2].0];
+            let [p0, p1, p2] = vtx.map(|v| pos[usize::from(v)]);
+            let norm = (p1 - p0).cross(p2 - p0);
+            let center = (p0 + p1 + p2) / 3.0;
+            // outward from the axis, except for the cap
+            if center.y < 0.199 {
+                let out = Vec3::new(center.x, 0.0, center.z);
+                assert!(norm.dot(out) > 0.0, "{vtx:?}");
+            } else {
+                assert!(norm.y > 0.0, "{vtx:?}");
+            }
+            let mut vtx = vtx;
+            vtx.sort_unstable();
+            assert!(faces.insert(vtx), "duplicate face {vtx:?}");
+        }
+        assert_eq!(faces.len(), 450 * 4 + 450);
+        Ok(())
+    }
+
+    #[test]
+    fn zero_spokes() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0).spoke(1.0))?;
+        let pinch = Ring::default().spoke(0.0).spoke(0.0).spoke(0.0);
+        husk.ring(pinch.spoke(0.0))?;
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0).spoke(1.0))?;
+        let mesh = husk.into_mesh()?;
+        assert!(mesh.build_report().degenerate_faces > 0);
+        for n in mesh.normals() {
+            assert!((n.length() - 1.0).abs() < 1e-6, "{n}");
+        }
+        let pos = mesh.positions();
+        for tri in mesh.indices().chunks_exact(3) {
+            let [p0, p1, p2] = [0, 1, 2].map(|i| pos[usize::from(tri[i].0)]);
+            assert!((p1 - p0).cross(p2 - p0).length() > 0.0);
+        }
+        Ok(())
+    }
+
+    /// Make a band between two rings, with spoke counts and seams
+    fn band_mesh(spokes: [usize; 2], seams: [f32; 2]) -> Result<Mesh> {
+        let mut husk = Husk::new();
+        for (count, seam) in spokes.iter().zip(seams) {
+            let mut ring = Ring::default().seam(seam);
+            for _ in 0..*count {
+                ring = ring.spoke(1.0);
+            }
+            husk.ring(ring)?;
+        }
+        husk.into_mesh()
+    }
+
+    #[test]
+    fn band_seams() -> Result<()> {
+        for spokes in [[3, 16], [16, 3], [3, 4], [5, 3]] {
+            // band shape depends only on relative seam angle
+            for rel in 0..36 {
+                let mut areas = Vec::new();
+                for s0 in 0..36 {
+                    let s1 = (s0 + rel) % 36;
+                    let seams =
+                        [s0, s1].map(|s| (s as f32 * 10.0).to_radians());
+                    let mesh = band_mesh(spokes, seams)?;
+                    let pos = mesh.positions();
+                    let mut faces = 0;
+                    let mut area = 0.0;
+                    for tri in mesh.indices().chunks_exact(3) {
+                        let [p0, p1, p2] =
+                            [0, 1, 2].map(|i| pos[usize::from(tri[i].0)]);
+                        let center = (p0 + p1 + p2) / 3.0;
+                        if center.y > 0.999 {
+                            continue; // cap
+                        }
+                        let norm = (p1 - p0).cross(p2 - p0);
+                        let out = Vec3::new(center.x, 0.0, center.z);
+                        assert!(norm.dot(out) > 0.0, "{spokes:?} {s0} {s1}");
+                        area += norm.length();
+                        faces += 1;
+                    }
+                    assert_eq!(faces, spokes[0] + spokes[1]);
+                    areas.push(area);
+                }
+                for area in &areas {
+                    assert!(
+                        (area - areas[0]).abs() < 1e-4,
+                        "{spokes:?} {rel} {areas:?}"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn branch_consecutive_rings() -> Result<()> {
+        let mut husk = Husk::new();
+        let ring = Ring::default().axis(Vec3::new(0.0, 1.0, 0.0));
+        husk.ring(ring.spoke(1.0).spoke("a").spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default().spoke(1.0).spoke("a").spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0).spoke(1.0))?;
+        let id = husk.labels.get("a").unwrap();
+        let mut edges: Vec<_> = husk.branches[&id]
+            .edges()
+            .map(|e| (e.0.min(e.1), e.0.max(e.1)))
+            .collect();
+        edges.sort_unstable();
+        // ring 0: vertices 0-2, ring 1: 3-5, ring 2: 6-9
+        // path around the branch, open at the bottom (no band below ring 0)
+        assert_eq!(edges, [(0, 3), (1, 4), (3, 7), (4, 8), (7, 8)]);
+        Ok(())
+    }
+
+    /// Count faces of a tube, with caps at either end
+    fn tube_faces(bottom: bool, top: bool) -> Result<usize> {
+        let mut husk = Husk::new();
+        husk.set_auto_cap(false);
+        let mut ring = Ring::default();
+        for _ in 0..8 {
+            ring = ring.spoke(1.0);
+        }
+        if bottom {
+            husk.ring_capped(ring)?;
+        } else {
+            husk.ring(ring)?;
+        }
+        husk.ring(Ring::default())?;
+        husk.ring(Ring::default())?;
+        if top {
+            husk.cap_current()?;
+        }
+        let mesh = husk.into_mesh()?;
+        // caps face outward
+        let pos = mesh.positions();
+        for tri in mesh.indices().chunks_exact(3) {
+            let [p0, p1, p2] = [0, 1, 2].map(|i| pos[usize::from(tri[i].0)]);
+            let norm = (p1 - p0).cross(p2 - p0);
+            let out = (p0 + p1 + p2) / 3.0 - Vec3::new(0.0, 1.0, 0.0);
+            assert!(norm.dot(out) > 0.0);
+        }
+        Ok(mesh.indices().len() / 3)
+    }
+
+    #[test]
+    fn caps() -> Result<()> {
+        assert_eq!(tube_faces(false, false)?, 32);
+        assert_eq!(tube_faces(true, false)?, 40);
+        assert_eq!(tube_faces(false, true)?, 40);
+        assert_eq!(tube_faces(true, true)?, 48);
+        // implicit cap at export
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        husk.ring(Ring::default())?;
+        assert_eq!(husk.to_mesh()?.indices().len() / 3, 9);
+        husk.set_auto_cap(false);
+        assert_eq!(husk.to_mesh()?.indices().len() / 3, 6);
+        Ok(())
+    }
+
+    /// Make a cylinder from Y=0 to 3, with a dome cap
+    fn dome_mesh(rings: usize) -> Result<Mesh> {
+        let mut husk = Husk::new();
+        let mut ring = Ring::default();
+        for _ in 0..8 {
+            ring = ring.spoke(1.0);
+        }
+        husk.ring(ring)?;
+        for _ in 0..3 {
+            husk.ring(Ring::default())?;
+        }
+        husk.cap_dome(rings)?;
+        husk.into_mesh()
+    }
+
+    /// Get maximum angle between vertex normals and a capsule surface
+    fn dome_normal_error(mesh: &Mesh) -> f32 {
+        mesh.positions()
+            .iter()
+            .zip(mesh.normals())
+            .filter(|(pos, _)| pos.y > 2.5)
+            .map(|(pos, norm)| {
+                let center = Vec3::new(0.0, pos.y.min(3.0), 0.0);
+                norm.angle_between(*pos - center)
+            })
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn cap_dome() -> Result<()> {
+        let mesh = dome_mesh(4)?;
+        assert_eq!(mesh.indices().len() / 3, 48 + 64 + 8);
+        let top = mesh.pos_max();
+        assert!((top.y - 4.0).abs() < 1e-4, "{top}");
+        let error = dome_normal_error(&mesh);
+        assert!(error < 0.15, "{error}");
+        // flat cap makes a hard turn at the seam
+        let error = dome_normal_error(&dome_mesh(0)?);
+        assert!(error > 0.3, "{error}");
+        Ok(())
+    }
+
+    /// Make a capped cylinder, with cap shading
+    fn cap_shading_mesh(shading: Option<Shading>) -> Result<Mesh> {
+        let mut husk = Husk::new();
+        let mut ring = Ring::default().shading(Shading::Smooth);
+        for _ in 0..8 {
+            ring = ring.spoke(1.0);
+        }
+        husk.ring(ring)?;
+        let ring = Ring::default();
+        husk.ring(match shading {
+            Some(shading) => ring.cap_shading(shading),
+            None => ring,
+        })?;
+        husk.into_mesh()
+    }
+
+    #[test]
+    fn cap_shading() -> Result<()> {
+        // ring vertices, hub, cap UV disc and UV seam duplicates
+        let mesh = cap_shading_mesh(None)?;
+        assert_eq!(mesh.positions().len(), 16 + 1 + 8 + 2);
+        let mesh = cap_shading_mesh(Some(Shading::Flat))?;
+        assert!(mesh.positions().len() > 27);
+        // top ring vertices are split between side and cap
+        let top: Vec<_> = mesh
+            .positions()
+            .iter()
+            .zip(mesh.normals())
+            .filter(|(pos, _)| pos.y == 1.0 && pos.length() > 1.0)
+            .collect();
+        let side = top.iter().filter(|(_, n)| n.y.abs() < 0.8).count();
+        let cap = top.iter().filter(|(_, n)| n.y > 0.99).count();
+        // one side vertex is a UV seam duplicate
+        assert_eq!(side, 9);
+        assert_eq!(side + cap, top.len());
+        assert!(cap >= 8);
+        Ok(())
+    }
+
+    #[test]
+    fn ringed() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.set_auto_cap(false);
+        let mut ring = Ring::default().shading(Shading::Ringed);
+        for _ in 0..8 {
+            ring = ring.spoke(1.0);
+        }
+        husk.ring(ring)?;
+        husk.ring(Ring::default().scale(2.0))?;
+        husk.ring(Ring::default().scale(1.0))?;
+        let mesh = husk.into_mesh()?;
+        // middle ring vertices are split between bands (plus UV seams)
+        assert_eq!(mesh.positions().len(), 32 + 4);
+        let (pos, norm, uv) = (mesh.positions(), mesh.normals(), mesh.uvs());
+        for i in 0..pos.len() {
+            for j in i + 1..pos.len() {
+                if pos[i] == pos[j] && uv[i] == uv[j] {
+                    // crease between bands
+                    assert!(norm[i].dot(norm[j]) < 0.9);
+                    assert!(norm[i].y * norm[j].y < 0.0);
+                }
+            }
+        }
+        // smooth around each band
+        for (p, n) in pos.iter().zip(norm) {
+            let radial = Vec3::new(p.x, 0.0, p.z).normalize();
+            let horiz = Vec3::new(n.x, 0.0, n.z).normalize();
+            assert!(radial.dot(horiz) > 0.999, "{p} {n}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn uv_seam() -> Result<()> {
+        let mut husk = Husk::new();
+        let mut ring = Ring::default();
+        for _ in 0..8 {
+            ring = ring.spoke(1.0);
+        }
+        husk.ring(ring)?;
+        husk.ring(Ring::default().twist(0.3))?;
+        husk.ring(Ring::default())?;
+        let mesh = husk.into_mesh()?;
+        let (pos, norm, uv) = (mesh.positions(), mesh.normals(), mesh.uvs());
+        assert_eq!(uv.len(), pos.len());
+        for tri in mesh.indices().chunks_exact(3) {
+            let [u0, u1, u2] = [0, 1, 2].map(|i| uv[usize::from(tri[i].0)].x);
+            // no faces smeared across the seam
+            assert!(u0.max(u1).max(u2) - u0.min(u1).min(u2) < 0.5);
+        }
+        // seam duplicates share positions and normals
+        let mut seams = 0;
+        for i in 0..pos.len() {
+            for j in 0..pos.len() {
+                if pos[i] == pos[j] && uv[j].x - uv[i].x == 1.0 {
+                    assert_eq!(uv[i].y, uv[j].y);
+                    assert!(norm[i].dot(norm[j]) > 0.9999);
+                    seams += 1;
+                }
+            }
+        }
+        assert!(seams >= 3, "{seams}");
+        // V is normalized over height
+        for (p, uv) in pos.iter().zip(uv) {
+            if p.y > 0.0 && p.y < 2.0 {
+                assert!((uv.y - p.y / 2.0).abs() < 1e-6);
+            }
+        }
+        // top cap is a disc
+        let hub = pos.iter().position(|p| *p == Vec3::new(0.0, 2.0, 0.0));
+        let hub_uv = uv[hub.unwrap()];
+        assert_eq!(hub_uv, Vec2::new(0.5, 1.0));
+        let disc = uv
+            .iter()
+            .filter(|uv| (uv.distance(hub_uv) - CAP_UV_RADIUS).abs() < 1e-6)
+            .count();
+        assert_eq!(disc, 8);
+        Ok(())
+    }
+
+    #[test]
+    fn ring_colors() -> Result<()> {
+        const RED: Vec4 = Vec4::new(1.0, 0.0, 0.0, 1.0);
+        const BLUE: Vec4 = Vec4::new(0.0, 0.0, 1.0, 1.0);
+        let mut husk = Husk::new();
+        let ring = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        husk.ring(ring.spoke(1.0).color(RED.to_array()))?;
+        let ring = Ring::default().spoke(1.0).spoke(1.0).spoke("b");
+        husk.ring(ring.spoke("b").spoke(1.0).spoke(1.0))?;
+        let ring = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        husk.ring(ring.spoke(1.0).color(BLUE.to_array()))?;
+        let ring = husk.branch("b")?;
+        husk.ring(ring)?;
+        husk.ring(Ring::default().spoke(0.2).spoke(0.2).spoke(0.2))?;
+        let mesh = husk.into_mesh()?;
+        let (pos, colors) = (mesh.positions(), mesh.colors());
+        assert_eq!(colors.len(), pos.len());
+        for (p, c) in pos.iter().zip(colors) {
+            if p.y < 1.5 {
+                // branch picks up color of its parent ring
+                assert_eq!(*c, RED, "{p}");
+            } else if p.x.abs() < 0.5 && p.z.abs() < 0.5 {
+                // top cap
+                assert_eq!(*c, BLUE, "{p}");
+            }
+            assert!(*c == RED || *c == BLUE, "{p}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ring_materials() -> Result<()> {
+        let mut husk = Husk::new();
+        let ring = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        husk.ring(ring.spoke(1.0).material("bark"))?;
+        husk.ring(Ring::default())?;
+        assert_eq!(husk.current_ring().unwrap().material(), Some("bark"));
+        husk.ring(Ring::default().material("cut"))?;
+        let mesh = husk.into_mesh()?;
+        assert_eq!(mesh.materials(), ["bark", "cut"]);
+        let pos = mesh.positions();
+        for (f, tri) in mesh.indices().chunks_exact(3).enumerate() {
+            let top = tri.iter().all(|v| pos[usize::from(v.0)].y > 1.5);
+            let material = if top { 1 } else { 0 };
+            assert_eq!(mesh.face_material(f), Some(material));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn taper_explicit_scale() -> Result<()> {
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+        husk.taper(0.5, 5, Taper::Linear);
+        husk.ring(Ring::default())?;
+        assert!((ring_scale(&husk) - 0.9).abs() < 1e-6);
+        husk.ring(Ring::default().scale(3.0))?;
+        assert_eq!(ring_scale(&husk), 3.0);
+        husk.ring(Ring::default())?;
+        assert!((ring_scale(&husk) - 0.7).abs() < 1e-6);
+        Ok(())
+    }
+
+    /// Hash mesh indices and positions (FNV-1a)
+    fn mesh_hash(mesh: &Mesh) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+        let words = mesh.indices().iter().map(|v| u32::from(v.0)).chain(
+            mesh.positions()
+                .iter()
+                .flat_map(|p| p.to_array())
+                .map(f32::to_bits),
+        );
+        for word in words {
+            hash = (hash ^ u64::from(word)).wrapping_mul(0x100_0000_01b3);
+        }
+        hash
+    }
+
+    #[test]
+    fn branch_regression() -> Result<()> {
+        use crate::Spoke;
+        let mut husk = Husk::new();
+        let axis = Vec3::new(0.0, 1.0, 0.0);
+        husk.ring(Ring::default().axis(axis).spoke(1.0).spoke(1.0).spoke(1.0))?;
+        let ring = Ring::default()
+            .spoke(1.0)
+            .spoke("a")
+            .spoke("a")
+            .spoke(1.0)
+            .spoke(Spoke::from(1.2).with_sharp(true))
+            .spoke("b");
+        husk.ring(ring)?;
+        husk.ring(Ring::default().spoke(1.0).spoke(0.8).spoke(1.0).spoke(0.8))?;
+        husk.ring(Ring::default().shading(Shading::Flat).spoke(0.5))?;
+        let ring = husk.branch("a")?;
+        husk.ring(ring)?;
+        husk.ring(Ring::default().spoke(0.3).spoke(0.3).spoke(0.3))?;
+        husk.ring(Ring::default().scale(0.5))?;
+        let ring = husk.branch("b")?;
+        husk.ring(ring)?;
+        husk.ring(Ring::default().spoke(0.2).spoke(0.2).spoke(0.2))?;
+        let mesh = husk.into_mesh()?;
+        assert_eq!(mesh.indices().len(), 117);
+        // recaptured after adding UV seam and cap duplicates
+        assert_eq!(mesh_hash(&mesh), 5921020930427533933);
+        Ok(())
+    }
+    #[test]
+    fn too_many_vertices() -> Result<()> {
+        let mut ring = Ring::default().axis(Vec3::new(0.0, 1.0, 0.0));
+        for _ in 0..256 {
+            ring = ring.spoke(1.0);
+        }
+        let mut husk = Husk::new();
+        husk.ring(ring)?;
+        for _ in 1..256 {
+            husk.ring(Ring::default())?;
+        }
+        // exactly at the limit; the cap hub pushes it over
+        let err = husk.write_gltf(std::io::sink()).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::TooManyVertices {
+                count: 65537,
+                max: 65536,
+            }
+        ));
+        let err = husk.ring(Ring::default()).unwrap_err();
+        assert!(matches!(err, Error::TooManyVertices { .. }));
         Ok(())
     }
 }
@@ -0,0 +1,546 @@
+// import.rs    glTF import module
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::error::{Error, Result};
+use crate::gltf::MeshNode;
+use crate::mesh::{Mesh, Vertex};
+use crate::obj;
+use crate::ply::{self, PlyFormat};
+use glam::{Affine3A, Mat4, Quat, Vec3};
+use serde_json::Value;
+use std::io::{Read, Write};
+
+/// Imported glTF model
+///
+/// Made by [import_glb].
+#[derive(Default)]
+pub struct GltfImport {
+    /// Triangle meshes (one per primitive), with node transforms applied
+    pub meshes: Vec<Mesh>,
+
+    /// Count of all primitives (including non-triangle)
+    pub primitives: usize,
+
+    /// Count of materials
+    pub materials: usize,
+}
+
+/// Triangles primitive mode
+const TRIANGLES: u64 = 4;
+
+/// Make an invalid glTF error
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::InvalidGltf(msg.into())
+}
+
+/// Get a JSON value as `usize`
+fn as_usize(val: &Value) -> Option<usize> {
+    val.as_u64().map(|v| v as usize)
+}
+
+/// Get a JSON array of numbers as `f32` values
+fn as_floats(val: &Value) -> Option<Vec<f32>> {
+    val.as_array()?
+        .iter()
+        .map(|v| v.as_f64().map(|f| f as f32))
+        .collect()
+}
+
+/// Import a GLB (binary glTF) model
+///
+/// All triangle primitives of the default scene are imported as separate
+/// meshes.  Node transforms are applied to positions and normals.  Normals
+/// are zero if not present.
+///
+/// ```rust,no_run
+/// # use homunculus::{import_glb, Error};
+/// # use std::fs::File;
+/// # fn main() -> Result<(), Error> {
+/// let model = import_glb(File::open("husk.glb")?)?;
+/// for mesh in &model.meshes {
+///     println!("vertices: {}", mesh.positions().len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn import_glb<R: Read>(mut reader: R) -> Result<GltfImport> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+    let (json, bin) = glb_chunks(&buf)?;
+    let json: Value = serde_json::from_slice(json)
+        .map_err(|e| invalid(format!("JSON: {e}")))?;
+    let mut import = GltfImport {
+        materials: json["materials"].as_array().map_or(0, |m| m.len()),
+        ..Default::default()
+    };
+    let scene = as_usize(&json["scene"]).unwrap_or(0);
+    let roots = match json["scenes"][scene]["nodes"].as_array() {
+        Some(nodes) => nodes.clone(),
+        None => (0..json["nodes"].as_array().map_or(0, |n| n.len()))
+            .map(Value::from)
+            .collect(),
+    };
+    let mut stack: Vec<_> = roots
+        .iter()
+        .rev()
+        .map(|n| (as_usize(n), Affine3A::IDENTITY))
+        .collect();
+    while let Some((node, parent)) = stack.pop() {
+        let node = node.ok_or_else(|| invalid("node index"))?;
+        let nd = &json["nodes"][node];
+        if nd.is_null() {
+            return Err(invalid(format!("node {node}")));
+        }
+        let xform = parent * node_transform(nd)?;
+        if let Some(mesh) = as_usize(&nd["mesh"]) {
+            import.add_mesh(&json, bin, mesh, xform)?;
+        }
+        if let Some(children) = nd["children"].as_array() {
+            for child in children.iter().rev() {
+                stack.push((as_usize(child), xform));
+            }
+        }
+    }
+    Ok(import)
+}
+
+/// Split a GLB into JSON and BIN chunks
+fn glb_chunks(buf: &[u8]) -> Result<(&[u8], &[u8])> {
+    if buf.len() < 20 || &buf[0..4] != b"glTF" {
+        return Err(Error::InvalidGlbHeader("magic"));
+    }
+    if buf[4..8] != 2u32.to_le_bytes() {
+        return Err(Error::InvalidGlbHeader("version"));
+    }
+    // unwrap note: slice is always 4 bytes
+    let len = u32::from_le_bytes(buf[8..12].try_into().unwrap());
+    if len as usize > buf.len() {
+        return Err(Error::InvalidGlbHeader("length"));
+    }
+    let chunk = |offset: usize| -> Option<(&[u8], &[u8], usize)> {
+        let len =
+            u32::from_le_bytes(buf.get(offset..offset + 4)?.try_into().ok()?);
+        let end = offset + 8 + len as usize;
+        let ctype = buf.get(offset + 4..offset + 8)?;
+        Some((ctype, buf.get(offset + 8..end)?, end))
+    };
+    let (ctype, json, end) =
+        chunk(12).ok_or(Error::InvalidGlbHeader("JSON chunk"))?;
+    if ctype != b"JSON" {
+        return Err(Error::InvalidGlbHeader("JSON chunk"));
+    }
+    let bin = match chunk(end) {
+        Some((b"BIN\0", bin, _)) => bin,
+        _ => &[],
+    };
+    Ok((json, bin))
+}
+
+/// Get local transform of a node
+fn node_transform(node: &Value) -> Result<Affine3A> {
+    if let Some(m) = as_floats(&node["matrix"]) {
+        let m: [f32; 16] = m.try_into().map_err(|_| invalid("node matrix"))?;
+        return Ok(Affine3A::from_mat4(Mat4::from_cols_array(&m)));
+    }
+    let vec3 = |val: &Value, default: Vec3| match as_floats(val) {
+        Some(v) if v.len() == 3 => Ok(Vec3::new(v[0], v[1], v[2])),
+        Some(_) => Err(invalid("node vector")),
+        None => Ok(default),
+    };
+    let translation = vec3(&node["translation"], Vec3::ZERO)?;
+    let scale = vec3(&node["scale"], Vec3::ONE)?;
+    let rotation = match as_floats(&node["rotation"]) {
+        Some(r) if r.len() == 4 => Quat::from_xyzw(r[0], r[1], r[2], r[3]),
+        Some(_) => return Err(invalid("node rotation")),
+        None => Quat::IDENTITY,
+    };
+    Ok(Affine3A::from_scale_rotation_translation(
+        scale,
+        rotation,
+        translation,
+    ))
+}
+
+impl Mesh {
+    /// Read a mesh from a GLB (binary glTF)
+    ///
+    /// The first triangle primitive is read, with its node transform
+    /// applied.  See [import_glb] to read all primitives.
+    ///
+    /// ```rust,no_run
+    /// # use homunculus::{Error, Mesh};
+    /// # use std::fs::File;
+    /// # fn main() -> Result<(), Error> {
+    /// let mesh = Mesh::from_glb(File::open("husk.glb")?)?;
+    /// let lod = mesh.decimate(0.5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_glb<R: Read>(reader: R) -> Result<Mesh> {
+        import_glb(reader)?
+            .meshes
+            .into_iter()
+            .next()
+            .ok_or_else(|| invalid("no triangle primitive"))
+    }
+}
+
+impl GltfImport {
+    /// Get mesh nodes for all meshes
+    fn nodes(&self) -> Vec<MeshNode<'_>> {
+        self.meshes.iter().map(MeshNode::new).collect()
+    }
+
+    /// Write all meshes as [Wavefront OBJ] `.obj`
+    ///
+    /// [Wavefront OBJ]: https://en.wikipedia.org/wiki/Wavefront_.obj_file
+    pub fn write_obj<W: Write>(&self, writer: W) -> Result<()> {
+        obj::export(writer, &self.nodes())?;
+        Ok(())
+    }
+
+    /// Write all meshes as [PLY] `.ply`
+    ///
+    /// [PLY]: https://en.wikipedia.org/wiki/PLY_(file_format)
+    pub fn write_ply<W: Write>(
+        &self,
+        writer: W,
+        format: PlyFormat,
+    ) -> Result<()> {
+        ply::export(writer, &self.nodes(), format)?;
+        Ok(())
+    }
+
+    /// Add all primitives of a mesh
+    fn add_mesh(
+        &mut self,
+        json: &Value,
+        bin: &[u8],
+        mesh: usize,
+        xform: Affine3A,
+    ) -> Result<()> {
+        let prims = json["meshes"][mesh]["primitives"]
+            .as_array()
+            .ok_or_else(|| invalid(format!("mesh {mesh}")))?;
+        let normal_mat = xform.matrix3.inverse().transpose();
+        for prim in prims {
+            self.primitives += 1;
+            let mode = prim["mode"].as_u64().unwrap_or(TRIANGLES);
+            if mode != TRIANGLES {
+                continue;
+            }
+            let attrs = &prim["attributes"];
+            let pos_acc = as_usize(&attrs["POSITION"])
+                .ok_or_else(|| invalid("missing POSITION"))?;
+            let pos: Vec<Vec3> = read_vec3(json, bin, pos_acc)?
+                .into_iter()
+                .map(|p| xform.transform_point3(p))
+                .collect();
+            let norm = match as_usize(&attrs["NORMAL"]) {
+                Some(acc) => read_vec3(json, bin, acc)?
+                    .into_iter()
+                    .map(|n| (normal_mat * n).normalize_or_zero())
+                    .collect(),
+                None => vec![Vec3::ZERO; pos.len()],
+            };
+            if norm.len() != pos.len() {
+                return Err(invalid("NORMAL count"));
+            }
+            let indices = match as_usize(&prim["indices"]) {
+                Some(acc) => read_indices(json, bin, acc)?,
+                None => (0..pos.len()).collect(),
+            };
+            if indices.iter().any(|i| *i >= pos.len()) {
+                return Err(invalid("index out of range"));
+            }
+            let indices = indices
+                .into_iter()
+                .map(|i| u16::try_from(i).map(Vertex))
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|_| invalid("too many vertices"))?;
+            self.meshes.push(Mesh::from_parts(pos, norm, indices));
+        }
+        Ok(())
+    }
+}
+
+/// Get size of an accessor component type
+fn component_size(ctype: u64) -> Option<usize> {
+    match ctype {
+        5120 | 5121 => Some(1),
+        5122 | 5123 => Some(2),
+        5125 | 5126 => Some(4),
+        _ => None,
+    }
+}
+
+/// Get element bytes for each item of an accessor
+fn accessor_elements<'a>(
+    json: &Value,
+    bin: &'a [u8],
+    acc: usize,
+    components: usize,
+) -> Result<(Vec<&'a [u8]>, u64, bool)> {
+    let accessor = &json["accessors"][acc];
+    if !accessor["sparse"].is_null() {
+        return Err(invalid("sparse accessor"));
+    }
+    let ctype = accessor["componentType"]
+        .as_u64()
+        .ok_or_else(|| invalid(format!("accessor {acc}")))?;
+    let csize =
+        component_size(ctype).ok_or(Error::UnsupportedComponentType(ctype))?;
+    let count = as_usize(&accessor["count"])
+        .ok_or_else(|| invalid(format!("accessor {acc}")))?;
+    let view = as_usize(&accessor["bufferView"])
+        .ok_or_else(|| invalid(format!("accessor {acc} view")))?;
+    let view = &json["bufferViews"][view];
+    if as_usize(&view["buffer"]).unwrap_or(0) != 0 {
+        return Err(invalid("external buffer"));
+    }
+    let offset = as_usize(&view["byteOffset"]).unwrap_or(0)
+        + as_usize(&accessor["byteOffset"]).unwrap_or(0);
+    let size = csize * components;
+    let stride = as_usize(&view["byteStride"]).unwrap_or(size);
+    let elements = (0..count)
+        .map(|i| bin.get(offset + i * stride..offset + i * stride + size))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| invalid(format!("accessor {acc} out of bounds")))?;
+    let normalized = accessor["normalized"].as_bool().unwrap_or(false);
+    Ok((elements, ctype, normalized))
+}
+
+/// Read one component as `f32`
+fn read_component(b: &[u8], ctype: u64, normalized: bool) -> f32 {
+    match (ctype, normalized) {
+        (5126, _) => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        (5120, true) => (f32::from(b[0] as i8) / 127.0).max(-1.0),
+        (5121, true) => f32::from(b[0]) / 255.0,
+        (5122, true) => {
+            (f32::from(i16::from_le_bytes([b[0], b[1]])) / 32767.0).max(-1.0)
+        }
+        (5123, true) => f32::from(u16::from_le_bytes([b[0], b[1]])) / 65535.0,
+        (5120, false) => f32::from(b[0] as i8),
+        (5121, false) => f32::from(b[0]),
+        (5122, false) => f32::from(i16::from_le_bytes([b[0], b[1]])),
+        (5123, false) => f32::from(u16::from_le_bytes([b[0], b[1]])),
+        _ => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32,
+    }
+}
+
+/// Read a `VEC3` accessor
+fn read_vec3(json: &Value, bin: &[u8], acc: usize) -> Result<Vec<Vec3>> {
+    if json["accessors"][acc]["type"] != "VEC3" {
+        return Err(invalid(format!("accessor {acc} not VEC3")));
+    }
+    let (elements, ctype, normalized) = accessor_elements(json, bin, acc, 3)?;
+    let csize = component_size(ctype).unwrap_or(4);
+    Ok(elements
+        .into_iter()
+        .map(|e| {
+            let c =
+                |i: usize| read_component(&e[i * csize..], ctype, normalized);
+            Vec3::new(c(0), c(1), c(2))
+        })
+        .collect())
+}
+
+/// Read a `SCALAR` index accessor
+fn read_indices(json: &Value, bin: &[u8], acc: usize) -> Result<Vec<usize>> {
+    let (elements, ctype, _) = accessor_elements(json, bin, acc, 1)?;
+    elements
+        .into_iter()
+        .map(|e| match ctype {
+            5121 => Ok(usize::from(e[0])),
+            5123 => Ok(usize::from(u16::from_le_bytes([e[0], e[1]]))),
+            5125 => Ok(u32::from_le_bytes([e[0], e[1], e[2], e[3]]) as usize),
+            _ => Err(Error::UnsupportedComponentType(ctype)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{GltfOptions, Husk, Ring, Scene};
+
+    fn vase() -> Husk {
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0).spoke(1.0))
+            .unwrap();
+        husk.ring(Ring::default().axis(Vec3::new(0.0, 2.0, 0.0)).scale(1.5))
+            .unwrap();
+        husk.ring(Ring::default().scale(0.5)).unwrap();
+        husk
+    }
+
+    fn round_trip(options: &GltfOptions) -> (Mesh, GltfImport) {
+        let mut glb = Vec::new();
+        vase().write_gltf_with(&mut glb, options).unwrap();
+        let mesh = vase().into_mesh().unwrap();
+        (mesh, import_glb(&glb[..]).unwrap())
+    }
+
+    #[test]
+    fn import_plain() {
+        let (mesh, import) = round_trip(&GltfOptions::default());
+        assert_eq!(import.meshes.len(), 1);
+        assert_eq!(import.primitives, 1);
+        assert_eq!(import.materials, 0);
+        let diff = mesh.diff(&import.meshes[0]);
+        assert_eq!(diff.max_deviation, 0.0);
+        assert!(!diff.exceeds(0.0));
+        let normals = import.meshes[0].normals().iter().zip(mesh.normals());
+        assert!(normals.into_iter().all(|(n0, n1)| n0.distance(*n1) < 1e-6));
+    }
+
+    #[test]
+    fn import_quantized() {
+        let options = GltfOptions {
+            quantize: true,
+            ..Default::default()
+        };
+        let (mesh, import) = round_trip(&options);
+        let diff = mesh.diff(&import.meshes[0]);
+        assert!(diff.max_deviation > 0.0);
+        assert!(!diff.exceeds(1e-3));
+    }
+
+    #[test]
+    fn import_scene() {
+        let mut scene = Scene::default();
+        scene.add("a", vase(), Vec3::ZERO, Quat::IDENTITY).unwrap();
+        scene
+            .add("b", vase(), Vec3::X * 5.0, Quat::IDENTITY)
+            .unwrap();
+        let mut glb = Vec::new();
+        scene.write_gltf(&mut glb).unwrap();
+        let import = import_glb(&glb[..]).unwrap();
+        assert_eq!(import.meshes.len(), 2);
+        let diff = import.meshes[0].diff(&import.meshes[1]);
+        assert!((diff.min_delta - Vec3::X * 5.0).length() < 1e-6);
+        assert!((diff.max_deviation - 5.0).abs() < 1e-5);
+        assert!(diff.exceeds(1.0));
+    }
+
+    #[test]
+    fn import_errors() {
+        assert!(import_glb(&b"not a glb"[..]).is_err());
+        let mut glb = Vec::new();
+        vase().write_gltf(&mut glb).unwrap();
+        glb.truncate(glb.len() - 16);
+        assert!(import_glb(&glb[..]).is_err());
+    }
+
+    /// Pack JSON and BIN chunks into a GLB
+    fn pack_glb(json: &Value, bin: &[u8]) -> Vec<u8> {
+        let mut json = json.to_string().into_bytes();
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
+        }
+        let len = 12 + 8 + json.len() + 8 + bin.len();
+        let mut glb = Vec::with_capacity(len);
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(len as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(&json);
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"BIN\0");
+        glb.extend_from_slice(bin);
+        glb
+    }
+
+    #[test]
+    fn mesh_from_glb() {
+        let mut glb = Vec::new();
+        vase().write_gltf(&mut glb).unwrap();
+        let mesh = Mesh::from_glb(&glb[..]).unwrap();
+        assert!(!vase().into_mesh().unwrap().diff(&mesh).exceeds(0.0));
+        // u32 indices, and interleaved positions with a byte stride
+        let (json, bin) = glb_chunks(&glb).unwrap();
+        let mut json: Value = serde_json::from_slice(json).unwrap();
+        let indices: Vec<u8> = mesh
+            .indices()
+            .iter()
+            .flat_map(|v| u32::from(v.0).to_le_bytes())
+            .collect();
+        let interleaved: Vec<u8> = mesh
+            .positions()
+            .iter()
+            .flat_map(|p| {
+                let mut b = p.to_array().map(f32::to_le_bytes).concat();
+                b.extend_from_slice(&[0; 4]);
+                b
+            })
+            .collect();
+        let mut bin = bin.to_vec();
+        let idx_offset = bin.len();
+        bin.extend_from_slice(&indices);
+        let pos_offset = bin.len();
+        bin.extend_from_slice(&interleaved);
+        let views = json["bufferViews"].as_array_mut().unwrap();
+        views.push(serde_json::json!({
+            "buffer": 0,
+            "byteOffset": idx_offset,
+            "byteLength": indices.len(),
+        }));
+        views.push(serde_json::json!({
+            "buffer": 0,
+            "byteOffset": pos_offset,
+            "byteLength": interleaved.len(),
+            "byteStride": 16,
+        }));
+        let n_views = views.len();
+        let prim = &json["meshes"][0]["primitives"][0];
+        let idx_acc = as_usize(&prim["indices"]).unwrap();
+        let pos_acc = as_usize(&prim["attributes"]["POSITION"]).unwrap();
+        json["accessors"][idx_acc]["componentType"] = 5125.into();
+        json["accessors"][idx_acc]["bufferView"] = (n_views - 2).into();
+        json["accessors"][pos_acc]["bufferView"] = (n_views - 1).into();
+        json["buffers"][0]["byteLength"] = bin.len().into();
+        let repacked = Mesh::from_glb(&pack_glb(&json, &bin)[..]).unwrap();
+        assert!(!mesh.diff(&repacked).exceeds(0.0));
+        // unsupported component type
+        json["accessors"][idx_acc]["componentType"] = 5124.into();
+        assert!(matches!(
+            Mesh::from_glb(&pack_glb(&json, &bin)[..]),
+            Err(Error::UnsupportedComponentType(5124))
+        ));
+    }
+
+    #[test]
+    fn glb_header_errors() {
+        assert!(matches!(
+            Mesh::from_glb(&[0; 32][..]),
+            Err(Error::InvalidGlbHeader("magic"))
+        ));
+        let mut glb = Vec::new();
+        vase().write_gltf(&mut glb).unwrap();
+        glb[4] = 1;
+        assert!(matches!(
+            Mesh::from_glb(&glb[..]),
+            Err(Error::InvalidGlbHeader("version"))
+        ));
+        glb[4] = 2;
+        glb[10] = 0xFF;
+        assert!(matches!(
+            Mesh::from_glb(&glb[..]),
+            Err(Error::InvalidGlbHeader("length"))
+        ));
+    }
+
+    #[test]
+    fn import_to_obj() {
+        let mut glb = Vec::new();
+        vase().write_gltf(&mut glb).unwrap();
+        let import = import_glb(&glb[..]).unwrap();
+        let mut obj = Vec::new();
+        import.write_obj(&mut obj).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+        let faces = obj.lines().filter(|l| l.starts_with("f ")).count();
+        assert_eq!(faces, import.meshes[0].indices().len() / 3);
+    }
+}
@@ -0,0 +1,186 @@
+// intersect.rs     Mesh intersection tests
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::mesh::Mesh;
+use crate::raycast::Bvh;
+use glam::{Affine3A, Vec3};
+
+/// Triangle for intersection tests
+struct Tri {
+    /// Vertex positions
+    vtx: [Vec3; 3],
+}
+
+/// Tolerance for plane distances
+///
+/// Triangles closer than this are considered touching, not intersecting.
+const EPSILON: f32 = 1e-5;
+
+impl Tri {
+    /// Check if another triangle has vertices on both sides of its plane
+    fn straddles(&self, other: &Tri) -> bool {
+        let [v0, v1, v2] = self.vtx;
+        let norm = (v1 - v0).cross(v2 - v0).normalize_or_zero();
+        if norm == Vec3::ZERO {
+            return false;
+        }
+        let dist = other.vtx.map(|v| norm.dot(v - v0));
+        let above = dist.iter().any(|d| *d > EPSILON);
+        let below = dist.iter().any(|d| *d < -EPSILON);
+        above && below
+    }
+
+    /// Get point where a line segment pierces the triangle
+    ///
+    /// Uses the Möller–Trumbore algorithm.
+    fn pierce(&self, p0: Vec3, p1: Vec3) -> Option<Vec3> {
+        let [v0, v1, v2] = self.vtx;
+        let dir = p1 - p0;
+        let e1 = v1 - v0;
+        let e2 = v2 - v0;
+        let p = dir.cross(e2);
+        let det = e1.dot(p);
+        if det.abs() <= f32::EPSILON {
+            return None;
+        }
+        let s = p0 - v0;
+        let u = s.dot(p) / det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(e1);
+        let v = dir.dot(q) / det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = e2.dot(q) / det;
+        (0.0..=1.0).contains(&t).then(|| p0 + dir * t)
+    }
+
+    /// Get points where edges pierce another triangle
+    fn edge_points(&self, other: &Tri, pts: &mut Vec<Vec3>) {
+        let [v0, v1, v2] = self.vtx;
+        for (p0, p1) in [(v0, v1), (v1, v2), (v2, v0)] {
+            if let Some(pt) = other.pierce(p0, p1) {
+                pts.push(pt);
+            }
+        }
+    }
+
+    /// Get approximate intersection location with another triangle
+    ///
+    /// Each triangle is first rejected if the other is entirely on one side
+    /// of its plane (Möller); coplanar triangles are only touching.  The
+    /// location is the center of the intersection segment, which must be
+    /// longer than `EPSILON` (otherwise the triangles are only touching).
+    fn intersection(&self, other: &Tri) -> Option<Vec3> {
+        if !self.straddles(other) || !other.straddles(self) {
+            return None;
+        }
+        let mut pts = Vec::with_capacity(6);
+        self.edge_points(other, &mut pts);
+        other.edge_points(self, &mut pts);
+        let first = *pts.first()?;
+        if pts.iter().all(|pt| pt.distance(first) <= EPSILON) {
+            return None;
+        }
+        Some(pts.iter().sum::<Vec3>() / pts.len() as f32)
+    }
+}
+
+impl Mesh {
+    /// Find intersections with another mesh
+    fn find_intersections(
+        &self,
+        other: &Mesh,
+        xform_other: Affine3A,
+        first: bool,
+    ) -> Vec<Vec3> {
+        let mut pts = Vec::new();
+        let bvh0 = Bvh::new(self);
+        let bvh1 = Bvh::with_xform(other, xform_other);
+        bvh0.overlapping(&bvh1, |t0, t1| {
+            let (t0, t1) = (Tri { vtx: *t0 }, Tri { vtx: *t1 });
+            match t0.intersection(&t1) {
+                Some(pt) => {
+                    pts.push(pt);
+                    !first
+                }
+                None => true,
+            }
+        });
+        pts
+    }
+
+    /// Check whether the mesh intersects another mesh
+    ///
+    /// The other mesh is placed with `xform_other`.  Surfaces which are
+    /// only touching do not intersect.
+    pub fn intersects(&self, other: &Mesh, xform_other: Affine3A) -> bool {
+        !self.find_intersections(other, xform_other, true).is_empty()
+    }
+
+    /// Get approximate intersection locations with another mesh
+    ///
+    /// One location is returned for each pair of intersecting triangles.
+    pub fn intersections(
+        &self,
+        other: &Mesh,
+        xform_other: Affine3A,
+    ) -> Vec<Vec3> {
+        self.find_intersections(other, xform_other, false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build_cube;
+
+    /// Make a unit cube (centered on origin)
+    fn cube() -> Mesh {
+        let mut cube = build_cube().unwrap();
+        cube.translate(Vec3::splat(-0.5));
+        cube
+    }
+
+    fn offset(x: f32) -> Affine3A {
+        Affine3A::from_translation(Vec3::new(x, 0.25, 0.1))
+    }
+
+    #[test]
+    fn overlapping() {
+        let cube = cube();
+        assert!(cube.intersects(&cube, offset(0.5)));
+        let pts = cube.intersections(&cube, offset(0.5));
+        assert!(!pts.is_empty());
+        for pt in pts {
+            assert!(pt.x >= -EPSILON && pt.x <= 0.5 + EPSILON, "{pt}");
+        }
+        let rotated = Affine3A::from_rotation_y(0.7);
+        assert!(cube.intersects(&cube, rotated));
+    }
+
+    #[test]
+    fn separated() {
+        let cube = cube();
+        assert!(!cube.intersects(&cube, offset(1.5)));
+        assert!(!cube.intersects(&cube, offset(-3.0)));
+        assert!(cube.intersections(&cube, offset(1.5)).is_empty());
+        // nested cube does not intersect surfaces
+        let small = Affine3A::from_scale(Vec3::splat(0.5));
+        assert!(!cube.intersects(&cube, small));
+    }
+
+    #[test]
+    fn near_touching() {
+        let cube = cube();
+        // faces touching
+        assert!(!cube.intersects(&cube, offset(1.0)));
+        // tiny gap
+        assert!(!cube.intersects(&cube, offset(1.0 + 1e-4)));
+        // slight overlap
+        assert!(cube.intersects(&cube, offset(1.0 - 1e-3)));
+    }
+}
@@ -4,12 +4,54 @@
 //
 #![doc = include_str!("../README.md")]
 
+mod ao;
+#[cfg(feature = "bevy")]
+mod bevy_mesh;
+mod clip;
+mod decimate;
 mod error;
+#[cfg(feature = "hom-format")]
+mod expr;
 mod gltf;
+mod hull;
 mod husk;
+mod import;
+mod intersect;
 mod mesh;
+mod mirror;
+#[cfg(feature = "hom-format")]
+mod model;
+mod obj;
+mod plane;
+mod ply;
+pub mod prelude;
+mod primitives;
+mod raycast;
 mod ring;
+mod scene;
+mod section;
+mod shell;
+mod skeleton;
+mod stats;
+mod subdivide;
 
 pub use error::Error;
-pub use husk::Husk;
-pub use ring::{Ring, Shading, Spoke};
+pub use gltf::{GltfOptions, Material, UpAxis};
+#[cfg(feature = "serde")]
+pub use husk::HuskSnapshot;
+pub use husk::{FrameMode, Husk, Jitter, Taper};
+pub use import::{import_glb, GltfImport};
+pub use mesh::{BuildReport, Face, Mesh, MeshBuilder, MeshDiff};
+#[cfg(feature = "hom-format")]
+pub use model::ModelDef;
+pub use plane::Plane;
+pub use ply::PlyFormat;
+pub use primitives::{
+    build_capsule, build_cone, build_cube, build_cylinder, build_torus,
+    build_tree, build_uv_sphere,
+};
+pub use ring::{Ring, RingView, Shading, Spoke};
+pub use scene::Scene;
+pub use section::SectionOptions;
+pub use skeleton::{Joint, Skeleton};
+pub use stats::MeshStats;
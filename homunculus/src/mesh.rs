@@ -2,21 +2,39 @@
 //
 // Copyright (c) 2022=2023  Douglas Lau
 //
-use glam::Vec3;
+use crate::error::{Error, Result};
+use crate::skeleton::Skeleton;
+use glam::{Affine3A, IVec3, Quat, Vec2, Vec3, Vec4};
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::TAU;
+use std::ops::Range;
+
+/// Maximum number of vertices in a mesh (indices are `u16`)
+pub(crate) const MAX_VERTICES: usize = u16::MAX as usize + 1;
 
 /// Vertex index
 #[repr(transparent)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Vertex(pub u16);
 
-impl From<usize> for Vertex {
-    fn from(v: usize) -> Self {
-        Self(v.try_into().expect("Too many vertices"))
+impl TryFrom<usize> for Vertex {
+    type Error = Error;
+
+    fn try_from(v: usize) -> Result<Self> {
+        match u16::try_from(v) {
+            Ok(v) => Ok(Self(v)),
+            Err(_) => Err(Error::TooManyVertices {
+                count: v + 1,
+                max: MAX_VERTICES,
+            }),
+        }
     }
 }
 
 /// Triangle face
 ///
+/// Vertices are in counter-clockwise order, viewed from the front.
+///
 /// ```text
 /// v0______v2
 ///   \    /
@@ -25,6 +43,7 @@ impl From<usize> for Vertex {
 ///     v1
 /// ```
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Face {
     /// Vertex positions
     vtx: [usize; 3],
@@ -34,16 +53,59 @@ pub struct Face {
 }
 
 /// Mesh builder
-#[derive(Default)]
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MeshBuilder {
     /// Vertex positions
     pos: Vec<Vec3>,
 
     /// Triangle faces
     faces: Vec<Face>,
+
+    /// Vertex texture coordinates (empty if none)
+    uv: Vec<Vec2>,
+
+    /// Vertex colors (empty if none)
+    color: Vec<Vec4>,
+
+    /// Vertex to share normals with (differs for UV seam duplicates)
+    share: Vec<usize>,
+
+    /// Material names
+    materials: Vec<String>,
+
+    /// Current material (index into names)
+    material: Option<u16>,
+
+    /// Material of each face
+    face_materials: Vec<Option<u16>>,
+
+    /// Crease angle (radians)
+    crease_angle: Option<f32>,
+
+    /// Count of removed degenerate faces
+    degenerate_faces: usize,
+
+    /// Vertices on the trailing side of sharp creases
+    crease_vtx: HashSet<usize>,
+
+    /// Skeleton joint of each vertex (empty if none)
+    joints: Vec<u16>,
+}
+
+/// Report of problems found while building a mesh
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BuildReport {
+    /// Degenerate faces (zero area or repeated vertex), removed from mesh
+    pub degenerate_faces: usize,
+
+    /// Vertices with no valid normal, using a fallback
+    pub fallback_normals: usize,
 }
 
 /// 3D Mesh
+///
+/// A triangle mesh with vertex positions and normals.
 pub struct Mesh {
     /// Vertex positions
     pos: Vec<Vec3>,
@@ -51,24 +113,78 @@ pub struct Mesh {
     /// Vertex normals
     norm: Vec<Vec3>,
 
+    /// Vertex texture coordinates (empty if none)
+    pub(crate) uv: Vec<Vec2>,
+
+    /// Vertex colors (empty if none)
+    pub(crate) color: Vec<Vec4>,
+
+    /// Material names
+    pub(crate) materials: Vec<String>,
+
+    /// Material of each face (empty if no materials)
+    pub(crate) face_materials: Vec<Option<u16>>,
+
+    /// Surface of each face (empty if unknown)
+    pub(crate) surfaces: Vec<u16>,
+
     /// Vertex indices
     indices: Vec<Vertex>,
+
+    /// Vertex ambient occlusion (empty if not baked)
+    pub(crate) ao: Vec<f32>,
+
+    /// Skeleton joint of each vertex (empty if none)
+    pub(crate) joints: Vec<u16>,
+
+    /// Skeleton for skinning
+    pub(crate) skeleton: Option<Skeleton>,
+
+    /// Build report
+    report: BuildReport,
+}
+
+/// Differences between two meshes
+///
+/// Made by [Mesh::diff].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshDiff {
+    /// Vertex counts (old, new)
+    pub vertices: (usize, usize),
+
+    /// Face counts (old, new)
+    pub faces: (usize, usize),
+
+    /// Change of minimum bounding box position
+    pub min_delta: Vec3,
+
+    /// Change of maximum bounding box position
+    pub max_delta: Vec3,
+
+    /// Maximum distance between identically-indexed vertices
+    pub max_deviation: f32,
 }
 
 impl Face {
     /// Create a new face
+    ///
+    /// - `vtx`: vertex indices, from [MeshBuilder::push_vtx]
+    /// - `surface`: surface number, for shading
+    ///
+    /// Faces on the same surface which share a vertex are shaded smoothly
+    /// across it.  When faces on different surfaces share a vertex, it is
+    /// split when building the mesh, making a sharp edge.  For flat shading,
+    /// give each face (or each flat polygon) its own surface.  Surfaces are
+    /// kept on the built mesh (see [Mesh::surfaces]).
+    ///
+    /// Faces with a repeated vertex are removed when building a mesh.
+    ///
+    /// [MeshBuilder::push_vtx]: struct.MeshBuilder.html#method.push_vtx
+    /// [Mesh::surfaces]: struct.Mesh.html#method.surfaces
     pub fn new(vtx: [usize; 3], surface: u16) -> Self {
-        debug_assert_ne!(vtx[0], vtx[1]);
-        debug_assert_ne!(vtx[1], vtx[2]);
-        debug_assert_ne!(vtx[2], vtx[0]);
         Self { vtx, surface }
     }
 
-    /// Get surface number for a vertex
-    fn vertex_surface(&self, idx: usize) -> Option<u16> {
-        self.vtx.contains(&idx).then_some(self.surface)
-    }
-
     /// Split a vertex
     fn split_vertex(&mut self, idx: usize, i: usize) {
         if self.vtx[0] == idx {
@@ -83,137 +199,946 @@ impl Face {
 
 impl MeshBuilder {
     /// Create a mesh builder with capacity for N faces
-    fn with_capacity(n_faces: usize) -> Self {
+    ///
+    /// Space is reserved for up to 3 vertices per face.  See also
+    /// [Mesh::builder].
+    ///
+    /// [Mesh::builder]: struct.Mesh.html#method.builder
+    pub fn with_capacity(n_faces: usize) -> Self {
         let pos = Vec::with_capacity(n_faces * 3);
-        let faces = Vec::with_capacity(n_faces * 3);
-        MeshBuilder { pos, faces }
+        let faces = Vec::with_capacity(n_faces);
+        let share = Vec::with_capacity(n_faces * 3);
+        MeshBuilder {
+            pos,
+            faces,
+            uv: Vec::new(),
+            color: Vec::new(),
+            share,
+            materials: Vec::new(),
+            material: None,
+            face_materials: Vec::with_capacity(n_faces),
+            crease_angle: None,
+            degenerate_faces: 0,
+            crease_vtx: HashSet::new(),
+            joints: Vec::new(),
+        }
     }
 
-    /// Get a vertex
+    /// Get the number of vertices
+    pub fn len(&self) -> usize {
+        self.pos.len()
+    }
+
+    /// Check if there are no vertices
+    pub fn is_empty(&self) -> bool {
+        self.pos.is_empty()
+    }
+
+    /// Get a vertex position
+    ///
+    /// # Panics
+    ///
+    /// - If `idx` is out of range
     pub fn vertex(&self, idx: usize) -> Vec3 {
         self.pos[idx]
     }
 
     /// Push a vertex position
-    pub fn push_vtx(&mut self, pos: Vec3) -> usize {
+    ///
+    /// Returns the vertex index, or an error if there would be more than
+    /// 65536 vertices.
+    pub fn push_vtx(&mut self, pos: Vec3) -> Result<usize> {
         let idx = self.pos.len();
+        if idx >= MAX_VERTICES {
+            return Err(Error::TooManyVertices {
+                count: idx + 1,
+                max: MAX_VERTICES,
+            });
+        }
         self.pos.push(pos);
-        idx
+        self.share.push(idx);
+        Ok(idx)
+    }
+
+    /// Push a vertex on the trailing side of a sharp crease
+    ///
+    /// It is never welded to a vertex which is not on a crease.
+    pub(crate) fn push_crease_vtx(&mut self, pos: Vec3) -> Result<usize> {
+        let vid = self.push_vtx(pos)?;
+        self.crease_vtx.insert(vid);
+        Ok(vid)
+    }
+
+    /// Push a copy of a vertex (with texture coordinates and color)
+    fn copy_vtx(&mut self, idx: usize) -> Result<usize> {
+        let vid = self.push_vtx(self.pos[idx])?;
+        if let Some(uv) = self.uv(idx) {
+            self.set_uv(vid, uv);
+        }
+        if let Some(color) = self.color.get(idx) {
+            self.set_color(vid, *color);
+        }
+        if self.crease_vtx.contains(&idx) {
+            self.crease_vtx.insert(vid);
+        }
+        if let Some(joint) = self.joints.get(idx) {
+            self.set_joint(vid, *joint);
+        }
+        Ok(vid)
+    }
+
+    /// Push a UV seam duplicate of a vertex
+    ///
+    /// The duplicate has different texture coordinates, but shares its
+    /// normal with the original vertex.
+    pub(crate) fn push_uv_dup(
+        &mut self,
+        idx: usize,
+        uv: Vec2,
+    ) -> Result<usize> {
+        let vid = self.copy_vtx(idx)?;
+        self.share[vid] = self.share[idx];
+        self.set_uv(vid, uv);
+        Ok(vid)
+    }
+
+    /// Get texture coordinates of a vertex
+    pub fn uv(&self, idx: usize) -> Option<Vec2> {
+        self.uv.get(idx).copied()
+    }
+
+    /// Set texture coordinates of a vertex
+    ///
+    /// Vertices without texture coordinates get zeros.
+    pub fn set_uv(&mut self, idx: usize, uv: Vec2) {
+        if self.uv.len() <= idx {
+            self.uv.resize(self.pos.len(), Vec2::ZERO);
+        }
+        self.uv[idx] = uv;
+    }
+
+    /// Set color of a vertex
+    ///
+    /// Vertices without colors get white.
+    pub fn set_color(&mut self, idx: usize, color: Vec4) {
+        if self.color.len() <= idx {
+            self.color.resize(self.pos.len(), Vec4::ONE);
+        }
+        self.color[idx] = color;
+    }
+
+    /// Set skeleton joint of a vertex
+    ///
+    /// Vertices without joints get joint 0.
+    pub(crate) fn set_joint(&mut self, idx: usize, joint: u16) {
+        if self.joints.len() <= idx {
+            self.joints.resize(self.pos.len(), 0);
+        }
+        self.joints[idx] = joint;
+    }
+
+    /// Set material for faces pushed after this
+    ///
+    /// # Panics
+    ///
+    /// - If there are more than 65536 materials
+    pub fn set_material(&mut self, name: Option<&str>) {
+        self.material = name.map(|name| self.material_index(name));
+    }
+
+    /// Get index of a material name, adding it if needed
+    fn material_index(&mut self, name: &str) -> u16 {
+        let idx = match self.materials.iter().position(|m| m == name) {
+            Some(idx) => idx,
+            None => {
+                self.materials.push(name.to_string());
+                self.materials.len() - 1
+            }
+        };
+        // unwrap note: more materials than that is unreasonable
+        u16::try_from(idx).unwrap()
+    }
+
+    /// Get the number of faces
+    pub fn face_count(&self) -> usize {
+        self.faces.len()
     }
 
     /// Push a face
+    ///
+    /// # Panics
+    ///
+    /// - If any vertex index is out of range (see [try_push_face])
+    ///
+    /// [try_push_face]: struct.MeshBuilder.html#method.try_push_face
     pub fn push_face(&mut self, face: Face) {
         let idx = self.pos.len();
         if face.vtx[0] >= idx || face.vtx[1] >= idx || face.vtx[2] >= idx {
             panic!("Invalid vertex");
         }
         self.faces.push(face);
+        self.face_materials.push(self.material);
     }
 
-    /// Build the mesh
-    pub fn build(self) -> Mesh {
-        Mesh::new(self.split_vertices())
+    /// Push a face, checking its vertex indices
+    ///
+    /// # Errors
+    ///
+    /// - [Error::InvalidVertexIndex] if any index is out of range
+    /// - [Error::RepeatedVertexIndex] if the indices are not distinct
+    ///
+    /// [Error::InvalidVertexIndex]: enum.Error.html#variant.InvalidVertexIndex
+    /// [Error::RepeatedVertexIndex]: enum.Error.html#variant.RepeatedVertexIndex
+    pub fn try_push_face(&mut self, face: Face) -> Result<()> {
+        let len = self.pos.len();
+        let [v0, v1, v2] = face.vtx;
+        if let Some(index) = face.vtx.into_iter().find(|v| *v >= len) {
+            return Err(Error::InvalidVertexIndex { index, len });
+        }
+        if v0 == v1 || v0 == v2 {
+            return Err(Error::RepeatedVertexIndex(v0));
+        }
+        if v1 == v2 {
+            return Err(Error::RepeatedVertexIndex(v1));
+        }
+        self.push_face(face);
+        Ok(())
     }
 
-    /// Split all non-smooth vertices
-    fn split_vertices(mut self) -> Self {
-        let vertices = self.pos.len();
-        for idx in 0..vertices {
-            while self.vertex_needs_split(idx) {
-                self.split_vertex(idx);
+    /// Append vertices and faces from another builder
+    ///
+    /// Vertices are transformed by `xform`, and surface numbers are offset
+    /// by `surface`.
+    pub(crate) fn append(
+        &mut self,
+        other: MeshBuilder,
+        xform: Affine3A,
+        surface: u16,
+    ) -> Result<()> {
+        let offset = self.pos.len();
+        let count = offset + other.pos.len();
+        if count > MAX_VERTICES {
+            return Err(Error::TooManyVertices {
+                count,
+                max: MAX_VERTICES,
+            });
+        }
+        self.pos
+            .extend(other.pos.iter().map(|pos| xform.transform_point3(*pos)));
+        self.share.extend(other.share.iter().map(|v| v + offset));
+        merge_attr(&mut self.uv, offset, other.uv, count, Vec2::ZERO);
+        merge_attr(&mut self.color, offset, other.color, count, Vec4::ONE);
+        merge_attr(&mut self.joints, offset, other.joints, count, 0);
+        self.crease_vtx
+            .extend(other.crease_vtx.iter().map(|v| v + offset));
+        for (face, mat) in other.faces.iter().zip(other.face_materials) {
+            let mat =
+                mat.map(|m| self.material_index(&other.materials[m as usize]));
+            let vtx = face.vtx.map(|v| v + offset);
+            self.faces.push(Face::new(vtx, surface + face.surface));
+            self.face_materials.push(mat);
+        }
+        self.degenerate_faces += other.degenerate_faces;
+        Ok(())
+    }
+
+    /// Normalize V texture coordinates to range from 0 to 1
+    pub(crate) fn normalize_uv_height(&mut self) {
+        let height = self.uv.iter().map(|uv| uv.y).fold(0.0, f32::max);
+        if height > 0.0 {
+            for uv in self.uv.iter_mut() {
+                uv.y /= height;
             }
         }
-        self
     }
 
-    /// Check if a vertex needs splitting
-    fn vertex_needs_split(&self, idx: usize) -> bool {
-        let mut surface = None;
-        for face in &self.faces {
-            let surf = face.vertex_surface(idx);
-            if let (Some(s), Some(sf)) = (surf, surface) {
-                if s != sf {
-                    return true;
+    /// Map texture coordinates of faces to a disc around a hub vertex
+    ///
+    /// Vertices other than the hub are duplicated, and placed at `radius`
+    /// from the hub, by angle from their U coordinate.
+    pub(crate) fn map_uv_disc(
+        &mut self,
+        faces: Range<usize>,
+        hub: usize,
+        radius: f32,
+    ) -> Result<()> {
+        let Some(center) = self.uv(hub) else {
+            return Ok(());
+        };
+        let mut dups = HashMap::new();
+        for f in faces {
+            for i in 0..3 {
+                let v = self.faces[f].vtx[i];
+                if v == hub {
+                    continue;
                 }
+                let dup = match dups.get(&v) {
+                    Some(dup) => *dup,
+                    None => {
+                        let u = self.uv(v).unwrap_or_default().x;
+                        let (sin, cos) = (u * TAU).sin_cos();
+                        let uv = center + Vec2::new(cos, sin) * radius;
+                        let dup = self.push_uv_dup(v, uv)?;
+                        dups.insert(v, dup);
+                        dup
+                    }
+                };
+                self.faces[f].vtx[i] = dup;
             }
-            surface = surf;
         }
-        false
+        Ok(())
     }
 
-    /// Split one vertex
-    fn split_vertex(&mut self, idx: usize) {
-        let mut surfaces = Vec::with_capacity(8);
-        for face in &self.faces {
-            if let Some(surf) = face.vertex_surface(idx) {
-                if surfaces.is_empty() {
-                    surfaces.push((surf, idx));
-                } else if !surfaces.iter().any(|(s, _i)| surf == *s) {
-                    surfaces.push((surf, 0));
+    /// Split vertices of faces which wrap around U texture coordinates
+    ///
+    /// When a face spans more than half of the U range, its vertices with
+    /// low U are replaced with duplicates, offset by 1.
+    pub(crate) fn split_uv_seams(&mut self) -> Result<()> {
+        if self.uv.is_empty() {
+            return Ok(());
+        }
+        self.uv.resize(self.pos.len(), Vec2::ZERO);
+        let mut dups = HashMap::new();
+        for f in 0..self.faces.len() {
+            let us = self.faces[f].vtx.map(|v| self.uv[v].x);
+            let min = us.iter().copied().fold(f32::MAX, f32::min);
+            let max = us.iter().copied().fold(f32::MIN, f32::max);
+            if max - min <= 0.5 {
+                continue;
+            }
+            for (i, u) in us.iter().enumerate() {
+                let v = self.faces[f].vtx[i];
+                if *u < 0.5 {
+                    let dup = match dups.get(&v) {
+                        Some(dup) => *dup,
+                        None => {
+                            let uv = self.uv[v] + Vec2::X;
+                            let dup = self.push_uv_dup(v, uv)?;
+                            dups.insert(v, dup);
+                            dup
+                        }
+                    };
+                    self.faces[f].vtx[i] = dup;
                 }
             }
         }
-        let pos = self.pos[idx];
-        for surface in &mut surfaces {
-            if surface.1 == 0 {
-                surface.1 = self.push_vtx(pos);
+        Ok(())
+    }
+
+    /// Set crease angle (radians)
+    ///
+    /// When building, vertices are split wherever the angle between
+    /// adjacent faces exceeds this.
+    pub fn set_crease_angle(&mut self, angle: f32) {
+        self.crease_angle = Some(angle);
+    }
+
+    /// Weld vertices closer than `epsilon`
+    ///
+    /// Only vertices with matching texture coordinates, colors and skeleton
+    /// joints are welded.  This is done before splitting vertices between surfaces, so
+    /// flat shading is not affected.  Faces which become degenerate are
+    /// removed when building.  Returns the number of vertices removed.
+    ///
+    /// # Panics
+    ///
+    /// - If `epsilon` is not positive and finite
+    pub fn weld(&mut self, epsilon: f32) -> usize {
+        assert!(epsilon > 0.0 && epsilon.is_finite());
+        if !self.uv.is_empty() {
+            self.uv.resize(self.pos.len(), Vec2::ZERO);
+        }
+        if !self.color.is_empty() {
+            self.color.resize(self.pos.len(), Vec4::ONE);
+        }
+        if !self.joints.is_empty() {
+            self.joints.resize(self.pos.len(), 0);
+        }
+        let cell = |p: Vec3| (p / epsilon).floor().as_ivec3();
+        let mut grid = HashMap::<IVec3, Vec<usize>>::new();
+        let mut remap = Vec::with_capacity(self.pos.len());
+        let mut kept = Vec::with_capacity(self.pos.len());
+        for v in 0..self.pos.len() {
+            let c = cell(self.pos[v]);
+            let mut found = None;
+            'search: for x in -1..=1 {
+                for y in -1..=1 {
+                    for z in -1..=1 {
+                        let cands = grid.get(&(c + IVec3::new(x, y, z)));
+                        for &w in cands.into_iter().flatten() {
+                            if self.can_weld(v, w, epsilon) {
+                                found = Some(w);
+                                break 'search;
+                            }
+                        }
+                    }
+                }
+            }
+            match found {
+                Some(w) => remap.push(remap[w]),
+                None => {
+                    remap.push(kept.len());
+                    kept.push(v);
+                    grid.entry(c).or_default().push(v);
+                }
+            }
+        }
+        let removed = self.pos.len() - kept.len();
+        if removed > 0 {
+            self.pos = kept.iter().map(|v| self.pos[*v]).collect();
+            self.share = kept.iter().map(|v| remap[self.share[*v]]).collect();
+            if !self.uv.is_empty() {
+                self.uv = kept.iter().map(|v| self.uv[*v]).collect();
+            }
+            if !self.color.is_empty() {
+                self.color = kept.iter().map(|v| self.color[*v]).collect();
+            }
+            if !self.joints.is_empty() {
+                self.joints = kept.iter().map(|v| self.joints[*v]).collect();
+            }
+            self.crease_vtx =
+                self.crease_vtx.iter().map(|v| remap[*v]).collect();
+            for face in &mut self.faces {
+                face.vtx = face.vtx.map(|v| remap[v]);
             }
         }
-        for face in &mut self.faces {
-            if let Some(surf) = face.vertex_surface(idx) {
-                if let Some(i) =
-                    surfaces.iter().find_map(|(s, i)| (surf == *s).then_some(i))
-                {
-                    if *i != idx {
-                        face.split_vertex(idx, *i);
+        removed
+    }
+
+    /// Check if two vertices can be welded
+    fn can_weld(&self, v0: usize, v1: usize, epsilon: f32) -> bool {
+        self.pos[v0].distance(self.pos[v1]) <= epsilon
+            && self.uv.get(v0) == self.uv.get(v1)
+            && self.color.get(v0) == self.color.get(v1)
+            && self.joints.get(v0) == self.joints.get(v1)
+            && self.crease_vtx.contains(&v0) == self.crease_vtx.contains(&v1)
+    }
+
+    /// Build the mesh
+    ///
+    /// Splitting non-smooth vertices can push the vertex count over
+    /// 65536, which returns an error.
+    pub fn build(mut self) -> Result<Mesh> {
+        self.remove_degenerate_faces();
+        if let Some(angle) = self.crease_angle {
+            self.split_creases(angle)?;
+        }
+        Mesh::new(self.split_vertices()?)
+    }
+
+    /// Remove degenerate faces (zero area or repeated vertex)
+    fn remove_degenerate_faces(&mut self) {
+        let faces = std::mem::take(&mut self.faces);
+        let materials = std::mem::take(&mut self.face_materials);
+        for (face, material) in faces.into_iter().zip(materials) {
+            if self.face_normal(&face).is_some() {
+                self.faces.push(face);
+                self.face_materials.push(material);
+            } else {
+                self.degenerate_faces += 1;
+            }
+        }
+    }
+
+    /// Get unit normal of a face (`None` if degenerate)
+    fn face_normal(&self, face: &Face) -> Option<Vec3> {
+        let [p0, p1, p2] = face.vtx.map(|v| self.pos[v]);
+        (p1 - p0).cross(p2 - p0).try_normalize()
+    }
+
+    /// Split vertices at creases
+    ///
+    /// Faces around each vertex are grouped across shared edges, unless
+    /// the angle between them exceeds `angle`.  Each group after the
+    /// first gets a new vertex.
+    fn split_creases(&mut self, angle: f32) -> Result<()> {
+        let min_dot = angle.cos();
+        let normals: Vec<_> =
+            self.faces.iter().map(|f| self.face_normal(f)).collect();
+        let mut vfaces = vec![Vec::new(); self.pos.len()];
+        for (f, face) in self.faces.iter().enumerate() {
+            for v in face.vtx {
+                vfaces[v].push(f);
+            }
+        }
+        for (v, faces) in vfaces.iter().enumerate() {
+            let is_smooth = |f0: usize, f1: usize| {
+                let shared = self.faces[f0]
+                    .vtx
+                    .iter()
+                    .filter(|i| self.faces[f1].vtx.contains(i))
+                    .count();
+                shared == 2
+                    && match (normals[f0], normals[f1]) {
+                        (Some(n0), Some(n1)) => n0.dot(n1) >= min_dot,
+                        _ => true,
+                    }
+            };
+            // flood fill groups of faces around the vertex
+            let mut groups = vec![None; faces.len()];
+            let mut n_groups = 0;
+            for i in 0..faces.len() {
+                if groups[i].is_some() {
+                    continue;
+                }
+                groups[i] = Some(n_groups);
+                let mut stack = vec![i];
+                while let Some(j) = stack.pop() {
+                    for k in 0..faces.len() {
+                        if groups[k].is_none() && is_smooth(faces[j], faces[k])
+                        {
+                            groups[k] = Some(n_groups);
+                            stack.push(k);
+                        }
+                    }
+                }
+                n_groups += 1;
+            }
+            let mut vids = vec![v];
+            for _ in 1..n_groups {
+                vids.push(self.copy_vtx(v)?);
+            }
+            for (f, group) in faces.iter().zip(groups) {
+                // unwrap note: all faces were assigned a group
+                let vid = vids[group.unwrap()];
+                if vid != v {
+                    self.faces[*f].split_vertex(v, vid);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Split all non-smooth vertices
+    ///
+    /// Each vertex is copied for every surface after the first one which
+    /// shares it, in order of faces.
+    fn split_vertices(mut self) -> Result<Self> {
+        let vertices = self.pos.len();
+        let mut vfaces = vec![Vec::new(); vertices];
+        for (f, face) in self.faces.iter().enumerate() {
+            for (i, v) in face.vtx.iter().enumerate() {
+                if !face.vtx[..i].contains(v) {
+                    vfaces[*v].push(f);
+                }
+            }
+        }
+        let mut surfaces = Vec::with_capacity(8);
+        for (idx, faces) in vfaces.iter().enumerate() {
+            surfaces.clear();
+            for f in faces {
+                let surf = self.faces[*f].surface;
+                let vid = match surfaces.iter().find(|(s, _)| *s == surf) {
+                    Some((_, vid)) => *vid,
+                    None => {
+                        let vid = if surfaces.is_empty() {
+                            idx
+                        } else {
+                            let vid = self.copy_vtx(idx)?;
+                            // normals are still kept apart by surface
+                            self.share[vid] = self.share[idx];
+                            vid
+                        };
+                        surfaces.push((surf, vid));
+                        vid
                     }
+                };
+                if vid != idx {
+                    self.faces[*f].split_vertex(idx, vid);
                 }
             }
         }
+        Ok(self)
     }
 
     /// Calculate normals for all vertices
-    fn build_normals(&self) -> Vec<Vec3> {
+    ///
+    /// Degenerate faces are skipped.  Vertices without a valid normal use
+    /// the average normal of neighboring vertices, or +Y.
+    fn build_normals(&self, report: &mut BuildReport) -> Vec<Vec3> {
         let vertices = self.pos.len();
-        let mut norm = vec![Vec3::default(); vertices];
+        let mut norm =
+            accumulate_normals(&self.pos, self.faces.iter().map(|f| f.vtx));
+        self.share_normals(&mut norm);
+        let mut norm = normalize_all(&norm);
+        let fallback: Vec<_> = (0..vertices)
+            .filter(|v| norm[*v].is_none())
+            .map(|v| (v, self.neighbor_normal(&norm, v)))
+            .collect();
+        report.fallback_normals = fallback.len();
+        for (v, n) in fallback {
+            norm[v] = Some(n);
+        }
+        // unwrap note: all None values were replaced with fallbacks
+        norm.into_iter().map(|n| n.unwrap()).collect()
+    }
+
+    /// Share normals of UV seam duplicates on the same surface
+    fn share_normals(&self, norm: &mut [Vec3]) {
+        if self.share.iter().enumerate().all(|(v, s)| v == *s) {
+            return;
+        }
+        let mut surface = vec![0; self.pos.len()];
         for face in &self.faces {
-            let vtx = [face.vtx[0], face.vtx[1], face.vtx[2]];
-            let pos = [self.pos[vtx[0]], self.pos[vtx[1]], self.pos[vtx[2]]];
-            let trin = (pos[0] - pos[1]).cross(pos[0] - pos[2]).normalize();
-            let a0 = (pos[1] - pos[0]).angle_between(pos[2] - pos[0]);
-            norm[vtx[0]] += trin * a0;
-            let a1 = (pos[2] - pos[1]).angle_between(pos[0] - pos[1]);
-            norm[vtx[1]] += trin * a1;
-            let a2 = (pos[0] - pos[2]).angle_between(pos[1] - pos[2]);
-            norm[vtx[2]] += trin * a2;
+            for v in face.vtx {
+                surface[v] = face.surface;
+            }
+        }
+        let mut sums = HashMap::new();
+        for (v, n) in norm.iter().enumerate() {
+            *sums
+                .entry((self.share[v], surface[v]))
+                .or_insert(Vec3::ZERO) += *n;
+        }
+        for (v, n) in norm.iter_mut().enumerate() {
+            *n = sums[&(self.share[v], surface[v])];
         }
-        norm.iter().map(|n| n.normalize()).collect()
+    }
+
+    /// Get average normal of neighboring vertices (or +Y)
+    fn neighbor_normal(&self, norm: &[Option<Vec3>], v: usize) -> Vec3 {
+        self.faces
+            .iter()
+            .filter(|f| f.vtx.contains(&v))
+            .flat_map(|f| f.vtx)
+            .filter_map(|n| norm[n])
+            .sum::<Vec3>()
+            .try_normalize()
+            .unwrap_or(Vec3::Y)
     }
 
     /// Build `Vec` of indices for all faces
-    fn build_indices(&self) -> Vec<Vertex> {
+    fn build_indices(&self) -> Result<Vec<Vertex>> {
         let mut indices = Vec::with_capacity(self.faces.len() * 3);
         for face in &self.faces {
-            indices.push(face.vtx[0].into());
-            indices.push(face.vtx[1].into());
-            indices.push(face.vtx[2].into());
+            for vtx in face.vtx {
+                indices.push(Vertex::try_from(vtx)?);
+            }
         }
-        indices
+        Ok(indices)
+    }
+}
+
+impl MeshDiff {
+    /// Check if differences exceed a tolerance
+    ///
+    /// Any change in vertex or face counts is always significant.
+    pub fn exceeds(&self, tolerance: f32) -> bool {
+        self.vertices.0 != self.vertices.1
+            || self.faces.0 != self.faces.1
+            || self.min_delta.abs().max_element() > tolerance
+            || self.max_delta.abs().max_element() > tolerance
+            || self.max_deviation > tolerance
     }
 }
 
 impl Mesh {
-    /// Create a new mesh builder
+    /// Create a new mesh builder, with capacity for 1024 faces
+    ///
+    /// This can be used to make a mesh directly from vertices and faces,
+    /// without a [Husk].
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Face, Mesh};
+    /// # use glam::Vec3;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut builder = Mesh::builder();
+    /// let v0 = builder.push_vtx(Vec3::new(0.0, 0.0, 0.0))?;
+    /// let v1 = builder.push_vtx(Vec3::new(1.0, 0.0, 0.0))?;
+    /// let v2 = builder.push_vtx(Vec3::new(0.0, 1.0, 0.0))?;
+    /// builder.try_push_face(Face::new([v0, v1, v2], 0))?;
+    /// let mesh = builder.build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [husk]: struct.Husk.html
     pub fn builder() -> MeshBuilder {
         MeshBuilder::with_capacity(1024)
     }
 
+    /// Create a mesh from vertex attributes and indices
+    pub(crate) fn from_parts(
+        pos: Vec<Vec3>,
+        norm: Vec<Vec3>,
+        indices: Vec<Vertex>,
+    ) -> Self {
+        debug_assert_eq!(pos.len(), norm.len());
+        Mesh {
+            pos,
+            norm,
+            uv: Vec::new(),
+            color: Vec::new(),
+            materials: Vec::new(),
+            face_materials: Vec::new(),
+            surfaces: Vec::new(),
+            indices,
+            ao: Vec::new(),
+            joints: Vec::new(),
+            skeleton: None,
+            report: BuildReport::default(),
+        }
+    }
+
     /// Create a new mesh
-    fn new(builder: MeshBuilder) -> Self {
-        let norm = builder.build_normals();
-        let indices = builder.build_indices();
+    fn new(builder: MeshBuilder) -> Result<Self> {
+        let mut report = BuildReport {
+            degenerate_faces: builder.degenerate_faces,
+            ..Default::default()
+        };
+        let norm = builder.build_normals(&mut report);
+        if report != BuildReport::default() {
+            log::warn!(
+                "{} degenerate faces, {} fallback normals",
+                report.degenerate_faces,
+                report.fallback_normals
+            );
+        }
+        let indices = builder.build_indices()?;
+        let mut uv = builder.uv;
+        if !uv.is_empty() {
+            uv.resize(builder.pos.len(), Vec2::ZERO);
+        }
+        let mut color = builder.color;
+        if !color.is_empty() {
+            color.resize(builder.pos.len(), Vec4::ONE);
+        }
+        let mut joints = builder.joints;
+        if !joints.is_empty() {
+            joints.resize(builder.pos.len(), 0);
+        }
+        let face_materials = if builder.materials.is_empty() {
+            Vec::new()
+        } else {
+            builder.face_materials
+        };
+        let surfaces = builder.faces.iter().map(|f| f.surface).collect();
         let pos = builder.pos;
-        Mesh { pos, norm, indices }
+        Ok(Mesh {
+            pos,
+            norm,
+            uv,
+            color,
+            materials: builder.materials,
+            face_materials,
+            surfaces,
+            indices,
+            ao: Vec::new(),
+            joints,
+            skeleton: None,
+            report,
+        })
+    }
+
+    /// Make a copy with uniform scale and rotation applied
+    pub(crate) fn transformed(&self, scale: f32, rotation: Quat) -> Self {
+        Mesh {
+            pos: self.pos.iter().map(|p| rotation * (*p * scale)).collect(),
+            norm: self.norm.iter().map(|n| rotation * *n).collect(),
+            uv: self.uv.clone(),
+            color: self.color.clone(),
+            materials: self.materials.clone(),
+            face_materials: self.face_materials.clone(),
+            surfaces: self.surfaces.clone(),
+            indices: self.indices.clone(),
+            ao: self.ao.clone(),
+            joints: self.joints.clone(),
+            skeleton: self
+                .skeleton
+                .as_ref()
+                .map(|s| s.transformed(scale, rotation)),
+            report: self.report,
+        }
+    }
+
+    /// Merge another mesh into this one
+    ///
+    /// Indices of the other mesh are offset by the vertex count of this one.
+    /// If only one mesh has texture coordinates, colors or ambient occlusion,
+    /// the other is filled with defaults.  Materials are matched by name.
+    /// The skeleton and joints are not kept.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # use glam::Vec3;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut torso = Husk::new();
+    /// torso.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+    /// torso.ring(Ring::default().axis(Vec3::new(0.0, 2.0, 0.0)))?;
+    /// let mut arm = Husk::new();
+    /// arm.ring(Ring::default().spoke(0.2).spoke(0.2).spoke(0.2))?;
+    /// arm.ring(Ring::default().axis(Vec3::new(1.0, 0.0, 0.0)))?;
+    /// let mut mesh = torso.into_mesh()?;
+    /// mesh.merge(arm.into_mesh()?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn merge(&mut self, other: Mesh) -> Result<()> {
+        let offset = self.pos.len();
+        let count = offset + other.pos.len();
+        if count > MAX_VERTICES {
+            return Err(Error::TooManyVertices {
+                count,
+                max: MAX_VERTICES,
+            });
+        }
+        let indices = other
+            .indices
+            .iter()
+            .map(|v| Vertex::try_from(usize::from(v.0) + offset))
+            .collect::<Result<Vec<_>>>()?;
+        let faces = self.indices.len() / 3;
+        let other_faces = other.indices.len() / 3;
+        if !other.materials.is_empty() {
+            self.face_materials.resize(faces, None);
+            for f in 0..other_faces {
+                let mat = other.face_material(f).map(|m| {
+                    let name = &other.materials[m];
+                    match self.materials.iter().position(|n| n == name) {
+                        Some(m) => m,
+                        None => {
+                            self.materials.push(name.clone());
+                            self.materials.len() - 1
+                        }
+                    }
+                });
+                // unwrap note: more materials than that is unreasonable
+                let mat = mat.map(|m| u16::try_from(m).unwrap());
+                self.face_materials.push(mat);
+            }
+        } else if !self.materials.is_empty() {
+            self.face_materials.resize(faces + other_faces, None);
+        }
+        if self.surfaces.len() == faces && other.surfaces.len() == other_faces {
+            // keep surfaces distinct, for smooth shading groups
+            let base = self.surfaces.iter().max().map_or(0, |s| s + 1);
+            self.surfaces
+                .extend(other.surfaces.iter().map(|s| base + s));
+        } else {
+            self.surfaces.clear();
+        }
+        merge_attr(&mut self.uv, offset, other.uv, count, Vec2::ZERO);
+        merge_attr(&mut self.color, offset, other.color, count, Vec4::ONE);
+        merge_attr(&mut self.ao, offset, other.ao, count, 1.0);
+        self.pos.extend(other.pos);
+        self.norm.extend(other.norm);
+        self.indices.extend(indices);
+        self.report.degenerate_faces += other.report.degenerate_faces;
+        self.report.fallback_normals += other.report.fallback_normals;
+        self.joints.clear();
+        self.skeleton = None;
+        Ok(())
+    }
+
+    /// Apply an affine transform
+    ///
+    /// Normals are transformed by the inverse-transpose, so non-uniform
+    /// scale keeps them perpendicular to faces.  A mirroring transform
+    /// reverses triangle winding, so faces still point outward.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # use glam::{Affine3A, Quat, Vec3};
+    /// # use std::f32::consts::FRAC_PI_2;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.ring(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+    /// husk.ring(Ring::default().axis(Vec3::new(0.0, 2.0, 0.0)))?;
+    /// let mut mesh = husk.into_mesh()?;
+    /// // Y-up to Z-up
+    /// mesh.transform(Affine3A::from_rotation_x(FRAC_PI_2));
+    /// mesh.translate(Vec3::new(1.0, 0.0, 0.0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transform(&mut self, xform: Affine3A) {
+        let normal_mat = xform.matrix3.inverse().transpose();
+        for p in &mut self.pos {
+            *p = xform.transform_point3(*p);
+        }
+        for n in &mut self.norm {
+            *n = (normal_mat * *n).try_normalize().unwrap_or(*n);
+        }
+        if xform.matrix3.determinant() < 0.0 {
+            self.swap_winding();
+        }
+        if let Some(skeleton) = &mut self.skeleton {
+            skeleton.transform(xform);
+        }
+    }
+
+    /// Swap winding order of all faces
+    fn swap_winding(&mut self) {
+        for tri in self.indices.chunks_exact_mut(3) {
+            tri.swap(1, 2);
+        }
+    }
+
+    /// Flip winding order of all faces, and invert normals
+    ///
+    /// This turns an [inside out] mesh right side out.
+    ///
+    /// [inside out]: struct.Mesh.html#method.is_inside_out
+    pub fn flip_winding(&mut self) {
+        self.swap_winding();
+        for n in &mut self.norm {
+            *n = -*n;
+        }
+    }
+
+    /// Recompute vertex normals from faces
+    ///
+    /// This is useful after changing positions.  Normals are angle-weighted,
+    /// as when building.  Vertices at the same position which currently
+    /// have the same normal (such as texture seam duplicates) are treated
+    /// as one; vertices split for flat shading or creases are kept apart.
+    /// A vertex without any valid face keeps its normal.
+    pub fn recompute_normals(&mut self) {
+        let faces = self
+            .indices
+            .chunks_exact(3)
+            .map(|tri| [0, 1, 2].map(|i| usize::from(tri[i].0)));
+        let norm = accumulate_normals(&self.pos, faces);
+        let keys: Vec<_> = self
+            .pos
+            .iter()
+            .zip(&self.norm)
+            .map(|(p, n)| {
+                (
+                    p.to_array().map(f32::to_bits),
+                    n.to_array().map(f32::to_bits),
+                )
+            })
+            .collect();
+        let mut sums = HashMap::new();
+        for (key, n) in keys.iter().zip(&norm) {
+            *sums.entry(key).or_insert(Vec3::ZERO) += *n;
+        }
+        for (key, n) in keys.iter().zip(&mut self.norm) {
+            if let Some(sum) = sums[key].try_normalize() {
+                *n = sum;
+            }
+        }
+    }
+
+    /// Translate all vertices
+    pub fn translate(&mut self, translation: Vec3) {
+        self.transform(Affine3A::from_translation(translation));
+    }
+
+    /// Rotate all vertices around the origin
+    pub fn rotate(&mut self, rotation: Quat) {
+        self.transform(Affine3A::from_quat(rotation));
+    }
+
+    /// Scale all vertices from the origin
+    ///
+    /// # Panics
+    ///
+    /// If any component of `scale` is zero.
+    pub fn scale(&mut self, scale: Vec3) {
+        assert!(scale.cmpne(Vec3::ZERO).all(), "zero scale");
+        self.transform(Affine3A::from_scale(scale));
     }
 
     /// Get slice of all vertex positions
@@ -226,12 +1151,144 @@ impl Mesh {
         &self.norm[..]
     }
 
+    /// Get slice of all vertex texture coordinates
+    ///
+    /// This is empty for meshes without texture coordinates.
+    pub fn uvs(&self) -> &[Vec2] {
+        &self.uv[..]
+    }
+
+    /// Get slice of all vertex colors (RGBA)
+    ///
+    /// This is empty for meshes without colors.
+    pub fn colors(&self) -> &[Vec4] {
+        &self.color[..]
+    }
+
+    /// Get slice of material names
+    ///
+    /// This is empty for meshes without materials.
+    pub fn materials(&self) -> &[String] {
+        &self.materials[..]
+    }
+
+    /// Get material of a face (index into [Mesh::materials])
+    pub fn face_material(&self, face: usize) -> Option<usize> {
+        self.face_materials
+            .get(face)
+            .copied()
+            .flatten()
+            .map(usize::from)
+    }
+
+    /// Get surface of a face
+    ///
+    /// Vertices are split between surfaces, so faces on a surface can be
+    /// smoothly shaded together.
+    pub fn face_surface(&self, face: usize) -> Option<u16> {
+        self.surfaces.get(face).copied()
+    }
+
+    /// Get surfaces of all faces
+    ///
+    /// There is one surface for each triangle, in the same order as
+    /// [indices].  This is empty if surfaces are unknown, such as for an
+    /// imported mesh.
+    ///
+    /// [indices]: struct.Mesh.html#method.indices
+    pub fn surfaces(&self) -> &[u16] {
+        &self.surfaces
+    }
+
+    /// Get indices of triangles, grouped by material
+    ///
+    /// Groups without a material are first, then in order of material.
+    pub(crate) fn material_groups(&self) -> Vec<(Option<usize>, Vec<Vertex>)> {
+        if self.materials.is_empty() {
+            return vec![(None, self.indices.clone())];
+        }
+        let mut groups: Vec<(Option<usize>, Vec<Vertex>)> = Vec::new();
+        for (face, tri) in self.indices.chunks_exact(3).enumerate() {
+            let material = self.face_material(face);
+            match groups.iter_mut().find(|(m, _)| *m == material) {
+                Some((_, indices)) => indices.extend_from_slice(tri),
+                None => groups.push((material, tri.to_vec())),
+            }
+        }
+        groups.sort_by_key(|(m, _)| *m);
+        groups
+    }
+
     /// Get slice of vertex/normal indices for all triangles
     pub fn indices(&self) -> &[Vertex] {
         &self.indices[..]
     }
 
+    /// Get report of problems found while building
+    pub fn build_report(&self) -> BuildReport {
+        self.report
+    }
+
+    /// Get skeleton for skinning
+    ///
+    /// Only meshes built by a [Husk] with a skeleton have one.
+    ///
+    /// [husk]: struct.Husk.html
+    pub fn skeleton(&self) -> Option<&Skeleton> {
+        self.skeleton.as_ref()
+    }
+
+    /// Get skeleton joint of each vertex
+    ///
+    /// These are indices into [Skeleton::joints], with a weight of 1 for
+    /// each vertex.  This is empty if the mesh has no skeleton.
+    ///
+    /// [Skeleton::joints]: struct.Skeleton.html#method.joints
+    pub fn joints(&self) -> &[u16] {
+        &self.joints
+    }
+
+    /// Get slice of vertex ambient occlusion
+    ///
+    /// Values are from 0 (fully occluded) to 1 (unoccluded).  This is empty
+    /// unless baked with [Mesh::bake_ao].
+    pub fn ambient_occlusion(&self) -> &[f32] {
+        &self.ao[..]
+    }
+
+    /// Compare with another (newer) mesh
+    ///
+    /// Vertex deviation is only checked for indices present in both meshes.
+    pub fn diff(&self, other: &Mesh) -> MeshDiff {
+        let max_deviation = self
+            .pos
+            .iter()
+            .zip(&other.pos)
+            .map(|(p0, p1)| p0.distance(*p1))
+            .fold(0.0, f32::max);
+        let (min_delta, max_delta) =
+            if self.pos.is_empty() || other.pos.is_empty() {
+                (Vec3::ZERO, Vec3::ZERO)
+            } else {
+                (
+                    other.pos_min() - self.pos_min(),
+                    other.pos_max() - self.pos_max(),
+                )
+            };
+        MeshDiff {
+            vertices: (self.pos.len(), other.pos.len()),
+            faces: (self.indices.len() / 3, other.indices.len() / 3),
+            min_delta,
+            max_delta,
+            max_deviation,
+        }
+    }
+
     /// Get minimum position
+    ///
+    /// # Panics
+    ///
+    /// - If the mesh has no vertices
     pub fn pos_min(&self) -> Vec3 {
         self.positions()
             .iter()
@@ -241,6 +1298,10 @@ impl Mesh {
     }
 
     /// Get maximum position
+    ///
+    /// # Panics
+    ///
+    /// - If the mesh has no vertices
     pub fn pos_max(&self) -> Vec3 {
         self.positions()
             .iter()
@@ -249,3 +1310,428 @@ impl Mesh {
             .unwrap()
     }
 }
+
+/// Get angle-weighted normal of a face at each of its vertices
+///
+/// Returns `None` for a degenerate face.
+fn corner_normals(pos: &[Vec3], vtx: [usize; 3]) -> Option<[Vec3; 3]> {
+    let p = vtx.map(|v| pos[v]);
+    let trin = (p[0] - p[1]).cross(p[0] - p[2]).try_normalize()?;
+    let a0 = (p[1] - p[0]).angle_between(p[2] - p[0]);
+    let a1 = (p[2] - p[1]).angle_between(p[0] - p[1]);
+    let a2 = (p[0] - p[2]).angle_between(p[1] - p[2]);
+    Some([trin * a0, trin * a1, trin * a2])
+}
+
+/// Add corner normals of faces to the sum at each vertex
+fn add_corner_normals(
+    norm: &mut [Vec3],
+    faces: impl Iterator<Item = ([usize; 3], Option<[Vec3; 3]>)>,
+) {
+    for (vtx, corners) in faces {
+        if let Some(corners) = corners {
+            for (v, n) in vtx.iter().zip(corners) {
+                norm[*v] += n;
+            }
+        }
+    }
+}
+
+/// Accumulate angle-weighted face normals at each vertex
+///
+/// Degenerate faces are skipped.  The sums are not normalized.
+#[cfg(not(all(feature = "rayon", not(target_arch = "wasm32"))))]
+fn accumulate_normals(
+    pos: &[Vec3],
+    faces: impl Iterator<Item = [usize; 3]>,
+) -> Vec<Vec3> {
+    let mut norm = vec![Vec3::default(); pos.len()];
+    add_corner_normals(
+        &mut norm,
+        faces.map(|vtx| (vtx, corner_normals(pos, vtx))),
+    );
+    norm
+}
+
+/// Accumulate angle-weighted face normals at each vertex (in parallel)
+///
+/// Degenerate faces are skipped.  The sums are not normalized.  Corner
+/// normals of each face are calculated in parallel, then added in face
+/// order, so the sums are identical to the serial version.  Rayon is not
+/// used on `wasm32`, so normals are accumulated serially there.
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+fn accumulate_normals(
+    pos: &[Vec3],
+    faces: impl Iterator<Item = [usize; 3]>,
+) -> Vec<Vec3> {
+    use rayon::prelude::*;
+    let faces: Vec<[usize; 3]> = faces.collect();
+    let corners: Vec<_> = faces
+        .par_iter()
+        .map(|vtx| corner_normals(pos, *vtx))
+        .collect();
+    let mut norm = vec![Vec3::default(); pos.len()];
+    add_corner_normals(&mut norm, faces.into_iter().zip(corners));
+    norm
+}
+
+/// Normalize vertex normal sums (`None` for zero-length)
+#[cfg(not(all(feature = "rayon", not(target_arch = "wasm32"))))]
+fn normalize_all(norm: &[Vec3]) -> Vec<Option<Vec3>> {
+    norm.iter().map(|n| n.try_normalize()).collect()
+}
+
+/// Normalize vertex normal sums (in parallel)
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+fn normalize_all(norm: &[Vec3]) -> Vec<Option<Vec3>> {
+    use rayon::prelude::*;
+    norm.par_iter().map(|n| n.try_normalize()).collect()
+}
+
+/// Merge a vertex attribute, which may be empty in either mesh
+fn merge_attr<T: Copy>(
+    attr: &mut Vec<T>,
+    offset: usize,
+    other: Vec<T>,
+    count: usize,
+    default: T,
+) {
+    if attr.is_empty() && other.is_empty() {
+        return;
+    }
+    attr.resize(offset, default);
+    attr.extend(other);
+    attr.resize(count, default);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::primitives::cube_builder;
+
+    #[test]
+    fn degenerate_face() {
+        let mut builder = Mesh::builder();
+        builder.push_vtx(Vec3::new(0.0, 0.0, 0.0)).unwrap();
+        builder.push_vtx(Vec3::new(1.0, 0.0, 0.0)).unwrap();
+        builder.push_vtx(Vec3::new(0.0, 0.0, 1.0)).unwrap();
+        builder.push_vtx(Vec3::new(2.0, 0.0, 0.0)).unwrap();
+        builder.push_face(Face::new([0, 2, 1], 0));
+        // collinear face
+        builder.push_face(Face::new([0, 1, 3], 0));
+        // repeated vertex
+        builder.push_face(Face::new([0, 2, 2], 0));
+        let mesh = builder.build().unwrap();
+        assert_eq!(mesh.indices().len(), 3);
+        for n in mesh.normals() {
+            assert!(n.is_finite());
+            assert!((n.length() - 1.0).abs() < 1e-6);
+        }
+        // vertex 3 is only on the degenerate face
+        assert_eq!(mesh.normals()[3], Vec3::Y);
+        assert_eq!(
+            mesh.build_report(),
+            BuildReport {
+                degenerate_faces: 2,
+                fallback_normals: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn try_push_face() {
+        let mut builder = cube_builder(false);
+        let faces = builder.face_count();
+        assert!(matches!(
+            builder.try_push_face(Face::new([0, 1, 8], 0)),
+            Err(Error::InvalidVertexIndex { index: 8, len: 8 })
+        ));
+        assert!(matches!(
+            builder.try_push_face(Face::new([2, 1, 2], 0)),
+            Err(Error::RepeatedVertexIndex(2))
+        ));
+        assert_eq!(builder.face_count(), faces);
+        builder.try_push_face(Face::new([0, 1, 7], 0)).unwrap();
+        assert_eq!(builder.face_count(), faces + 1);
+    }
+
+    #[test]
+    fn builder_counts() {
+        let mut builder = MeshBuilder::with_capacity(12);
+        assert!(builder.is_empty());
+        let v0 = builder.push_vtx(Vec3::ZERO).unwrap();
+        let v1 = builder.push_vtx(Vec3::X).unwrap();
+        let v2 = builder.push_vtx(Vec3::Y).unwrap();
+        assert_eq!(builder.len(), 3);
+        assert_eq!(builder.vertex(v1), Vec3::X);
+        builder.push_face(Face::new([v0, v1, v2], 0));
+        assert_eq!(builder.face_count(), 1);
+        let mesh = builder.build().unwrap();
+        assert_eq!(mesh.surfaces(), [0]);
+        for n in mesh.normals() {
+            assert_eq!(*n, Vec3::Z);
+        }
+    }
+
+    #[test]
+    fn weld() {
+        let mut builder = Mesh::builder();
+        builder.push_vtx(Vec3::new(0.0, 0.0, 0.0)).unwrap();
+        builder.push_vtx(Vec3::new(1.0, 0.0, 0.0)).unwrap();
+        builder.push_vtx(Vec3::new(0.0, 0.0, 1.0)).unwrap();
+        // near vertex 0, but in another grid cell
+        builder.push_vtx(Vec3::new(-0.001, 0.0, 0.0)).unwrap();
+        builder.push_vtx(Vec3::new(0.0, 0.0, -1.0)).unwrap();
+        builder.push_face(Face::new([0, 2, 1], 0));
+        builder.push_face(Face::new([3, 1, 4], 0));
+        let mut welded = builder.clone();
+        assert_eq!(welded.weld(0.01), 1);
+        let mesh = welded.build().unwrap();
+        assert_eq!(mesh.positions().len(), 4);
+        assert_eq!(mesh.indices()[3].0, 0);
+        // different colors are not welded
+        builder.set_color(3, Vec4::new(1.0, 0.0, 0.0, 1.0));
+        assert_eq!(builder.weld(0.01), 0);
+    }
+
+    #[test]
+    fn merge() {
+        let mut mesh = cube_builder(false).build().unwrap();
+        let mut other = cube_builder(false);
+        other.set_material(Some("red"));
+        other.set_color(0, Vec4::new(1.0, 0.0, 0.0, 1.0));
+        for f in cube_builder(false).faces {
+            other.push_face(f);
+        }
+        let other = other.build().unwrap();
+        let (n_vtx, n_idx) = (mesh.positions().len(), mesh.indices().len());
+        mesh.merge(other).unwrap();
+        assert_eq!(mesh.positions().len(), n_vtx * 2);
+        assert_eq!(mesh.normals().len(), n_vtx * 2);
+        assert_eq!(mesh.indices().len(), n_idx * 3);
+        assert_eq!(mesh.indices()[n_idx].0 as usize, n_vtx);
+        assert_eq!(mesh.colors().len(), n_vtx * 2);
+        assert_eq!(mesh.colors()[0], Vec4::ONE);
+        assert_eq!(mesh.colors()[n_vtx], Vec4::new(1.0, 0.0, 0.0, 1.0));
+        assert_eq!(mesh.materials(), ["red"]);
+        assert_eq!(mesh.face_material(0), None);
+        assert_eq!(mesh.face_material(n_idx / 3 * 2), Some(0));
+        assert_eq!(mesh.face_surface(n_idx / 3), Some(1));
+        assert!(mesh.uvs().is_empty());
+    }
+
+    #[test]
+    fn merge_too_many() {
+        let mut builder = Mesh::builder();
+        for _ in 0..MAX_VERTICES {
+            builder.push_vtx(Vec3::ZERO).unwrap();
+        }
+        let mut mesh = builder.build().unwrap();
+        let other = cube_builder(false).build().unwrap();
+        assert!(matches!(
+            mesh.merge(other),
+            Err(Error::TooManyVertices { count, .. }) if count == MAX_VERTICES + 8
+        ));
+        assert_eq!(mesh.positions().len(), MAX_VERTICES);
+    }
+
+    /// Flat-shaded unit cube
+    fn flat_cube() -> Mesh {
+        let mut builder = cube_builder(false);
+        builder.set_crease_angle(30.0_f32.to_radians());
+        builder.build().unwrap()
+    }
+
+    /// Check that normals are perpendicular to faces, pointing outward
+    fn check_face_normals(mesh: &Mesh) {
+        let center = (mesh.pos_min() + mesh.pos_max()) * 0.5;
+        for tri in mesh.indices().chunks_exact(3) {
+            let [p0, p1, p2] = [0, 1, 2].map(|i| mesh.pos[tri[i].0 as usize]);
+            let face = (p1 - p0).cross(p2 - p0).normalize();
+            assert!(face.dot(p0 - center) > 0.0, "{face}");
+            for v in tri {
+                let norm = mesh.norm[v.0 as usize];
+                assert!((norm.length() - 1.0).abs() < 1e-6);
+                assert!(norm.dot(face) > 1.0 - 1e-6, "{norm} {face}");
+            }
+        }
+    }
+
+    #[test]
+    fn transform_rotate() {
+        let mut mesh = flat_cube();
+        mesh.rotate(Quat::from_rotation_y(90.0_f32.to_radians()));
+        assert!(mesh.pos_min().abs_diff_eq(Vec3::new(0.0, 0.0, -1.0), 1e-6));
+        assert!(mesh.pos_max().abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-6));
+        check_face_normals(&mesh);
+        let orig = flat_cube();
+        for (n0, n1) in orig.normals().iter().zip(mesh.normals()) {
+            let expected = Vec3::new(n0.z, n0.y, -n0.x);
+            assert!(n1.abs_diff_eq(expected, 1e-6), "{n1} {expected}");
+        }
+        mesh.translate(Vec3::new(0.0, 0.0, 1.0));
+        assert!(mesh.pos_min().abs_diff_eq(Vec3::ZERO, 1e-6));
+        assert!(mesh.pos_max().abs_diff_eq(Vec3::ONE, 1e-6));
+    }
+
+    #[test]
+    fn transform_scale() {
+        // non-uniform scale with shear, to tilt the faces
+        let mut mesh = flat_cube();
+        let shear = Affine3A::from_cols_array(&[
+            1.0, 0.0, 0.0, 0.5, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0,
+        ]);
+        mesh.transform(shear);
+        mesh.scale(Vec3::new(3.0, 1.0, 0.5));
+        assert!(mesh.pos_max().abs_diff_eq(Vec3::new(4.5, 1.0, 0.5), 1e-6));
+        check_face_normals(&mesh);
+        // mirror
+        mesh.scale(Vec3::new(-1.0, 1.0, 1.0));
+        assert!(mesh.pos_min().abs_diff_eq(Vec3::new(-4.5, 0.0, 0.0), 1e-6));
+        check_face_normals(&mesh);
+    }
+
+    #[test]
+    fn recompute_normals() {
+        let mut flat = cube_builder(false);
+        flat.set_crease_angle(30.0_f32.to_radians());
+        for builder in [cube_builder(false), flat] {
+            let expected = builder.clone().build().unwrap();
+            let mut mesh = builder.build().unwrap();
+            mesh.norm.iter_mut().for_each(|n| *n = -*n);
+            mesh.recompute_normals();
+            assert_eq!(mesh.normals(), expected.normals());
+        }
+        // texture seam duplicates still share normals
+        let mut husk = crate::Husk::new();
+        let ring = crate::Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        husk.ring(ring.spoke(1.0).spoke(1.0)).unwrap();
+        husk.ring(crate::Ring::default().scale(0.5)).unwrap();
+        husk.ring(crate::Ring::default().scale(1.5)).unwrap();
+        let mut mesh = husk.into_mesh().unwrap();
+        mesh.rotate(Quat::from_rotation_z(1.0));
+        let expected = mesh.normals().to_vec();
+        mesh.recompute_normals();
+        for (n0, n1) in expected.iter().zip(mesh.normals()) {
+            assert!(n0.abs_diff_eq(*n1, 1e-5), "{n0} {n1}");
+        }
+    }
+
+    #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+    #[test]
+    fn parallel_normals() {
+        let mut husk = crate::Husk::new();
+        let mut ring = crate::Ring::default().axis(Vec3::new(0.0, 0.1, 0.0));
+        for i in 0..128 {
+            ring = ring.spoke(1.0 + 0.1 * (i % 3) as f32);
+        }
+        husk.ring(ring).unwrap();
+        for i in 0..200 {
+            let scale = 1.0 + 0.2 * (i as f32 * 0.1).sin();
+            husk.ring(crate::Ring::default().scale(scale)).unwrap();
+        }
+        let mesh = husk.into_mesh().unwrap();
+        let faces: Vec<_> = mesh
+            .indices
+            .chunks_exact(3)
+            .map(|tri| [0, 1, 2].map(|i| usize::from(tri[i].0)))
+            .collect();
+        let norm = accumulate_normals(&mesh.pos, faces.iter().copied());
+        let mut serial = vec![Vec3::ZERO; mesh.pos.len()];
+        add_corner_normals(
+            &mut serial,
+            faces
+                .iter()
+                .map(|vtx| (*vtx, corner_normals(&mesh.pos, *vtx))),
+        );
+        assert_eq!(norm, serial);
+    }
+
+    #[test]
+    fn surfaces() {
+        let mut builder = Mesh::builder();
+        for i in 0..8 {
+            let [x, y, z] = [i & 1, (i >> 1) & 1, (i >> 2) & 1];
+            let pos = Vec3::new(x as f32, y as f32, z as f32);
+            builder.push_vtx(pos).unwrap();
+        }
+        for (s, quad) in [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let s = s as u16;
+            builder.push_face(Face::new([quad[0], quad[1], quad[2]], s));
+            builder.push_face(Face::new([quad[0], quad[2], quad[3]], s));
+        }
+        let mut mesh = builder.build().unwrap();
+        assert_eq!(mesh.surfaces().len(), mesh.indices().len() / 3);
+        for s in 0..6 {
+            let count = mesh.surfaces().iter().filter(|f| **f == s).count();
+            assert_eq!(count, 2);
+        }
+        let surfaces = mesh.surfaces().to_vec();
+        mesh.transform(Affine3A::from_scale(Vec3::new(-1.0, 1.0, 1.0)));
+        assert_eq!(mesh.surfaces(), surfaces);
+        // faces on each surface share vertices, split from other surfaces
+        assert_eq!(mesh.positions().len(), 24);
+        for n in mesh.normals() {
+            assert_eq!(n.abs().max_element(), 1.0, "{n}");
+        }
+    }
+
+    #[test]
+    fn flip_winding() {
+        let mut builder = Mesh::builder();
+        for i in 0..8 {
+            let [x, y, z] = [i & 1, (i >> 1) & 1, (i >> 2) & 1];
+            let pos = Vec3::new(x as f32, y as f32, z as f32);
+            builder.push_vtx(pos).unwrap();
+        }
+        // faces in reverse order
+        for quad in [
+            [1, 3, 2, 0],
+            [6, 7, 5, 4],
+            [4, 5, 1, 0],
+            [3, 7, 6, 2],
+            [2, 6, 4, 0],
+            [5, 7, 3, 1],
+        ] {
+            builder.push_face(Face::new([quad[0], quad[1], quad[2]], 0));
+            builder.push_face(Face::new([quad[0], quad[2], quad[3]], 0));
+        }
+        builder.set_crease_angle(30.0_f32.to_radians());
+        let mut mesh = builder.build().unwrap();
+        assert!(mesh.is_inside_out());
+        let center = Vec3::splat(0.5);
+        for (p, n) in mesh.positions().iter().zip(mesh.normals()) {
+            assert!(n.dot(*p - center) < 0.0, "{n}");
+        }
+        mesh.flip_winding();
+        assert!(!mesh.is_inside_out());
+        check_face_normals(&mesh);
+        assert!(!flat_cube().is_inside_out());
+    }
+
+    #[test]
+    fn crease_angle() {
+        let mesh = cube_builder(false).build().unwrap();
+        assert_eq!(mesh.positions().len(), 8);
+        let mut builder = cube_builder(false);
+        builder.set_crease_angle(30.0_f32.to_radians());
+        let mesh = builder.build().unwrap();
+        assert_eq!(mesh.positions().len(), 24);
+        for (pos, norm) in mesh.positions().iter().zip(mesh.normals()) {
+            let axis = norm.abs().max_element();
+            assert!((axis - 1.0).abs() < 1e-6, "{norm}");
+            // normals point outward
+            assert!(norm.dot(*pos - Vec3::splat(0.5)) > 0.0, "{pos} {norm}");
+        }
+    }
+}
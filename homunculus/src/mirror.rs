@@ -0,0 +1,176 @@
+// mirror.rs  Mirror-symmetric meshes
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::error::Result;
+use crate::mesh::{Mesh, Vertex};
+use crate::plane::Plane;
+
+/// Distance for joining vertices to the mirror plane
+const EPSILON: f32 = 1e-4;
+
+/// Minimum cosine between a normal and its reflection to be smoothed
+const MIN_SMOOTH: f32 = 0.5;
+
+impl Mesh {
+    /// Make a copy merged with its reflection across a plane
+    ///
+    /// This is useful for symmetric models, where only one half is defined.
+    /// Reflected faces have their winding flipped, so both halves face out.
+    /// Vertices within a small distance of the plane are moved onto it, and
+    /// shared with their reflections unless they are on a sharp crease.
+    /// Faces lying in the plane are removed.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Plane, Ring};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.ring_capped(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+    /// husk.ring(Ring::default().spoke(0.0))?;
+    /// let half = husk.into_mesh()?;
+    /// // a pair of cones, 2 units apart
+    /// let mesh = half.mirrored(Plane::x(2.0))?;
+    /// assert_eq!(mesh.indices().len(), half.indices().len() * 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [Error::TooManyVertices] if the result has more than 65536 vertices
+    ///
+    /// [Error::TooManyVertices]: enum.Error.html#variant.TooManyVertices
+    pub fn mirrored(&self, plane: Plane) -> Result<Mesh> {
+        let on_plane: Vec<bool> = self
+            .positions()
+            .iter()
+            .map(|p| plane.distance(*p).abs() <= EPSILON)
+            .collect();
+        let mut pos = self.positions().to_vec();
+        let mut norm = self.normals().to_vec();
+        let mut uv = self.uvs().to_vec();
+        let mut color = self.colors().to_vec();
+        let mut ao = self.ao.clone();
+        let mut joints = self.joints.clone();
+        // vertex of the reflected copy, for each vertex
+        let mut mirror = Vec::with_capacity(pos.len());
+        for v in 0..on_plane.len() {
+            let n = norm[v];
+            let rn = plane.reflect_dir(n);
+            if on_plane[v] {
+                pos[v] = plane.project(pos[v]);
+                if n.dot(rn) >= MIN_SMOOTH {
+                    let pn = n - plane.normal() * plane.normal().dot(n);
+                    norm[v] = pn.try_normalize().unwrap_or(n);
+                    mirror.push(v);
+                    continue;
+                }
+            }
+            mirror.push(pos.len());
+            pos.push(plane.reflect(pos[v]));
+            norm.push(rn);
+            if !uv.is_empty() {
+                uv.push(uv[v]);
+            }
+            if !color.is_empty() {
+                color.push(color[v]);
+            }
+            if !ao.is_empty() {
+                ao.push(ao[v]);
+            }
+            if !joints.is_empty() {
+                joints.push(joints[v]);
+            }
+        }
+        let faces: Vec<usize> = self
+            .indices()
+            .chunks_exact(3)
+            .enumerate()
+            .filter(|(_, tri)| !tri.iter().all(|v| on_plane[usize::from(v.0)]))
+            .map(|(f, _)| f)
+            .collect();
+        let mut indices = Vec::with_capacity(faces.len() * 6);
+        for f in &faces {
+            indices.extend_from_slice(&self.indices()[f * 3..f * 3 + 3]);
+        }
+        for f in &faces {
+            let tri = &self.indices()[f * 3..f * 3 + 3];
+            for v in [tri[0], tri[2], tri[1]] {
+                indices.push(Vertex::try_from(mirror[usize::from(v.0)])?);
+            }
+        }
+        let n_faces = self.indices().len() / 3;
+        let mut mesh = Mesh::from_parts(pos, norm, indices);
+        mesh.uv = uv;
+        mesh.color = color;
+        mesh.ao = ao;
+        mesh.joints = joints;
+        mesh.skeleton.clone_from(&self.skeleton);
+        mesh.materials = self.materials.clone();
+        if self.face_materials.len() == n_faces {
+            mesh.face_materials =
+                faces.iter().map(|f| self.face_materials[*f]).collect();
+            mesh.face_materials.extend_from_within(..);
+        }
+        if self.surfaces.len() == n_faces {
+            mesh.surfaces = faces.iter().map(|f| self.surfaces[*f]).collect();
+            mesh.surfaces.extend_from_within(..);
+        }
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mesh::Face;
+    use crate::{Mesh, Plane};
+    use glam::Vec3;
+
+    /// Make half of a cube, split diagonally by the plane `x = z`
+    fn half_cube() -> Mesh {
+        let mut builder = Mesh::builder();
+        for pt in [
+            (-1.0, -1.0, -1.0),
+            (1.0, -1.0, -1.0),
+            (1.0, -1.0, 1.0),
+            (-1.0, 1.0, -1.0),
+            (1.0, 1.0, -1.0),
+            (1.0, 1.0, 1.0),
+        ] {
+            builder.push_vtx(Vec3::from(pt)).unwrap();
+        }
+        // bottom, top, -Z side and +X side
+        for vtx in [
+            [0, 1, 2],
+            [3, 5, 4],
+            [0, 3, 4],
+            [0, 4, 1],
+            [1, 4, 5],
+            [1, 5, 2],
+        ] {
+            builder.push_face(Face::new(vtx, 0));
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn cube() {
+        let half = half_cube();
+        assert!(!half.is_watertight());
+        let plane = Plane::new(Vec3::new(1.0, 0.0, -1.0), Vec3::ZERO);
+        let cube = half.mirrored(plane).unwrap();
+        assert_eq!(cube.indices().len(), 12 * 3);
+        assert!(cube.is_watertight());
+        assert!(!cube.is_inside_out());
+        assert!((cube.volume() - 8.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn faces_in_plane() {
+        let half = half_cube();
+        let plane = Plane::x(1.0);
+        // +X side is removed
+        let mirrored = half.mirrored(plane).unwrap();
+        assert_eq!(mirrored.indices().len(), 4 * 2 * 3);
+    }
+}
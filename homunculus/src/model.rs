@@ -0,0 +1,1448 @@
+// model.rs     Model definitions (.hom format)
+//
+// Copyright (c) 2022-2024  Douglas Lau
+//
+use crate::error::{Error, Result};
+use crate::expr::{eval, eval_degrees, parse_vars, Vars};
+use crate::gltf::Material;
+use crate::husk::{Husk, Jitter};
+use crate::plane::Plane;
+use crate::ring::{Ring, Shading, Spoke};
+use crate::scene::Scene;
+use glam::{EulerRot, Quat, Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::str::FromStr;
+
+/// Point definition
+#[derive(Clone, Debug)]
+enum PtDef {
+    /// Distance from axis
+    Distance(f32),
+
+    /// Branch label, with distance from axis
+    Branch(String, f32),
+}
+
+/// Ring definition
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RingDef {
+    /// Ring branch label
+    branch: Option<String>,
+
+    /// Axis vector
+    axis: Option<String>,
+
+    /// Point limits
+    points: Vec<String>,
+
+    /// Polygon points (angle in degrees, then point limit)
+    polygon: Vec<String>,
+
+    /// Scale factor
+    scale: Option<String>,
+
+    /// Center offset (`x z`, not inherited)
+    offset: Option<String>,
+
+    /// Shading setting
+    shading: Option<String>,
+
+    /// Random jitter of spoke distances
+    jitter: Option<String>,
+
+    /// Twist relative to previous ring (degrees)
+    twist: Option<String>,
+
+    /// Angle of first point (degrees)
+    seam: Option<String>,
+
+    /// Vertex color (`r g b` or `r g b a`)
+    color: Option<String>,
+
+    /// Material name
+    material: Option<String>,
+
+    /// Helix (turns, radius, pitch and rings per turn)
+    helix: Option<String>,
+
+    /// Intermediate rings interpolated from previous ring
+    steps: Option<String>,
+
+    /// Number of points to resample
+    resample: Option<String>,
+}
+
+/// Helix parameters
+struct Helix {
+    turns: f32,
+    radius: f32,
+    pitch: f32,
+    rings_per_turn: usize,
+}
+
+/// Material definition
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MaterialDef {
+    /// Material name (matching ring materials)
+    name: Option<String>,
+
+    /// Base color (`r g b` or `r g b a`)
+    color: Option<String>,
+
+    /// Metalness factor
+    metallic: Option<String>,
+
+    /// Roughness factor
+    roughness: Option<String>,
+
+    /// Render back faces
+    double_sided: Option<bool>,
+}
+
+/// Lathe definition (profile revolved around the Y axis)
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LatheDef {
+    /// Number of spokes in each ring
+    segments: Option<String>,
+
+    /// Profile points (pairs of radius, then height)
+    profile: Vec<String>,
+
+    /// Cap the end (default true)
+    cap: Option<bool>,
+}
+
+/// Named part of a multi-model definition
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PartDef {
+    /// Node / mesh name
+    name: String,
+
+    /// Translation vector
+    translate: Option<String>,
+
+    /// Rotation around X, Y and Z axes (degrees)
+    rotate: Option<String>,
+
+    /// Vec of all rings
+    ring: Vec<RingDef>,
+}
+
+/// Definition of a 3D model (`.hom` file)
+///
+/// Definitions are in [MuON] format, with rings or named parts.  They can be
+/// built into a [Husk] or [Scene] with `try_from`.
+///
+/// ```rust
+/// # use homunculus::{Error, Husk, ModelDef};
+/// # fn main() -> Result<(), Error> {
+/// let def: ModelDef = "ring:\n  points: 1 * 4\nring:\n  points: 0\n".parse()?;
+/// let husk = Husk::try_from(&def)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [MuON]: https://github.com/muon-data/muon
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ModelDef {
+    /// Variable definitions (`name=expr`)
+    #[serde(rename = "let", default)]
+    vars: Vec<String>,
+
+    /// Random number seed
+    seed: Option<u64>,
+
+    /// Distance for welding vertices
+    weld: Option<f32>,
+
+    /// Mirror axis (`x`, `y` or `z`)
+    mirror: Option<String>,
+
+    /// Levels of subdivision
+    subdivide: Option<usize>,
+
+    /// Fix winding order of inside out meshes
+    fix_winding: Option<bool>,
+
+    /// Wall thickness of hollow shell
+    thickness: Option<f32>,
+
+    /// Lathe profile (instead of rings)
+    lathe: Option<LatheDef>,
+
+    /// Vec of all rings
+    #[serde(default)]
+    ring: Vec<RingDef>,
+
+    /// Named parts (instead of rings)
+    #[serde(default)]
+    model: Vec<PartDef>,
+
+    /// Material definitions
+    #[serde(default)]
+    material: Vec<MaterialDef>,
+}
+
+/// Make an invalid field error
+fn invalid(field: &'static str, value: impl ToString) -> Error {
+    Error::InvalidField {
+        field,
+        value: value.to_string(),
+    }
+}
+
+/// Parse a vector of 3 components
+///
+/// Components are separated by spaces, so expressions must not contain
+/// any spaces.
+fn parse_vec3(code: &str, vars: &Vars) -> Result<Option<Vec3>> {
+    let xyz: Vec<_> = code.split_whitespace().collect();
+    if let [x, y, z] = xyz[..] {
+        let x = eval(x, vars)?;
+        let y = eval(y, vars)?;
+        let z = eval(z, vars)?;
+        return Ok(Some(Vec3::new(x, y, z)));
+    }
+    Ok(None)
+}
+
+/// Parse a color (`r g b` or `r g b a`)
+///
+/// Components must be from 0 to 1, and alpha defaults to 1.
+fn parse_color(color: &str, vars: &Vars) -> Result<[f32; 4]> {
+    let mut rgba = [1.0; 4];
+    let comps: Vec<&str> = color.split_whitespace().collect();
+    if comps.len() < 3 || comps.len() > 4 {
+        return Err(invalid("color", color));
+    }
+    for (c, comp) in rgba.iter_mut().zip(comps) {
+        *c = eval(comp, vars)?;
+        if !(0.0..=1.0).contains(c) {
+            return Err(invalid("color", color));
+        }
+    }
+    Ok(rgba)
+}
+
+impl PtDef {
+    /// Parse a point definition
+    ///
+    /// Distances can be expressions, but cannot contain spaces (`*` followed
+    /// by a space is a repeat count).  A branch label can have a distance
+    /// after a colon (`arm:1.6`), otherwise it defaults to 1.
+    fn parse(code: &str, vars: &Vars) -> Result<Self> {
+        if !code.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            return Ok(PtDef::Distance(eval(code, vars)?));
+        }
+        let (label, distance) = match code.split_once(':') {
+            Some((label, dist)) => (label, eval(dist, vars)?),
+            None => (code, 1.0),
+        };
+        if label.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            Ok(PtDef::Branch(label.into(), distance))
+        } else {
+            Err(invalid("branch label", label))
+        }
+    }
+
+    /// Parse a point definition with optional shading suffix
+    ///
+    /// A `!` suffix makes the spoke sharp, and `~` makes it smooth.
+    fn parse_shaded(code: &str, vars: &Vars) -> Result<(Self, Option<bool>)> {
+        let (code, sharp) = match code.chars().last() {
+            Some('!') => (&code[..code.len() - 1], Some(true)),
+            Some('~') => (&code[..code.len() - 1], Some(false)),
+            Some(c) if c.is_alphanumeric() || "_.)".contains(c) => (code, None),
+            Some(c) => return Err(invalid("point suffix", c)),
+            None => (code, None),
+        };
+        Ok((PtDef::parse(code, vars)?, sharp))
+    }
+
+    /// Make a spoke
+    fn spoke(self, sharp: Option<bool>) -> Spoke {
+        let mut spoke = match self {
+            PtDef::Distance(d) => Spoke::from(d),
+            PtDef::Branch(b, d) => Spoke::from((d, b.as_ref())),
+        };
+        spoke.sharp = sharp;
+        spoke
+    }
+}
+
+impl LatheDef {
+    /// Parse profile points
+    fn profile(&self, vars: &Vars) -> Result<Vec<Vec2>> {
+        if !self.profile.len().is_multiple_of(2) {
+            return Err(invalid("profile", "missing height for last radius"));
+        }
+        self.profile
+            .chunks_exact(2)
+            .map(|pair| {
+                Ok(Vec2::new(eval(&pair[0], vars)?, eval(&pair[1], vars)?))
+            })
+            .collect()
+    }
+
+    /// Build a husk from the lathe
+    fn build(&self, vars: &Vars) -> Result<Husk> {
+        let segments = match &self.segments {
+            Some(segments) => eval(segments, vars)?,
+            None => 16.0,
+        };
+        if !(3.0..=1024.0).contains(&segments) || segments.fract() != 0.0 {
+            return Err(invalid("segments", segments));
+        }
+        let profile = self.profile(vars)?;
+        if profile.len() < 2 {
+            return Err(Error::InvalidModel(
+                "Lathe profile must have at least 2 points",
+            ));
+        }
+        let mut husk = Husk::lathe(&profile, segments as usize)?;
+        if self.cap == Some(false) {
+            husk.set_auto_cap(false);
+        }
+        Ok(husk)
+    }
+}
+
+impl RingDef {
+    /// Parse axis vector
+    fn axis(&self, vars: &Vars) -> Result<Option<Vec3>> {
+        match &self.axis {
+            Some(axis) => match parse_vec3(axis, vars)? {
+                Some(axis) => Ok(Some(axis)),
+                None => Err(invalid("axis", axis)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Parse center offset
+    fn offset(&self, vars: &Vars) -> Result<Option<Vec2>> {
+        let Some(offset) = &self.offset else {
+            return Ok(None);
+        };
+        match offset.split_whitespace().collect::<Vec<_>>()[..] {
+            [x, z] => Ok(Some(Vec2::new(eval(x, vars)?, eval(z, vars)?))),
+            _ => Err(invalid("offset", offset)),
+        }
+    }
+
+    /// Get vertex color
+    fn color(&self, vars: &Vars) -> Result<Option<[f32; 4]>> {
+        match &self.color {
+            Some(color) => Ok(Some(parse_color(color, vars)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get scale factor (single value)
+    fn scale(&self, vars: &Vars) -> Result<Option<f32>> {
+        match &self.scale {
+            Some(scale) if self.scale_xz(vars)?.is_none() => {
+                Ok(Some(eval(scale, vars)?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Get X and Z scale factors (pair of values)
+    fn scale_xz(&self, vars: &Vars) -> Result<Option<(f32, f32)>> {
+        let Some(scale) = &self.scale else {
+            return Ok(None);
+        };
+        match scale.split_whitespace().collect::<Vec<_>>()[..] {
+            [sx, sz] => Ok(Some((eval(sx, vars)?, eval(sz, vars)?))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Parse helix parameters
+    fn helix(&self, vars: &Vars) -> Result<Option<Helix>> {
+        let Some(helix) = &self.helix else {
+            return Ok(None);
+        };
+        if self.axis.is_some() {
+            return Err(Error::InvalidModel("Helix cannot have an axis"));
+        }
+        let params: Vec<_> = helix.split_whitespace().collect();
+        let [turns, radius, pitch, rings] = params[..] else {
+            return Err(invalid("helix", helix));
+        };
+        let rings = eval(rings, vars)?;
+        if !(3.0..=1024.0).contains(&rings) || rings.fract() != 0.0 {
+            return Err(invalid("helix rings", rings));
+        }
+        Ok(Some(Helix {
+            turns: eval(turns, vars)?,
+            radius: eval(radius, vars)?,
+            pitch: eval(pitch, vars)?,
+            rings_per_turn: rings as usize,
+        }))
+    }
+
+    /// Get number of interpolated steps
+    fn steps(&self, vars: &Vars) -> Result<Option<usize>> {
+        let Some(steps) = &self.steps else {
+            return Ok(None);
+        };
+        if self.helix.is_some() {
+            return Err(Error::InvalidModel("Helix cannot have steps"));
+        }
+        let steps = eval(steps, vars)?;
+        if !(0.0..=1024.0).contains(&steps) || steps.fract() != 0.0 {
+            return Err(invalid("steps", steps));
+        }
+        Ok(Some(steps as usize))
+    }
+
+    /// Get jitter amount
+    fn jitter(&self, vars: &Vars) -> Result<Option<f32>> {
+        match &self.jitter {
+            Some(jitter) => {
+                let jitter = eval(jitter, vars)?;
+                if !jitter.is_finite() || jitter < 0.0 {
+                    return Err(invalid("jitter", jitter));
+                }
+                Ok(Some(jitter))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Get twist (degrees)
+    fn twist(&self, vars: &Vars) -> Result<Option<f32>> {
+        match &self.twist {
+            Some(twist) => {
+                let twist = eval_degrees(twist, vars)?;
+                if !twist.is_finite() {
+                    return Err(invalid("twist", twist));
+                }
+                Ok(Some(twist))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Get seam angle (degrees)
+    fn seam(&self, vars: &Vars) -> Result<Option<f32>> {
+        match &self.seam {
+            Some(seam) => {
+                let seam = eval_degrees(seam, vars)?;
+                if !(0.0..360.0).contains(&seam) {
+                    return Err(invalid("seam", seam));
+                }
+                Ok(Some(seam))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Get shading (`flat`, `smooth` or `ringed`)
+    fn shading(&self) -> Result<Option<Shading>> {
+        let Some(shading) = &self.shading else {
+            return Ok(None);
+        };
+        match shading.to_ascii_lowercase().as_str() {
+            "flat" => Ok(Some(Shading::Flat)),
+            "smooth" => Ok(Some(Shading::Smooth)),
+            "ringed" => Ok(Some(Shading::Ringed)),
+            _ => Err(invalid(
+                "shading",
+                format!("{shading} (expected flat, smooth or ringed)"),
+            )),
+        }
+    }
+
+    /// Check if points are a shape shorthand, such as `[star 5 2 1]`
+    fn is_shape(&self) -> bool {
+        self.points.first().is_some_and(|p| p.starts_with('['))
+    }
+
+    /// Build a ring from shape shorthand
+    ///
+    /// Shapes are `polygon n radius`, `star points outer inner`,
+    /// `ellipse n rx rz` and `superellipse n rx rz exponent`.
+    fn shape(&self, vars: &Vars) -> Result<Ring> {
+        let code = self.points.join(" ");
+        let Some(shape) =
+            code.strip_prefix('[').and_then(|c| c.strip_suffix(']'))
+        else {
+            return Err(invalid("shape", code));
+        };
+        let mut params = shape.split_whitespace();
+        let name = params.next().unwrap_or_default();
+        let vals = params
+            .map(|p| eval(p, vars))
+            .collect::<Result<Vec<f32>>>()?;
+        let count = |n: f32| {
+            if (3.0..=1024.0).contains(&n) && n.fract() == 0.0 {
+                Ok(n as usize)
+            } else {
+                Err(invalid("shape count", n))
+            }
+        };
+        if vals.iter().skip(1).any(|v| !(v.is_finite() && *v > 0.0)) {
+            return Err(invalid("shape", code));
+        }
+        Ok(match (name, &vals[..]) {
+            ("polygon", [n, r]) => Ring::polygon(count(*n)?, *r),
+            ("star", [n, ro, ri]) => Ring::star(count(*n)?, *ro, *ri),
+            ("ellipse", [n, rx, rz]) => Ring::ellipse(count(*n)?, *rx, *rz),
+            ("superellipse", [n, rx, rz, e]) => {
+                Ring::try_superellipse(count(*n)?, *rx, *rz, *e)?
+            }
+            _ => return Err(invalid("shape", code)),
+        })
+    }
+
+    /// Get point definitions, with shading overrides
+    fn point_defs(&self, vars: &Vars) -> Result<Vec<(PtDef, Option<bool>)>> {
+        let mut defs = vec![];
+        if self.is_shape() {
+            return Ok(defs);
+        }
+        let mut repeat = false;
+        for code in &self.points {
+            if repeat {
+                let count =
+                    code.parse().map_err(|_| invalid("repeat count", code))?;
+                // branch labels are unique, so only repeat the distance
+                let ptd = match defs.last() {
+                    Some((PtDef::Branch(_, d), sharp)) => {
+                        (PtDef::Distance(*d), *sharp)
+                    }
+                    Some(def) => def.clone(),
+                    None => (PtDef::Distance(1.0), None),
+                };
+                for _ in 1..count {
+                    defs.push(ptd.clone());
+                }
+                repeat = false;
+                continue;
+            }
+            if code == "*" {
+                repeat = true;
+                continue;
+            }
+            let def = PtDef::parse_shaded(code, vars)
+                .map_err(|e| invalid("point def", format!("{code} ({e})")))?;
+            defs.push(def);
+        }
+        Ok(defs)
+    }
+
+    /// Get polygon definitions (angle in degrees, point definition)
+    ///
+    /// Angles must be strictly increasing from 0 to 360.
+    fn polygon_defs(
+        &self,
+        vars: &Vars,
+    ) -> Result<Vec<(f32, PtDef, Option<bool>)>> {
+        if !self.polygon.len().is_multiple_of(2) {
+            return Err(invalid("polygon", "missing point for last angle"));
+        }
+        let mut defs = Vec::with_capacity(self.polygon.len() / 2);
+        let mut prev = None;
+        for pair in self.polygon.chunks_exact(2) {
+            let (angle, code) = (&pair[0], &pair[1]);
+            let deg = eval(angle, vars).map_err(|e| {
+                invalid("polygon angle", format!("{angle} ({e})"))
+            })?;
+            if !(0.0..360.0).contains(&deg) || prev.is_some_and(|p| deg <= p) {
+                return Err(invalid(
+                    "polygon angle",
+                    format!("{angle} {code}"),
+                ));
+            }
+            prev = Some(deg);
+            let (def, sharp) = PtDef::parse_shaded(code, vars)
+                .map_err(|e| invalid("point def", format!("{code} ({e})")))?;
+            defs.push((deg, def, sharp));
+        }
+        Ok(defs)
+    }
+
+    /// Build ring from definition
+    fn build(&self, mut ring: Ring, vars: &Vars) -> Result<Ring> {
+        if self.is_shape() {
+            if ring.is_branch_base() {
+                return Err(Error::InvalidModel(
+                    "Branch ring cannot have a shape",
+                ));
+            }
+            ring = self.shape(vars)?;
+        }
+        if let Some(axis) = self.axis(vars)? {
+            ring = ring.try_axis(axis)?;
+        }
+        if let Some(scale) = self.scale(vars)? {
+            ring = ring.try_scale(scale)?;
+        }
+        if let Some((sx, sz)) = self.scale_xz(vars)? {
+            ring = ring.try_scale_xz(sx, sz)?;
+        }
+        if let Some(offset) = self.offset(vars)? {
+            ring = ring.try_offset(offset)?;
+        }
+        if let Some(shading) = self.shading()? {
+            ring = ring.shading(shading);
+        }
+        if let Some(color) = self.color(vars)? {
+            ring = ring.try_color(color)?;
+        }
+        if let Some(material) = &self.material {
+            ring = ring.material(material);
+        }
+        if let Some(twist) = self.twist(vars)? {
+            ring = ring.try_twist(twist.to_radians())?;
+        }
+        if let Some(seam) = self.seam(vars)? {
+            ring = ring.try_seam(seam.to_radians())?;
+        }
+        for (pt, sharp) in self.point_defs(vars)? {
+            ring = ring.try_spoke(pt.spoke(sharp))?;
+        }
+        let polygon = self.polygon_defs(vars)?;
+        if !polygon.is_empty() && !self.points.is_empty() {
+            return Err(Error::InvalidModel(
+                "Ring cannot have both points and polygon",
+            ));
+        }
+        for (deg, pt, sharp) in polygon {
+            ring =
+                ring.try_spoke(pt.spoke(sharp).with_angle(deg.to_radians()))?;
+        }
+        if let Some(count) = &self.resample {
+            let count = eval(count, vars)?;
+            if !(1.0..=1024.0).contains(&count) || count.fract() != 0.0 {
+                return Err(invalid("resample", count));
+            }
+            ring = ring.try_resample(count as usize)?;
+        }
+        Ok(ring)
+    }
+}
+
+impl MaterialDef {
+    /// Build material from definition
+    fn build(&self, vars: &Vars) -> Result<Material> {
+        let mut material = Material {
+            name: self.name.clone(),
+            double_sided: self.double_sided.unwrap_or_default(),
+            ..Default::default()
+        };
+        if let Some(color) = &self.color {
+            material.base_color = parse_color(color, vars)?;
+        }
+        if let Some(metallic) = &self.metallic {
+            material.metallic = eval(metallic, vars)?;
+            if !(0.0..=1.0).contains(&material.metallic) {
+                return Err(invalid("metallic", metallic));
+            }
+        }
+        if let Some(roughness) = &self.roughness {
+            material.roughness = eval(roughness, vars)?;
+            if !(0.0..=1.0).contains(&material.roughness) {
+                return Err(invalid("roughness", roughness));
+            }
+        }
+        Ok(material)
+    }
+}
+
+impl PartDef {
+    /// Get translation
+    fn translation(&self, vars: &Vars) -> Result<Vec3> {
+        match &self.translate {
+            Some(translate) => match parse_vec3(translate, vars)? {
+                Some(translation) => Ok(translation),
+                None => Err(invalid("translate", translate)),
+            },
+            None => Ok(Vec3::ZERO),
+        }
+    }
+
+    /// Get rotation
+    fn rotation(&self, vars: &Vars) -> Result<Quat> {
+        match &self.rotate {
+            Some(rotate) => match parse_vec3(rotate, vars)? {
+                Some(r) => Ok(Quat::from_euler(
+                    EulerRot::XYZ,
+                    r.x.to_radians(),
+                    r.y.to_radians(),
+                    r.z.to_radians(),
+                )),
+                None => Err(invalid("rotate", rotate)),
+            },
+            None => Ok(Quat::IDENTITY),
+        }
+    }
+}
+
+/// Get the plane for a mirror axis
+fn mirror_plane(axis: &str) -> Result<Plane> {
+    match axis {
+        "x" | "X" => Ok(Plane::x(0.0)),
+        "y" | "Y" => Ok(Plane::y(0.0)),
+        "z" | "Z" => Ok(Plane::z(0.0)),
+        _ => Err(invalid("mirror axis", axis)),
+    }
+}
+
+/// Build a husk from ring definitions
+fn build_husk(def: &ModelDef, rings: &[RingDef], vars: &Vars) -> Result<Husk> {
+    let seed = def.seed.unwrap_or_default();
+    let mut husk = match &def.lathe {
+        Some(_) if !rings.is_empty() => {
+            return Err(Error::InvalidModel(
+                "Model cannot have both lathe and rings",
+            ))
+        }
+        Some(lathe) => lathe
+            .build(vars)
+            .map_err(|e| Error::LatheDef(Box::new(e)))?,
+        None => Husk::new(),
+    };
+    if let Some(epsilon) = def.weld {
+        if !(epsilon > 0.0 && epsilon.is_finite()) {
+            return Err(invalid("weld", epsilon));
+        }
+        husk.try_weld(epsilon)?;
+    }
+    if let Some(axis) = &def.mirror {
+        husk.mirror(mirror_plane(axis)?);
+    }
+    if let Some(levels) = def.subdivide {
+        husk.subdivide(levels);
+    }
+    if def.fix_winding == Some(true) {
+        husk.fix_winding();
+    }
+    if let Some(thickness) = def.thickness {
+        if !(thickness > 0.0 && thickness.is_finite()) {
+            return Err(invalid("thickness", thickness));
+        }
+        husk.try_shell(thickness)?;
+    }
+    for material in &def.material {
+        husk.material(material.build(vars)?);
+    }
+    for (i, ring_def) in rings.iter().enumerate() {
+        let ring = match &ring_def.branch {
+            Some(label) => husk.branch(label)?,
+            None => Ring::default(),
+        };
+        if let Some(distance) = ring_def.jitter(vars)? {
+            husk.jitter(Jitter {
+                distance,
+                axis: 0.0,
+                seed,
+            });
+        }
+        let ring_err = |e| Error::RingDef {
+            ring: i,
+            source: Box::new(e),
+        };
+        let ring = ring_def.build(ring, vars).map_err(ring_err)?;
+        let helix = ring_def.helix(vars).map_err(ring_err)?;
+        let steps = ring_def.steps(vars).map_err(ring_err)?;
+        match (helix, steps) {
+            (Some(h), _) => {
+                husk.helix(h.turns, h.radius, h.pitch, h.rings_per_turn, ring)?
+            }
+            (None, Some(steps)) => husk.ring_lerp(ring, steps)?,
+            (None, None) => husk.ring(ring)?,
+        }
+    }
+    let open = husk.open_branches();
+    if !open.is_empty() {
+        let labels: Vec<String> = open
+            .into_iter()
+            .map(|label| match branch_ring(rings, label, vars) {
+                Some(i) => format!("{label} (ring {i})"),
+                None => label.to_string(),
+            })
+            .collect();
+        return Err(Error::UnresolvedBranches(labels));
+    }
+    Ok(husk)
+}
+
+/// Find the first ring with a branch label
+fn branch_ring(rings: &[RingDef], label: &str, vars: &Vars) -> Option<usize> {
+    rings.iter().position(|ring| {
+        ring.point_defs(vars).is_ok_and(|defs| {
+            defs.iter()
+                .any(|(pt, _)| matches!(pt, PtDef::Branch(b, _) if b == label))
+        })
+    })
+}
+
+impl FromStr for ModelDef {
+    type Err = Error;
+
+    fn from_str(muon: &str) -> Result<Self> {
+        muon_rs::from_str(muon).map_err(|e| Error::ParseModel(e.to_string()))
+    }
+}
+
+impl ModelDef {
+    /// Read a model definition
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        muon_rs::from_reader(reader)
+            .map_err(|e| Error::ParseModel(e.to_string()))
+    }
+
+    /// Set distance for welding vertices
+    pub fn set_weld(&mut self, epsilon: f32) {
+        self.weld = Some(epsilon);
+    }
+
+    /// Set mirror axis
+    pub fn set_mirror(&mut self, axis: &str) {
+        self.mirror = Some(axis.to_string());
+    }
+
+    /// Set levels of subdivision
+    pub fn set_subdivide(&mut self, levels: usize) {
+        self.subdivide = Some(levels);
+    }
+
+    /// Set fixing winding order of inside out meshes
+    pub fn set_fix_winding(&mut self, fix: bool) {
+        self.fix_winding = Some(fix);
+    }
+
+    /// Set wall thickness of hollow shell
+    pub fn set_thickness(&mut self, thickness: f32) {
+        self.thickness = Some(thickness);
+    }
+
+    /// Check if the model has named parts
+    pub fn has_parts(&self) -> bool {
+        !self.model.is_empty()
+    }
+
+    /// Get all ring definitions (of every part)
+    fn rings(&self) -> impl Iterator<Item = &RingDef> {
+        self.ring
+            .iter()
+            .chain(self.model.iter().flat_map(|m| &m.ring))
+    }
+
+    /// Get random number seed used by the model
+    ///
+    /// This is `None` unless a seed is declared or jitter is used.
+    pub fn seed(&self) -> Option<u64> {
+        match self.seed {
+            Some(seed) => Some(seed),
+            None => self.rings().any(|r| r.jitter.is_some()).then_some(0),
+        }
+    }
+}
+
+impl Husk {
+    /// Read a husk from a model definition (`.hom` file)
+    ///
+    /// Models with named parts must be built as a [Scene] instead.
+    ///
+    /// ```rust,no_run
+    /// # use homunculus::{Error, Husk};
+    /// # use std::fs::File;
+    /// # fn main() -> Result<(), Error> {
+    /// let husk = Husk::from_hom_reader(File::open("vase.hom")?)?;
+    /// husk.write_gltf(File::create("vase.glb")?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_hom_reader<R: Read>(reader: R) -> Result<Self> {
+        Husk::try_from(&ModelDef::from_reader(reader)?)
+    }
+}
+
+impl TryFrom<&ModelDef> for Husk {
+    type Error = Error;
+
+    fn try_from(def: &ModelDef) -> Result<Self> {
+        if def.has_parts() {
+            return Err(Error::InvalidModel("Model has named parts"));
+        }
+        let vars = parse_vars(&def.vars)?;
+        build_husk(def, &def.ring, &vars)
+    }
+}
+
+impl TryFrom<&ModelDef> for Scene {
+    type Error = Error;
+
+    fn try_from(def: &ModelDef) -> Result<Self> {
+        if !def.ring.is_empty() {
+            return Err(Error::InvalidModel(
+                "Model cannot have both rings and named parts",
+            ));
+        }
+        if def.lathe.is_some() {
+            return Err(Error::InvalidModel(
+                "Model cannot have both lathe and named parts",
+            ));
+        }
+        let vars = parse_vars(&def.vars)?;
+        let mut scene = Scene::default();
+        for (i, part) in def.model.iter().enumerate() {
+            if def.model[..i].iter().any(|p| p.name == part.name) {
+                return Err(Error::DuplicateName(part.name.clone()));
+            }
+            let husk = build_husk(def, &part.ring, &vars).map_err(|e| {
+                Error::PartDef {
+                    name: part.name.clone(),
+                    source: Box::new(e),
+                }
+            })?;
+            let translation = part.translation(&vars)?;
+            let rotation = part.rotation(&vars)?;
+            scene.add(&part.name, husk, translation, rotation)?;
+        }
+        Ok(scene)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::Result;
+
+    const VASE: &str = include_str!("../../hom/assets/vase.hom");
+
+    const ROCK: &str = include_str!("../../hom/assets/rock.hom");
+
+    fn model(muon: &str) -> Result<Husk> {
+        let def: ModelDef = muon.parse()?;
+        Ok(Husk::try_from(&def)?)
+    }
+
+    fn glb(muon: &str) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        model(muon)?.write_gltf(&mut buf)?;
+        Ok(buf)
+    }
+
+    fn polygon(muon: &str) -> Result<Vec<(f32, PtDef, Option<bool>)>> {
+        let def: ModelDef = muon.parse()?;
+        Ok(def.ring[0].polygon_defs(&Vars::new())?)
+    }
+
+    #[test]
+    fn polygons() -> Result<()> {
+        let defs = polygon("ring:\n  polygon: 0 1.5 30 a 90 0.5\n")?;
+        assert_eq!(defs.len(), 3);
+        assert_eq!(defs[1].0, 30.0);
+        assert!(matches!(&defs[1].1, PtDef::Branch(b, _) if b == "a"));
+        assert!(matches!(defs[2].1, PtDef::Distance(d) if d == 0.5));
+        let defs = polygon("ring:\n  polygon: 0 1\n  polygon: 180 2\n")?;
+        assert_eq!(defs.len(), 2);
+        let err = polygon("ring:\n  polygon: 0 1 30\n").unwrap_err();
+        assert!(err.to_string().contains("missing point"));
+        let err = polygon("ring:\n  polygon: 0 1 90 1 45 1\n").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid polygon angle: 45 1");
+        let err = polygon("ring:\n  polygon: 0 1 0 1\n").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid polygon angle: 0 1");
+        let err = polygon("ring:\n  polygon: 90 1 360 1\n").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid polygon angle: 360 1");
+        assert!(polygon("ring:\n  polygon: -10 1\n").is_err());
+        assert!(model("ring:\n  points: 1 1\n  polygon: 0 1\n").is_err());
+        let foot = model(include_str!("../../hom/assets/foot.hom"))?;
+        assert!((foot.spine_length() - 1.8).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn weld() -> Result<()> {
+        const RINGS: &str = "ring:\n  points: 1 * 6\n\
+            ring:\n  axis: 0 0 0\n\
+            ring:\n  axis: 0 1 0\n";
+        let mesh = model(RINGS)?.into_mesh()?;
+        let welded = model(&format!("weld: 0.001\n{RINGS}"))?.into_mesh()?;
+        assert!(welded.positions().len() < mesh.positions().len());
+        assert!(model(&format!("weld: 0\n{RINGS}")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn subdivide() -> Result<()> {
+        const RINGS: &str = "ring:\n  points: 1 * 4\n\
+            ring:\n  axis: 0 1 0\n  points: 0\n";
+        let mesh = model(RINGS)?.into_mesh()?;
+        let smooth = model(&format!("subdivide: 2\n{RINGS}"))?.into_mesh()?;
+        assert_eq!(smooth.indices().len(), mesh.indices().len() * 16);
+        Ok(())
+    }
+
+    #[test]
+    fn fix_winding() -> Result<()> {
+        const RINGS: &str = "ring:\n  points: 1 * 4\n\
+            ring:\n  axis: 0 1 0\n  points: 0\n";
+        let mesh =
+            model(&format!("fix_winding: true\n{RINGS}"))?.into_mesh()?;
+        assert!(!mesh.is_inside_out());
+        Ok(())
+    }
+
+    #[test]
+    fn unresolved_branches() -> Result<()> {
+        const RINGS: &str = "ring:\n  points: 1 armL 1 armR\n\
+            ring:\n  axis: 0 1 0\n\
+            ring:\n  branch: armR\n  axis: 1 0 0\n";
+        let err = model(RINGS).err().unwrap();
+        assert_eq!(err.to_string(), "Unresolved branches: armL (ring 0)");
+        let rings = format!("{RINGS}ring:\n  branch: armL\n  axis: -1 0 0\n");
+        model(&rings)?.into_mesh()?;
+        Ok(())
+    }
+
+    #[test]
+    fn mirror() -> Result<()> {
+        const RINGS: &str = "ring:\n  axis: 0 1 0\n  points: 0\n\
+            ring:\n  polygon: 0 1 90 1 180 1\n\
+            ring:\n  points: 0\n";
+        let half = model(RINGS)?.into_mesh()?;
+        let mesh = model(&format!("mirror: z\n{RINGS}"))?.into_mesh()?;
+        assert!(mesh.is_watertight());
+        assert!(mesh.indices().len() > half.indices().len());
+        assert!(model(&format!("mirror: w\n{RINGS}")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn thickness() -> Result<()> {
+        const CUP: &str =
+            "lathe:\n  segments: 12\n  profile: 1 0 1 2\n  cap: false\n";
+        let mesh = model(CUP)?.into_mesh()?;
+        assert!(!mesh.is_watertight());
+        let shell = model(&format!("thickness: 0.1\n{CUP}"))?.into_mesh()?;
+        assert!(shell.is_watertight());
+        assert!(model(&format!("thickness: -1\n{CUP}")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn lathe() -> Result<()> {
+        const LATHE: &str =
+            "lathe:\n  segments: 12\n  profile: 0 0 1 0 0.5 $h\n";
+        let mesh = model(&format!("let: h=2\n{LATHE}"))?.into_mesh()?;
+        assert!(mesh.is_watertight());
+        assert_eq!(mesh.pos_max().y, 2.0);
+        let open = model(&format!("let: h=2\n{LATHE}  cap: false\n"))?;
+        assert!(!open.into_mesh()?.is_watertight());
+        assert!(model("lathe:\n  profile: 1 0\n").is_err());
+        assert!(model("lathe:\n  profile: 1 0 1\n").is_err());
+        assert!(model("lathe:\n  segments: 2\n  profile: 1 0 1 1\n").is_err());
+        let err = model("lathe:\n  profile: 1 1 1 0\n").err().unwrap();
+        assert!(err.to_string().starts_with("Lathe: "), "{err}");
+        assert!(model(&format!("{LATHE}ring:\n  points: 1 1 1\n")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn helix() -> Result<()> {
+        const SPRING: &str = "ring:\n  points: 0.1 * 8\n\
+            ring:\n  helix: 2 1 0.5 $n\n";
+        let husk = model(&format!("let: n=12\n{SPRING}"))?;
+        let end = husk.current_ring().unwrap().center();
+        assert!(end.distance(Vec3::new(0.0, 1.0, 0.0)) < 1e-4, "{end}");
+        assert!(model("ring:\n  points: 1 1 1\n  helix: 2 1 0.5\n").is_err());
+        assert!(model("ring:\n  points: 1 1 1\n  helix: 2 1 0.5 2\n").is_err());
+        let err = model(
+            "ring:\n  points: 1 1 1\n  axis: 0 1 0\n  \
+            helix: 2 1 0.5 12\n",
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.to_string(), "Ring 0: Helix cannot have an axis");
+        Ok(())
+    }
+
+    #[test]
+    fn resample() -> Result<()> {
+        let mesh = model(
+            "ring:\n  points: 1 1 1\n  resample: 12\n\
+             ring:\n  points: 1 * 12\n",
+        )?
+        .into_mesh()?;
+        // band of 24 faces and end cap of 12
+        assert_eq!(mesh.indices().len() / 3, 24 + 12);
+        assert!(model("ring:\n  points: 1 1 1\n  resample: 0\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn steps() -> Result<()> {
+        const RINGS: &str = "ring:\n  points: 1 * 6\n\
+            ring:\n  axis: 0 2 0\n  points: 3 * 6\n";
+        let mesh = model(RINGS)?.into_mesh()?;
+        let smooth = model(&format!("{RINGS}  steps: 3\n"))?.into_mesh()?;
+        assert_eq!(
+            smooth.indices().len(),
+            mesh.indices().len() + 6 * 2 * 3 * 3
+        );
+        assert!(model(&format!("{RINGS}  steps: -1\n")).is_err());
+        Ok(())
+    }
+
+    fn scene_json(muon: &str) -> Result<serde_json::Value> {
+        let def: ModelDef = muon.parse()?;
+        let mut glb = Vec::new();
+        Scene::try_from(&def)?.write_gltf(&mut glb)?;
+        let len = u32::from_le_bytes(glb[12..16].try_into()?) as usize;
+        Ok(serde_json::from_slice(&glb[20..20 + len])?)
+    }
+
+    #[test]
+    fn invalid_ring() -> Result<()> {
+        let model = "ring:\n  points: 1 1 1\nring:\n  scale: -2\n";
+        let def: ModelDef = model.parse()?;
+        let Err(err) = Husk::try_from(&def) else {
+            panic!("invalid scale");
+        };
+        assert_eq!(err.to_string(), "Ring 1: Invalid scale: -2");
+        Ok(())
+    }
+
+    #[test]
+    fn ring_color() -> Result<()> {
+        let def: ModelDef = "ring:\n  color: 0.5 $g 0.25\n".parse()?;
+        let mut vars = Vars::new();
+        vars.insert("g".to_string(), 0.75);
+        assert_eq!(def.ring[0].color(&vars)?, Some([0.5, 0.75, 0.25, 1.0]));
+        assert!(model("ring:\n  points: 1 1 1\n  color: 1 0 0 0.5\n").is_ok());
+        let err = model("ring:\n  points: 1 1 1\n  color: 1 2 0\n")
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Invalid color: 1 2 0"));
+        assert!(model("ring:\n  points: 1 1 1\n  color: 1 0\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn ring_material() -> Result<()> {
+        let mesh = model(
+            "ring:\n  points: 1 1 1\n  material: side\n\
+             ring:\n  material: cap\n",
+        )?
+        .into_mesh()?;
+        assert_eq!(mesh.materials(), ["side", "cap"]);
+        Ok(())
+    }
+
+    #[test]
+    fn materials() -> Result<()> {
+        const BARK: &str = "material:
+  name: bark
+  color: 0.5 0.25 0
+  roughness: 0.5
+  double_sided: true
+ring:
+  points: 1 1 1
+  material: bark
+";
+        let husk = model(BARK)?;
+        assert_eq!(
+            husk.materials(),
+            [Material {
+                name: Some("bark".to_string()),
+                base_color: [0.5, 0.25, 0.0, 1.0],
+                metallic: 0.0,
+                roughness: 0.5,
+                double_sided: true,
+            }]
+        );
+        let err = model("material:\n  metallic: 2\n").err().unwrap();
+        assert_eq!(err.to_string(), "Invalid metallic: 2");
+        Ok(())
+    }
+
+    #[test]
+    fn named_models() -> Result<()> {
+        const WIZARD: &str = include_str!("../../hom/assets/wizard.hom");
+        let def: ModelDef = WIZARD.parse()?;
+        assert!(def.has_parts());
+        assert!(Husk::try_from(&def).is_err());
+        let json = scene_json(WIZARD)?;
+        let nodes = json["nodes"].as_array().unwrap();
+        let meshes = json["meshes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(meshes.len(), 3);
+        let names: Vec<_> = nodes.iter().map(|n| n["name"].clone()).collect();
+        assert_eq!(names, ["wizard", "staff", "hat"]);
+        assert_eq!(meshes[2]["name"], "hat");
+        assert_eq!(nodes[1]["translation"][0].as_f64(), Some(0.6f32.into()));
+        assert!(nodes[1]["rotation"].is_array());
+        assert!(nodes[0].get("translation").is_none());
+        // single-model files are unchanged
+        let def: ModelDef = VASE.parse()?;
+        assert!(!def.has_parts());
+        assert!(Scene::try_from(&def).is_err());
+        let dup = "model:\n  name: a\n  ring:\n    points: 1 * 3\n";
+        let err = scene_json(&dup.repeat(2)).unwrap_err();
+        assert_eq!(err.to_string(), "Duplicate model name: a");
+        let both = format!("ring:\n  points: 1 * 3\n{dup}");
+        assert!(scene_json(&both).is_err());
+        let bad = dup.replace("name: a", "name: a\n  rotate: 0 90");
+        assert!(scene_json(&bad).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn point_shading() -> Result<()> {
+        const COLUMN: &str = include_str!("../../hom/assets/column.hom");
+        let def: ModelDef = COLUMN.parse()?;
+        let defs = def.ring[0].point_defs(&Vars::new())?;
+        assert_eq!(defs.len(), 12);
+        assert!(defs.iter().filter(|d| d.1 == Some(true)).count() == 1);
+        assert!(
+            matches!(defs[5], (PtDef::Distance(d), Some(true)) if d == 1.2)
+        );
+        let def: ModelDef = "ring:\n  points: a~ 1! $b~\n".parse()?;
+        let mut vars = Vars::new();
+        vars.insert("b".to_string(), 2.0);
+        let defs = def.ring[0].point_defs(&vars)?;
+        assert!(
+            matches!(&defs[0], (PtDef::Branch(b, _), Some(false)) if b == "a")
+        );
+        assert!(matches!(defs[1], (PtDef::Distance(_), Some(true))));
+        assert!(
+            matches!(defs[2], (PtDef::Distance(d), Some(false)) if d == 2.0)
+        );
+        let err = model("ring:\n  points: 1 1.4? 1\n").err().unwrap();
+        assert!(err.to_string().contains("Invalid point suffix: ?"));
+        assert!(model("ring:\n  points: 1 1!! 1\n").is_err());
+        assert!(model("ring:\n  points: 1 ! 1\n").is_err());
+        let smooth = COLUMN.replace("1.2!", "1.2");
+        assert_ne!(glb(COLUMN)?, glb(&smooth)?);
+        let flat = smooth.replace("smooth", "flat");
+        assert_ne!(glb(&flat)?, glb(&flat.replace("1.2", "1.2~"))?);
+        let defs = polygon("ring:\n  polygon: 0 1 90 1! 180 1~\n")?;
+        assert_eq!(defs[1].2, Some(true));
+        assert_eq!(defs[2].2, Some(false));
+        Ok(())
+    }
+
+    #[test]
+    fn shading() -> Result<()> {
+        const RINGS: &str = "ring:\n  points: 1 * 6\n  shading: $s\n\
+            ring:\n  axis: 0 1 0\n\
+            ring:\n  axis: 0 1 0\n";
+        let verts = |shading: &str| -> Result<usize> {
+            let mesh = model(&RINGS.replace("$s", shading))?.into_mesh()?;
+            Ok(mesh.positions().len())
+        };
+        let flat = verts("flat")?;
+        let smooth = verts("smooth")?;
+        let ringed = verts("ringed")?;
+        assert!(flat > ringed);
+        assert!(ringed > smooth);
+        assert_eq!(verts("Flat")?, flat);
+        let err = model(&RINGS.replace("$s", "glossy")).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Ring 0: Invalid shading: glossy \
+            (expected flat, smooth or ringed)"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn branch_distance() -> Result<()> {
+        let def: ModelDef =
+            "let: d=1.5\nring:\n  points: 0.5 arm arm2:1.6 leg:$d! 2\n"
+                .parse()?;
+        let defs = def.ring[0].point_defs(&parse_vars(&def.vars)?)?;
+        assert_eq!(defs.len(), 5);
+        assert!(matches!(defs[0].0, PtDef::Distance(d) if d == 0.5));
+        assert!(matches!(
+            &defs[1].0,
+            PtDef::Branch(b, d) if b == "arm" && *d == 1.0
+        ));
+        assert!(matches!(
+            &defs[2].0,
+            PtDef::Branch(b, d) if b == "arm2" && *d == 1.6
+        ));
+        assert!(matches!(
+            &defs[3],
+            (PtDef::Branch(b, d), Some(true)) if b == "leg" && *d == 1.5
+        ));
+        assert!(matches!(defs[4].0, PtDef::Distance(d) if d == 2.0));
+        let spoke = defs[2].0.clone().spoke(None);
+        assert_eq!(spoke.distance, 1.6);
+        assert_eq!(spoke.label.as_deref(), Some("arm2"));
+        // repeating a labeled point repeats only its distance
+        let def: ModelDef = "ring:\n  points: 1 * 2 arm:1.6 * 3\n".parse()?;
+        let defs = def.ring[0].point_defs(&Vars::new())?;
+        assert_eq!(defs.len(), 5);
+        assert!(matches!(
+            &defs[2].0,
+            PtDef::Branch(b, d) if b == "arm" && *d == 1.6
+        ));
+        assert!(matches!(defs[3].0, PtDef::Distance(d) if d == 1.6));
+        assert!(matches!(defs[4].0, PtDef::Distance(d) if d == 1.6));
+        for bad in ["arm:~", "a-b:1", "1.6:arm", "arm:1:2", "arm:x"] {
+            let def: ModelDef = format!("ring:\n  points: {bad}\n").parse()?;
+            let err = def.ring[0].point_defs(&Vars::new()).unwrap_err();
+            let msg = err.to_string();
+            assert!(msg.starts_with(&format!("Invalid point def: {bad} (")));
+        }
+        const RINGS: &str = "ring:\n  points: 1 * 3 arm:$d 1 * 3\n\
+            ring:\n  axis: 0 1 0\n\
+            ring:\n  branch: arm\n  axis: 1 0 0\n";
+        let mesh = model(&format!("let: d=1\n{RINGS}"))?.into_mesh()?;
+        let wide = model(&format!("let: d=1.6\n{RINGS}"))?.into_mesh()?;
+        assert_eq!(mesh.positions().len(), wide.positions().len());
+        assert_ne!(mesh.positions(), wide.positions());
+        Ok(())
+    }
+
+    #[test]
+    fn twist_seam() -> Result<()> {
+        const HORN: &str = include_str!("../../hom/assets/horn.hom");
+        let def: ModelDef = HORN.parse()?;
+        let vars = parse_vars(&def.vars)?;
+        assert_eq!(def.ring[0].twist(&vars)?, Some(15.0));
+        assert_eq!(def.ring[0].seam(&vars)?, Some(90.0));
+        assert_eq!(def.ring[1].twist(&vars)?, None);
+        assert_eq!(def.ring[7].twist(&vars)?, Some(30.0));
+        // round trip through def layer
+        let muon = muon_rs::to_string(&def)?;
+        let def2: ModelDef = muon.parse()?;
+        assert_eq!(def2.ring[0].twist, def.ring[0].twist);
+        assert_eq!(def2.ring[0].seam, def.ring[0].seam);
+        assert_eq!(glb(HORN)?, glb(&muon)?);
+        let untwisted = HORN.replace("let: twist=15", "let: twist=0");
+        assert_ne!(glb(HORN)?, glb(&untwisted)?);
+        let twist = |t: &str| format!("ring:\n  points: 1 * 3\n  twist: {t}\n");
+        assert!(model(&twist("-45")).is_ok());
+        assert!(model(&twist("15deg")).is_ok());
+        assert!(model(&twist("1/0")).is_err());
+        let seam = |t: &str| format!("ring:\n  points: 1 * 3\n  seam: {t}\n");
+        assert!(model(&seam("359")).is_ok());
+        assert!(model(&seam("360")).is_err());
+        assert!(model(&seam("-1")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn jitter() -> Result<()> {
+        let def: ModelDef = ROCK.parse()?;
+        assert_eq!(def.seed(), Some(1234));
+        assert_eq!(glb(ROCK)?, glb(ROCK)?);
+        let reseeded = ROCK.replace("seed: 1234", "seed: 4321");
+        assert_ne!(glb(ROCK)?, glb(&reseeded)?);
+        let smooth = ROCK.replace("jitter: 0.1", "jitter: 0");
+        let smooth = smooth.replace("jitter: 0.2", "jitter: 0");
+        let plain = ROCK.replace("  jitter: 0.1\n", "");
+        let plain = plain.replace("  jitter: 0.2\n", "");
+        assert_eq!(glb(&smooth)?, glb(&plain)?);
+        let def: ModelDef = plain.parse()?;
+        assert_eq!(def.seed(), Some(1234));
+        let def: ModelDef = VASE.parse()?;
+        assert_eq!(def.seed(), None);
+        assert!(model("ring:\n  points: 1 * 3\n  jitter: -1\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn variables() -> Result<()> {
+        let def: ModelDef = VASE.parse()?;
+        let vars = parse_vars(&def.vars)?;
+        assert_eq!(vars["neck"], 0.6);
+        let ring = &def.ring[0];
+        assert_eq!(ring.axis(&vars)?, Some(Vec3::new(0.0, 0.5, 0.0)));
+        assert_eq!(def.ring[1].scale(&vars)?, Some(1.5));
+        let defs = def.ring[2].point_defs(&vars)?;
+        assert_eq!(defs.len(), 5);
+        assert!(matches!(defs[1].0, PtDef::Distance(d) if d == 0.6 * 1.1));
+        let husk = model(VASE)?;
+        assert!((husk.spine_length() - 1.5).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn scale_xz() -> Result<()> {
+        let muon = "let: z=0.5\nring:\n  points: 1 * 4\n  scale: 1.5 $z\n";
+        let mesh = model(muon)?.into_mesh()?;
+        assert!((mesh.pos_max().x - 1.5).abs() < 1e-6);
+        assert!((mesh.pos_max().z - 0.5).abs() < 1e-6);
+        assert!(model("ring:\n  points: 1 * 4\n  scale: 1 2 3\n").is_err());
+        assert!(model("ring:\n  points: 1 * 4\n  scale: 1 -2\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn shapes() -> Result<()> {
+        let husk = model("ring:\n  points: [star 5 2 1]\n")?;
+        assert_eq!(husk.current_ring().unwrap().spoke_count(), 10);
+        let husk = model("ring:\n  points: [superellipse 12 1 2 4]\n")?;
+        assert_eq!(husk.current_ring().unwrap().spoke_count(), 12);
+        let mesh = model("ring:\n  points: [polygon 4 1]\n  scale: 2\n")?
+            .into_mesh()?;
+        assert_eq!(mesh.pos_max().x, 2.0);
+        let mesh =
+            model("ring:\n  points: [ellipse 8 2 0.5]\n")?.into_mesh()?;
+        assert!((mesh.pos_max().z - 0.5).abs() < 1e-6);
+        assert!(model("ring:\n  points: [star 2 2 1]\n").is_err());
+        assert!(model("ring:\n  points: [star 5 2]\n").is_err());
+        assert!(model("ring:\n  points: [polygon 4 -1]\n").is_err());
+        assert!(model("ring:\n  points: [gear 4 1]\n").is_err());
+        assert!(model("ring:\n  points: []\n").is_err());
+        assert!(model("ring:\n  points: [polygon 4 1\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn offset() -> Result<()> {
+        let husk = model(
+            "let: x=0.5\nring:\n  points: 1 * 4\n\
+             ring:\n  axis: 0 1 0\n  offset: $x -0.25\n",
+        )?;
+        let center = husk.current_ring().unwrap().center();
+        assert!(center.distance(Vec3::new(0.5, 1.0, -0.25)) < 1e-6);
+        assert!(model("ring:\n  points: 1 * 4\n  offset: 1\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn variable_errors() -> Result<()> {
+        let err = model("ring:\n  points: $girth * 3\n").err().unwrap();
+        assert!(err.to_string().contains("Unknown variable: $girth"));
+        let err = model("ring:\n  points: 1 * 3\n  scale: 1/0\n");
+        assert!(err.err().unwrap().to_string().contains("Division by zero"));
+        let err = model("let: a=1\nring:\n  points: 1\n  axis: 0 $a\n");
+        assert!(err.err().unwrap().to_string().contains("Invalid axis"));
+        let def: ModelDef = "ring:\n  points: arm2 1\n".parse()?;
+        let defs = def.ring[0].point_defs(&Vars::new())?;
+        assert!(matches!(&defs[0].0, PtDef::Branch(b, _) if b == "arm2"));
+        let def: ModelDef = "ring:\n  points: a-b 1\n".parse()?;
+        assert!(def.ring[0].point_defs(&Vars::new()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn assets() -> Result<()> {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../hom/assets");
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "hom") {
+                let file = std::fs::File::open(&path)?;
+                let def = ModelDef::from_reader(file)?;
+                if def.has_parts() {
+                    Scene::try_from(&def)?;
+                } else {
+                    Husk::try_from(&def)?.into_mesh()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
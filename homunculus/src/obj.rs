@@ -0,0 +1,145 @@
+// obj.rs       Wavefront OBJ module
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::gltf::MeshNode;
+use std::io::{BufWriter, Result, Write};
+
+/// Export mesh nodes to a writer as Wavefront OBJ
+///
+/// Vertices are written as they are in each mesh, with split vertices
+/// repeated, so shading matches glTF export.  Node placement is applied,
+/// and each surface is a smoothing group.
+pub(crate) fn export<W: Write>(writer: W, nodes: &[MeshNode]) -> Result<()> {
+    let mut w = BufWriter::new(writer);
+    writeln!(w, "# homunculus {}", env!("CARGO_PKG_VERSION"))?;
+    // OBJ indices are 1-based, and count across all objects
+    let (mut base, mut base_uv) = (1, 1);
+    // smoothing group 0 is "off"
+    let mut base_group = 1;
+    for node in nodes {
+        let mesh = node.mesh;
+        if let Some(name) = node.name {
+            writeln!(w, "o {name}")?;
+            writeln!(w, "g {name}")?;
+        }
+        for p in mesh.positions() {
+            let p = node.rotation * *p + node.translation;
+            writeln!(w, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+        for uv in mesh.uvs() {
+            // OBJ texture origin is bottom left
+            writeln!(w, "vt {} {}", uv.x, 1.0 - uv.y)?;
+        }
+        for n in mesh.normals() {
+            let n = node.rotation * *n;
+            writeln!(w, "vn {} {} {}", n.x, n.y, n.z)?;
+        }
+        let has_uv = !mesh.uvs().is_empty();
+        let mut surface = None;
+        for (f, tri) in mesh.indices().chunks_exact(3).enumerate() {
+            let s = mesh.face_surface(f);
+            if let Some(s) = s.filter(|s| Some(*s) != surface) {
+                writeln!(w, "s {}", base_group + u32::from(s))?;
+                surface = Some(s);
+            }
+            write!(w, "f")?;
+            for v in tri {
+                let vt = usize::from(v.0) + base_uv;
+                let v = usize::from(v.0) + base;
+                if has_uv {
+                    write!(w, " {v}/{vt}/{v}")?;
+                } else {
+                    write!(w, " {v}//{v}")?;
+                }
+            }
+            writeln!(w)?;
+        }
+        base += mesh.positions().len();
+        base_uv += mesh.uvs().len();
+        let surfaces = (0..mesh.indices().len() / 3)
+            .filter_map(|f| mesh.face_surface(f))
+            .max()
+            .map_or(0, |s| u32::from(s) + 1);
+        base_group += surfaces;
+    }
+    w.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Husk, Ring, Shading};
+
+    /// Parsed OBJ records
+    #[derive(Default)]
+    struct Obj {
+        names: Vec<String>,
+        pos: Vec<[f32; 3]>,
+        norm: Vec<[f32; 3]>,
+        faces: Vec<[usize; 3]>,
+        groups: usize,
+    }
+
+    /// Parse an OBJ (only what the exporter writes)
+    fn parse(obj: &str) -> Obj {
+        let mut parsed = Obj::default();
+        let vec3 = |vals: Vec<&str>| -> [f32; 3] {
+            let v: Vec<f32> = vals.iter().map(|v| v.parse().unwrap()).collect();
+            [v[0], v[1], v[2]]
+        };
+        for line in obj.lines() {
+            let mut vals = line.split_whitespace();
+            match vals.next() {
+                Some("o") | Some("g") => {
+                    parsed.names.push(vals.next().unwrap().to_string());
+                }
+                Some("v") => parsed.pos.push(vec3(vals.collect())),
+                Some("vn") => parsed.norm.push(vec3(vals.collect())),
+                Some("s") => parsed.groups += 1,
+                Some("f") => {
+                    let vtx: Vec<usize> = vals
+                        .map(|v| {
+                            let idx: Vec<&str> = v.split('/').collect();
+                            assert_eq!(idx[0], idx[2]);
+                            idx[0].parse().unwrap()
+                        })
+                        .collect();
+                    parsed.faces.push([vtx[0], vtx[1], vtx[2]]);
+                }
+                _ => (),
+            }
+        }
+        parsed
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut husk = Husk::new();
+        let base = Ring::default()
+            .shading(Shading::Flat)
+            .spoke(1.0)
+            .spoke(1.0)
+            .spoke(1.0)
+            .spoke(1.0);
+        husk.ring(base).unwrap();
+        husk.ring(Ring::default().spoke(0.0)).unwrap();
+        husk.name("pyramid");
+        let mut buf = Vec::new();
+        husk.write_obj(&mut buf).unwrap();
+        let obj = parse(std::str::from_utf8(&buf).unwrap());
+        let mesh = husk.to_mesh().unwrap();
+        assert_eq!(obj.names, ["pyramid", "pyramid"]);
+        assert_eq!(obj.pos.len(), mesh.positions().len());
+        assert_eq!(obj.norm.len(), mesh.normals().len());
+        assert_eq!(obj.faces.len(), mesh.indices().len() / 3);
+        // one smoothing group for each flat side
+        assert_eq!(obj.groups, 4);
+        for (face, tri) in obj.faces.iter().zip(mesh.indices().chunks(3)) {
+            for (v, idx) in face.iter().zip(tri) {
+                let vid = usize::from(idx.0);
+                assert_eq!(*v, vid + 1);
+                assert_eq!(obj.pos[v - 1], mesh.positions()[vid].to_array());
+            }
+        }
+    }
+}
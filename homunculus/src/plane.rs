@@ -1,41 +1,91 @@
 // plane.rs     Plane module
 //
-// Copyright (c) 2022  Douglas Lau
+// Copyright (c) 2022-2024  Douglas Lau
 //
 use glam::Vec3;
 
 /// Geometric plane
 ///
-/// Stored in Hessian Normal form
+/// Used for cross-sections and mirroring.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Plane {
-    /// Normal vector
-    pub normal: Vec3,
+    /// Unit normal
+    normal: Vec3,
 
-    /// Distance to origin
-    pub origin_dist: f32,
+    /// Distance from origin along normal
+    offset: f32,
 }
 
 impl Plane {
-    /// Create a new plane (with normal and one point on the plane)
+    /// Create a plane from a normal and a point on the plane
+    ///
+    /// # Panics
+    ///
+    /// - If `normal` is zero, infinite, or NaN
     pub fn new(normal: Vec3, point: Vec3) -> Self {
         let normal = normal.normalize();
-        let origin_dist = -normal.dot(point);
+        assert!(normal.is_finite());
         Plane {
             normal,
-            origin_dist,
+            offset: normal.dot(point),
         }
     }
 
-    /// Calculate distance to a point
+    /// Create a plane perpendicular to the X axis
+    pub fn x(x: f32) -> Self {
+        Plane::new(Vec3::X, Vec3::new(x, 0.0, 0.0))
+    }
+
+    /// Create a plane perpendicular to the Y axis
+    pub fn y(y: f32) -> Self {
+        Plane::new(Vec3::Y, Vec3::new(0.0, y, 0.0))
+    }
+
+    /// Create a plane perpendicular to the Z axis
+    pub fn z(z: f32) -> Self {
+        Plane::new(Vec3::Z, Vec3::new(0.0, 0.0, z))
+    }
+
+    /// Get the unit normal
+    pub fn normal(&self) -> Vec3 {
+        self.normal
+    }
+
+    /// Get signed distance of a point from the plane
     ///
-    /// Negative value returned for negative half-space
-    pub fn point_dist(&self, point: Vec3) -> f32 {
-        self.normal.dot(point) + self.origin_dist
+    /// Points on the side the normal faces have positive distance.
+    pub fn distance(&self, pt: Vec3) -> f32 {
+        self.normal.dot(pt) - self.offset
     }
 
     /// Project a point onto the plane
-    pub fn project_point(&self, point: Vec3) -> Vec3 {
-        point - self.normal * self.point_dist(point)
+    pub fn project(&self, pt: Vec3) -> Vec3 {
+        pt - self.normal * self.distance(pt)
+    }
+
+    /// Get basis vectors for plane coordinates
+    ///
+    /// For the Y plane, these are X and Z.
+    pub(crate) fn basis(&self) -> (Vec3, Vec3) {
+        let up = if self.normal.y.abs() < 0.9 {
+            Vec3::Y
+        } else {
+            Vec3::Z
+        };
+        let u = self.normal.cross(up).normalize();
+        let v = u.cross(self.normal);
+        (u, v)
+    }
+
+    /// Reflect a point across the plane
+    pub fn reflect(&self, pt: Vec3) -> Vec3 {
+        pt - self.normal * (2.0 * self.distance(pt))
+    }
+
+    /// Reflect a direction vector across the plane
+    pub fn reflect_dir(&self, dir: Vec3) -> Vec3 {
+        dir - self.normal * (2.0 * self.normal.dot(dir))
     }
 }
 
@@ -45,21 +95,30 @@ mod test {
 
     #[test]
     fn up_plane() {
-        let p = Plane::new(Vec3::new(0.0, 1.0, 0.0), Vec3::ZERO);
-        assert_eq!(p.point_dist(Vec3::new(0.0, 0.0, 0.0)), 0.0);
-        assert_eq!(p.point_dist(Vec3::new(1.0, 1.0, 1.0)), 1.0);
-        assert_eq!(p.point_dist(Vec3::new(0.0, 1.0, 1.0)), 1.0);
-        assert_eq!(p.point_dist(Vec3::new(0.0, 1.0, 0.0)), 1.0);
-        assert_eq!(p.point_dist(Vec3::new(0.0, -1.0, 0.0)), -1.0);
+        let p = Plane::y(0.0);
+        assert_eq!(p.distance(Vec3::new(0.0, 0.0, 0.0)), 0.0);
+        assert_eq!(p.distance(Vec3::new(1.0, 1.0, 1.0)), 1.0);
+        assert_eq!(p.distance(Vec3::new(0.0, 1.0, 1.0)), 1.0);
+        assert_eq!(p.distance(Vec3::new(0.0, 1.0, 0.0)), 1.0);
+        assert_eq!(p.distance(Vec3::new(0.0, -1.0, 0.0)), -1.0);
     }
 
     #[test]
     fn angled_plane() {
         let p = Plane::new(Vec3::new(1.0, 1.0, 1.0), Vec3::ZERO);
-        assert_eq!(p.point_dist(Vec3::new(0.0, 0.0, 0.0)), 0.0);
-        assert_eq!(p.point_dist(Vec3::new(1.0, 0.0, 0.0)), 0.57735026);
-        assert_eq!(p.point_dist(Vec3::new(0.0, 1.0, 0.0)), 0.57735026);
-        assert_eq!(p.point_dist(Vec3::new(0.0, 0.0, 1.0)), 0.57735026);
-        assert_eq!(p.point_dist(Vec3::new(0.0, -1.0, 0.0)), -0.57735026);
+        assert_eq!(p.distance(Vec3::new(0.0, 0.0, 0.0)), 0.0);
+        assert_eq!(p.distance(Vec3::new(1.0, 0.0, 0.0)), 0.57735026);
+        assert_eq!(p.distance(Vec3::new(0.0, 1.0, 0.0)), 0.57735026);
+        assert_eq!(p.distance(Vec3::new(0.0, 0.0, 1.0)), 0.57735026);
+        assert_eq!(p.distance(Vec3::new(0.0, -1.0, 0.0)), -0.57735026);
+    }
+
+    #[test]
+    fn reflect() {
+        let p = Plane::x(1.0);
+        let pt = Vec3::new(3.0, 2.0, -1.0);
+        assert_eq!(p.reflect(pt), Vec3::new(-1.0, 2.0, -1.0));
+        assert_eq!(p.project(pt), Vec3::new(1.0, 2.0, -1.0));
+        assert_eq!(p.reflect_dir(pt), Vec3::new(-3.0, 2.0, -1.0));
     }
 }
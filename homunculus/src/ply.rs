@@ -0,0 +1,174 @@
+// ply.rs       Polygon File Format module
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::gltf::MeshNode;
+use glam::{Vec3, Vec4};
+use std::io::{BufWriter, Result, Write};
+
+/// PLY encoding
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlyFormat {
+    /// Binary, little-endian
+    #[default]
+    Binary,
+
+    /// ASCII text
+    Ascii,
+}
+
+/// Vertex for PLY export
+struct PlyVertex {
+    pos: Vec3,
+    norm: Vec3,
+    color: [u8; 3],
+}
+
+impl PlyFormat {
+    /// Get format name for header
+    fn name(self) -> &'static str {
+        match self {
+            PlyFormat::Binary => "binary_little_endian",
+            PlyFormat::Ascii => "ascii",
+        }
+    }
+}
+
+/// Export mesh nodes to a writer as PLY
+///
+/// All nodes are merged, with their placement applied.  Vertex colors are
+/// written if any mesh has them.
+pub(crate) fn export<W: Write>(
+    writer: W,
+    nodes: &[MeshNode],
+    format: PlyFormat,
+) -> Result<()> {
+    let has_color = nodes.iter().any(|n| !n.mesh.colors().is_empty());
+    let mut vertices = Vec::new();
+    let mut faces = Vec::new();
+    for node in nodes {
+        let mesh = node.mesh;
+        // unwrap note: each mesh has fewer than 65536 vertices
+        let base = u32::try_from(vertices.len()).unwrap();
+        for (v, (p, n)) in
+            mesh.positions().iter().zip(mesh.normals()).enumerate()
+        {
+            let color = mesh.colors().get(v).copied().unwrap_or(Vec4::ONE);
+            let color = (color.truncate() * f32::from(u8::MAX)).round();
+            vertices.push(PlyVertex {
+                pos: node.rotation * *p + node.translation,
+                norm: node.rotation * *n,
+                color: color.to_array().map(|c| c as u8),
+            });
+        }
+        for tri in mesh.indices().chunks_exact(3) {
+            faces.push([0, 1, 2].map(|i| base + u32::from(tri[i].0)));
+        }
+    }
+    let mut w = BufWriter::new(writer);
+    writeln!(w, "ply")?;
+    writeln!(w, "format {} 1.0", format.name())?;
+    writeln!(w, "comment homunculus {}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(w, "element vertex {}", vertices.len())?;
+    for prop in ["x", "y", "z", "nx", "ny", "nz"] {
+        writeln!(w, "property float {prop}")?;
+    }
+    if has_color {
+        for prop in ["red", "green", "blue"] {
+            writeln!(w, "property uchar {prop}")?;
+        }
+    }
+    writeln!(w, "element face {}", faces.len())?;
+    writeln!(w, "property list uchar uint vertex_indices")?;
+    writeln!(w, "end_header")?;
+    match format {
+        PlyFormat::Binary => {
+            for v in &vertices {
+                for c in v.pos.to_array().iter().chain(&v.norm.to_array()) {
+                    w.write_all(&c.to_le_bytes())?;
+                }
+                if has_color {
+                    w.write_all(&v.color)?;
+                }
+            }
+            for face in &faces {
+                w.write_all(&[3])?;
+                for v in face {
+                    w.write_all(&v.to_le_bytes())?;
+                }
+            }
+        }
+        PlyFormat::Ascii => {
+            for v in &vertices {
+                let (p, n) = (v.pos, v.norm);
+                write!(w, "{} {} {} {} {} {}", p.x, p.y, p.z, n.x, n.y, n.z)?;
+                if has_color {
+                    let [r, g, b] = v.color;
+                    write!(w, " {r} {g} {b}")?;
+                }
+                writeln!(w)?;
+            }
+            for [v0, v1, v2] in &faces {
+                writeln!(w, "3 {v0} {v1} {v2}")?;
+            }
+        }
+    }
+    w.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::primitives::pyramid;
+    use crate::Ring;
+
+    #[test]
+    fn ascii_round_trip() {
+        let husk = pyramid(Ring::default().color([1.0, 0.5, 0.0, 1.0]));
+        let mut buf = Vec::new();
+        husk.write_ply(&mut buf, PlyFormat::Ascii).unwrap();
+        let ply = String::from_utf8(buf).unwrap();
+        let (header, body) = ply.split_once("end_header\n").unwrap();
+        let count = |elem: &str| -> usize {
+            let prefix = format!("element {elem} ");
+            let line = header.lines().find(|l| l.starts_with(&prefix));
+            line.unwrap()[prefix.len()..].parse().unwrap()
+        };
+        let mesh = husk.to_mesh().unwrap();
+        let (n_vtx, n_face) = (count("vertex"), count("face"));
+        assert_eq!(n_vtx, mesh.positions().len());
+        assert_eq!(n_face, mesh.indices().len() / 3);
+        assert!(header.contains("property uchar red\n"));
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), n_vtx + n_face);
+        for line in &lines[..n_vtx] {
+            let vals: Vec<&str> = line.split_whitespace().collect();
+            assert_eq!(vals[6..], ["255", "128", "0"]);
+        }
+        for line in &lines[n_vtx..] {
+            let vals: Vec<usize> = line
+                .split_whitespace()
+                .map(|v| v.parse().unwrap())
+                .collect();
+            assert_eq!(vals[0], 3);
+            assert!(vals[1..].iter().all(|v| *v < n_vtx));
+        }
+    }
+
+    #[test]
+    fn binary_size() {
+        let husk = pyramid(Ring::default());
+        let mut buf = Vec::new();
+        husk.write_ply(&mut buf, PlyFormat::Binary).unwrap();
+        let end = b"end_header\n";
+        let start = buf.windows(end.len()).position(|w| w == end).unwrap();
+        let header = std::str::from_utf8(&buf[..start]).unwrap();
+        assert!(header.contains("format binary_little_endian 1.0\n"));
+        assert!(!header.contains("red"));
+        let mesh = husk.to_mesh().unwrap();
+        let n_vtx = mesh.positions().len();
+        let n_face = mesh.indices().len() / 3;
+        let len = buf.len() - start - end.len();
+        assert_eq!(len, n_vtx * 24 + n_face * 13);
+    }
+}
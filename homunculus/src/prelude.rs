@@ -0,0 +1,24 @@
+// prelude.rs   Common imports
+//
+// Copyright (c) 2024  Douglas Lau
+//
+//! Everything needed to build a model
+//!
+//! ```rust
+//! use homunculus::prelude::*;
+//! # fn main() -> Result<(), Error> {
+//! let mut husk = Husk::new();
+//! let base = Ring::default()
+//!     .shading(Shading::Flat)
+//!     .spoke(1.0)
+//!     .spoke(Spoke::from(1.0).with_sharp(true))
+//!     .spoke(1.0)
+//!     .spoke(1.0);
+//! husk.ring(base)?;
+//! husk.ring(Ring::default().spoke(0.0))?;
+//! let mut glb = Vec::new();
+//! husk.write_gltf(&mut glb)?;
+//! # Ok(())
+//! # }
+//! ```
+pub use crate::{Error, Husk, Ring, Shading, Spoke};
@@ -0,0 +1,346 @@
+// primitives.rs  Primitive shape generators
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::error::Result;
+use crate::husk::Husk;
+use crate::mesh::{Face, Mesh, MeshBuilder};
+use crate::ring::Ring;
+use fastrand::Rng;
+use glam::{Vec2, Vec3};
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// Get profile points (radius, height) on a unit circle arc
+///
+/// Latitudes are from `lat0` to `lat1` (radians), offset by `height`.
+fn arc(
+    rings: usize,
+    lat0: f32,
+    lat1: f32,
+    height: f32,
+) -> impl Iterator<Item = Vec2> {
+    (0..=rings).map(move |i| {
+        let lat = lat0 + (lat1 - lat0) * i as f32 / rings as f32;
+        // exact points at the poles
+        let radius = if lat.abs() == FRAC_PI_2 {
+            0.0
+        } else {
+            lat.cos()
+        };
+        Vec2::new(radius, height + lat.sin())
+    })
+}
+
+/// Build a UV sphere husk, with radius 1
+///
+/// It is built along the +Y axis, from the south pole at the origin.
+/// There are `rings` bands from pole to pole, each with `segments` faces
+/// around.
+///
+/// # Panics
+///
+/// - If `rings` is less than 2 or `segments` is less than 3
+pub fn build_uv_sphere(rings: usize, segments: usize) -> Result<Husk> {
+    assert!(rings >= 2);
+    let profile: Vec<_> = arc(rings, -FRAC_PI_2, FRAC_PI_2, 1.0).collect();
+    Husk::lathe(&profile, segments)
+}
+
+/// Build a closed cylinder husk, with radius 1
+///
+/// It is built along the +Y axis, with the base at the origin.
+///
+/// # Panics
+///
+/// - If `segments` is less than 3
+pub fn build_cylinder(segments: usize, height: f32) -> Result<Husk> {
+    Husk::lathe(&[Vec2::new(1.0, 0.0), Vec2::new(1.0, height)], segments)
+}
+
+/// Build a closed cone husk, with base radius 1
+///
+/// It is built along the +Y axis, with the base at the origin.
+///
+/// # Panics
+///
+/// - If `segments` is less than 3
+pub fn build_cone(segments: usize, height: f32) -> Result<Husk> {
+    Husk::lathe(&[Vec2::new(1.0, 0.0), Vec2::new(0.0, height)], segments)
+}
+
+/// Build a capsule husk, with radius 1
+///
+/// It is built along the +Y axis, from the bottom at the origin.  Each
+/// hemisphere end has `rings` bands, and the cylinder between them is
+/// `height` long.
+///
+/// # Panics
+///
+/// - If `rings` is zero or `segments` is less than 3
+pub fn build_capsule(
+    rings: usize,
+    segments: usize,
+    height: f32,
+) -> Result<Husk> {
+    assert!(rings > 0);
+    let bottom = arc(rings, -FRAC_PI_2, 0.0, 1.0);
+    let top = arc(rings, 0.0, FRAC_PI_2, 1.0 + height);
+    let profile: Vec<_> = bottom.chain(top).collect();
+    Husk::lathe(&profile, segments)
+}
+
+/// Random tree builder
+struct Tree {
+    /// Tree husk
+    husk: Husk,
+
+    /// Random number generator
+    rng: Rng,
+
+    /// Labels and scales of limbs to grow
+    limbs: Vec<(String, f32)>,
+}
+
+impl Tree {
+    /// Make a ring, with an optional branch label on a random spoke
+    fn make_ring(&mut self, label: Option<&str>, spokes: usize) -> Ring {
+        let mut ring = Ring::default().axis(Vec3::new(0.0, 1.0, 0.0));
+        let b = self.rng.usize(..spokes);
+        for i in 0..spokes {
+            ring = match label {
+                Some(label) if i == b => ring.spoke(label),
+                _ => ring.spoke(1.0),
+            };
+        }
+        ring
+    }
+
+    /// Grow one limb, adding labels of sub-limbs
+    fn grow(&mut self, mut scale: f32, spokes: usize) -> Result<()> {
+        let mut i = 0;
+        while scale > 0.05 {
+            let sc = scale * 0.5;
+            let ring = if i % 3 == 1 && self.rng.f32() > scale && sc > 0.05 {
+                let label = format!("B{}", self.limbs.len());
+                let ring = self.make_ring(Some(&label), spokes);
+                self.limbs.push((label, sc));
+                ring
+            } else {
+                self.make_ring(None, spokes)
+            };
+            let x = self.rng.f32() * 0.01 - (0.01 * 0.5);
+            let z = self.rng.f32() * 0.04 - (0.04 * 0.5);
+            let axis = Vec3::new(x, scale, z);
+            let ring = ring.axis(axis).scale(scale).color(bark(scale));
+            self.husk.ring(ring)?;
+            scale *= 0.96;
+            i += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Get bark color, darker at the base
+fn bark(scale: f32) -> [f32; 4] {
+    let t = scale.clamp(0.0, 1.0);
+    [0.45 - 0.25 * t, 0.32 - 0.19 * t, 0.2 - 0.13 * t, 1.0]
+}
+
+/// Build a random tree husk
+///
+/// The trunk is built along the +Y axis, from the origin.  The same `seed`
+/// always makes the same tree.
+pub fn build_tree(seed: u64) -> Result<Husk> {
+    let mut tree = Tree {
+        husk: Husk::new(),
+        rng: Rng::with_seed(seed),
+        limbs: Vec::new(),
+    };
+    tree.grow(1.0, 6)?;
+    let mut grown = 0;
+    while grown < tree.limbs.len() {
+        let (label, scale) = tree.limbs[grown].clone();
+        grown += 1;
+        let ring = tree.husk.branch(label)?;
+        // limb rings have one spoke per base point
+        let spokes = ring.base_point_count().max(3);
+        tree.husk.ring(ring)?;
+        tree.grow(scale, spokes)?;
+    }
+    Ok(tree.husk)
+}
+
+/// Build a unit cube mesh, from the origin to (1, 1, 1)
+///
+/// Each side is a separate surface, so it is flat shaded.
+pub fn build_cube() -> Result<Mesh> {
+    cube_builder(true).build()
+}
+
+/// Make a unit cube mesh builder, from the origin to (1, 1, 1)
+///
+/// With `sides`, each side is a separate surface; otherwise all faces are
+/// on surface 0.
+pub(crate) fn cube_builder(sides: bool) -> MeshBuilder {
+    let mut builder = MeshBuilder::with_capacity(12);
+    for i in 0..8 {
+        let [x, y, z] = [i & 1, (i >> 1) & 1, (i >> 2) & 1];
+        // unwrap note: only 8 vertices
+        builder
+            .push_vtx(Vec3::new(x as f32, y as f32, z as f32))
+            .unwrap();
+    }
+    let quads = [
+        [0, 2, 3, 1],
+        [4, 5, 7, 6],
+        [0, 1, 5, 4],
+        [2, 6, 7, 3],
+        [0, 4, 6, 2],
+        [1, 3, 7, 5],
+    ];
+    for (side, [v0, v1, v2, v3]) in (0..).zip(quads) {
+        let surface = if sides { side } else { 0 };
+        builder.push_face(Face::new([v0, v1, v2], surface));
+        builder.push_face(Face::new([v0, v2, v3], surface));
+    }
+    builder
+}
+
+/// Build a pyramid husk with a triangle `base` ring (test fixture)
+///
+/// Three spokes are added to the base, which may have other settings.
+#[cfg(test)]
+pub(crate) fn pyramid(base: Ring) -> Husk {
+    let mut husk = Husk::new();
+    husk.ring(base.spoke(1.0).spoke(1.0).spoke(1.0)).unwrap();
+    husk.ring(Ring::default().spoke(0.0)).unwrap();
+    husk
+}
+
+/// Build a torus mesh around the Y axis, centered at the origin
+///
+/// The tube has radius `minor_radius`, with `minor_seg` faces around it.
+/// Its center circle has radius `major_radius`, with `major_seg` segments.
+///
+/// # Panics
+///
+/// - If `major_seg` or `minor_seg` is less than 3
+pub fn build_torus(
+    major_seg: usize,
+    minor_seg: usize,
+    minor_radius: f32,
+    major_radius: f32,
+) -> Result<Mesh> {
+    assert!(major_seg >= 3 && minor_seg >= 3);
+    let mut builder = MeshBuilder::with_capacity(major_seg * minor_seg * 2);
+    for i in 0..major_seg {
+        let phi = 2.0 * PI * i as f32 / major_seg as f32;
+        for j in 0..minor_seg {
+            let theta = 2.0 * PI * j as f32 / minor_seg as f32;
+            let dist = major_radius + minor_radius * theta.cos();
+            let pos = Vec3::new(
+                dist * phi.cos(),
+                minor_radius * theta.sin(),
+                dist * phi.sin(),
+            );
+            builder.push_vtx(pos)?;
+        }
+    }
+    let vtx = |i: usize, j: usize| (i % major_seg) * minor_seg + j % minor_seg;
+    for i in 0..major_seg {
+        for j in 0..minor_seg {
+            let [a, b] = [vtx(i, j), vtx(i + 1, j)];
+            let [c, d] = [vtx(i, j + 1), vtx(i + 1, j + 1)];
+            builder.push_face(Face::new([a, c, b], 0));
+            builder.push_face(Face::new([b, c, d], 0));
+        }
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn face_count(mesh: &Mesh) -> usize {
+        mesh.indices().len() / 3
+    }
+
+    #[test]
+    fn uv_sphere() -> Result<()> {
+        let mesh = build_uv_sphere(8, 12)?.into_mesh()?;
+        // no faces at the poles are degenerate
+        assert_eq!(face_count(&mesh), 12 * 2 * 7);
+        assert_eq!(mesh.build_report().degenerate_faces, 0);
+        // 7 rings of 12 spokes and 2 poles, with texture seam duplicates
+        assert_eq!(mesh.positions().len(), 95);
+        assert!(mesh.is_watertight());
+        for p in mesh.positions() {
+            assert!((p.distance(Vec3::Y) - 1.0).abs() < 1e-5, "{p}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn cylinder() -> Result<()> {
+        let mesh = build_cylinder(16, 2.0)?.into_mesh()?;
+        assert_eq!(face_count(&mesh), 16 * 4);
+        assert!(mesh.is_watertight());
+        assert_eq!(mesh.pos_max().y, 2.0);
+        assert!(mesh.volume() > 0.9 * PI * 2.0);
+        Ok(())
+    }
+
+    #[test]
+    fn cone() -> Result<()> {
+        let mesh = build_cone(16, 2.0)?.into_mesh()?;
+        assert_eq!(face_count(&mesh), 16 * 2);
+        assert!(mesh.is_watertight());
+        assert!(mesh.volume() > 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn capsule() -> Result<()> {
+        let mesh = build_capsule(4, 12, 1.0)?.into_mesh()?;
+        // both hemispheres and the cylinder (pole bands have half)
+        assert_eq!(face_count(&mesh), 12 * 2 * (4 * 2));
+        assert!(mesh.is_watertight());
+        assert!((mesh.pos_max().y - 3.0).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn tree() -> Result<()> {
+        let mesh0 = build_tree(1234)?.into_mesh()?;
+        let mesh1 = build_tree(1234)?.into_mesh()?;
+        assert_eq!(mesh0.positions(), mesh1.positions());
+        assert!(!mesh0.colors().is_empty());
+        assert!(mesh0.pos_max().y > 1.0);
+        Ok(())
+    }
+
+    #[test]
+    fn cube() -> Result<()> {
+        let mesh = build_cube()?;
+        // vertices are split between sides
+        assert_eq!(mesh.positions().len(), 24);
+        assert_eq!(face_count(&mesh), 12);
+        assert!(mesh.is_watertight());
+        assert!((mesh.volume() - 1.0).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn torus() -> Result<()> {
+        let mesh = build_torus(24, 12, 0.25, 1.0)?;
+        assert_eq!(mesh.positions().len(), 24 * 12);
+        assert_eq!(face_count(&mesh), 24 * 12 * 2);
+        assert!(mesh.is_watertight());
+        assert!(!mesh.is_inside_out());
+        // volume is 2 π² R r² (less for polygons)
+        let volume = 2.0 * PI * PI * 0.25 * 0.25;
+        assert!(mesh.volume() < volume && mesh.volume() > volume * 0.9);
+        Ok(())
+    }
+}
@@ -0,0 +1,392 @@
+// raycast.rs   Ray casting module
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::mesh::Mesh;
+use glam::{Affine3A, Vec3};
+use std::ops::Range;
+
+/// Maximum triangles in a leaf node
+const LEAF_TRIS: usize = 4;
+
+/// Ray with precomputed inverse direction
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Ray {
+    /// Origin point
+    origin: Vec3,
+
+    /// Direction (not necessarily normalized)
+    dir: Vec3,
+
+    /// Inverse of direction, for slab tests
+    inv_dir: Vec3,
+}
+
+/// Contents of a BVH node
+#[derive(Clone, Debug)]
+enum Content {
+    /// Range of triangles
+    Leaf(Range<usize>),
+
+    /// Child node indices
+    Branch(usize, usize),
+}
+
+/// Bounding volume hierarchy node
+#[derive(Clone, Debug)]
+struct Node {
+    /// Minimum position
+    min: Vec3,
+
+    /// Maximum position
+    max: Vec3,
+
+    /// Node contents
+    content: Content,
+}
+
+/// Bounding volume hierarchy of mesh triangles
+pub(crate) struct Bvh {
+    /// Triangle vertex positions
+    tris: Vec<[Vec3; 3]>,
+
+    /// Nodes (root first)
+    nodes: Vec<Node>,
+}
+
+impl Ray {
+    /// Create a new ray
+    pub fn new(origin: Vec3, dir: Vec3) -> Self {
+        Ray {
+            origin,
+            dir,
+            inv_dir: dir.recip(),
+        }
+    }
+
+    /// Get distance to a bounding box (in units of direction length)
+    fn box_distance(&self, min: Vec3, max: Vec3) -> Option<f32> {
+        let t0 = (min - self.origin) * self.inv_dir;
+        let t1 = (max - self.origin) * self.inv_dir;
+        let near = t0.min(t1).max_element().max(0.0);
+        let far = t0.max(t1).min_element();
+        (near <= far).then_some(near)
+    }
+
+    /// Get distance to a triangle (in units of direction length)
+    ///
+    /// Uses the Möller–Trumbore algorithm.  Both sides are hit.
+    fn tri_distance(&self, tri: &[Vec3; 3]) -> Option<f32> {
+        let [v0, v1, v2] = *tri;
+        let e1 = v1 - v0;
+        let e2 = v2 - v0;
+        let p = self.dir.cross(e2);
+        let det = e1.dot(p);
+        if det.abs() <= f32::EPSILON {
+            return None;
+        }
+        let s = self.origin - v0;
+        let u = s.dot(p) / det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(e1);
+        let v = self.dir.dot(q) / det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = e2.dot(q) / det;
+        (t > 0.0).then_some(t)
+    }
+}
+
+/// Get bounds of triangles
+fn tri_bounds(tris: &[[Vec3; 3]]) -> (Vec3, Vec3) {
+    tris.iter().flatten().fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), p| (min.min(*p), max.max(*p)),
+    )
+}
+
+/// Check if two bounding boxes overlap
+fn boxes_overlap(a: (Vec3, Vec3), b: (Vec3, Vec3)) -> bool {
+    a.0.cmple(b.1).all() && a.1.cmpge(b.0).all()
+}
+
+/// Get centroid of a triangle
+fn centroid(tri: &[Vec3; 3]) -> Vec3 {
+    (tri[0] + tri[1] + tri[2]) / 3.0
+}
+
+impl Bvh {
+    /// Build a BVH for a mesh
+    pub fn new(mesh: &Mesh) -> Self {
+        Self::with_xform(mesh, Affine3A::IDENTITY)
+    }
+
+    /// Build a BVH for a mesh placed with a transform
+    pub fn with_xform(mesh: &Mesh, xform: Affine3A) -> Self {
+        let pos = mesh.positions();
+        let tris = mesh
+            .indices()
+            .chunks_exact(3)
+            .map(|t| {
+                [0, 1, 2]
+                    .map(|i| xform.transform_point3(pos[usize::from(t[i].0)]))
+            })
+            .collect();
+        let mut bvh = Bvh {
+            tris,
+            nodes: Vec::new(),
+        };
+        if !bvh.tris.is_empty() {
+            bvh.build_node(0..bvh.tris.len());
+        }
+        bvh
+    }
+
+    /// Build a node for a range of triangles
+    ///
+    /// Triangles are split at the median centroid along the longest axis.
+    fn build_node(&mut self, range: Range<usize>) -> usize {
+        let (min, max) = tri_bounds(&self.tris[range.clone()]);
+        let idx = self.nodes.len();
+        self.nodes.push(Node {
+            min,
+            max,
+            content: Content::Leaf(range.clone()),
+        });
+        if range.len() > LEAF_TRIS {
+            let size = max - min;
+            let axis = if size.x >= size.y && size.x >= size.z {
+                0
+            } else if size.y >= size.z {
+                1
+            } else {
+                2
+            };
+            let tris = &mut self.tris[range.clone()];
+            let mid = tris.len() / 2;
+            tris.select_nth_unstable_by(mid, |a, b| {
+                centroid(a)[axis].total_cmp(&centroid(b)[axis])
+            });
+            let mid = range.start + mid;
+            let left = self.build_node(range.start..mid);
+            let right = self.build_node(mid..range.end);
+            self.nodes[idx].content = Content::Branch(left, right);
+        }
+        idx
+    }
+
+    /// Get distance to the nearest hit (in units of direction length)
+    pub fn cast(&self, ray: &Ray) -> Option<f32> {
+        let mut nearest: Option<f32> = None;
+        let mut stack = Vec::with_capacity(32);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            match ray.box_distance(node.min, node.max) {
+                Some(t) if nearest.is_none_or(|n| t < n) => (),
+                _ => continue,
+            }
+            match &node.content {
+                Content::Leaf(range) => {
+                    for tri in &self.tris[range.clone()] {
+                        if let Some(t) = ray.tri_distance(tri) {
+                            if nearest.is_none_or(|n| t < n) {
+                                nearest = Some(t);
+                            }
+                        }
+                    }
+                }
+                Content::Branch(left, right) => {
+                    stack.push(*left);
+                    stack.push(*right);
+                }
+            }
+        }
+        nearest
+    }
+
+    /// Check if a ray hits any triangle
+    pub fn hits(&self, ray: &Ray) -> bool {
+        let mut stack = Vec::with_capacity(32);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            if ray.box_distance(node.min, node.max).is_none() {
+                continue;
+            }
+            match &node.content {
+                Content::Leaf(range) => {
+                    let tris = &self.tris[range.clone()];
+                    if tris.iter().any(|tri| ray.tri_distance(tri).is_some()) {
+                        return true;
+                    }
+                }
+                Content::Branch(left, right) => {
+                    stack.push(*left);
+                    stack.push(*right);
+                }
+            }
+        }
+        false
+    }
+
+    /// Visit pairs of triangles with overlapping bounding boxes
+    ///
+    /// Both hierarchies are descended together, splitting the larger node
+    /// of each overlapping pair.  Visiting stops when `visit` returns
+    /// `false`.
+    pub fn overlapping<F>(&self, other: &Bvh, mut visit: F)
+    where
+        F: FnMut(&[Vec3; 3], &[Vec3; 3]) -> bool,
+    {
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() && !other.nodes.is_empty() {
+            stack.push((0, 0));
+        }
+        while let Some((i0, i1)) = stack.pop() {
+            let (n0, n1) = (&self.nodes[i0], &other.nodes[i1]);
+            if !boxes_overlap((n0.min, n0.max), (n1.min, n1.max)) {
+                continue;
+            }
+            match (&n0.content, &n1.content) {
+                (Content::Leaf(r0), Content::Leaf(r1)) => {
+                    for t0 in &self.tris[r0.clone()] {
+                        let b0 = tri_bounds(std::slice::from_ref(t0));
+                        for t1 in &other.tris[r1.clone()] {
+                            let b1 = tri_bounds(std::slice::from_ref(t1));
+                            if boxes_overlap(b0, b1) && !visit(t0, t1) {
+                                return;
+                            }
+                        }
+                    }
+                }
+                (Content::Branch(left, right), Content::Leaf(_)) => {
+                    stack.push((*left, i1));
+                    stack.push((*right, i1));
+                }
+                (Content::Leaf(_), Content::Branch(left, right)) => {
+                    stack.push((i0, *left));
+                    stack.push((i0, *right));
+                }
+                (Content::Branch(l0, r0), Content::Branch(l1, r1)) => {
+                    let s0 = (n0.max - n0.min).length_squared();
+                    let s1 = (n1.max - n1.min).length_squared();
+                    if s0 >= s1 {
+                        stack.push((*l0, i1));
+                        stack.push((*r0, i1));
+                    } else {
+                        stack.push((i0, *l1));
+                        stack.push((i0, *r1));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Mesh {
+    /// Cast a ray at the mesh
+    ///
+    /// Returns the distance to the nearest hit, in units of `dir` length.
+    /// Both front and back faces are hit.
+    pub fn raycast(&self, origin: Vec3, dir: Vec3) -> Option<f32> {
+        Bvh::new(self).cast(&Ray::new(origin, dir))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Husk, Ring};
+
+    /// Make a tube along Y axis, from 0 to 10
+    fn tube() -> Mesh {
+        let mut husk = Husk::new();
+        let mut ring = Ring::default().axis(Vec3::new(0.0, 1.0, 0.0));
+        for _ in 0..12 {
+            ring = ring.spoke(1.0);
+        }
+        husk.ring(ring).unwrap();
+        for _ in 0..10 {
+            husk.ring(Ring::default()).unwrap();
+        }
+        husk.into_mesh().unwrap()
+    }
+
+    #[test]
+    fn raycast() {
+        let mesh = tube();
+        let t = mesh.raycast(Vec3::new(5.0, 4.5, 0.0), Vec3::NEG_X).unwrap();
+        assert!(t > 3.9 && t < 4.1, "{t}");
+        // from inside, back faces are hit
+        let t = mesh
+            .raycast(Vec3::new(0.0, 4.5, 0.0), Vec3::X * 2.0)
+            .unwrap();
+        assert!(t > 0.45 && t < 0.55, "{t}");
+        assert!(mesh.raycast(Vec3::new(5.0, 4.5, 0.0), Vec3::X).is_none());
+        assert!(mesh
+            .raycast(Vec3::new(5.0, 20.0, 0.0), Vec3::NEG_X)
+            .is_none());
+    }
+
+    #[test]
+    fn bvh_matches_brute_force() {
+        let mesh = tube();
+        let bvh = Bvh::new(&mesh);
+        let mut rng = fastrand::Rng::with_seed(7);
+        for _ in 0..200 {
+            let mut v = || rng.f32() * 2.0 - 1.0;
+            let origin = Vec3::new(v() * 3.0, v() * 6.0 + 5.0, v() * 3.0);
+            let dir = Vec3::new(v(), v(), v());
+            let ray = Ray::new(origin, dir);
+            let brute = bvh
+                .tris
+                .iter()
+                .filter_map(|tri| ray.tri_distance(tri))
+                .reduce(f32::min);
+            assert_eq!(bvh.cast(&ray), brute);
+            assert_eq!(bvh.hits(&ray), brute.is_some());
+        }
+    }
+
+    #[test]
+    fn overlapping_matches_brute_force() {
+        let mesh = tube();
+        let bvh0 = Bvh::new(&mesh);
+        let xform = Affine3A::from_rotation_z(0.5)
+            * Affine3A::from_translation(Vec3::new(0.7, 2.0, 0.0));
+        let bvh1 = Bvh::with_xform(&mesh, xform);
+        let mut pairs = Vec::new();
+        bvh0.overlapping(&bvh1, |t0, t1| {
+            pairs.push((*t0, *t1));
+            true
+        });
+        let mut brute = 0;
+        for t0 in &bvh0.tris {
+            let b0 = tri_bounds(std::slice::from_ref(t0));
+            for t1 in &bvh1.tris {
+                let b1 = tri_bounds(std::slice::from_ref(t1));
+                if boxes_overlap(b0, b1) {
+                    brute += 1;
+                    assert!(pairs.contains(&(*t0, *t1)));
+                }
+            }
+        }
+        assert!(brute > 0);
+        assert_eq!(pairs.len(), brute);
+        // visiting stops early
+        let mut count = 0;
+        bvh0.overlapping(&bvh1, |_, _| {
+            count += 1;
+            false
+        });
+        assert_eq!(count, 1);
+    }
+}
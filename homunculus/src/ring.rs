@@ -2,14 +2,21 @@
 //
 // Copyright (c) 2022-2023  Douglas Lau
 //
+use crate::error::{ignore_invalid, Error, Result};
 use crate::mesh::MeshBuilder;
-use glam::{Affine3A, Mat3A, Quat, Vec2, Vec3, Vec3A};
+use glam::{Affine3A, Mat3A, Quat, Vec2, Vec3, Vec3A, Vec4};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{HashMap, HashSet};
 use std::f32::consts::PI;
 use std::ops::Add;
+use std::sync::Arc;
 
 /// Angular degrees
-#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
-pub struct Degrees(pub u16);
+///
+/// Used for ordering points around a ring, so comparisons are total.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Degrees(pub f32);
 
 /// Ring spoke
 ///
@@ -25,16 +32,29 @@ pub struct Degrees(pub u16);
 /// [branch]: struct.Husk.html#method.branch
 /// [ring]: struct.Ring.html#method.spoke
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spoke {
     /// Distance from axis
     pub distance: f32,
 
     /// Label for branch points
     pub label: Option<String>,
+
+    /// Angle around ring (radians)
+    ///
+    /// When `None`, spokes are distributed evenly around the ring.
+    pub angle: Option<f32>,
+
+    /// Shading override along spoke
+    ///
+    /// `Some(true)` makes a sharp crease, `Some(false)` is smooth, and
+    /// `None` follows the ring [Shading].
+    pub sharp: Option<bool>,
 }
 
 /// Vertex normal shading
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Shading {
     /// Flat shading
     Flat,
@@ -50,26 +70,52 @@ pub enum Shading {
 const EMPTY_RING: &[Spoke] = &[Spoke {
     distance: 0.0,
     label: None,
+    angle: None,
+    sharp: None,
 }];
 
+/// Interned branch label
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabelId(u32);
+
+/// Interned branch labels
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Labels {
+    /// Label names, indexed by id
+    names: Vec<String>,
+}
+
 /// Point type
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Pt {
     /// Vertex index
     Vertex(usize),
 
     /// Branch label
-    Branch(String, Vec3),
+    Branch(LabelId, Vec3),
 }
 
 /// A point on a ring
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     /// Point type
     pub pt: Pt,
 
     /// Degrees around ring
     pub order: Degrees,
+
+    /// Shading override
+    pub sharp: Option<bool>,
+
+    /// Vertex on trailing side of a sharp crease
+    pub crease: Option<usize>,
+
+    /// Ring of a band (0 or 1)
+    pub ring: u8,
 }
 
 /// Ring around a [Husk]
@@ -78,6 +124,7 @@ pub struct Point {
 ///
 /// [husk]: struct.Husk.html
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ring {
     /// Spacing to next ring
     spacing: Option<f32>,
@@ -85,37 +132,96 @@ pub struct Ring {
     /// Spoke scale factor
     scale: Option<f32>,
 
+    /// Scale factors of local X and Z axes
+    scale_xz: Option<Vec2>,
+
+    /// Offset of center in local XZ plane (not inherited)
+    offset: Vec2,
+
+    /// Maximum relative change of spoke distances
+    jitter: Option<f32>,
+
     /// Vertex normal shading
     shading: Option<Shading>,
 
-    /// Spokes from center to ring
-    spokes: Vec<Spoke>,
+    /// Vertex normal shading of caps
+    cap_shading: Option<Shading>,
+
+    /// Vertex color (RGBA)
+    color: Option<Vec4>,
+
+    /// Material name
+    material: Option<String>,
+
+    /// Twist relative to previous ring (radians)
+    twist: Option<f32>,
+
+    /// Angle of first spoke (radians)
+    seam: Option<f32>,
+
+    /// Accumulated twist rotation (radians)
+    rotation: f32,
+
+    /// Spokes from center to ring (shared with inheriting rings)
+    spokes: Arc<Vec<Spoke>>,
 
     /// Local-to-global transform
     xform: Affine3A,
 
+    /// Rotation of transform before a random tilt (not inherited)
+    untilted: Option<Mat3A>,
+
     /// Points on ring
     points: Vec<Point>,
+
+    /// Base point positions (branch rings only)
+    base: Vec<Vec3>,
+}
+
+/// Read-only view of a ring added to a [Husk]
+///
+/// Made by [Husk::current_ring].  Values are resolved, after inheritance
+/// from previous rings.
+///
+/// [husk]: struct.Husk.html
+/// [Husk::current_ring]: struct.Husk.html#method.current_ring
+#[derive(Clone, Copy, Debug)]
+pub struct RingView<'a> {
+    /// Viewed ring
+    ring: &'a Ring,
 }
 
 /// Edge between two vertices
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Edge(pub usize, pub usize);
 
 /// Branch data
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Branch {
     /// Internal connection points (non-edge)
     internal: Vec<Vec3>,
 
     /// Edges at base of branch
     edges: Vec<Edge>,
+
+    /// Arc length of base on parent branch
+    offset: Option<f32>,
+
+    /// Vertex color of parent ring
+    color: Option<Vec4>,
+
+    /// Material of parent ring
+    material: Option<String>,
+
+    /// Index of first parent ring
+    ring: Option<usize>,
 }
 
 impl From<f32> for Degrees {
     fn from(angle: f32) -> Self {
-        let deg = angle.to_degrees().rem_euclid(360.0);
-        Degrees(deg.round() as u16)
+        Degrees(angle.to_degrees().rem_euclid(360.0))
     }
 }
 
@@ -123,7 +229,34 @@ impl Add for Degrees {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        Degrees(self.0 + rhs.0 % 360)
+        Degrees((self.0 + rhs.0) % 360.0)
+    }
+}
+
+impl Degrees {
+    /// Get how far another angle is behind this one, from 0 to 360
+    pub fn behind(self, other: Self) -> Self {
+        Degrees((self.0 - other.0).rem_euclid(360.0))
+    }
+}
+
+impl PartialEq for Degrees {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Degrees {}
+
+impl PartialOrd for Degrees {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Degrees {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
     }
 }
 
@@ -138,6 +271,8 @@ impl From<f32> for Spoke {
         Spoke {
             distance,
             label: None,
+            angle: None,
+            sharp: None,
         }
     }
 }
@@ -147,6 +282,8 @@ impl From<&str> for Spoke {
         Spoke {
             distance: 1.0,
             label: Some(label.to_string()),
+            angle: None,
+            sharp: None,
         }
     }
 }
@@ -156,22 +293,210 @@ impl From<(f32, &str)> for Spoke {
         Spoke {
             distance: val.0,
             label: Some(val.1.to_string()),
+            angle: None,
+            sharp: None,
         }
     }
 }
 
+impl Spoke {
+    /// Place spoke at an explicit angle around the ring (radians)
+    ///
+    /// Angle 0 is the local X-axis.
+    ///
+    /// ```rust
+    /// # use homunculus::{Ring, Spoke};
+    /// # use std::f32::consts::PI;
+    /// let ring = Ring::default()
+    ///     .spoke(Spoke::from(1.5).with_angle(0.0))
+    ///     .spoke(Spoke::from(1.2).with_angle(PI * 0.25))
+    ///     .spoke(Spoke::from(1.0).with_angle(PI));
+    /// ```
+    pub fn with_angle(mut self, angle: f32) -> Self {
+        self.angle = Some(angle);
+        self
+    }
+
+    /// Override ring shading along spoke
+    ///
+    /// A sharp spoke makes a crease on a smooth or ringed ring, and a
+    /// non-sharp spoke is smoothed on a flat ring.
+    ///
+    /// ```rust
+    /// # use homunculus::{Ring, Spoke};
+    /// let ring = Ring::default()
+    ///     .spoke(1.0)
+    ///     .spoke(Spoke::from(1.4).with_sharp(true))
+    ///     .spoke(1.0);
+    /// ```
+    pub fn with_sharp(mut self, sharp: bool) -> Self {
+        self.sharp = Some(sharp);
+        self
+    }
+}
+
+impl Labels {
+    /// Get the id of a label, interning it if needed
+    pub fn intern(&mut self, label: &str) -> LabelId {
+        let id = match self.names.iter().position(|n| n == label) {
+            Some(i) => i,
+            None => {
+                self.names.push(label.to_string());
+                self.names.len() - 1
+            }
+        };
+        // unwrap note: more than u32::MAX labels is not practical
+        LabelId(id.try_into().unwrap())
+    }
+
+    /// Get the id of a label, if interned
+    pub fn get(&self, label: &str) -> Option<LabelId> {
+        self.names
+            .iter()
+            .position(|n| n == label)
+            .map(|i| LabelId(i as u32))
+    }
+
+    /// Get the name of a label
+    pub fn name(&self, id: LabelId) -> &str {
+        &self.names[id.0 as usize]
+    }
+}
+
 impl Point {
     /// Create a new point
     pub fn new(pt: Pt, order: Degrees) -> Self {
-        Point { pt, order }
+        Point {
+            pt,
+            order,
+            sharp: None,
+            crease: None,
+            ring: 0,
+        }
+    }
+
+    /// Get point for faces on the trailing side
+    ///
+    /// For a sharp crease, this uses a separate vertex.
+    pub fn trailing(&self) -> Point {
+        match self.crease {
+            Some(vid) => Point {
+                ring: self.ring,
+                ..Point::new(Pt::Vertex(vid), self.order)
+            },
+            None => *self,
+        }
+    }
+
+    /// Check if point is smoothed on a flat ring
+    pub fn is_smooth(&self) -> bool {
+        self.sharp == Some(false)
+    }
+}
+
+/// Check a ring axis
+fn check_axis(axis: Vec3) -> Result<()> {
+    if axis.is_finite() {
+        Ok(())
+    } else {
+        Err(Error::InvalidAxis(axis))
+    }
+}
+
+/// Resample spokes to a count, evenly spaced
+///
+/// Distances are interpolated linearly by angle around the ring.  Labels
+/// and shading overrides are not kept.
+fn resample_spokes(spokes: &[Spoke], count: usize) -> Vec<Spoke> {
+    let len = spokes.len();
+    let mut pts: Vec<(f32, f32)> = spokes
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let angle = s.angle.unwrap_or(2.0 * PI * i as f32 / len as f32);
+            (angle, s.distance)
+        })
+        .collect();
+    pts.sort_by(|a, b| a.0.total_cmp(&b.0));
+    (0..count)
+        .map(|j| {
+            let angle = 2.0 * PI * j as f32 / count as f32;
+            let i = pts.partition_point(|p| p.0 <= angle);
+            let (a0, d0) = match i {
+                0 => (pts[len - 1].0 - 2.0 * PI, pts[len - 1].1),
+                _ => pts[i - 1],
+            };
+            let (a1, d1) = match pts.get(i) {
+                Some(p) => *p,
+                None => (pts[0].0 + 2.0 * PI, pts[0].1),
+            };
+            let t = if a1 > a0 {
+                (angle - a0) / (a1 - a0)
+            } else {
+                0.0
+            };
+            Spoke::from(d0 + (d1 - d0) * t)
+        })
+        .collect()
+}
+
+/// Check a ring color
+fn check_color(color: [f32; 4]) -> Result<()> {
+    if color.iter().all(|c| (0.0..=1.0).contains(c)) {
+        Ok(())
+    } else {
+        Err(Error::InvalidColor(Vec4::from(color)))
+    }
+}
+
+/// Check a ring twist
+fn check_twist(twist: f32) -> Result<()> {
+    if twist.is_finite() {
+        Ok(())
+    } else {
+        Err(Error::InvalidTwist(twist))
     }
 }
 
+/// Check a ring seam angle
+fn check_seam(seam: f32) -> Result<()> {
+    if (0.0..2.0 * PI).contains(&seam) {
+        Ok(())
+    } else {
+        Err(Error::InvalidSeam(seam))
+    }
+}
+
+/// Check a superellipse exponent
+fn check_exponent(exponent: f32) -> Result<()> {
+    if !(exponent > 0.0 && exponent.is_finite()) {
+        return Err(Error::InvalidExponent(exponent));
+    }
+    Ok(())
+}
+
+/// Get the radius of spoke `i` of `n` on a superellipse
+fn superellipse_radius(i: usize, n: usize, rx: f32, rz: f32, e: f32) -> f32 {
+    let angle = 2.0 * PI * i as f32 / n as f32;
+    let x = (angle.cos() / rx).abs().powf(e);
+    let z = (angle.sin() / rz).abs().powf(e);
+    (x + z).powf(-1.0 / e)
+}
+
+/// Handle an invalid ring setting
+///
+/// Panics in debug builds, otherwise logs a warning.
+fn invalid(ring: Ring, err: Error) -> Ring {
+    ignore_invalid(err);
+    ring
+}
+
 impl Ring {
     /// Create a new ring from a branch
     pub(crate) fn with_branch(branch: Branch, builder: &MeshBuilder) -> Self {
         let center = branch.center();
-        let axis = branch.axis(builder, center);
+        // degenerate axes are rejected by Husk::branch
+        let axis = branch.axis(builder).unwrap_or(Vec3::Y);
         // FIXME: rotate xform so that X-axis aligns with parent rings
         //        and positive Z-axis is transverse
         let xform = Affine3A::from_translation(center);
@@ -179,15 +504,29 @@ impl Ring {
         let mut ring = Ring {
             spacing: None,
             xform,
+            untilted: None,
             scale: None,
+            scale_xz: None,
+            offset: Vec2::ZERO,
+            jitter: None,
             shading: None,
-            spokes: vec![Spoke::default(); count],
+            cap_shading: None,
+            color: branch.color,
+            material: branch.material.clone(),
+            twist: None,
+            seam: None,
+            rotation: 0.0,
+            spokes: Arc::new(vec![Spoke::default(); count]),
             points: Vec::new(),
+            base: Vec::new(),
         };
         ring.transform_rotate(axis);
         for (order, vid) in branch.edge_angles(&ring, builder) {
             ring.points.push(Point::new(Pt::Vertex(vid), order));
+            ring.base.push(builder.vertex(vid));
         }
+        let radius = ring.mean_radius(builder);
+        ring.spokes = Arc::new(vec![Spoke::from(radius); count]);
         ring
     }
 
@@ -195,17 +534,33 @@ impl Ring {
     pub(crate) fn with_ring(&self, ring: &Self) -> Self {
         let spacing = ring.spacing.or(self.spacing);
         let spokes = if ring.spokes.is_empty() {
-            self.spokes.clone()
+            Arc::clone(&self.spokes)
         } else {
-            ring.spokes.clone()
+            Arc::clone(&ring.spokes)
         };
+        let twist = ring.twist.or(self.twist);
+        let mut xform = self.xform;
+        if let Some(untilted) = self.untilted {
+            xform.matrix3 = untilted;
+        }
         let mut ring = Ring {
             spacing,
-            xform: self.xform * ring.xform,
+            xform: xform * ring.xform,
+            untilted: None,
             scale: ring.scale.or(self.scale),
+            scale_xz: ring.scale_xz.or(self.scale_xz),
+            offset: ring.offset,
+            jitter: ring.jitter.or(self.jitter),
             shading: ring.shading.or(self.shading),
+            cap_shading: ring.cap_shading.or(self.cap_shading),
+            color: ring.color.or(self.color),
+            material: ring.material.clone().or_else(|| self.material.clone()),
+            twist,
+            seam: ring.seam.or(self.seam),
+            rotation: (self.rotation + twist.unwrap_or(0.0)) % (2.0 * PI),
             spokes,
             points: Vec::new(),
+            base: Vec::new(),
         };
         ring.transform_translate();
         ring
@@ -217,31 +572,297 @@ impl Ring {
     ///
     /// # Panics
     ///
-    /// - If any axis component is infinite or NaN
-    pub fn axis(mut self, axis: Vec3) -> Self {
-        assert!(axis.x.is_finite());
-        assert!(axis.y.is_finite());
-        assert!(axis.z.is_finite());
+    /// In debug builds, on any error from [Ring::try_axis].  Otherwise, an
+    /// invalid axis is ignored with a warning.
+    pub fn axis(self, axis: Vec3) -> Self {
+        match check_axis(axis) {
+            Ok(()) => self.with_axis(axis),
+            Err(e) => invalid(self, e),
+        }
+    }
+
+    /// Set ring axis, checking for errors
+    ///
+    /// Returns [Error::InvalidAxis] if any component is infinite or NaN.
+    pub fn try_axis(self, axis: Vec3) -> Result<Self> {
+        check_axis(axis)?;
+        Ok(self.with_axis(axis))
+    }
+
+    /// Set ring axis (unchecked)
+    fn with_axis(mut self, axis: Vec3) -> Self {
         self.transform_rotate(axis);
         self
     }
 
     /// Set ring scale
     ///
-    /// Spoke distances are scaled by this factor.
+    /// Spoke distances are scaled by this factor.  On a branch base ring,
+    /// the base points are not changed; the scale applies to following
+    /// rings, which start with spokes at the mean radius of the base.
     ///
     /// # Panics
     ///
-    /// - If this is a branch ring
-    /// - If the scale is negative, infinite, or NaN
-    pub fn scale(mut self, scale: f32) -> Self {
-        assert!(self.points.is_empty(), "cannot scale a branch ring");
-        assert!(scale.is_finite());
-        assert!(scale.is_sign_positive());
+    /// In debug builds, on any error from [Ring::try_scale].  Otherwise, an
+    /// invalid scale is ignored with a warning.
+    pub fn scale(self, scale: f32) -> Self {
+        match self.check_scale(scale) {
+            Ok(()) => self.with_scale(scale),
+            Err(e) => invalid(self, e),
+        }
+    }
+
+    /// Set ring scale, checking for errors
+    ///
+    /// Returns [Error::InvalidScale] if the scale is negative, infinite, or
+    /// NaN.
+    pub fn try_scale(self, scale: f32) -> Result<Self> {
+        self.check_scale(scale)?;
+        Ok(self.with_scale(scale))
+    }
+
+    /// Check a ring scale
+    fn check_scale(&self, scale: f32) -> Result<()> {
+        if !scale.is_finite() || !scale.is_sign_positive() {
+            return Err(Error::InvalidScale(scale));
+        }
+        Ok(())
+    }
+
+    /// Set ring scale (unchecked)
+    fn with_scale(mut self, scale: f32) -> Self {
         self.scale = Some(scale);
         self
     }
 
+    /// Set ring scale of local X and Z axes
+    ///
+    /// Spoke positions are scaled independently along the X and Z axes of
+    /// the ring, after the [Ring::scale] factor.  Like that, it is inherited
+    /// by following rings, and on a branch base ring it only applies to
+    /// them.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, on any error from [Ring::try_scale_xz].  Otherwise,
+    /// an invalid scale is ignored with a warning.
+    pub fn scale_xz(self, sx: f32, sz: f32) -> Self {
+        match self.check_scale_xz(sx, sz) {
+            Ok(()) => self.with_scale_xz(sx, sz),
+            Err(e) => invalid(self, e),
+        }
+    }
+
+    /// Set ring scale of local X and Z axes, checking for errors
+    ///
+    /// Returns [Error::InvalidScale] if either scale is negative, infinite,
+    /// or NaN.
+    pub fn try_scale_xz(self, sx: f32, sz: f32) -> Result<Self> {
+        self.check_scale_xz(sx, sz)?;
+        Ok(self.with_scale_xz(sx, sz))
+    }
+
+    /// Check ring scale of local X and Z axes
+    fn check_scale_xz(&self, sx: f32, sz: f32) -> Result<()> {
+        self.check_scale(sx)?;
+        self.check_scale(sz)
+    }
+
+    /// Set ring scale of local X and Z axes (unchecked)
+    fn with_scale_xz(mut self, sx: f32, sz: f32) -> Self {
+        self.scale_xz = Some(Vec2::new(sx, sz));
+        self
+    }
+
+    /// Make an elliptical ring
+    ///
+    /// It has `n` even spokes, with radius `rx` along the local X axis and
+    /// `rz` along Z, using [Ring::scale_xz].
+    ///
+    /// ```rust
+    /// # use homunculus::Ring;
+    /// let torso = Ring::ellipse(16, 1.5, 0.8);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if either radius is negative, infinite, or NaN
+    pub fn ellipse(n: usize, rx: f32, rz: f32) -> Self {
+        (0..n)
+            .fold(Ring::default(), |ring, _| ring.spoke(1.0))
+            .scale_xz(rx, rz)
+    }
+
+    /// Make a regular polygon ring
+    ///
+    /// It has `n` spokes at `radius`, one at each corner.
+    ///
+    /// ```rust
+    /// # use homunculus::{Ring, Shading};
+    /// let square = Ring::polygon(4, 1.0).shading(Shading::Flat);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if `radius` is negative, infinite, or NaN
+    pub fn polygon(n: usize, radius: f32) -> Self {
+        (0..n).fold(Ring::default(), |ring, _| ring.spoke(radius))
+    }
+
+    /// Make a star ring
+    ///
+    /// There are `points` outer spokes at `r_outer`, alternating with inner
+    /// spokes at `r_inner`, starting with an outer spoke.
+    ///
+    /// ```rust
+    /// # use homunculus::Ring;
+    /// let star = Ring::star(5, 2.0, 1.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if either radius is negative, infinite, or NaN
+    pub fn star(points: usize, r_outer: f32, r_inner: f32) -> Self {
+        (0..points).fold(Ring::default(), |ring, _| {
+            ring.spoke(r_outer).spoke(r_inner)
+        })
+    }
+
+    /// Make a superellipse ring
+    ///
+    /// It has `n` even spokes, with radius `rx` along the local X axis and
+    /// `rz` along Z.  An `exponent` of 2 is an ellipse; larger values
+    /// approach a rectangle, and smaller values are pinched toward the axes.
+    ///
+    /// ```rust
+    /// # use homunculus::Ring;
+    /// let rounded_box = Ring::superellipse(24, 1.0, 0.5, 4.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, on any error from [Ring::try_superellipse].
+    /// Otherwise, an invalid exponent makes an ellipse, and invalid spokes
+    /// are ignored, with a warning.
+    pub fn superellipse(n: usize, rx: f32, rz: f32, exponent: f32) -> Self {
+        match check_exponent(exponent) {
+            Ok(()) => (0..n).fold(Ring::default(), |ring, i| {
+                ring.spoke(superellipse_radius(i, n, rx, rz, exponent))
+            }),
+            Err(e) => invalid(Ring::ellipse(n, rx, rz), e),
+        }
+    }
+
+    /// Make a superellipse ring, checking for errors
+    ///
+    /// Returns an error:
+    /// - [Error::InvalidExponent] if `exponent` is not positive and finite
+    /// - [Error::InvalidSpoke] if either radius is not positive and finite
+    pub fn try_superellipse(
+        n: usize,
+        rx: f32,
+        rz: f32,
+        exponent: f32,
+    ) -> Result<Self> {
+        check_exponent(exponent)?;
+        (0..n).try_fold(Ring::default(), |ring, i| {
+            ring.try_spoke(superellipse_radius(i, n, rx, rz, exponent))
+        })
+    }
+
+    /// Set ring center offset
+    ///
+    /// The center is moved within the ring's local XZ plane, without
+    /// changing the axis.  Unlike most settings, it is not inherited by
+    /// following rings.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, on any error from [Ring::try_offset].  Otherwise,
+    /// an invalid offset is ignored with a warning.
+    pub fn offset(self, offset: Vec2) -> Self {
+        match self.check_offset(offset) {
+            Ok(()) => self.with_offset(offset),
+            Err(e) => invalid(self, e),
+        }
+    }
+
+    /// Set ring center offset, checking for errors
+    ///
+    /// Returns an error:
+    /// - [Error::BranchRing] if this is a branch ring
+    /// - [Error::InvalidOffset] if either component is infinite or NaN
+    pub fn try_offset(self, offset: Vec2) -> Result<Self> {
+        self.check_offset(offset)?;
+        Ok(self.with_offset(offset))
+    }
+
+    /// Check a ring offset
+    fn check_offset(&self, offset: Vec2) -> Result<()> {
+        if !self.points.is_empty() {
+            return Err(Error::BranchRing("cannot offset"));
+        }
+        if !offset.is_finite() {
+            return Err(Error::InvalidOffset(offset));
+        }
+        Ok(())
+    }
+
+    /// Set ring center offset (unchecked)
+    fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Set ring jitter
+    ///
+    /// Spoke distances are multiplied by a random factor within
+    /// `1 ± amount`.  Like [Ring::scale], it is inherited by following
+    /// rings, and it takes precedence over the distance set by
+    /// [Husk::jitter].  Random values are derived from the seed set by
+    /// [Husk::set_rng_seed] and the ring index, so results are reproducible.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, on any error from [Ring::try_jitter].  Otherwise,
+    /// an invalid jitter is ignored with a warning.
+    ///
+    /// [Husk::jitter]: struct.Husk.html#method.jitter
+    /// [Husk::set_rng_seed]: struct.Husk.html#method.set_rng_seed
+    pub fn jitter(self, amount: f32) -> Self {
+        match self.check_jitter(amount) {
+            Ok(()) => self.with_jitter(amount),
+            Err(e) => invalid(self, e),
+        }
+    }
+
+    /// Set ring jitter, checking for errors
+    ///
+    /// Returns an error:
+    /// - [Error::BranchRing] if this is a branch ring
+    /// - [Error::InvalidJitter] if the amount is negative, infinite, or NaN
+    pub fn try_jitter(self, amount: f32) -> Result<Self> {
+        self.check_jitter(amount)?;
+        Ok(self.with_jitter(amount))
+    }
+
+    /// Check a ring jitter
+    fn check_jitter(&self, amount: f32) -> Result<()> {
+        if !self.points.is_empty() {
+            return Err(Error::BranchRing("cannot jitter"));
+        }
+        if !amount.is_finite() || !amount.is_sign_positive() {
+            return Err(Error::InvalidJitter(amount));
+        }
+        Ok(())
+    }
+
+    /// Set ring jitter (unchecked)
+    fn with_jitter(mut self, amount: f32) -> Self {
+        self.jitter = Some(amount);
+        self
+    }
+
     /// Set vertex normal shading
     ///
     /// Values: `Flat`, `Smooth`, or `Ringed`
@@ -250,16 +871,242 @@ impl Ring {
         self
     }
 
+    /// Set vertex normal shading of caps
+    ///
+    /// When unset, caps use the ring shading.
+    pub fn cap_shading(mut self, shading: Shading) -> Self {
+        self.cap_shading = Some(shading);
+        self
+    }
+
+    /// Set vertex color (RGBA)
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, on any error from [Ring::try_color].  Otherwise, an
+    /// invalid color is ignored with a warning.
+    pub fn color(self, color: [f32; 4]) -> Self {
+        match check_color(color) {
+            Ok(()) => self.with_color(color),
+            Err(e) => invalid(self, e),
+        }
+    }
+
+    /// Set vertex color (RGBA), checking for errors
+    ///
+    /// Returns [Error::InvalidColor] if any component is not within 0 to 1.
+    pub fn try_color(self, color: [f32; 4]) -> Result<Self> {
+        check_color(color)?;
+        Ok(self.with_color(color))
+    }
+
+    /// Set vertex color (unchecked)
+    fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = Some(Vec4::from(color));
+        self
+    }
+
+    /// Set material name
+    ///
+    /// Faces are grouped by material when exporting, so a material can be
+    /// assigned to each group in a modeling tool.  Bands use the material of
+    /// their first ring, and caps use the material of the capped ring.
+    pub fn material(mut self, name: &str) -> Self {
+        self.material = Some(name.to_string());
+        self
+    }
+
+    /// Set ring scale if it has not been set
+    ///
+    /// Branch rings are not affected.
+    pub(crate) fn scale_or(mut self, scale: f32) -> Self {
+        if self.scale.is_none() && self.points.is_empty() {
+            self.scale = Some(scale);
+        }
+        self
+    }
+
+    /// Resample spokes to a count, evenly spaced
+    ///
+    /// Spoke distances are interpolated around the ring, so it can make
+    /// well-shaped bands with a ring of a different spoke count.  Branch
+    /// labels and shading overrides are not kept, and spokes added later
+    /// are not resampled.
+    ///
+    /// ```rust
+    /// # use homunculus::Ring;
+    /// let ring = Ring::default().spoke(1.0).spoke(2.0).spoke(1.5).resample(12);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, on any error from [Ring::try_resample].  Otherwise,
+    /// it is ignored with a warning.
+    pub fn resample(self, count: usize) -> Self {
+        match self.check_resample(count) {
+            Ok(()) => self.with_resample(count),
+            Err(e) => invalid(self, e),
+        }
+    }
+
+    /// Resample spokes to a count, checking for errors
+    ///
+    /// Returns an error:
+    /// - [Error::BranchRing] if this is a branch ring
+    /// - [Error::InvalidCount] if `count` is zero
+    pub fn try_resample(self, count: usize) -> Result<Self> {
+        self.check_resample(count)?;
+        Ok(self.with_resample(count))
+    }
+
+    /// Check a resample count
+    fn check_resample(&self, count: usize) -> Result<()> {
+        if !self.points.is_empty() {
+            return Err(Error::BranchRing("cannot resample"));
+        }
+        if count == 0 {
+            return Err(Error::InvalidCount(count));
+        }
+        Ok(())
+    }
+
+    /// Resample spokes (unchecked)
+    fn with_resample(mut self, count: usize) -> Self {
+        if !self.spokes.is_empty() {
+            self.spokes = Arc::new(resample_spokes(&self.spokes, count));
+        }
+        self
+    }
+
+    /// Set ring twist (radians)
+    ///
+    /// Spokes are rotated around the axis by this angle, relative to the
+    /// previous ring.  Twist accumulates from ring to ring, so an inherited
+    /// twist produces a spiral.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, on any error from [Ring::try_twist].  Otherwise, an
+    /// invalid twist is ignored with a warning.
+    pub fn twist(self, twist: f32) -> Self {
+        match check_twist(twist) {
+            Ok(()) => self.with_twist(twist),
+            Err(e) => invalid(self, e),
+        }
+    }
+
+    /// Set ring twist (radians), checking for errors
+    ///
+    /// Returns [Error::InvalidTwist] if the twist is infinite or NaN.
+    pub fn try_twist(self, twist: f32) -> Result<Self> {
+        check_twist(twist)?;
+        Ok(self.with_twist(twist))
+    }
+
+    /// Set ring twist (unchecked)
+    fn with_twist(mut self, twist: f32) -> Self {
+        self.twist = Some(twist);
+        self
+    }
+
+    /// Set seam angle (radians)
+    ///
+    /// This is the angle of the first spoke around the axis, before any
+    /// twist is applied.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, on any error from [Ring::try_seam].  Otherwise, an
+    /// invalid seam is ignored with a warning.
+    pub fn seam(self, seam: f32) -> Self {
+        match check_seam(seam) {
+            Ok(()) => self.with_seam(seam),
+            Err(e) => invalid(self, e),
+        }
+    }
+
+    /// Set seam angle (radians), checking for errors
+    ///
+    /// Returns [Error::InvalidSeam] if the seam is not within 0 to 2π.
+    pub fn try_seam(self, seam: f32) -> Result<Self> {
+        check_seam(seam)?;
+        Ok(self.with_seam(seam))
+    }
+
+    /// Set seam angle (unchecked)
+    fn with_seam(mut self, seam: f32) -> Self {
+        self.seam = Some(seam);
+        self
+    }
+
+    /// Check if this is a branch base ring
+    ///
+    /// Base rings are returned by [Husk::branch].  Their points are shared
+    /// with the parent branch, so they cannot have spokes added
+    /// ([Error::BranchRing]).  An axis, scale or X/Z scale applies to
+    /// following rings.
+    ///
+    /// [Husk::branch]: struct.Husk.html#method.branch
+    pub fn is_branch_base(&self) -> bool {
+        !self.base.is_empty()
+    }
+
+    /// Get the number of points on a branch base ring
+    ///
+    /// This is zero for other rings.
+    pub fn base_point_count(&self) -> usize {
+        self.base.len()
+    }
+
+    /// Get the positions of points on a branch base ring
+    ///
+    /// Positions are in world coordinates, in order around the ring.
+    pub fn base_points(&self) -> &[Vec3] {
+        &self.base[..]
+    }
+
     /// Get the ring scale (or default value)
-    fn scale_or_default(&self) -> f32 {
+    pub(crate) fn scale_or_default(&self) -> f32 {
         self.scale.unwrap_or(1.0)
     }
 
+    /// Get the ring jitter (or a default value)
+    pub(crate) fn jitter_or(&self, jitter: f32) -> f32 {
+        self.jitter.unwrap_or(jitter)
+    }
+
+    /// Get the scale factors of local X and Z axes (or default value)
+    pub(crate) fn scale_xz_or_default(&self) -> Vec2 {
+        self.scale_xz.unwrap_or(Vec2::ONE)
+    }
+
+    /// Get the scale factors of local axes as a vector
+    fn scale_xz_vec3(&self) -> Vec3 {
+        let scale = self.scale_xz_or_default();
+        Vec3::new(scale.x, 1.0, scale.y)
+    }
+
     /// Get the vertex normal shading (or default value)
     pub(crate) fn shading_or_default(&self) -> Shading {
         self.shading.unwrap_or(Shading::Smooth)
     }
 
+    /// Get the vertex color
+    pub(crate) fn vertex_color(&self) -> Option<Vec4> {
+        self.color
+    }
+
+    /// Get the material name
+    pub(crate) fn material_name(&self) -> Option<&str> {
+        self.material.as_deref()
+    }
+
+    /// Get the vertex normal shading of caps (or ring shading)
+    pub(crate) fn cap_shading_or_default(&self) -> Shading {
+        self.cap_shading
+            .unwrap_or_else(|| self.shading_or_default())
+    }
+
     /// Add a spoke
     ///
     /// A `label` is used for [branch] points.
@@ -276,16 +1123,50 @@ impl Ring {
     ///
     /// # Panics
     ///
-    /// - If this is a branch ring
-    /// - If spoke distance is negative, infinite, or NaN
+    /// In debug builds, on any error from [Ring::try_spoke].  Otherwise, an
+    /// invalid spoke is ignored with a warning.
     ///
     /// [branch]: struct.Husk.html#method.branch
-    pub fn spoke<S: Into<Spoke>>(mut self, spoke: S) -> Self {
-        assert!(self.points.is_empty(), "cannot add spoke to a branch ring");
+    pub fn spoke<S: Into<Spoke>>(self, spoke: S) -> Self {
         let spoke = spoke.into();
-        assert!(spoke.distance.is_sign_positive());
-        assert!(spoke.distance.is_finite());
-        self.spokes.push(spoke);
+        match self.check_spoke(&spoke) {
+            Ok(()) => self.with_spoke(spoke),
+            Err(e) => invalid(self, e),
+        }
+    }
+
+    /// Add a spoke, checking for errors
+    ///
+    /// Returns an error:
+    /// - [Error::BranchRing] if this is a branch ring
+    /// - [Error::InvalidSpoke] if distance is negative, infinite, or NaN
+    /// - [Error::InvalidAngle] if angle is not within 0 to 2π
+    pub fn try_spoke<S: Into<Spoke>>(self, spoke: S) -> Result<Self> {
+        let spoke = spoke.into();
+        self.check_spoke(&spoke)?;
+        Ok(self.with_spoke(spoke))
+    }
+
+    /// Check a spoke
+    fn check_spoke(&self, spoke: &Spoke) -> Result<()> {
+        if !self.points.is_empty() {
+            return Err(Error::BranchRing("cannot add spoke"));
+        }
+        let distance = spoke.distance;
+        if !distance.is_finite() || !distance.is_sign_positive() {
+            return Err(Error::InvalidSpoke(distance));
+        }
+        match spoke.angle {
+            Some(angle) if !(0.0..2.0 * PI).contains(&angle) => {
+                Err(Error::InvalidAngle(angle))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Add a spoke (unchecked)
+    fn with_spoke(mut self, spoke: Spoke) -> Self {
+        Arc::make_mut(&mut self.spokes).push(spoke);
         self
     }
 
@@ -300,13 +1181,15 @@ impl Ring {
 
     /// Get half step in degrees
     pub(crate) fn half_step(&self) -> Degrees {
-        let deg = 180 / self.spokes.len();
-        Degrees(deg as u16)
+        Degrees(180.0 / self.spokes.len() as f32)
     }
 
     /// Calculate the angle of a spoke
     pub(crate) fn angle(&self, i: usize) -> f32 {
-        2.0 * PI * i as f32 / self.spokes.len() as f32
+        match self.spokes.get(i).and_then(|s| s.angle) {
+            Some(angle) => angle,
+            None => 2.0 * PI * i as f32 / self.spokes.len() as f32,
+        }
     }
 
     /// Translate a transform from axis
@@ -317,61 +1200,237 @@ impl Ring {
     }
 
     /// Rotate a transform from axis
+    ///
+    /// The ring is rotated by the shortest arc from +Y to the axis,
+    /// relative to the previous ring.
     fn transform_rotate(&mut self, axis: Vec3) {
         self.spacing = Some(axis.length());
-        let axis = axis.normalize();
-        if axis.x != 0.0 {
-            // project to XY plane, then rotate around Z axis
-            let up = Vec2::new(0.0, 1.0);
-            let proj = Vec2::new(axis.x, axis.y);
-            let angle = up.angle_between(proj) * proj.length();
-            self.xform.matrix3 *= Mat3A::from_rotation_z(angle);
-        }
-        if axis.z != 0.0 {
-            // project to YZ plane, then rotate around X axis
-            let up = Vec2::new(1.0, 0.0);
-            let proj = Vec2::new(axis.y, axis.z);
-            let angle = up.angle_between(proj) * proj.length();
-            self.xform.matrix3 *= Mat3A::from_rotation_x(angle);
+        if let Some(axis) = axis.try_normalize() {
+            let rot = Quat::from_rotation_arc(Vec3::Y, axis);
+            self.xform.matrix3 *= Mat3A::from_quat(rot);
         }
     }
 
     /// Make a point for the given spoke
-    fn make_point(&self, i: usize, spoke: &Spoke) -> (Degrees, Vec3) {
-        let angle = self.angle(i);
+    fn make_point(&self, i: usize, distance: f32) -> (Degrees, Vec3) {
+        let angle = self.angle(i) + self.seam.unwrap_or(0.0) + self.rotation;
         let order = Degrees::from(angle);
         let rot = Quat::from_rotation_y(angle);
-        let distance = spoke.distance * self.scale_or_default();
-        let pos = rot * Vec3::new(distance, 0.0, 0.0);
-        let pos = self.xform.transform_point3(pos);
+        let distance = distance * self.scale_or_default();
+        let pos = rot * Vec3::new(distance, 0.0, 0.0) * self.scale_xz_vec3();
+        let pos = self.xform.transform_point3(pos + self.offset_vec3(0.0));
         (order, pos)
     }
 
-    /// Make hub point
-    pub(crate) fn make_hub(&self) -> (Degrees, Vec3) {
-        let pos = self.xform.transform_point3(Vec3::ZERO);
-        (Degrees(0), pos)
+    /// Get center offset, at a height along the axis
+    fn offset_vec3(&self, height: f32) -> Vec3 {
+        Vec3::new(self.offset.x, height, self.offset.y)
+    }
+
+    /// Get center of ring (including offset)
+    pub(crate) fn center(&self) -> Vec3 {
+        self.xform.transform_point3(self.offset_vec3(0.0))
+    }
+
+    /// Get transform of ring
+    pub(crate) fn xform(&self) -> Affine3A {
+        self.xform
+    }
+
+    /// Copy points from another ring, offsetting vertex indices
+    pub(crate) fn copy_points(&mut self, ring: &Ring, offset: usize) {
+        self.points = ring
+            .points()
+            .map(|point| {
+                let mut point = *point;
+                if let Pt::Vertex(vid) = point.pt {
+                    point.pt = Pt::Vertex(vid + offset);
+                }
+                point.crease = point.crease.map(|v| v + offset);
+                point
+            })
+            .collect();
+    }
+
+    /// Transform a direction from world to ring coordinates
+    pub(crate) fn local_dir(&self, dir: Vec3) -> Vec3 {
+        self.xform.matrix3.inverse().mul_vec3(dir)
+    }
+
+    /// Make hub point, at a height along the axis
+    pub(crate) fn make_hub(&self, height: f32) -> (Degrees, Vec3) {
+        let pos = self.xform.transform_point3(self.offset_vec3(height));
+        (Degrees(0.0), pos)
+    }
+
+    /// Get mean distance of ring points from center
+    pub(crate) fn mean_radius(&self, builder: &MeshBuilder) -> f32 {
+        if self.points.is_empty() {
+            return 0.0;
+        }
+        let center = self.center();
+        let total: f32 = self
+            .points()
+            .map(|pt| match pt.pt {
+                Pt::Vertex(vid) => builder.vertex(vid).distance(center),
+                Pt::Branch(_, pos) => pos.distance(center),
+            })
+            .sum();
+        total / self.points.len() as f32
+    }
+
+    /// Make a dome ring following this ring
+    ///
+    /// Spokes are copied without labels, and scaled by `factor`.  A branch
+    /// base ring gets even spokes at `radius`.
+    pub(crate) fn dome_ring(
+        &self,
+        radius: f32,
+        step: f32,
+        factor: f32,
+    ) -> Self {
+        let scale = self.scale_or_default();
+        let spokes = if self.is_branch_base() {
+            vec![Spoke::from(radius / scale); self.base.len()]
+        } else {
+            self.spokes()
+                .map(|s| Spoke {
+                    label: None,
+                    ..s.clone()
+                })
+                .collect()
+        };
+        let ring = Ring {
+            scale: Some(scale * factor),
+            offset: self.offset,
+            twist: Some(0.0),
+            spokes: Arc::new(spokes),
+            ..Default::default()
+        };
+        ring.with_axis(Vec3::new(0.0, step, 0.0))
+    }
+
+    /// Make rings interpolated from this ring toward another
+    ///
+    /// Returns `steps` intermediate rings, followed by `ring`.  Spoke
+    /// distances, scale and spacing are interpolated linearly, and the axis
+    /// rotation of `ring` is divided evenly among them.  Spokes of this ring
+    /// are resampled if the counts differ, and a branch base ring starts
+    /// from its mean radius.
+    pub(crate) fn lerp_rings(
+        &self,
+        ring: &Ring,
+        steps: usize,
+        builder: &MeshBuilder,
+    ) -> Vec<Self> {
+        let target = self.with_ring(ring);
+        let count = target.spokes.len();
+        let scale = self.scale_or_default();
+        let distances: Vec<f32> = if self.is_branch_base() {
+            vec![self.mean_radius(builder) / scale; count]
+        } else if self.spokes.len() == count {
+            self.spokes().map(|s| s.distance).collect()
+        } else {
+            let spokes: Vec<_> = self.spokes().cloned().collect();
+            resample_spokes(&spokes, count)
+                .iter()
+                .map(|s| s.distance)
+                .collect()
+        };
+        let scale_xz = self.scale_xz_or_default();
+        let spacing = self.spacing.unwrap_or(1.0);
+        let rot = Quat::from_mat3a(&ring.xform.matrix3);
+        let rot = Quat::IDENTITY.slerp(rot, 1.0 / (steps + 1) as f32);
+        let mut rings = Vec::with_capacity(steps + 1);
+        for i in 1..=steps {
+            let t = i as f32 / (steps + 1) as f32;
+            let spokes = distances
+                .iter()
+                .zip(target.spokes())
+                .map(|(d, s)| Spoke {
+                    distance: d + (s.distance - d) * t,
+                    label: None,
+                    ..s.clone()
+                })
+                .collect();
+            let mut lerp = Ring {
+                // explicit scale only, so a taper is not overridden
+                scale: ring.scale.map(|s| scale + (s - scale) * t),
+                scale_xz: ring.scale_xz.map(|s| scale_xz.lerp(s, t)),
+                offset: self.offset.lerp(ring.offset, t),
+                spokes: Arc::new(spokes),
+                ..Default::default()
+            };
+            if let Some(s) = ring.spacing {
+                let axis =
+                    rot * Vec3::new(0.0, spacing + (s - spacing) * t, 0.0);
+                lerp = lerp.with_axis(axis);
+            }
+            rings.push(lerp);
+        }
+        let mut ring = ring.clone();
+        ring.xform.matrix3 = Mat3A::from_quat(rot);
+        rings.push(ring);
+        rings
+    }
+
+    /// Tilt the ring axis
+    ///
+    /// Rotate by `angle` around a local horizontal direction at `azimuth`.
+    /// The tilt is not inherited by following rings.
+    pub(crate) fn tilt(&mut self, azimuth: f32, angle: f32) {
+        if self.points.is_empty() && angle != 0.0 {
+            let dir = Vec3::new(azimuth.cos(), 0.0, azimuth.sin());
+            self.untilted.get_or_insert(self.xform.matrix3);
+            self.xform.matrix3 *= Mat3A::from_axis_angle(dir, angle);
+        }
+    }
+
+    /// Fix frame so that local X axis is toward world X
+    ///
+    /// The ring axis is not changed.
+    pub(crate) fn fix_frame(&mut self) {
+        let up = Vec3::from(self.xform.matrix3.y_axis).normalize();
+        let x = (Vec3::X - up * up.x)
+            .try_normalize()
+            .unwrap_or_else(|| (Vec3::Z - up * up.z).normalize());
+        self.xform.matrix3 =
+            Mat3A::from_cols(x.into(), up.into(), x.cross(up).into());
     }
 
     /// Make ring points
-    pub(crate) fn make_points(&mut self, builder: &mut MeshBuilder) {
+    ///
+    /// Spoke distances are multiplied by `factors` (when present).
+    pub(crate) fn make_points(
+        &mut self,
+        builder: &mut MeshBuilder,
+        labels: &mut Labels,
+        factors: &[f32],
+    ) -> Result<()> {
         let mut points = Vec::with_capacity(self.spokes.len());
         for (i, spoke) in self.spokes().enumerate() {
-            let (order, pos) = self.make_point(i, spoke);
+            let distance = spoke.distance * factors.get(i).unwrap_or(&1.0);
+            let (order, pos) = self.make_point(i, distance);
             match &spoke.label {
                 None => {
-                    let vid = builder.push_vtx(pos);
-                    points.push(Point::new(Pt::Vertex(vid), order));
+                    let vid = builder.push_vtx(pos)?;
+                    let mut point = Point::new(Pt::Vertex(vid), order);
+                    point.sharp = spoke.sharp;
+                    if spoke.sharp == Some(true)
+                        && self.shading_or_default() != Shading::Flat
+                    {
+                        point.crease = Some(builder.push_crease_vtx(pos)?);
+                    }
+                    points.push(point);
                 }
                 Some(label) => {
-                    points.push(Point::new(
-                        Pt::Branch(label.to_string(), pos),
-                        order,
-                    ));
+                    let id = labels.intern(label);
+                    points.push(Point::new(Pt::Branch(id, pos), order));
                 }
             }
         }
         self.points = points;
+        Ok(())
     }
 
     /// Get iterator of points on ring
@@ -380,19 +1439,83 @@ impl Ring {
     }
 
     /// Get points offset by a fixed angle (in descending order)
-    pub(crate) fn points_offset(&self, hs_other: Degrees) -> Vec<Point> {
+    ///
+    /// Points are tagged with `ring` number, for making bands.
+    pub(crate) fn points_offset(
+        &self,
+        hs_other: Degrees,
+        ring: u8,
+    ) -> Vec<Point> {
         let mut pts = Vec::with_capacity(self.points.len());
         for point in self.points() {
-            let mut point = point.clone();
+            let mut point = *point;
+            point.ring = ring;
             // adjust degrees by half step of other ring
             point.order = point.order + hs_other;
             pts.push(point);
         }
-        pts.sort_by(|a, b| b.order.partial_cmp(&a.order).unwrap());
+        pts.sort_by_key(|p| Reverse(p.order));
         pts
     }
 }
 
+impl<'a> RingView<'a> {
+    /// Create a new ring view
+    pub(crate) fn new(ring: &'a Ring) -> Self {
+        RingView { ring }
+    }
+
+    /// Get the spoke scale factor
+    pub fn scale(&self) -> f32 {
+        self.ring.scale_or_default()
+    }
+
+    /// Get the scale factors of local X and Z axes
+    pub fn scale_xz(&self) -> Vec2 {
+        self.ring.scale_xz_or_default()
+    }
+
+    /// Get the center offset
+    pub fn offset(&self) -> Vec2 {
+        self.ring.offset
+    }
+
+    /// Get the spacing to the next ring
+    pub fn spacing(&self) -> f32 {
+        self.ring.spacing.unwrap_or(1.0)
+    }
+
+    /// Get the vertex normal shading
+    pub fn shading(&self) -> Shading {
+        self.ring.shading_or_default()
+    }
+
+    /// Get the vertex normal shading of caps
+    pub fn cap_shading(&self) -> Shading {
+        self.ring.cap_shading_or_default()
+    }
+
+    /// Get the vertex color (RGBA)
+    pub fn color(&self) -> Option<[f32; 4]> {
+        self.ring.color.map(|c| c.to_array())
+    }
+
+    /// Get the material name
+    pub fn material(&self) -> Option<&str> {
+        self.ring.material_name()
+    }
+
+    /// Get the number of spokes (or base points, for a branch ring)
+    pub fn spoke_count(&self) -> usize {
+        self.ring.points().len()
+    }
+
+    /// Get the center position (world coordinates)
+    pub fn center(&self) -> Vec3 {
+        self.ring.center()
+    }
+}
+
 impl Branch {
     /// Push an edge
     pub fn push_edge(&mut self, v0: usize, v1: usize) {
@@ -404,15 +1527,78 @@ impl Branch {
         self.internal.push(pos);
     }
 
+    /// Set arc length of base on parent branch (if not already set)
+    pub fn set_offset(&mut self, arclength: f32) {
+        self.offset.get_or_insert(arclength);
+    }
+
+    /// Set vertex color of parent ring (if not already set)
+    pub fn set_color(&mut self, color: Option<Vec4>) {
+        if self.color.is_none() {
+            self.color = color;
+        }
+    }
+
+    /// Set material of parent ring (if not already set)
+    pub fn set_material(&mut self, material: Option<&str>) {
+        if self.material.is_none() {
+            self.material = material.map(str::to_string);
+        }
+    }
+
+    /// Set index of parent ring (if not already set)
+    pub fn set_ring(&mut self, ring: usize) {
+        self.ring.get_or_insert(ring);
+    }
+
+    /// Get arc length of base on parent branch
+    pub fn offset(&self) -> f32 {
+        self.offset.unwrap_or(0.0)
+    }
+
+    /// Get index of first parent ring
+    pub fn ring(&self) -> usize {
+        self.ring.unwrap_or(0)
+    }
+
+    /// Check if base edges are all connected
+    ///
+    /// Spokes with the same label, separated by other spokes, make
+    /// disconnected groups of edges.
+    pub fn is_connected(&self) -> bool {
+        let Some(first) = self.edges.first() else {
+            return true;
+        };
+        let mut adjacent = HashMap::<usize, Vec<usize>>::new();
+        for Edge(v0, v1) in &self.edges {
+            adjacent.entry(*v0).or_default().push(*v1);
+            adjacent.entry(*v1).or_default().push(*v0);
+        }
+        let mut found = HashSet::from([first.0]);
+        let mut stack = vec![first.0];
+        while let Some(vid) = stack.pop() {
+            for v in &adjacent[&vid] {
+                if found.insert(*v) {
+                    stack.push(*v);
+                }
+            }
+        }
+        found.len() == adjacent.len()
+    }
+
     /// Calculate branch base axis
-    fn axis(&self, builder: &MeshBuilder, center: Vec3) -> Vec3 {
+    ///
+    /// Returns `None` if the base is degenerate, such as with collinear
+    /// edges.
+    pub(crate) fn axis(&self, builder: &MeshBuilder) -> Option<Vec3> {
+        let center = self.center();
         let mut norm = Vec3::ZERO;
         for edge in self.edges() {
             let v0 = builder.vertex(edge.0);
             let v1 = builder.vertex(edge.1);
             norm += (v0 - center).cross(v1 - center);
         }
-        norm.normalize()
+        norm.try_normalize()
     }
 
     /// Get edge vertices sorted by common end-points
@@ -440,7 +1626,7 @@ impl Branch {
     }
 
     /// Get an iterator of branch edges
-    fn edges(&self) -> impl Iterator<Item = &Edge> {
+    pub(crate) fn edges(&self) -> impl Iterator<Item = &Edge> {
         self.edges.iter()
     }
 
@@ -483,3 +1669,123 @@ impl Branch {
         angles
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn try_setters() {
+        let ring = Ring::default();
+        assert!(matches!(
+            ring.clone().try_scale(-1.0),
+            Err(Error::InvalidScale(_))
+        ));
+        assert!(matches!(
+            ring.clone().try_scale(f32::NAN),
+            Err(Error::InvalidScale(_))
+        ));
+        assert!(matches!(
+            ring.clone().try_spoke(f32::INFINITY),
+            Err(Error::InvalidSpoke(_))
+        ));
+        assert!(matches!(
+            ring.clone().try_spoke(Spoke::from(1.0).with_angle(7.0)),
+            Err(Error::InvalidAngle(_))
+        ));
+        assert!(matches!(
+            ring.clone().try_axis(Vec3::new(0.0, f32::NAN, 0.0)),
+            Err(Error::InvalidAxis(_))
+        ));
+        let ring = ring
+            .try_axis(Vec3::new(0.0, 2.0, 0.0))
+            .and_then(|r| r.try_scale(0.5))
+            .and_then(|r| r.try_spoke(1.0))
+            .unwrap();
+        assert_eq!(ring.scale, Some(0.5));
+        assert_eq!(ring.spokes.len(), 1);
+        assert!(matches!(
+            ring.clone().try_color([0.5, 1.5, 0.0, 1.0]),
+            Err(Error::InvalidColor(_))
+        ));
+        assert!(matches!(
+            ring.clone().try_twist(f32::NAN),
+            Err(Error::InvalidTwist(_))
+        ));
+        assert!(matches!(
+            ring.clone().try_seam(-0.5),
+            Err(Error::InvalidSeam(_))
+        ));
+        assert!(matches!(
+            ring.clone().try_resample(0),
+            Err(Error::InvalidCount(0))
+        ));
+        assert!(matches!(
+            Ring::try_superellipse(8, 1.0, 1.0, 0.0),
+            Err(Error::InvalidExponent(_))
+        ));
+        assert!(matches!(
+            Ring::try_superellipse(8, f32::NAN, 1.0, 2.0),
+            Err(Error::InvalidSpoke(_))
+        ));
+        let ring = ring
+            .try_color([0.5, 1.0, 0.0, 1.0])
+            .and_then(|r| r.try_twist(0.5))
+            .and_then(|r| r.try_seam(1.0))
+            .and_then(|r| r.try_resample(4))
+            .unwrap();
+        assert_eq!(ring.twist, Some(0.5));
+        assert_eq!(ring.seam, Some(1.0));
+        assert_eq!(ring.spokes.len(), 4);
+    }
+
+    #[test]
+    fn resample() {
+        let ring = Ring::default()
+            .spoke(1.0)
+            .spoke(Spoke::from(3.0).with_angle(PI / 2.0))
+            .spoke(Spoke::from(2.0).with_angle(PI))
+            .resample(8);
+        let distances: Vec<f32> = ring.spokes().map(|s| s.distance).collect();
+        assert_eq!(distances, [1.0, 2.0, 3.0, 2.5, 2.0, 1.75, 1.5, 1.25]);
+        assert!(ring.spokes().all(|s| s.angle.is_none()));
+    }
+
+    fn distances(ring: &Ring) -> Vec<f32> {
+        ring.spokes().map(|s| s.distance).collect()
+    }
+
+    fn assert_near(a: &[f32], b: &[f32]) {
+        assert_eq!(a.len(), b.len(), "{a:?} {b:?}");
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < 1e-5, "{a:?} {b:?}");
+        }
+    }
+
+    #[test]
+    fn shapes() {
+        assert_eq!(distances(&Ring::polygon(4, 1.5)), [1.5; 4]);
+        assert_eq!(
+            distances(&Ring::star(3, 2.0, 0.5)),
+            [2.0, 0.5, 2.0, 0.5, 2.0, 0.5]
+        );
+        // exponent 2 is an ellipse
+        let ellipse = distances(&Ring::superellipse(8, 2.0, 1.0, 2.0));
+        let diag = 2.0 / 5.0_f32.sqrt() * 2.0_f32.sqrt();
+        assert_near(&ellipse, &[2.0, diag, 1.0, diag, 2.0, diag, 1.0, diag]);
+        // large exponent approaches a rectangle
+        let rect = distances(&Ring::superellipse(8, 1.0, 1.0, 50.0));
+        let corner = 2.0_f32.sqrt() * 2.0_f32.powf(-1.0 / 50.0);
+        assert_near(
+            &rect,
+            &[1.0, corner, 1.0, corner, 1.0, corner, 1.0, corner],
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn invalid_scale_panics() {
+        let _ring = Ring::default().scale(-1.0);
+    }
+}
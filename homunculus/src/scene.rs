@@ -0,0 +1,480 @@
+// scene.rs     Scene module
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::error::Result;
+use crate::gltf::{self, GltfOptions, Material, MeshNode, Placed};
+use crate::husk::Husk;
+use crate::mesh::{BuildReport, Mesh};
+use crate::obj;
+use crate::ply::{self, PlyFormat};
+use glam::{Affine3A, Quat, Vec3};
+use std::io::Write;
+
+/// Named mesh with placement
+struct Part {
+    /// Node / mesh name
+    name: String,
+
+    /// Built mesh
+    mesh: Mesh,
+
+    /// Translation
+    translation: Vec3,
+
+    /// Rotation
+    rotation: Quat,
+
+    /// Material definitions
+    materials: Vec<Material>,
+
+    /// Named anchor transforms
+    anchors: Vec<(String, Affine3A)>,
+}
+
+/// Scene of named [Husk]s
+///
+/// Each husk is exported as a separate named mesh and node.
+///
+/// ```rust,no_run
+/// # use homunculus::{Error, Husk, Scene};
+/// # use glam::{Quat, Vec3};
+/// # use std::fs::File;
+/// # fn main() -> Result<(), Error> {
+/// let creature = Husk::new();
+/// let staff = Husk::new();
+/// // add rings …
+/// let mut scene = Scene::default();
+/// scene.add("creature", creature, Vec3::ZERO, Quat::IDENTITY)?;
+/// scene.add("staff", staff, Vec3::new(0.5, 0.0, 0.0), Quat::IDENTITY)?;
+/// let file = File::create("scene.glb")?;
+/// scene.write_gltf(file)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct Scene {
+    /// All parts in scene
+    parts: Vec<Part>,
+}
+
+impl Scene {
+    /// Add a husk to the scene
+    ///
+    /// The current branch of the husk is capped.
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        husk: Husk,
+        translation: Vec3,
+        rotation: Quat,
+    ) -> Result<()> {
+        let materials = husk.materials().to_vec();
+        let anchors = husk.anchors().to_vec();
+        self.parts.push(Part {
+            name: name.into(),
+            mesh: husk.into_mesh()?,
+            translation,
+            rotation,
+            materials,
+            anchors,
+        });
+        Ok(())
+    }
+
+    /// Add a mesh to the scene
+    ///
+    /// This is for meshes made without a [Husk], such as with
+    /// [Mesh::builder].  The mesh has no material definitions.
+    ///
+    /// [Mesh::builder]: struct.Mesh.html#method.builder
+    pub fn add_mesh(
+        &mut self,
+        name: impl Into<String>,
+        mesh: Mesh,
+        translation: Vec3,
+        rotation: Quat,
+    ) {
+        self.parts.push(Part {
+            name: name.into(),
+            mesh,
+            translation,
+            rotation,
+            materials: Vec::new(),
+            anchors: Vec::new(),
+        });
+    }
+
+    /// Get report of problems found while building all parts
+    pub fn build_report(&self) -> BuildReport {
+        let mut report = BuildReport::default();
+        for part in &self.parts {
+            let r = part.mesh.build_report();
+            report.degenerate_faces += r.degenerate_faces;
+            report.fallback_normals += r.fallback_normals;
+        }
+        report
+    }
+
+    /// Get names of parts which are inside out
+    ///
+    /// See [Mesh::is_inside_out].
+    ///
+    /// [Mesh::is_inside_out]: struct.Mesh.html#method.is_inside_out
+    pub fn inside_out(&self) -> Vec<&str> {
+        self.parts
+            .iter()
+            .filter(|part| part.mesh.is_inside_out())
+            .map(|part| &part.name[..])
+            .collect()
+    }
+
+    /// Get mesh nodes for all parts
+    fn nodes(&self) -> Vec<MeshNode<'_>> {
+        self.parts
+            .iter()
+            .map(|part| MeshNode {
+                name: Some(&part.name),
+                mesh: &part.mesh,
+                translation: part.translation,
+                rotation: part.rotation,
+                materials: &part.materials,
+                anchors: &part.anchors,
+            })
+            .collect()
+    }
+
+    /// Write scene as [glTF] `.glb`
+    ///
+    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
+    pub fn write_gltf<W: Write>(&self, writer: W) -> Result<()> {
+        self.write_gltf_with(writer, &GltfOptions::default())
+    }
+
+    /// Write scene as [glTF] `.glb`, with export options
+    ///
+    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
+    pub fn write_gltf_with<W: Write>(
+        &self,
+        writer: W,
+        options: &GltfOptions,
+    ) -> Result<()> {
+        gltf::export(writer, &self.nodes(), options)?;
+        Ok(())
+    }
+
+    /// Write scene as [glTF] `.gltf` JSON, with an external `.bin` buffer
+    ///
+    /// The buffer is written to `bin_writer`, and `bin_uri` is its file
+    /// name, relative to the `.gltf` file.
+    ///
+    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
+    pub fn write_gltf_json<J: Write, B: Write>(
+        &self,
+        writer: J,
+        bin_uri: &str,
+        bin_writer: B,
+        options: &GltfOptions,
+    ) -> Result<()> {
+        let nodes = self.nodes();
+        gltf::export_json(writer, bin_uri, bin_writer, &nodes, options)?;
+        Ok(())
+    }
+
+    /// Write scene as [glTF] `.gltf` JSON, with an embedded buffer
+    ///
+    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
+    pub fn write_gltf_embedded<W: Write>(
+        &self,
+        writer: W,
+        options: &GltfOptions,
+    ) -> Result<()> {
+        gltf::export_embedded(writer, &self.nodes(), options)?;
+        Ok(())
+    }
+
+    /// Write scene as [Wavefront OBJ] `.obj`
+    ///
+    /// Each part is a separate named object, with its placement applied.
+    ///
+    /// [Wavefront OBJ]: https://en.wikipedia.org/wiki/Wavefront_.obj_file
+    pub fn write_obj<W: Write>(&self, writer: W) -> Result<()> {
+        self.write_obj_with(writer, &GltfOptions::default())
+    }
+
+    /// Write scene as [Wavefront OBJ] `.obj`, with export options
+    ///
+    /// Only the scale, center and up axis options are used.
+    ///
+    /// [Wavefront OBJ]: https://en.wikipedia.org/wiki/Wavefront_.obj_file
+    pub fn write_obj_with<W: Write>(
+        &self,
+        writer: W,
+        options: &GltfOptions,
+    ) -> Result<()> {
+        let nodes = self.nodes();
+        let placed = Placed::new(&nodes, options);
+        obj::export(writer, &placed.nodes(&nodes, options))?;
+        Ok(())
+    }
+
+    /// Write scene as [PLY] `.ply`
+    ///
+    /// All parts are merged into one mesh, with their placement applied.
+    ///
+    /// [PLY]: https://en.wikipedia.org/wiki/PLY_(file_format)
+    pub fn write_ply<W: Write>(
+        &self,
+        writer: W,
+        format: PlyFormat,
+    ) -> Result<()> {
+        self.write_ply_with(writer, format, &GltfOptions::default())
+    }
+
+    /// Write scene as [PLY] `.ply`, with export options
+    ///
+    /// Only the scale, center and up axis options are used.
+    ///
+    /// [PLY]: https://en.wikipedia.org/wiki/PLY_(file_format)
+    pub fn write_ply_with<W: Write>(
+        &self,
+        writer: W,
+        format: PlyFormat,
+        options: &GltfOptions,
+    ) -> Result<()> {
+        let nodes = self.nodes();
+        let placed = Placed::new(&nodes, options);
+        ply::export(writer, &placed.nodes(&nodes, options), format)?;
+        Ok(())
+    }
+
+    /// Write vertex normals of scene as [glTF] `.glb` line segments
+    ///
+    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
+    pub fn write_normals_gltf<W: Write>(
+        &self,
+        writer: W,
+        length: f32,
+    ) -> Result<()> {
+        self.write_normals_gltf_with(writer, length, &GltfOptions::default())
+    }
+
+    /// Write vertex normals of scene as [glTF] `.glb` line segments, with
+    /// export options
+    ///
+    /// Only the scale, center and up axis options are used, so the lines
+    /// match a model exported with the same options.
+    ///
+    /// [gltf]: https://en.wikipedia.org/wiki/GlTF
+    pub fn write_normals_gltf_with<W: Write>(
+        &self,
+        writer: W,
+        length: f32,
+        options: &GltfOptions,
+    ) -> Result<()> {
+        gltf::export_normals_debug(writer, &self.nodes(), length, options)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::primitives::pyramid;
+    use crate::{Ring, UpAxis};
+    use serde_json::Value;
+
+    fn scene_json(scene: Scene, options: &GltfOptions) -> Value {
+        let mut glb = Vec::new();
+        scene.write_gltf_with(&mut glb, options).unwrap();
+        let len = u32::from_le_bytes(glb[12..16].try_into().unwrap());
+        serde_json::from_slice(&glb[20..20 + len as usize]).unwrap()
+    }
+
+    fn two_pyramids() -> Scene {
+        let mut scene = Scene::default();
+        scene
+            .add("a", pyramid(Ring::default()), Vec3::ZERO, Quat::IDENTITY)
+            .unwrap();
+        let rot = Quat::from_rotation_z(1.0);
+        scene
+            .add("b", pyramid(Ring::default()), Vec3::X, rot)
+            .unwrap();
+        scene
+    }
+
+    #[test]
+    fn add_mesh() {
+        let mut scene = Scene::default();
+        let mesh = pyramid(Ring::default()).into_mesh().unwrap();
+        let faces = mesh.indices().len() / 3;
+        scene.add_mesh("mesh", mesh, Vec3::ZERO, Quat::IDENTITY);
+        let json = scene_json(scene, &GltfOptions::default());
+        assert_eq!(json["nodes"][0]["name"], "mesh");
+        let acc = &json["accessors"];
+        let count = acc.as_array().unwrap().iter().find_map(|a| {
+            (a["type"] == "SCALAR").then(|| a["count"].as_u64().unwrap())
+        });
+        assert_eq!(count, Some(faces as u64 * 3));
+    }
+
+    #[test]
+    fn inside_out() {
+        assert!(two_pyramids().inside_out().is_empty());
+    }
+
+    #[test]
+    fn named_nodes() {
+        let json = scene_json(two_pyramids(), &GltfOptions::default());
+        assert_eq!(json["meshes"].as_array().unwrap().len(), 2);
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 2);
+        assert_eq!(json["scenes"][0]["nodes"], serde_json::json!([0, 1]));
+        assert_eq!(json["meshes"][0]["name"], "a");
+        assert_eq!(json["meshes"][1]["name"], "b");
+        assert_eq!(json["nodes"][0]["name"], "a");
+        assert_eq!(json["nodes"][1]["name"], "b");
+        assert_eq!(json["nodes"][1]["mesh"], 1);
+        assert!(json["nodes"][0].get("translation").is_none());
+        assert_eq!(json["nodes"][1]["translation"][0], 1.0);
+        assert!(json["nodes"][1]["rotation"].is_array());
+        assert!(json["scenes"][0].get("name").is_none());
+        let options = GltfOptions {
+            scene_name: Some("pyramids".to_string()),
+            ..Default::default()
+        };
+        let json = scene_json(two_pyramids(), &options);
+        assert_eq!(json["scenes"][0]["name"], "pyramids");
+    }
+
+    #[test]
+    fn obj_objects() {
+        let mut buf = Vec::new();
+        two_pyramids().write_obj(&mut buf).unwrap();
+        let obj = String::from_utf8(buf).unwrap();
+        let objects: Vec<_> =
+            obj.lines().filter(|l| l.starts_with("o ")).collect();
+        assert_eq!(objects, ["o a", "o b"]);
+        let n_pos = pyramid(Ring::default())
+            .to_mesh()
+            .unwrap()
+            .positions()
+            .len();
+        let max_idx = obj
+            .lines()
+            .filter_map(|l| l.strip_prefix("f "))
+            .flat_map(|f| f.split_whitespace())
+            .map(|v| v.split('/').next().unwrap().parse::<usize>().unwrap())
+            .max();
+        assert_eq!(max_idx, Some(n_pos * 2));
+    }
+
+    /// Get bounds of vertex positions
+    fn pos_bounds<'a>(pos: impl Iterator<Item = &'a str>) -> (Vec3, Vec3) {
+        pos.map(|v| {
+            let c: Vec<f32> =
+                v.split_whitespace().map(|c| c.parse().unwrap()).collect();
+            Vec3::new(c[0], c[1], c[2])
+        })
+        .fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), p| {
+            (min.min(p), max.max(p))
+        })
+    }
+
+    /// Check bounds of scaled, centered and Z up output
+    fn check_placement(bounds: impl Fn(&GltfOptions) -> (Vec3, Vec3)) {
+        let (min, max) = bounds(&GltfOptions::default());
+        let options = GltfOptions::default()
+            .with_scale(2.0)
+            .with_up(UpAxis::Z)
+            .with_center();
+        let (zmin, zmax) = bounds(&options);
+        let size = (max - min) * 2.0;
+        let zsize = zmax - zmin;
+        assert!((zsize.x - size.x).abs() < 1e-5);
+        assert!((zsize.y - size.z).abs() < 1e-5);
+        assert!((zsize.z - size.y).abs() < 1e-5);
+        assert!(zmin.z.abs() < 1e-5);
+        assert!((zmin.x + zmax.x).abs() < 1e-5);
+        assert!((zmin.y + zmax.y).abs() < 1e-5);
+    }
+
+    #[test]
+    fn obj_placement() {
+        check_placement(|options| {
+            let mut buf = Vec::new();
+            two_pyramids().write_obj_with(&mut buf, options).unwrap();
+            let obj = String::from_utf8(buf).unwrap();
+            pos_bounds(obj.lines().filter_map(|l| l.strip_prefix("v ")))
+        });
+    }
+
+    #[test]
+    fn ply_placement() {
+        check_placement(|options| {
+            let mut buf = Vec::new();
+            two_pyramids()
+                .write_ply_with(&mut buf, PlyFormat::Ascii, options)
+                .unwrap();
+            let ply = String::from_utf8(buf).unwrap();
+            let (header, body) = ply.split_once("end_header\n").unwrap();
+            let count = header
+                .lines()
+                .find_map(|l| l.strip_prefix("element vertex "))
+                .unwrap()
+                .parse()
+                .unwrap();
+            pos_bounds(body.lines().take(count))
+        });
+    }
+
+    #[test]
+    fn normals_placement() {
+        let scene = || {
+            let mut scene = Scene::default();
+            scene
+                .add("a", pyramid(Ring::default()), Vec3::X, Quat::IDENTITY)
+                .unwrap();
+            scene
+        };
+        let options = GltfOptions::default()
+            .with_scale(2.0)
+            .with_up(UpAxis::Z)
+            .with_center();
+        let model = scene_json(scene(), &options);
+        let mut glb = Vec::new();
+        scene()
+            .write_normals_gltf_with(&mut glb, 0.0, &options)
+            .unwrap();
+        let len = u32::from_le_bytes(glb[12..16].try_into().unwrap());
+        let lines: Value =
+            serde_json::from_slice(&glb[20..20 + len as usize]).unwrap();
+        let node = &model["nodes"][0];
+        let pos = &model["accessors"][node_position(&model)];
+        assert_eq!(lines["nodes"][0]["translation"], node["translation"]);
+        assert_eq!(lines["accessors"][0]["min"], pos["min"]);
+        assert_eq!(lines["accessors"][0]["max"], pos["max"]);
+    }
+
+    /// Get accessor index of positions of the first mesh
+    fn node_position(json: &Value) -> usize {
+        let prim = &json["meshes"][0]["primitives"][0];
+        prim["attributes"]["POSITION"].as_u64().unwrap() as usize
+    }
+
+    #[test]
+    fn quantized_placement() {
+        let options = GltfOptions {
+            quantize: true,
+            ..Default::default()
+        };
+        let plain = scene_json(two_pyramids(), &GltfOptions::default());
+        let quant = scene_json(two_pyramids(), &options);
+        assert_eq!(
+            quant["nodes"][1]["rotation"],
+            plain["nodes"][1]["rotation"]
+        );
+        let t0 = quant["nodes"][0]["translation"].clone();
+        let t1 = quant["nodes"][1]["translation"].clone();
+        assert_ne!(t0, t1);
+    }
+}
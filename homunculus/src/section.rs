@@ -0,0 +1,280 @@
+// section.rs   Cross-section module
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::error::Result;
+use crate::husk::Husk;
+use crate::mesh::Mesh;
+use crate::plane::Plane;
+use glam::Vec2;
+use std::io::Write;
+
+/// Tolerance for matching segment end-points
+const EPSILON: f32 = 1e-5;
+
+/// Cross-section SVG options
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SectionOptions {
+    /// Length of one model unit (centimeters)
+    pub unit_cm: f32,
+}
+
+/// Polyline in plane coordinates
+#[derive(Clone, Debug, PartialEq)]
+struct Polyline {
+    /// Points
+    pts: Vec<Vec2>,
+
+    /// Closed loop
+    closed: bool,
+}
+
+impl Default for SectionOptions {
+    fn default() -> Self {
+        SectionOptions { unit_cm: 1.0 }
+    }
+}
+
+impl Plane {
+    /// Slice mesh triangles into segments (plane coordinates)
+    fn segments(&self, mesh: &Mesh) -> Vec<(Vec2, Vec2)> {
+        let (u, v) = self.basis();
+        let pos = mesh.positions();
+        let mut segments = Vec::new();
+        for idx in mesh.indices().chunks_exact(3) {
+            let tri = [0, 1, 2].map(|i| pos[usize::from(idx[i].0)]);
+            let mut pts = Vec::with_capacity(2);
+            for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])]
+            {
+                let (da, db) = (self.distance(a), self.distance(b));
+                if (da < 0.0) != (db < 0.0) {
+                    let p = a.lerp(b, da / (da - db));
+                    pts.push(Vec2::new(p.dot(u), p.dot(v)));
+                }
+            }
+            if let [p0, p1] = pts[..] {
+                if p0.distance(p1) > EPSILON {
+                    segments.push((p0, p1));
+                }
+            }
+        }
+        segments
+    }
+}
+
+/// Find a segment connected to a point, removing it
+fn take_connected(segments: &mut Vec<(Vec2, Vec2)>, pt: Vec2) -> Option<Vec2> {
+    let i = segments.iter().position(|(p0, p1)| {
+        p0.distance(pt) <= EPSILON || p1.distance(pt) <= EPSILON
+    })?;
+    let (p0, p1) = segments.swap_remove(i);
+    Some(if p0.distance(pt) <= EPSILON { p1 } else { p0 })
+}
+
+/// Remove points between collinear segments
+fn simplify(pts: &mut Vec<Vec2>, closed: bool) {
+    let n = pts.len();
+    let keep: Vec<bool> = (0..n)
+        .map(|i| {
+            if !closed && (i == 0 || i == n - 1) {
+                return true;
+            }
+            let prev = pts[(i + n - 1) % n];
+            let next = pts[(i + 1) % n];
+            let d0 = pts[i] - prev;
+            let d1 = next - pts[i];
+            d0.perp_dot(d1).abs() > EPSILON * d0.length() * d1.length()
+                || d0.dot(d1) < 0.0
+        })
+        .collect();
+    let mut keep = keep.into_iter();
+    pts.retain(|_| keep.next().unwrap_or(true));
+}
+
+/// Assemble segments into polylines
+fn chain_polylines(mut segments: Vec<(Vec2, Vec2)>) -> Vec<Polyline> {
+    let mut lines = Vec::new();
+    while let Some((start, end)) = segments.pop() {
+        let mut pts = vec![start, end];
+        // extend forward
+        let mut closed = false;
+        while let Some(pt) = take_connected(&mut segments, pts[pts.len() - 1]) {
+            if pt.distance(start) <= EPSILON {
+                closed = true;
+                break;
+            }
+            pts.push(pt);
+        }
+        if !closed {
+            // extend backward
+            while let Some(pt) = take_connected(&mut segments, pts[0]) {
+                pts.insert(0, pt);
+            }
+        }
+        simplify(&mut pts, closed);
+        lines.push(Polyline { pts, closed });
+    }
+    lines
+}
+
+impl Polyline {
+    /// Write as an SVG path
+    fn write_svg<W: Write>(&self, writer: &mut W, scale: f32) -> Result<()> {
+        write!(writer, "  <path d=\"")?;
+        for (i, pt) in self.pts.iter().enumerate() {
+            let cmd = if i == 0 { 'M' } else { 'L' };
+            let pt = *pt * scale;
+            write!(writer, "{cmd}{} {} ", num(pt.x), num(pt.y))?;
+        }
+        if self.closed {
+            write!(writer, "Z")?;
+        }
+        writeln!(writer, "\" />")?;
+        Ok(())
+    }
+}
+
+/// Format a number for SVG
+fn num(val: f32) -> String {
+    let s = format!("{val:.4}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    match s {
+        "-0" | "" => "0".to_string(),
+        _ => s.to_string(),
+    }
+}
+
+/// Write polylines as an SVG document
+fn write_svg<W: Write>(
+    mut writer: W,
+    lines: &[Polyline],
+    options: &SectionOptions,
+) -> Result<()> {
+    let scale = options.unit_cm;
+    let (min, max) = lines.iter().flat_map(|l| &l.pts).fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), p| (min.min(*p * scale), max.max(*p * scale)),
+    );
+    let (min, size) = if lines.is_empty() {
+        (Vec2::ZERO, Vec2::ZERO)
+    } else {
+        (min, max - min)
+    };
+    writeln!(
+        writer,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+         width=\"{w}cm\" height=\"{h}cm\" \
+         viewBox=\"{} {} {w} {h}\">",
+        num(min.x),
+        num(min.y),
+        w = num(size.x),
+        h = num(size.y),
+    )?;
+    writeln!(
+        writer,
+        "<g fill=\"none\" stroke=\"black\" stroke-width=\"0.05\">"
+    )?;
+    for line in lines {
+        line.write_svg(&mut writer, scale)?;
+    }
+    writeln!(writer, "</g>")?;
+    writeln!(writer, "</svg>")?;
+    Ok(())
+}
+
+impl Husk {
+    /// Write a cross-section as SVG
+    ///
+    /// The mesh is intersected with a plane, with segments assembled into
+    /// paths.  One model unit is 1 cm.
+    pub fn section_svg<W: Write>(&self, plane: Plane, writer: W) -> Result<()> {
+        self.section_svg_with(plane, writer, &SectionOptions::default())
+    }
+
+    /// Write a cross-section as SVG, with options
+    ///
+    /// Paths are closed for loops, and open where the mesh is open.
+    pub fn section_svg_with<W: Write>(
+        &self,
+        plane: Plane,
+        writer: W,
+        options: &SectionOptions,
+    ) -> Result<()> {
+        let mesh = self.to_mesh()?;
+        let lines = chain_polylines(plane.segments(&mesh));
+        write_svg(writer, &lines, options)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Ring, Spoke};
+    use glam::Vec3;
+    use std::f32::consts::PI;
+
+    /// Make a unit cube husk (open at bottom)
+    fn cube() -> Result<Husk> {
+        let mut husk = Husk::new();
+        let d = 0.5f32.sqrt();
+        let mut ring = Ring::default().axis(Vec3::new(0.0, 1.0, 0.0));
+        for i in 0..4 {
+            let angle = PI * (0.25 + 0.5 * i as f32);
+            ring = ring.spoke(Spoke::from(d).with_angle(angle));
+        }
+        husk.ring(ring.clone())?;
+        husk.ring(Ring::default())?;
+        Ok(husk)
+    }
+
+    fn svg(husk: &Husk, plane: Plane) -> Result<String> {
+        let mut svg = Vec::new();
+        husk.section_svg(plane, &mut svg)?;
+        Ok(String::from_utf8(svg).unwrap())
+    }
+
+    #[test]
+    fn cube_square() -> Result<()> {
+        let mut husk = cube()?;
+        let svg = svg(&husk, Plane::y(0.5))?;
+        let paths: Vec<_> =
+            svg.lines().filter(|l| l.contains("<path")).collect();
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].contains('Z'));
+        assert_eq!(paths[0].matches('L').count(), 3);
+        assert!(svg.contains("width=\"1cm\" height=\"1cm\""));
+        assert!(svg.contains("viewBox=\"-0.5 -0.5 1 1\""));
+        let mesh = husk.to_mesh()?;
+        let lines = chain_polylines(Plane::y(0.5).segments(&mesh));
+        let side = lines[0].pts[0].distance(lines[0].pts[1]);
+        assert!((side - 1.0).abs() < 1e-5);
+        // husk is still usable
+        husk.ring(Ring::default())?;
+        Ok(())
+    }
+
+    #[test]
+    fn open_path() -> Result<()> {
+        let husk = cube()?;
+        let svg = svg(&husk, Plane::x(0.0))?;
+        let paths: Vec<_> =
+            svg.lines().filter(|l| l.contains("<path")).collect();
+        // bottom is open, so path is not closed
+        assert_eq!(paths.len(), 1);
+        assert!(!paths[0].contains('Z'));
+        let mut svg = Vec::new();
+        let options = SectionOptions { unit_cm: 2.54 };
+        husk.section_svg_with(Plane::y(0.5), &mut svg, &options)?;
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains("width=\"2.54cm\""));
+        Ok(())
+    }
+
+    #[test]
+    fn empty() -> Result<()> {
+        let svg = svg(&cube()?, Plane::y(5.0))?;
+        assert!(!svg.contains("<path"));
+        assert!(svg.contains("viewBox=\"0 0 0 0\""));
+        Ok(())
+    }
+}
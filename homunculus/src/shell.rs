@@ -0,0 +1,223 @@
+// shell.rs  Hollow shells
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::error::Result;
+use crate::mesh::{Mesh, Vertex};
+use crate::subdivide::match_ids;
+use glam::Vec3;
+use std::collections::HashMap;
+
+/// Minimum cosine for mitered corners, limiting offsets to 3x thickness
+const MIN_MITER: f32 = 1.0 / 3.0;
+
+impl Mesh {
+    /// Make a copy as a hollow shell, with walls of a given thickness
+    ///
+    /// An inner surface is added, offset inward along the vertex normals and
+    /// facing the opposite direction.  Boundary edges of open ends are joined
+    /// to the inner surface by rim faces, so a watertight shell is made from
+    /// an open mesh.  Inner and rim faces are on separate surfaces, and
+    /// ambient occlusion is not kept.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.set_auto_cap(false);
+    /// husk.ring_capped(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+    /// husk.ring(Ring::default())?;
+    /// let cup = husk.into_mesh()?.shelled(0.1)?;
+    /// assert!(cup.is_watertight());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [Error::TooManyVertices] if the result has more than 65536 vertices
+    ///
+    /// # Panics
+    ///
+    /// - If `thickness` is not positive and finite
+    ///
+    /// [Error::TooManyVertices]: enum.Error.html#variant.TooManyVertices
+    pub fn shelled(&self, thickness: f32) -> Result<Mesh> {
+        assert!(thickness > 0.0 && thickness.is_finite());
+        let pos = self.positions();
+        let norm = self.normals();
+        let tris: Vec<[usize; 3]> = self
+            .indices()
+            .chunks_exact(3)
+            .map(|tri| [0, 1, 2].map(|i| usize::from(tri[i].0)))
+            .collect();
+        // topological vertices, joined by position
+        let (tv, n_top) =
+            match_ids(pos.iter().map(|p| p.to_array().map(f32::to_bits)));
+        // unit normals of faces
+        let face_norm: Vec<Vec3> = tris
+            .iter()
+            .map(|tri| {
+                let [p0, p1, p2] = tri.map(|v| pos[v]);
+                (p1 - p0).cross(p2 - p0).normalize_or_zero()
+            })
+            .collect();
+        // angle weighted offset directions of topological vertices
+        let mut dirs = vec![Vec3::ZERO; n_top];
+        for (tri, n) in tris.iter().zip(&face_norm) {
+            for i in 0..3 {
+                let p = pos[tri[i]];
+                let e0 = pos[tri[(i + 1) % 3]] - p;
+                let e1 = pos[tri[(i + 2) % 3]] - p;
+                dirs[tv[tri[i]]] += *n * e0.angle_between(e1);
+            }
+        }
+        let dirs: Vec<Vec3> =
+            dirs.into_iter().map(Vec3::normalize_or_zero).collect();
+        // mitered offsets, keeping each face at least `thickness` away
+        let mut miter = vec![1.0_f32; n_top];
+        for (tri, n) in tris.iter().zip(&face_norm) {
+            for v in tri {
+                let t = tv[*v];
+                if *n != Vec3::ZERO {
+                    miter[t] = miter[t].min(dirs[t].dot(*n));
+                }
+            }
+        }
+        let offsets: Vec<Vec3> = dirs
+            .iter()
+            .zip(miter)
+            .map(|(d, m)| *d * thickness / m.max(MIN_MITER))
+            .collect();
+        // faces adjacent to each topological edge, by directed edge
+        let mut edges = HashMap::<(usize, usize), Vec<(usize, usize)>>::new();
+        for (f, tri) in tris.iter().enumerate() {
+            for i in 0..3 {
+                let (a, b) = (tri[i], tri[(i + 1) % 3]);
+                let key = (tv[a].min(tv[b]), tv[a].max(tv[b]));
+                edges.entry(key).or_default().push((f, i));
+            }
+        }
+        let mut rims: Vec<(usize, usize)> = edges
+            .values()
+            .filter(|faces| faces.len() == 1)
+            .map(|faces| faces[0])
+            .collect();
+        rims.sort_unstable();
+        let n_vtx = pos.len();
+        let mut new_pos = pos.to_vec();
+        let mut new_norm = norm.to_vec();
+        new_pos.extend(pos.iter().zip(&tv).map(|(p, t)| *p - offsets[*t]));
+        new_norm.extend(norm.iter().map(|n| -*n));
+        let mut uv = self.uvs().to_vec();
+        if !uv.is_empty() {
+            uv.extend_from_within(..);
+        }
+        let mut color = self.colors().to_vec();
+        if !color.is_empty() {
+            color.extend_from_within(..);
+        }
+        let mut joints = self.joints.clone();
+        joints.extend_from_within(..);
+        let mut indices = self.indices().to_vec();
+        for tri in &tris {
+            for v in [tri[0], tri[2], tri[1]] {
+                indices.push(Vertex::try_from(n_vtx + v)?);
+            }
+        }
+        // rim faces, with their own vertices for flat shading
+        for (f, i) in &rims {
+            let (a, b) = (tris[*f][*i], tris[*f][(*i + 1) % 3]);
+            let quad = [b, a, n_vtx + a, n_vtx + b];
+            let [p0, p1, p2, p3] = quad.map(|v| new_pos[v]);
+            let n = (p1 - p0).cross(p2 - p0) + (p2 - p0).cross(p3 - p0);
+            let n = n.normalize_or_zero();
+            let base = new_pos.len();
+            for v in quad {
+                new_pos.push(new_pos[v]);
+                new_norm.push(n);
+                if !uv.is_empty() {
+                    uv.push(uv[v]);
+                }
+                if !color.is_empty() {
+                    color.push(color[v]);
+                }
+                if !joints.is_empty() {
+                    joints.push(joints[v]);
+                }
+            }
+            for v in [0, 1, 2, 0, 2, 3] {
+                indices.push(Vertex::try_from(base + v)?);
+            }
+        }
+        let mut mesh = Mesh::from_parts(new_pos, new_norm, indices);
+        mesh.uv = uv;
+        mesh.color = color;
+        mesh.joints = joints;
+        mesh.skeleton.clone_from(&self.skeleton);
+        mesh.materials = self.materials.clone();
+        if self.face_materials.len() == tris.len() {
+            mesh.face_materials = self.face_materials.repeat(2);
+            mesh.face_materials.extend(
+                rims.iter().flat_map(|(f, _)| [self.face_materials[*f]; 2]),
+            );
+        }
+        if self.surfaces.len() == tris.len() {
+            let base = self.surfaces.iter().max().map_or(0, |s| s + 1);
+            mesh.surfaces = self.surfaces.clone();
+            mesh.surfaces.extend(self.surfaces.iter().map(|s| base + s));
+            mesh.surfaces
+                .extend(rims.iter().flat_map(|_| [base * 2; 2]));
+        }
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Husk, Mesh, Ring};
+    use std::f32::consts::PI;
+
+    /// Make a cylinder, capped at the bottom and open at the top
+    fn open_cylinder(spokes: usize) -> Mesh {
+        let mut husk = Husk::new();
+        husk.set_auto_cap(false);
+        let ring = (0..spokes).fold(Ring::default(), |r, _| r.spoke(1.0));
+        husk.ring_capped(ring).unwrap();
+        husk.ring(Ring::default().axis(glam::Vec3::new(0.0, 2.0, 0.0)))
+            .unwrap();
+        husk.into_mesh().unwrap()
+    }
+
+    #[test]
+    fn hollow_cylinder() {
+        let mesh = open_cylinder(32);
+        assert!(!mesh.is_watertight());
+        let faces = mesh.indices().len() / 3;
+        let shell = mesh.shelled(0.1).unwrap();
+        assert!(shell.is_watertight());
+        assert!(!shell.is_inside_out());
+        // inner faces, plus two rim faces per open edge
+        assert_eq!(shell.indices().len() / 3, faces * 2 + 32 * 2);
+        assert_eq!(shell.surfaces().len(), shell.indices().len() / 3);
+        // wall volume of outer cup minus inner cup (approximately)
+        let area = 0.5 * 32.0 * (2.0 * PI / 32.0).sin();
+        let expected = area * 2.0 - area * 0.9 * 0.9 * 1.9;
+        let volume = shell.volume();
+        assert!((volume - expected).abs() < 0.05, "{volume} != {expected}");
+    }
+
+    #[test]
+    fn closed() {
+        let mut husk = Husk::new();
+        husk.ring_capped(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))
+            .unwrap();
+        husk.ring(Ring::default().spoke(0.0)).unwrap();
+        let mesh = husk.into_mesh().unwrap();
+        let shell = mesh.shelled(0.1).unwrap();
+        // no rim faces on a closed mesh
+        assert_eq!(shell.indices().len(), mesh.indices().len() * 2);
+        assert!(shell.is_watertight());
+        assert!(shell.volume() > 0.0 && shell.volume() < mesh.volume());
+    }
+}
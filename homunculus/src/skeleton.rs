@@ -0,0 +1,99 @@
+// skeleton.rs  Skeleton module
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use glam::{Affine3A, Quat, Vec3};
+
+/// Joint of a [Skeleton]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Joint {
+    /// Joint name
+    pub name: String,
+
+    /// Index of parent joint (`None` for a root)
+    pub parent: Option<usize>,
+
+    /// Position in bind pose
+    pub pos: Vec3,
+}
+
+/// Skeleton for skinning a mesh
+///
+/// Made by [Husk::skeleton], with a joint chain along each branch.  Each
+/// vertex is bound rigidly to one joint (see [Mesh::joints]).
+///
+/// [Husk::skeleton]: struct.Husk.html#method.skeleton
+/// [Mesh::joints]: struct.Mesh.html#method.joints
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Skeleton {
+    /// All joints, with parents before children
+    joints: Vec<Joint>,
+}
+
+impl Skeleton {
+    /// Push a joint, returning its index
+    ///
+    /// # Panics
+    ///
+    /// - If `parent` is not an existing joint
+    pub(crate) fn push(
+        &mut self,
+        name: String,
+        parent: Option<usize>,
+        pos: Vec3,
+    ) -> usize {
+        assert!(parent.is_none_or(|p| p < self.joints.len()));
+        self.joints.push(Joint { name, parent, pos });
+        self.joints.len() - 1
+    }
+
+    /// Get all joints
+    ///
+    /// Parents are always before their children.
+    pub fn joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    /// Get child joints of a joint
+    pub fn children(&self, joint: usize) -> impl Iterator<Item = usize> + '_ {
+        self.joints
+            .iter()
+            .enumerate()
+            .filter(move |(_, j)| j.parent == Some(joint))
+            .map(|(i, _)| i)
+    }
+
+    /// Make a copy with uniform scale and rotation applied
+    pub(crate) fn transformed(&self, scale: f32, rotation: Quat) -> Self {
+        let mut skeleton = self.clone();
+        for joint in &mut skeleton.joints {
+            joint.pos = rotation * (joint.pos * scale);
+        }
+        skeleton
+    }
+
+    /// Apply an affine transform
+    pub(crate) fn transform(&mut self, xform: Affine3A) {
+        for joint in &mut self.joints {
+            joint.pos = xform.transform_point3(joint.pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn children() {
+        let mut skeleton = Skeleton::default();
+        let root = skeleton.push("root".into(), None, Vec3::ZERO);
+        let a = skeleton.push("a".into(), Some(root), Vec3::Y);
+        let b = skeleton.push("b".into(), Some(root), Vec3::X);
+        skeleton.push("a.1".into(), Some(a), Vec3::Y * 2.0);
+        assert_eq!(skeleton.children(root).collect::<Vec<_>>(), [a, b]);
+        assert_eq!(skeleton.children(b).count(), 0);
+        let scaled = skeleton.transformed(2.0, Quat::IDENTITY);
+        assert_eq!(scaled.joints()[3].pos, Vec3::Y * 4.0);
+    }
+}
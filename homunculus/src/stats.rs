@@ -0,0 +1,195 @@
+// stats.rs     Mesh statistics
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::mesh::Mesh;
+use glam::Vec3;
+use std::collections::{HashMap, HashSet};
+
+/// Mesh statistics
+///
+/// Made by [Mesh::stats].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshStats {
+    /// Vertex count
+    pub vertices: usize,
+
+    /// Face (triangle) count
+    pub faces: usize,
+
+    /// Minimum bounding box position
+    pub min: Vec3,
+
+    /// Maximum bounding box position
+    pub max: Vec3,
+
+    /// Total surface area
+    pub surface_area: f32,
+
+    /// Enclosed volume (only meaningful if watertight)
+    pub volume: f32,
+
+    /// Count of boundary edges (zero if watertight)
+    pub boundary_edges: usize,
+}
+
+impl MeshStats {
+    /// Check if the mesh is watertight
+    pub fn is_watertight(&self) -> bool {
+        self.boundary_edges == 0
+    }
+}
+
+impl Mesh {
+    /// Get triangles as vertex positions
+    fn triangles(&self) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+        let pos = self.positions();
+        self.indices()
+            .chunks_exact(3)
+            .map(|tri| [0, 1, 2].map(|i| pos[usize::from(tri[i].0)]))
+    }
+
+    /// Calculate total surface area
+    pub fn surface_area(&self) -> f32 {
+        self.triangles()
+            .map(|[p0, p1, p2]| (p1 - p0).cross(p2 - p0).length() * 0.5)
+            .sum()
+    }
+
+    /// Calculate enclosed volume
+    ///
+    /// This is the sum of signed tetrahedron volumes from the origin to each
+    /// face, which is only meaningful if the mesh is [watertight].
+    ///
+    /// [watertight]: struct.Mesh.html#method.is_watertight
+    pub fn volume(&self) -> f32 {
+        self.triangles()
+            .map(|[p0, p1, p2]| p0.dot(p1.cross(p2)) / 6.0)
+            .sum()
+    }
+
+    /// Count boundary edges
+    ///
+    /// Vertices are split at seams and creases, so they are matched by
+    /// position.  In a closed mesh, each edge is shared by exactly two faces
+    /// with opposite winding; any other edge is on a boundary.
+    pub fn boundary_edges(&self) -> usize {
+        let mut welded = HashMap::new();
+        let ids: Vec<usize> = self
+            .positions()
+            .iter()
+            .map(|p| {
+                let n = welded.len();
+                *welded.entry(p.to_array().map(f32::to_bits)).or_insert(n)
+            })
+            .collect();
+        let mut edges = HashMap::<(usize, usize), usize>::new();
+        for tri in self.indices().chunks_exact(3) {
+            for i in 0..3 {
+                let a = ids[usize::from(tri[i].0)];
+                let b = ids[usize::from(tri[(i + 1) % 3].0)];
+                *edges.entry((a, b)).or_default() += 1;
+            }
+        }
+        let mut boundary = HashSet::new();
+        for (&(a, b), &count) in &edges {
+            if count != 1 || edges.get(&(b, a)) != Some(&1) {
+                boundary.insert((a.min(b), a.max(b)));
+            }
+        }
+        boundary.len()
+    }
+
+    /// Check if the mesh is inside out
+    ///
+    /// This is a heuristic, using the sign of the [volume].  It is only
+    /// reliable if the mesh is [watertight].  An inside out mesh can be fixed
+    /// with [flip_winding].
+    ///
+    /// [volume]: struct.Mesh.html#method.volume
+    /// [watertight]: struct.Mesh.html#method.is_watertight
+    /// [flip_winding]: struct.Mesh.html#method.flip_winding
+    pub fn is_inside_out(&self) -> bool {
+        self.volume() < 0.0
+    }
+
+    /// Check if the mesh is watertight (closed, with no boundary edges)
+    pub fn is_watertight(&self) -> bool {
+        self.boundary_edges() == 0
+    }
+
+    /// Get mesh statistics
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.ring_capped(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+    /// husk.ring(Ring::default().spoke(0.0))?;
+    /// let stats = husk.into_mesh()?.stats();
+    /// assert!(stats.is_watertight());
+    /// println!("volume: {}", stats.volume);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats(&self) -> MeshStats {
+        let (min, max) = if self.positions().is_empty() {
+            (Vec3::ZERO, Vec3::ZERO)
+        } else {
+            (self.pos_min(), self.pos_max())
+        };
+        MeshStats {
+            vertices: self.positions().len(),
+            faces: self.indices().len() / 3,
+            min,
+            max,
+            surface_area: self.surface_area(),
+            volume: self.volume(),
+            boundary_edges: self.boundary_edges(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{build_cube, Husk, Ring};
+
+    fn cylinder(closed: bool) -> Mesh {
+        let mut husk = Husk::new();
+        husk.set_auto_cap(closed);
+        let ring = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        let ring = ring.spoke(1.0).spoke(1.0).spoke(1.0);
+        if closed {
+            husk.ring_capped(ring).unwrap();
+        } else {
+            husk.ring(ring).unwrap();
+        }
+        husk.ring(Ring::default().axis(Vec3::new(0.0, 2.0, 0.0)))
+            .unwrap();
+        husk.into_mesh().unwrap()
+    }
+
+    #[test]
+    fn cube_stats() {
+        let stats = build_cube().unwrap().stats();
+        // vertices are split at creases
+        assert_eq!(stats.vertices, 24);
+        assert_eq!(stats.faces, 12);
+        assert_eq!((stats.min, stats.max), (Vec3::ZERO, Vec3::ONE));
+        assert!((stats.surface_area - 6.0).abs() < 1e-6);
+        assert!((stats.volume - 1.0).abs() < 1e-6);
+        assert!(stats.is_watertight());
+    }
+
+    #[test]
+    fn open_cylinder() {
+        let open = cylinder(false);
+        // top and bottom edges of 6 spokes
+        assert_eq!(open.boundary_edges(), 12);
+        assert!(!open.is_watertight());
+        let closed = cylinder(true);
+        assert!(closed.is_watertight());
+        assert!(closed.volume() > 0.0);
+    }
+}
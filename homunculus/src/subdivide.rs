@@ -0,0 +1,304 @@
+// subdivide.rs  Loop subdivision
+//
+// Copyright (c) 2024  Douglas Lau
+//
+use crate::error::Result;
+use crate::mesh::{Mesh, Vertex};
+use glam::{Quat, Vec3};
+use std::collections::{HashMap, HashSet};
+
+/// Get IDs of vertices, matched by a key
+pub(crate) fn match_ids<K, I>(keys: I) -> (Vec<usize>, usize)
+where
+    K: std::hash::Hash + Eq,
+    I: Iterator<Item = K>,
+{
+    let mut matched = HashMap::new();
+    let ids = keys
+        .map(|k| {
+            let n = matched.len();
+            *matched.entry(k).or_insert(n)
+        })
+        .collect();
+    (ids, matched.len())
+}
+
+/// Push an ID to a list, unless already present
+fn push_unique(list: &mut Vec<usize>, id: usize) {
+    if !list.contains(&id) {
+        list.push(id);
+    }
+}
+
+impl Mesh {
+    /// Make a copy with Loop subdivision applied
+    ///
+    /// Each level splits every face into four, smoothing the positions.
+    /// Vertices at the same position are joined to find adjacent faces.
+    /// Edges where vertices are split for flat shading or creases (but not
+    /// texture seams) are kept sharp, as are boundary edges.  Normals are
+    /// recomputed, and ambient occlusion is not kept.
+    ///
+    /// ```rust
+    /// # use homunculus::{Error, Husk, Ring};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut husk = Husk::new();
+    /// husk.ring_capped(Ring::default().spoke(1.0).spoke(1.0).spoke(1.0))?;
+    /// husk.ring(Ring::default().spoke(0.0))?;
+    /// let mesh = husk.into_mesh()?;
+    /// let smooth = mesh.subdivided(2)?;
+    /// assert_eq!(smooth.indices().len(), mesh.indices().len() * 16);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [Error::TooManyVertices] if the result has more than 65536 vertices
+    ///
+    /// [Error::TooManyVertices]: enum.Error.html#variant.TooManyVertices
+    pub fn subdivided(&self, levels: usize) -> Result<Mesh> {
+        let mut mesh = self.transformed(1.0, Quat::IDENTITY);
+        mesh.ao.clear();
+        for _ in 0..levels {
+            mesh = mesh.subdivide_once()?;
+        }
+        Ok(mesh)
+    }
+
+    /// Subdivide one level
+    fn subdivide_once(&self) -> Result<Mesh> {
+        let pos = self.positions();
+        let norm = self.normals();
+        let tris: Vec<[usize; 3]> = self
+            .indices()
+            .chunks_exact(3)
+            .map(|tri| [0, 1, 2].map(|i| usize::from(tri[i].0)))
+            .collect();
+        // topological vertices, joined by position
+        let (tv, n_top) =
+            match_ids(pos.iter().map(|p| p.to_array().map(f32::to_bits)));
+        // smooth vertices, joined by position and normal
+        let (sv, _) = match_ids(pos.iter().zip(norm).map(|(p, n)| {
+            (
+                p.to_array().map(f32::to_bits),
+                n.to_array().map(f32::to_bits),
+            )
+        }));
+        let edge_key =
+            |a: usize, b: usize| (tv[a].min(tv[b]), tv[a].max(tv[b]));
+        // faces (and edge position) adjacent to each topological edge
+        let mut edges = HashMap::<(usize, usize), Vec<(usize, usize)>>::new();
+        for (f, tri) in tris.iter().enumerate() {
+            for i in 0..3 {
+                let key = edge_key(tri[i], tri[(i + 1) % 3]);
+                edges.entry(key).or_default().push((f, i));
+            }
+        }
+        // split vertex of a face at a topological vertex
+        let split_vtx = |f: usize, t: usize| {
+            tris[f].iter().find(|v| tv[**v] == t).map(|v| sv[*v])
+        };
+        let sharp: HashSet<(usize, usize)> = edges
+            .iter()
+            .filter(|(key, faces)| match faces[..] {
+                [(f0, _), (f1, _)] => {
+                    split_vtx(f0, key.0) != split_vtx(f1, key.0)
+                        || split_vtx(f0, key.1) != split_vtx(f1, key.1)
+                }
+                _ => true,
+            })
+            .map(|(key, _)| *key)
+            .collect();
+        let mut top_pos = vec![Vec3::ZERO; n_top];
+        let mut nbrs = vec![Vec::new(); n_top];
+        let mut sharp_nbrs = vec![Vec::new(); n_top];
+        for (v, p) in pos.iter().enumerate() {
+            top_pos[tv[v]] = *p;
+        }
+        for tri in &tris {
+            for i in 0..3 {
+                let (a, b) = (tv[tri[i]], tv[tri[(i + 1) % 3]]);
+                push_unique(&mut nbrs[a], b);
+                push_unique(&mut nbrs[b], a);
+                if sharp.contains(&edge_key(tri[i], tri[(i + 1) % 3])) {
+                    push_unique(&mut sharp_nbrs[a], b);
+                    push_unique(&mut sharp_nbrs[b], a);
+                }
+            }
+        }
+        // smoothed positions of original vertices
+        let vtx_pos: Vec<Vec3> = (0..n_top)
+            .map(|t| {
+                let p = top_pos[t];
+                match sharp_nbrs[t][..] {
+                    [a, b] => p * 0.75 + (top_pos[a] + top_pos[b]) * 0.125,
+                    [] | [_] if !nbrs[t].is_empty() => {
+                        let n = nbrs[t].len();
+                        let beta = if n == 3 {
+                            3.0 / 16.0
+                        } else {
+                            3.0 / (8.0 * n as f32)
+                        };
+                        let sum: Vec3 =
+                            nbrs[t].iter().map(|n| top_pos[*n]).sum();
+                        p * (1.0 - n as f32 * beta) + sum * beta
+                    }
+                    // corners are kept in place
+                    _ => p,
+                }
+            })
+            .collect();
+        // position of a new vertex on a topological edge
+        let edge_pos = |key: (usize, usize)| {
+            let mid = top_pos[key.0] + top_pos[key.1];
+            match edges[&key][..] {
+                [(f0, i0), (f1, i1)] if !sharp.contains(&key) => {
+                    let c = top_pos[tv[tris[f0][(i0 + 2) % 3]]];
+                    let d = top_pos[tv[tris[f1][(i1 + 2) % 3]]];
+                    mid * 0.375 + (c + d) * 0.125
+                }
+                _ => mid * 0.5,
+            }
+        };
+        let mut new_pos: Vec<Vec3> = tv.iter().map(|t| vtx_pos[*t]).collect();
+        let mut new_norm = norm.to_vec();
+        let mut uv = self.uvs().to_vec();
+        let mut color = self.colors().to_vec();
+        let mut joints = self.joints.clone();
+        // new vertices on edges, shared by faces with the same vertices
+        let mut mids = HashMap::<(usize, usize), usize>::new();
+        let mut indices = Vec::with_capacity(tris.len() * 12);
+        for tri in &tris {
+            let m = [0, 1, 2].map(|i| {
+                let (a, b) = (tri[i], tri[(i + 1) % 3]);
+                *mids.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    new_pos.push(edge_pos(edge_key(a, b)));
+                    let n = (norm[a] + norm[b]).try_normalize();
+                    new_norm.push(n.unwrap_or(norm[a]));
+                    if !uv.is_empty() {
+                        uv.push(uv[a].lerp(uv[b], 0.5));
+                    }
+                    if !color.is_empty() {
+                        color.push(color[a].lerp(color[b], 0.5));
+                    }
+                    if !joints.is_empty() {
+                        joints.push(joints[a.min(b)]);
+                    }
+                    new_pos.len() - 1
+                })
+            });
+            for vtx in [
+                [tri[0], m[0], m[2]],
+                [m[0], tri[1], m[1]],
+                [m[2], m[1], tri[2]],
+                [m[0], m[1], m[2]],
+            ] {
+                for v in vtx {
+                    indices.push(Vertex::try_from(v)?);
+                }
+            }
+        }
+        let mut mesh = Mesh::from_parts(new_pos, new_norm, indices);
+        mesh.uv = uv;
+        mesh.color = color;
+        mesh.joints = joints;
+        mesh.skeleton.clone_from(&self.skeleton);
+        mesh.materials = self.materials.clone();
+        mesh.face_materials =
+            self.face_materials.iter().flat_map(|m| [*m; 4]).collect();
+        mesh.surfaces = self.surfaces.iter().flat_map(|s| [*s; 4]).collect();
+        mesh.recompute_normals();
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mesh::Face;
+    use crate::{Husk, Ring};
+
+    /// Make an octahedron, centered on the origin
+    fn octahedron() -> Mesh {
+        let mut husk = Husk::new();
+        husk.ring(Ring::default().axis(Vec3::Y).spoke(0.0)).unwrap();
+        let ring = Ring::default().spoke(1.0).spoke(1.0).spoke(1.0);
+        husk.ring(ring.spoke(1.0)).unwrap();
+        husk.ring(Ring::default().spoke(0.0)).unwrap();
+        let mut mesh = husk.into_mesh().unwrap();
+        mesh.translate(Vec3::new(0.0, -1.0, 0.0));
+        mesh
+    }
+
+    /// Get radius range of vertices and face centers
+    fn radius_range(mesh: &Mesh) -> f32 {
+        let pos = mesh.positions();
+        let centers = mesh.indices().chunks_exact(3).map(|tri| {
+            tri.iter().map(|v| pos[usize::from(v.0)]).sum::<Vec3>() / 3.0
+        });
+        let radii: Vec<f32> = pos
+            .iter()
+            .copied()
+            .chain(centers)
+            .map(Vec3::length)
+            .collect();
+        let min = radii.iter().copied().fold(f32::MAX, f32::min);
+        let max = radii.iter().copied().fold(0.0, f32::max);
+        (max - min) / max
+    }
+
+    #[test]
+    fn sphere() {
+        let mesh = octahedron();
+        assert_eq!(mesh.indices().len(), 8 * 3);
+        let mut range = radius_range(&mesh);
+        for levels in 1..4 {
+            let smooth = mesh.subdivided(levels).unwrap();
+            let faces = smooth.indices().len() / 3;
+            assert_eq!(faces, 8 * 4_usize.pow(levels as u32));
+            assert!(smooth.is_watertight());
+            assert_eq!(smooth.normals().len(), smooth.positions().len());
+            let r = radius_range(&smooth);
+            assert!(r < range, "level {levels}: {r} >= {range}");
+            range = r;
+        }
+        assert!(range < 0.2, "{range}");
+    }
+
+    #[test]
+    fn sharp_cube() {
+        let mut builder = Mesh::builder();
+        for i in 0..8 {
+            let [x, y, z] = [i & 1, (i >> 1) & 1, (i >> 2) & 1];
+            let pos = Vec3::new(x as f32, y as f32, z as f32);
+            builder.push_vtx(pos).unwrap();
+        }
+        for quad in [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ] {
+            builder.push_face(Face::new([quad[0], quad[1], quad[2]], 0));
+            builder.push_face(Face::new([quad[0], quad[2], quad[3]], 0));
+        }
+        builder.set_crease_angle(30.0_f32.to_radians());
+        let cube = builder.build().unwrap();
+        let smooth = cube.subdivided(2).unwrap();
+        // creases are kept sharp, so the cube is unchanged
+        assert_eq!(smooth.pos_min(), Vec3::ZERO);
+        assert_eq!(smooth.pos_max(), Vec3::ONE);
+        assert!((smooth.volume() - 1.0).abs() < 1e-5);
+        for n in smooth.normals() {
+            assert_eq!(n.abs().max_element(), 1.0, "{n}");
+        }
+    }
+
+    #[test]
+    fn too_many_vertices() {
+        assert!(octahedron().subdivided(8).is_err());
+    }
+}
@@ -0,0 +1,293 @@
+// pipeline.rs  End-to-end tests: build, export, import
+//
+// Copyright (c) 2024  Douglas Lau
+//
+// Golden values are in `tests/golden`.  To update them after an intended
+// change, run with `UPDATE_GOLDENS=1`.  Goldens of the shipped `.hom` assets
+// (`asset_*.json`) are checked with the `hom-format` feature.
+use glam::Vec3;
+use homunculus::{import_glb, Error, Husk, Mesh, Ring, Shading};
+#[cfg(feature = "hom-format")]
+use homunculus::{ModelDef, Scene};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Build the pyramid example
+fn pyramid() -> Result<Husk> {
+    let mut husk = Husk::new();
+    let base = Ring::default()
+        .shading(Shading::Flat)
+        .spoke(1.0)
+        .spoke(1.0)
+        .spoke(1.0)
+        .spoke(1.0);
+    husk.ring(base)?;
+    husk.ring(Ring::default().spoke(0.0))?;
+    Ok(husk)
+}
+
+/// Make a tree ring, with an optional branch label
+fn tree_ring(rng: &mut fastrand::Rng, label: Option<&str>) -> Ring {
+    let mut ring = Ring::default().axis(Vec3::new(0.0, 1.0, 0.0));
+    let b = rng.usize(..6);
+    for i in 0..6 {
+        ring = match label {
+            Some(label) if i == b => ring.spoke(label),
+            _ => ring.spoke(1.0),
+        };
+    }
+    ring
+}
+
+/// Make one tree branch, returning labels and scales of sub-branches
+fn tree_branch(
+    husk: &mut Husk,
+    rng: &mut fastrand::Rng,
+    mut scale: f32,
+) -> Result<Vec<(String, f32)>> {
+    let mut branches = Vec::new();
+    let mut i = 0;
+    while scale > 0.05 {
+        let sc = scale * 0.5;
+        let ring = if i % 3 == 1 && rng.f32() > scale && sc > 0.05 {
+            let label = format!("B{}", rng.u16(..10000));
+            let ring = tree_ring(rng, Some(&label));
+            branches.push((label, sc));
+            ring
+        } else {
+            tree_ring(rng, None)
+        };
+        let x = rng.f32() * 0.01 - (0.01 * 0.5);
+        let z = rng.f32() * 0.04 - (0.04 * 0.5);
+        husk.ring(ring.axis(Vec3::new(x, scale, z)).scale(scale))?;
+        scale *= 0.96;
+        i += 1;
+    }
+    Ok(branches)
+}
+
+/// Build the tree example (with a fixed seed)
+fn tree() -> Result<Husk> {
+    let mut rng = fastrand::Rng::with_seed(1234);
+    let mut husk = Husk::new();
+    let mut branches = tree_branch(&mut husk, &mut rng, 1.0)?;
+    while let Some((label, scale)) = branches.pop() {
+        let ring = husk.branch(label)?;
+        husk.ring(ring)?;
+        branches.extend(tree_branch(&mut husk, &mut rng, scale)?);
+    }
+    Ok(husk)
+}
+
+/// Build a biped, with legs, arms and a head
+fn biped() -> Result<Husk> {
+    let mut husk = Husk::new();
+    let up = Vec3::new(0.0, 0.5, 0.0);
+    // hips, with legs on the bottom
+    let mut hips = Ring::default().axis(up);
+    for label in ["leg R", "leg R", "", "leg L", "leg L", "", "", ""] {
+        hips = if label.is_empty() {
+            hips.spoke(1.0)
+        } else {
+            hips.spoke((1.0, label))
+        };
+    }
+    husk.ring(hips)?;
+    let mut waist = Ring::default();
+    for _ in 0..8 {
+        waist = waist.spoke(1.0);
+    }
+    husk.ring(waist.clone().scale(0.9))?;
+    // shoulders, with arms
+    let mut shoulders = Ring::default();
+    for label in ["arm R", "", "", "", "arm L", "", "", ""] {
+        shoulders = if label.is_empty() {
+            shoulders.spoke(1.0)
+        } else {
+            shoulders.spoke((1.1, label))
+        };
+    }
+    husk.ring(shoulders.scale(1.0))?;
+    husk.ring(waist.scale(0.8))?;
+    // neck and head
+    let mut neck = Ring::default().scale(0.3);
+    for _ in 0..8 {
+        neck = neck.spoke("head");
+    }
+    husk.ring(neck)?;
+    for (label, length, rings) in [
+        ("head", 0.4, 3),
+        ("arm R", 0.5, 4),
+        ("arm L", 0.5, 4),
+        ("leg R", 0.6, 5),
+        ("leg L", 0.6, 5),
+    ] {
+        let base = husk.branch(label)?;
+        let spokes = base.base_point_count();
+        husk.ring(base)?;
+        let mut ring = Ring::default().axis(Vec3::new(0.0, length, 0.0));
+        for _ in 0..spokes {
+            ring = ring.spoke(0.3);
+        }
+        husk.ring(ring)?;
+        for _ in 1..rings {
+            husk.ring(Ring::default())?;
+        }
+        husk.ring(Ring::default().scale(0.5))?;
+    }
+    Ok(husk)
+}
+
+/// Hash bytes (FNV-1a)
+fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x100_0000_01b3)
+    })
+}
+
+/// Hash a GLB, without the asset generator (which contains the version)
+fn glb_hash(glb: &[u8]) -> u64 {
+    let len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+    let mut json: Value = serde_json::from_slice(&glb[20..20 + len]).unwrap();
+    json["asset"].as_object_mut().unwrap().remove("generator");
+    let mut bytes = serde_json::to_vec(&json).unwrap();
+    bytes.extend_from_slice(&glb[20 + len..]);
+    hash(&bytes)
+}
+
+/// Count edges not shared by exactly two faces (welded by position)
+fn open_edges(mesh: &Mesh) -> usize {
+    let pos = mesh.positions();
+    let mut welded = HashMap::new();
+    let ids: Vec<usize> = pos
+        .iter()
+        .map(|p| {
+            let key = p.to_array().map(|c| (c * 1e4).round() as i64);
+            let len = welded.len();
+            *welded.entry(key).or_insert(len)
+        })
+        .collect();
+    let mut edges = HashMap::new();
+    for tri in mesh.indices().chunks_exact(3) {
+        let [v0, v1, v2] = [0, 1, 2].map(|i| ids[usize::from(tri[i].0)]);
+        for (a, b) in [(v0, v1), (v1, v2), (v2, v0)] {
+            *edges.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+        }
+    }
+    edges.values().filter(|c| **c != 2).count()
+}
+
+/// Export a husk to GLB, re-import and summarize it
+fn summarize(husk: Husk) -> Result<Value> {
+    let mut glb = Vec::new();
+    husk.write_gltf(&mut glb)?;
+    let summary = summarize_glb(&glb)?;
+    assert_eq!(import_glb(&glb[..])?.meshes.len(), 1);
+    Ok(summary)
+}
+
+/// Re-import a GLB and summarize all of its meshes
+fn summarize_glb(glb: &[u8]) -> Result<Value> {
+    let import = import_glb(glb)?;
+    let (mut vertices, mut faces, mut edges) = (0, 0, 0);
+    let mut min = Vec3::splat(f32::INFINITY);
+    let mut max = Vec3::splat(f32::NEG_INFINITY);
+    for mesh in &import.meshes {
+        for n in mesh.normals() {
+            assert!((n.length() - 1.0).abs() < 1e-4, "normal: {n}");
+        }
+        vertices += mesh.positions().len();
+        faces += mesh.indices().len() / 3;
+        edges += open_edges(mesh);
+        min = min.min(mesh.pos_min());
+        max = max.max(mesh.pos_max());
+    }
+    Ok(json!({
+        "vertices": vertices,
+        "faces": faces,
+        "open_edges": edges,
+        "min": min.to_array(),
+        "max": max.to_array(),
+        "hash": format!("{:016x}", glb_hash(glb)),
+    }))
+}
+
+/// Check a summary against a golden file
+fn check_golden(name: &str, summary: Value) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.json"));
+    if std::env::var_os("UPDATE_GOLDENS").is_some_and(|v| v == "1") {
+        let json = serde_json::to_string_pretty(&summary).unwrap();
+        std::fs::write(&path, json + "\n").unwrap();
+        return;
+    }
+    let golden = std::fs::read(&path).unwrap_or_else(|_| {
+        panic!("{} missing; run with UPDATE_GOLDENS=1", path.display())
+    });
+    let golden: Value = serde_json::from_slice(&golden).unwrap();
+    for key in ["vertices", "faces", "open_edges", "hash"] {
+        assert_eq!(summary[key], golden[key], "{name}: {key}");
+    }
+    for key in ["min", "max"] {
+        for i in 0..3 {
+            let v = summary[key][i].as_f64().unwrap();
+            let g = golden[key][i].as_f64().unwrap();
+            assert!((v - g).abs() < 1e-5, "{name}: {key} {v} != {g}");
+        }
+    }
+}
+
+#[test]
+fn pyramid_pipeline() -> Result<()> {
+    let summary = summarize(pyramid()?)?;
+    assert_eq!(summary["faces"], 4);
+    check_golden("pyramid", summary);
+    Ok(())
+}
+
+#[test]
+fn tree_pipeline() -> Result<()> {
+    check_golden("tree", summarize(tree()?)?);
+    Ok(())
+}
+
+#[test]
+fn biped_pipeline() -> Result<()> {
+    check_golden("biped", summarize(biped()?)?);
+    Ok(())
+}
+
+#[test]
+fn deterministic() -> Result<()> {
+    let a = summarize(tree()?)?;
+    let b = summarize(tree()?)?;
+    assert_eq!(a, b);
+    Ok(())
+}
+
+/// Build every shipped `.hom` asset, checking each against a golden file
+#[cfg(feature = "hom-format")]
+#[test]
+fn asset_pipeline() -> Result<()> {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../hom/assets");
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == "hom") {
+            let def = ModelDef::from_reader(std::fs::File::open(&path)?)?;
+            let mut glb = Vec::new();
+            if def.has_parts() {
+                Scene::try_from(&def)?.write_gltf(&mut glb)?;
+            } else {
+                Husk::try_from(&def)?.write_gltf(&mut glb)?;
+            }
+            let stem = path.file_stem().unwrap().to_string_lossy();
+            check_golden(&format!("asset_{stem}"), summarize_glb(&glb)?);
+        }
+    }
+    Ok(())
+}